@@ -0,0 +1,46 @@
+--
+-- Layout patch history. Every patch-layout run is stored as a new version
+-- of the layout, with the old and new dmx channel of each patched channel.
+--
+
+CREATE TABLE layout_patches (
+    patchid integer NOT NULL,
+    layoutid integer NOT NULL,
+    version integer NOT NULL,
+    source character varying(64) NOT NULL,
+    channels_internal integer[] NOT NULL,
+    old_dmx integer[] NOT NULL,
+    new_dmx integer[] NOT NULL,
+    CONSTRAINT pos_layout_id CHECK ((layoutid > 0)),
+    CONSTRAINT pos_version CHECK ((version > 0)),
+    CONSTRAINT changes_same_length CHECK (((array_length(channels_internal, 1) = array_length(old_dmx, 1)) AND (array_length(channels_internal, 1) = array_length(new_dmx, 1))))
+);
+
+ALTER TABLE layout_patches OWNER TO postgres;
+
+CREATE SEQUENCE layout_patches_patchid_seq
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER TABLE layout_patches_patchid_seq OWNER TO postgres;
+ALTER SEQUENCE layout_patches_patchid_seq OWNED BY layout_patches.patchid;
+ALTER TABLE ONLY layout_patches ALTER COLUMN patchid SET DEFAULT nextval('layout_patches_patchid_seq'::regclass);
+
+ALTER TABLE ONLY layout_patches
+    ADD CONSTRAINT layout_patches_pkey PRIMARY KEY (patchid);
+
+ALTER TABLE ONLY layout_patches
+    ADD CONSTRAINT layout_version_unique UNIQUE (layoutid, version);
+
+REVOKE ALL ON TABLE layout_patches FROM PUBLIC;
+REVOKE ALL ON TABLE layout_patches FROM postgres;
+GRANT ALL ON TABLE layout_patches TO postgres;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE layout_patches TO proton;
+
+REVOKE ALL ON SEQUENCE layout_patches_patchid_seq FROM PUBLIC;
+REVOKE ALL ON SEQUENCE layout_patches_patchid_seq FROM postgres;
+GRANT ALL ON SEQUENCE layout_patches_patchid_seq TO postgres;
+GRANT USAGE ON SEQUENCE layout_patches_patchid_seq TO proton;
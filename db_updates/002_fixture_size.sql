@@ -0,0 +1,8 @@
+--
+-- Fixture bounding box size, worked out from the fixture's channels
+-- when the layout is created.
+--
+
+ALTER TABLE fixtures ADD COLUMN size_x integer NOT NULL DEFAULT 0;
+ALTER TABLE fixtures ADD COLUMN size_y integer NOT NULL DEFAULT 0;
+ALTER TABLE fixtures ADD COLUMN size_z integer NOT NULL DEFAULT 0;
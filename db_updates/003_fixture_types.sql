@@ -0,0 +1,6 @@
+--
+-- Fixture type (e.g. RGB pixel, single color string), from the fixture
+-- definitions in a layout. NULL if the layout didn't define one.
+--
+
+ALTER TABLE fixtures ADD COLUMN fixture_type character varying(40);
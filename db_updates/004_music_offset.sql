@@ -0,0 +1,6 @@
+--
+-- Time in a sequence its music starts at, in milliseconds. Positive offsets leave
+-- room for a lighting intro, and negative offsets skip the start of the music.
+--
+
+ALTER TABLE sequences ADD COLUMN music_offset_ms integer NOT NULL DEFAULT 0;
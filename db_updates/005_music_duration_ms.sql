@@ -0,0 +1,8 @@
+--
+-- Music duration in milliseconds instead of whole seconds, so sequences can be
+-- checked against their music accurately. Existing durations are converted.
+--
+
+ALTER TABLE sequences ADD COLUMN music_dur_ms integer NOT NULL DEFAULT 0;
+UPDATE sequences SET music_dur_ms = music_dur_sec * 1000;
+ALTER TABLE sequences DROP COLUMN music_dur_sec;
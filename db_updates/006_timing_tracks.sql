@@ -0,0 +1,58 @@
+--
+-- Timing tracks (named lists of times, e.g. beats) and the energy envelopes
+-- of each frequency band of a sequence's music, one value per frame.
+--
+
+CREATE TABLE timing_tracks (
+    trackid integer NOT NULL,
+    seqid integer NOT NULL,
+    name character varying(64) NOT NULL,
+    marks integer[] NOT NULL,
+    CONSTRAINT pos_seq_id CHECK ((seqid > 0))
+);
+
+ALTER TABLE timing_tracks OWNER TO postgres;
+
+CREATE SEQUENCE timing_tracks_trackid_seq
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER TABLE timing_tracks_trackid_seq OWNER TO postgres;
+ALTER SEQUENCE timing_tracks_trackid_seq OWNED BY timing_tracks.trackid;
+ALTER TABLE ONLY timing_tracks ALTER COLUMN trackid SET DEFAULT nextval('timing_tracks_trackid_seq'::regclass);
+
+ALTER TABLE ONLY timing_tracks
+    ADD CONSTRAINT timing_tracks_pkey PRIMARY KEY (trackid);
+
+ALTER TABLE ONLY timing_tracks
+    ADD CONSTRAINT timing_track_name_unique UNIQUE (seqid, name);
+
+CREATE TABLE sequence_envelopes (
+    seqid integer NOT NULL,
+    band character varying(16) NOT NULL,
+    data integer[] NOT NULL,
+    CONSTRAINT pos_seq_id CHECK ((seqid > 0))
+);
+
+ALTER TABLE sequence_envelopes OWNER TO postgres;
+
+ALTER TABLE ONLY sequence_envelopes
+    ADD CONSTRAINT sequence_envelopes_pkey PRIMARY KEY (seqid, band);
+
+REVOKE ALL ON TABLE timing_tracks FROM PUBLIC;
+REVOKE ALL ON TABLE timing_tracks FROM postgres;
+GRANT ALL ON TABLE timing_tracks TO postgres;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE timing_tracks TO proton;
+
+REVOKE ALL ON SEQUENCE timing_tracks_trackid_seq FROM PUBLIC;
+REVOKE ALL ON SEQUENCE timing_tracks_trackid_seq FROM postgres;
+GRANT ALL ON SEQUENCE timing_tracks_trackid_seq TO postgres;
+GRANT USAGE ON SEQUENCE timing_tracks_trackid_seq TO proton;
+
+REVOKE ALL ON TABLE sequence_envelopes FROM PUBLIC;
+REVOKE ALL ON TABLE sequence_envelopes FROM postgres;
+GRANT ALL ON TABLE sequence_envelopes TO postgres;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE sequence_envelopes TO proton;
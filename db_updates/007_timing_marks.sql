@@ -0,0 +1,16 @@
+--
+-- Timing marks can have an end time and a label, e.g. for lyric phrases.
+-- Each mark's start, end and label are at the same index of the three arrays.
+-- Marks without an end time end where they start, and ones without a label
+-- have an empty label.
+--
+
+ALTER TABLE timing_tracks RENAME COLUMN marks TO mark_starts;
+ALTER TABLE timing_tracks ADD COLUMN mark_ends integer[];
+ALTER TABLE timing_tracks ADD COLUMN mark_labels text[];
+UPDATE timing_tracks SET
+    mark_ends = mark_starts,
+    mark_labels = array_fill(''::text, ARRAY[COALESCE(array_length(mark_starts, 1), 0)]);
+ALTER TABLE timing_tracks ALTER COLUMN mark_ends SET NOT NULL;
+ALTER TABLE timing_tracks ALTER COLUMN mark_labels SET NOT NULL;
+ALTER TABLE timing_tracks ADD CONSTRAINT marks_same_length CHECK (((array_length(mark_starts, 1) = array_length(mark_ends, 1)) AND (array_length(mark_starts, 1) = array_length(mark_labels, 1))));
@@ -0,0 +1,6 @@
+--
+-- Schedules for when each project's show runs, stored as the JSON they're
+-- read from. Projects without a schedule are only played by hand.
+--
+
+ALTER TABLE projects ADD COLUMN schedule text;
@@ -0,0 +1,42 @@
+--
+-- Dimming curves and output limits of channels and fixtures, applied to
+-- channel data as it's played or exported.
+--
+
+CREATE TABLE channel_dimming (
+    chanid integer NOT NULL,
+    curve text NOT NULL,
+    min_value integer NOT NULL,
+    max_value integer NOT NULL,
+    CONSTRAINT pos_chan_id CHECK ((chanid > 0)),
+    CONSTRAINT valid_limits CHECK ((0 <= min_value AND min_value <= max_value AND max_value <= 255))
+);
+
+ALTER TABLE channel_dimming OWNER TO postgres;
+
+ALTER TABLE ONLY channel_dimming
+    ADD CONSTRAINT channel_dimming_pkey PRIMARY KEY (chanid);
+
+CREATE TABLE fixture_dimming (
+    fixid integer NOT NULL,
+    curve text NOT NULL,
+    min_value integer NOT NULL,
+    max_value integer NOT NULL,
+    CONSTRAINT pos_fix_id CHECK ((fixid > 0)),
+    CONSTRAINT valid_limits CHECK ((0 <= min_value AND min_value <= max_value AND max_value <= 255))
+);
+
+ALTER TABLE fixture_dimming OWNER TO postgres;
+
+ALTER TABLE ONLY fixture_dimming
+    ADD CONSTRAINT fixture_dimming_pkey PRIMARY KEY (fixid);
+
+REVOKE ALL ON TABLE channel_dimming FROM PUBLIC;
+REVOKE ALL ON TABLE channel_dimming FROM postgres;
+GRANT ALL ON TABLE channel_dimming TO postgres;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE channel_dimming TO proton;
+
+REVOKE ALL ON TABLE fixture_dimming FROM PUBLIC;
+REVOKE ALL ON TABLE fixture_dimming FROM postgres;
+GRANT ALL ON TABLE fixture_dimming TO postgres;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE fixture_dimming TO proton;
@@ -0,0 +1,58 @@
+--
+-- Power drawn by channels and fixtures when fully on, the circuits they're
+-- wired to, and the most each of a layout's circuits can draw.
+--
+
+CREATE TABLE channel_loads (
+    chanid integer NOT NULL,
+    watts double precision NOT NULL,
+    circuit character varying(64),
+    CONSTRAINT pos_chan_id CHECK ((chanid > 0)),
+    CONSTRAINT non_neg_watts CHECK ((watts >= 0))
+);
+
+ALTER TABLE channel_loads OWNER TO postgres;
+
+ALTER TABLE ONLY channel_loads
+    ADD CONSTRAINT channel_loads_pkey PRIMARY KEY (chanid);
+
+CREATE TABLE fixture_loads (
+    fixid integer NOT NULL,
+    watts double precision NOT NULL,
+    circuit character varying(64),
+    CONSTRAINT pos_fix_id CHECK ((fixid > 0)),
+    CONSTRAINT non_neg_watts CHECK ((watts >= 0))
+);
+
+ALTER TABLE fixture_loads OWNER TO postgres;
+
+ALTER TABLE ONLY fixture_loads
+    ADD CONSTRAINT fixture_loads_pkey PRIMARY KEY (fixid);
+
+CREATE TABLE circuit_limits (
+    layoutid integer NOT NULL,
+    circuit character varying(64) NOT NULL,
+    max_amps double precision NOT NULL,
+    CONSTRAINT pos_layout_id CHECK ((layoutid > 0)),
+    CONSTRAINT pos_max_amps CHECK ((max_amps > 0))
+);
+
+ALTER TABLE circuit_limits OWNER TO postgres;
+
+ALTER TABLE ONLY circuit_limits
+    ADD CONSTRAINT circuit_limits_pkey PRIMARY KEY (layoutid, circuit);
+
+REVOKE ALL ON TABLE channel_loads FROM PUBLIC;
+REVOKE ALL ON TABLE channel_loads FROM postgres;
+GRANT ALL ON TABLE channel_loads TO postgres;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE channel_loads TO proton;
+
+REVOKE ALL ON TABLE fixture_loads FROM PUBLIC;
+REVOKE ALL ON TABLE fixture_loads FROM postgres;
+GRANT ALL ON TABLE fixture_loads TO postgres;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE fixture_loads TO proton;
+
+REVOKE ALL ON TABLE circuit_limits FROM PUBLIC;
+REVOKE ALL ON TABLE circuit_limits FROM postgres;
+GRANT ALL ON TABLE circuit_limits TO postgres;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE circuit_limits TO proton;
@@ -0,0 +1,83 @@
+--
+-- Controllers in each layout, the universes they take and their output
+-- ports, and the port each channel or fixture is wired to.
+--
+
+CREATE TABLE controllers (
+    controllerid integer NOT NULL,
+    layoutid integer NOT NULL,
+    name character varying(64) NOT NULL,
+    address character varying(64) NOT NULL,
+    protocol character varying(16) NOT NULL,
+    universes integer[] NOT NULL,
+    ports integer NOT NULL,
+    port_channels integer NOT NULL,
+    CONSTRAINT pos_layout_id CHECK ((layoutid > 0)),
+    CONSTRAINT pos_ports CHECK ((ports > 0)),
+    CONSTRAINT pos_port_channels CHECK ((port_channels > 0))
+);
+
+ALTER TABLE controllers OWNER TO postgres;
+
+CREATE SEQUENCE controllers_controllerid_seq
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+ALTER TABLE controllers_controllerid_seq OWNER TO postgres;
+ALTER SEQUENCE controllers_controllerid_seq OWNED BY controllers.controllerid;
+ALTER TABLE ONLY controllers ALTER COLUMN controllerid SET DEFAULT nextval('controllers_controllerid_seq'::regclass);
+
+ALTER TABLE ONLY controllers
+    ADD CONSTRAINT controllers_pkey PRIMARY KEY (controllerid);
+
+ALTER TABLE ONLY controllers
+    ADD CONSTRAINT layout_controller_name_unique UNIQUE (layoutid, name);
+
+CREATE TABLE channel_ports (
+    chanid integer NOT NULL,
+    controllerid integer NOT NULL,
+    port integer NOT NULL,
+    CONSTRAINT pos_chan_id CHECK ((chanid > 0)),
+    CONSTRAINT pos_port CHECK ((port > 0))
+);
+
+ALTER TABLE channel_ports OWNER TO postgres;
+
+ALTER TABLE ONLY channel_ports
+    ADD CONSTRAINT channel_ports_pkey PRIMARY KEY (chanid);
+
+CREATE TABLE fixture_ports (
+    fixid integer NOT NULL,
+    controllerid integer NOT NULL,
+    port integer NOT NULL,
+    CONSTRAINT pos_fix_id CHECK ((fixid > 0)),
+    CONSTRAINT pos_port CHECK ((port > 0))
+);
+
+ALTER TABLE fixture_ports OWNER TO postgres;
+
+ALTER TABLE ONLY fixture_ports
+    ADD CONSTRAINT fixture_ports_pkey PRIMARY KEY (fixid);
+
+REVOKE ALL ON TABLE controllers FROM PUBLIC;
+REVOKE ALL ON TABLE controllers FROM postgres;
+GRANT ALL ON TABLE controllers TO postgres;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE controllers TO proton;
+
+REVOKE ALL ON SEQUENCE controllers_controllerid_seq FROM PUBLIC;
+REVOKE ALL ON SEQUENCE controllers_controllerid_seq FROM postgres;
+GRANT ALL ON SEQUENCE controllers_controllerid_seq TO postgres;
+GRANT USAGE ON SEQUENCE controllers_controllerid_seq TO proton;
+
+REVOKE ALL ON TABLE channel_ports FROM PUBLIC;
+REVOKE ALL ON TABLE channel_ports FROM postgres;
+GRANT ALL ON TABLE channel_ports TO postgres;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE channel_ports TO proton;
+
+REVOKE ALL ON TABLE fixture_ports FROM PUBLIC;
+REVOKE ALL ON TABLE fixture_ports FROM postgres;
+GRANT ALL ON TABLE fixture_ports TO postgres;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE fixture_ports TO proton;
@@ -0,0 +1,316 @@
+use std::f32::consts::PI;
+use std::str::FromStr;
+
+use audio::DecodedAudio;
+use effects::MAX_VALUE;
+use error::Error;
+
+
+/// Length of the windows onsets and beats are found in
+const HOP_MS: u32 = 10;
+
+/// Frequencies the bands are split at
+const BASS_CUTOFF_HZ: f32 = 250.0;
+const HIGH_CUTOFF_HZ: f32 = 2000.0;
+
+/// Onsets closer together than this are counted once
+const MIN_ONSET_GAP_MS: u32 = 100;
+
+/// Tempos beats are searched for in, and the tempo preferred when several fit
+const MIN_BPM: f64 = 60.0;
+const MAX_BPM: f64 = 200.0;
+const PREFERRED_BPM: f64 = 120.0;
+
+/// How strongly beats are kept to the tempo, rather than following onsets
+const BEAT_TIGHTNESS: f64 = 100.0;
+
+/// Frequency bands energy is measured over
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, RustcEncodable)]
+pub enum Band {
+    Bass,
+    Mid,
+    High,
+}
+
+/// Beats, onsets and band energy of a sequence's music. Times are in milliseconds
+/// from the start of the sequence, snapped to the start of a frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioAnalysis {
+    pub tempo_bpm: Option<f64>,
+    pub beats: Vec<u32>,
+    pub onsets: Vec<u32>,
+    pub envelopes: Vec<(Band, Vec<u16>)>, // One value per frame, 0-255
+}
+
+impl Band {
+    /// Every band, lowest first
+    pub fn all() -> Vec<Band> {
+        vec![Band::Bass, Band::Mid, Band::High]
+    }
+
+    /// Name the band's envelope is stored under
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Band::Bass => "bass",
+            Band::Mid => "mid",
+            Band::High => "high",
+        }
+    }
+}
+
+impl FromStr for Band {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Band, Error> {
+        match s {
+            "bass" => Ok(Band::Bass),
+            "mid" => Ok(Band::Mid),
+            "high" => Ok(Band::High),
+            _ => Err(Error::InvalidBand(s.to_owned())),
+        }
+    }
+}
+
+/// Analyzes a sequence's music. The music starts music_offset_ms into the sequence.
+pub fn analyze(
+    audio: &DecodedAudio,
+    frame_duration_ms: u32,
+    num_frames: u32,
+    music_offset_ms: i32
+) -> AudioAnalysis {
+
+    let bands = split_bands(&audio.samples, audio.sample_rate);
+    let to_sample = |time_ms: i64| -> i64 {
+        (time_ms - music_offset_ms as i64) * audio.sample_rate as i64 / 1000
+    };
+
+    // Energy in each frame of the sequence
+    let envelopes = Band::all().into_iter()
+        .zip(bands.iter())
+        .map(|(band, samples)| {
+            let energy = (0..num_frames as i64)
+                .map(|frame| rms(samples,
+                    to_sample(frame * frame_duration_ms as i64),
+                    to_sample((frame + 1) * frame_duration_ms as i64)))
+                .collect::<Vec<f32>>();
+            (band, normalize(&energy))
+        })
+        .collect::<Vec<(Band, Vec<u16>)>>();
+
+    // Onsets and beats are found in the music's own time, in short hops
+    let hop_samples = (audio.sample_rate * HOP_MS / 1000) as usize;
+    let flux = if hop_samples > 0 { onset_strength(&bands, hop_samples) } else { Vec::new() };
+    let onset_hops = pick_onsets(&flux);
+    let (tempo_bpm, beat_hops) = match estimate_period(&flux) {
+        Some(period) => (
+            Some(60000.0 / (period * HOP_MS as f64)),
+            track_beats(&flux, period)
+        ),
+        None => (None, Vec::new()),
+    };
+
+    let to_frames = |hops: &Vec<usize>| -> Vec<u32> {
+        let mut times = Vec::with_capacity(hops.len());
+        for hop in hops {
+            let time_ms = (*hop as u32 * HOP_MS) as i64 + music_offset_ms as i64;
+            let frame = (time_ms as f64 / frame_duration_ms as f64).round() as i64;
+            if frame < 0 || frame >= num_frames as i64 {
+                continue;
+            }
+            let time_ms = frame as u32 * frame_duration_ms;
+            if times.last() != Some(&time_ms) {
+                times.push(time_ms);
+            }
+        }
+        times
+    };
+
+    AudioAnalysis {
+        tempo_bpm: tempo_bpm,
+        beats: to_frames(&beat_hops),
+        onsets: to_frames(&onset_hops),
+        envelopes: envelopes
+    }
+}
+
+/// Splits samples into bass, mid and high bands with simple low pass filters
+fn split_bands(samples: &Vec<f32>, sample_rate: u32) -> Vec<Vec<f32>> {
+    let bass = low_pass(&low_pass(samples, BASS_CUTOFF_HZ, sample_rate), BASS_CUTOFF_HZ, sample_rate);
+    let below_high = low_pass(&low_pass(samples, HIGH_CUTOFF_HZ, sample_rate), HIGH_CUTOFF_HZ, sample_rate);
+    let mid = below_high.iter().zip(bass.iter()).map(|(low, bass)| low - bass).collect();
+    let high = samples.iter().zip(below_high.iter()).map(|(sample, low)| sample - low).collect();
+    vec![bass, mid, high]
+}
+
+/// One pole low pass filter
+fn low_pass(samples: &Vec<f32>, cutoff_hz: f32, sample_rate: u32) -> Vec<f32> {
+    let dt = 1.0 / sample_rate as f32;
+    let alpha = dt / (1.0 / (2.0 * PI * cutoff_hz) + dt);
+    let mut last = 0.0;
+    samples.iter()
+        .map(|sample| {
+            last += alpha * (sample - last);
+            last
+        })
+        .collect()
+}
+
+/// Root mean square of the samples from start to end. Samples outside the music are silent.
+fn rms(samples: &Vec<f32>, start: i64, end: i64) -> f32 {
+    if end <= start {
+        return 0.0;
+    }
+    let first = start.max(0).min(samples.len() as i64) as usize;
+    let last = end.max(0).min(samples.len() as i64) as usize;
+    let sum = samples[first..last].iter().fold(0.0, |sum, sample| sum + sample * sample);
+    (sum / (end - start) as f32).sqrt()
+}
+
+/// Scales values so the largest is MAX_VALUE
+fn normalize(values: &Vec<f32>) -> Vec<u16> {
+    let max = values.iter().cloned().fold(0.0, f32::max);
+    if max <= 0.0 {
+        return vec![0; values.len()];
+    }
+    values.iter()
+        .map(|value| (value / max * MAX_VALUE as f32).round() as u16)
+        .collect()
+}
+
+/// How sharply each band's energy rises in each hop, summed over the bands
+fn onset_strength(bands: &Vec<Vec<f32>>, hop_samples: usize) -> Vec<f64> {
+    let num_hops = bands[0].len() / hop_samples;
+    let mut flux = vec![0.0; num_hops];
+    for samples in bands {
+        let mut last = 0.0;
+        for hop in 0..num_hops {
+            let energy = rms(samples, (hop * hop_samples) as i64, ((hop + 1) * hop_samples) as i64);
+            let level = (1.0 + 1000.0 * energy as f64).ln();
+            if hop > 0 && level > last {
+                flux[hop] += level - last;
+            }
+            last = level;
+        }
+    }
+    flux
+}
+
+/// Finds peaks in onset strength that stand out from their surroundings
+fn pick_onsets(flux: &Vec<f64>) -> Vec<usize> {
+    if flux.is_empty() {
+        return Vec::new();
+    }
+    let mean = flux.iter().fold(0.0, |sum, value| sum + value) / flux.len() as f64;
+    let window = 15;
+    let min_gap = (MIN_ONSET_GAP_MS / HOP_MS) as usize;
+
+    let mut onsets: Vec<usize> = Vec::new();
+    for hop in 1..flux.len() {
+        let is_peak = flux[hop] > flux[hop - 1] && (hop + 1 == flux.len() || flux[hop] >= flux[hop + 1]);
+        if !is_peak {
+            continue;
+        }
+        let start = hop.saturating_sub(window);
+        let end = (hop + window + 1).min(flux.len());
+        let local_mean = flux[start..end].iter().fold(0.0, |sum, value| sum + value) / (end - start) as f64;
+        if flux[hop] <= local_mean * 1.5 + mean * 0.5 {
+            continue;
+        }
+        match onsets.last() {
+            Some(&last) if hop - last < min_gap => {
+                if flux[hop] > flux[last] {
+                    *onsets.last_mut().unwrap() = hop;
+                }
+            },
+            _ => onsets.push(hop),
+        }
+    }
+    onsets
+}
+
+/// Estimates the beat period in hops from the autocorrelation of onset strength,
+/// weighted towards PREFERRED_BPM. None if the music has no onsets.
+fn estimate_period(flux: &Vec<f64>) -> Option<f64> {
+    let min_lag = (60000.0 / (MAX_BPM * HOP_MS as f64)).floor() as usize;
+    let max_lag = (60000.0 / (MIN_BPM * HOP_MS as f64)).ceil() as usize;
+    let preferred_lag = 60000.0 / (PREFERRED_BPM * HOP_MS as f64);
+    if flux.len() <= max_lag * 2 || flux.iter().all(|value| *value <= 0.0) {
+        return None;
+    }
+
+    let correlation = |lag: usize| -> f64 {
+        flux.iter().zip(flux[lag..].iter()).fold(0.0, |sum, (a, b)| sum + a * b)
+    };
+    let score = |lag: usize| -> f64 {
+        let octaves = (lag as f64 / preferred_lag).log2();
+        correlation(lag) * (-0.5 * octaves * octaves).exp()
+    };
+    let best = (min_lag..max_lag + 1)
+        .map(|lag| (lag, score(lag)))
+        .fold((0, 0.0), |best, lag_score| if lag_score.1 > best.1 { lag_score } else { best });
+    if best.1 <= 0.0 {
+        return None;
+    }
+
+    // Refine between hops with a parabola through the neighbouring scores
+    let lag = best.0;
+    if lag > min_lag && lag < max_lag {
+        let (before, at, after) = (score(lag - 1), best.1, score(lag + 1));
+        let curve = before - 2.0 * at + after;
+        if curve < 0.0 {
+            return Some(lag as f64 + 0.5 * (before - after) / curve);
+        }
+    }
+    Some(lag as f64)
+}
+
+/// Places beats on strong onsets while keeping close to the period, using
+/// dynamic programming (Ellis, "Beat Tracking by Dynamic Programming", 2007)
+fn track_beats(flux: &Vec<f64>, period: f64) -> Vec<usize> {
+    let mean = flux.iter().fold(0.0, |sum, value| sum + value) / flux.len() as f64;
+    let variance = flux.iter().fold(0.0, |sum, value| sum + (value - mean) * (value - mean)) / flux.len() as f64;
+    let std_dev = if variance > 0.0 { variance.sqrt() } else { 1.0 };
+    let strength = flux.iter().map(|value| value / std_dev).collect::<Vec<f64>>();
+
+    // Best score of a beat sequence ending at each hop, and the beat before it
+    let mut scores = strength.clone();
+    let mut previous: Vec<Option<usize>> = vec![None; flux.len()];
+    for hop in 0..flux.len() {
+        let earliest = hop as f64 - 2.0 * period;
+        let latest = hop as f64 - period / 2.0;
+        if latest < 0.0 {
+            continue;
+        }
+        let mut best: Option<(usize, f64)> = None;
+        for before in (earliest.max(0.0).ceil() as usize)..(latest.floor() as usize + 1) {
+            let gap = ((hop - before) as f64 / period).ln();
+            let score = scores[before] - BEAT_TIGHTNESS * gap * gap;
+            if best.map_or(true, |(_, best_score)| score > best_score) {
+                best = Some((before, score));
+            }
+        }
+        if let Some((before, score)) = best {
+            scores[hop] += score;
+            previous[hop] = Some(before);
+        }
+    }
+
+    // Trace back from the best scoring hop within the last period
+    let last_start = flux.len().saturating_sub(period.round() as usize);
+    let mut hop = (last_start..flux.len())
+        .fold(last_start, |best, hop| if scores[hop] > scores[best] { hop } else { best });
+    let mut beats = vec![hop];
+    while let Some(before) = previous[hop] {
+        beats.push(before);
+        hop = before;
+    }
+    beats.reverse();
+
+    // Beats are only kept to the tempo through silence, so drop any before or after the music
+    let first = flux.iter().position(|value| *value > 0.0).unwrap_or(0);
+    let last = flux.iter().rposition(|value| *value > 0.0).unwrap_or(0);
+    let slack = (period / 2.0).round() as usize;
+    beats.into_iter()
+        .filter(|beat| *beat + slack >= first && *beat <= last + slack)
+        .collect()
+}
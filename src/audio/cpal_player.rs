@@ -0,0 +1,162 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use cpal::{self, FromSample, SampleFormat, SampleRate, SizedSample, Stream, StreamConfig};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+use audio::{AudioPlayer, InterleavedAudio, Playback, SymphoniaBackend};
+use error::Error;
+
+
+/// Pure Rust player that decodes music with Symphonia and plays it on the
+/// default output device with cpal. Music the device can't take at its own
+/// sample rate is resampled as it plays.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpalPlayer;
+
+impl AudioPlayer for CpalPlayer {
+    fn play(&self, path: &Path, start_ms: u32) -> Result<Box<Playback>, Error> {
+        let music = try!(SymphoniaBackend.decode_interleaved(path));
+        if music.channels == 0 {
+            return Err(Error::Audio("Music has no channels".to_string()));
+        }
+
+        let host = cpal::default_host();
+        let device = try!(host.default_output_device()
+            .ok_or(Error::Audio("No audio output device found".to_string())));
+        let (config, sample_format) = try!(output_config(&device, &music));
+
+        let state = Arc::new(PlaybackState {
+            frames_played: AtomicUsize::new(0),
+            finished: AtomicBool::new(false)
+        });
+        let source = Source::new(music, start_ms, &config, state.clone());
+
+        let stream = try!(match sample_format {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, source),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, source),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, source),
+            format => Err(Error::Audio(format!("Unsupported output sample format: {}", format))),
+        });
+        try!(stream.play().map_err(|e| Error::Audio(e.to_string())));
+
+        Ok(Box::new(CpalPlayback {
+            stream: Some(stream),
+            state: state,
+            start_ms: start_ms,
+            sample_rate: config.sample_rate.0
+        }))
+    }
+}
+
+/// What the output callback shares with the playback
+struct PlaybackState {
+    frames_played: AtomicUsize, // Frames sent to the device since playback started
+    finished: AtomicBool,
+}
+
+/// Music being fed to the output device
+struct Source {
+    music: InterleavedAudio,
+    step: f64, // Music frames to move forward for each device frame
+    position: f64, // Fractional frame into the music
+    out_channels: usize,
+    state: Arc<PlaybackState>,
+}
+
+impl Source {
+    /// Sets up music to be played start_ms into it with the device's config
+    fn new(music: InterleavedAudio, start_ms: u32, config: &StreamConfig, state: Arc<PlaybackState>) -> Source {
+        let sample_rate = music.sample_rate as f64;
+        Source {
+            music: music,
+            step: sample_rate / config.sample_rate.0 as f64,
+            position: start_ms as f64 * sample_rate / 1000.0,
+            out_channels: config.channels as usize,
+            state: state
+        }
+    }
+
+    /// Fills a buffer of interleaved device frames, interpolating between the
+    /// music's samples. Device channels past the music's take its last channel.
+    fn fill<T: SizedSample + FromSample<f32>>(&mut self, data: &mut [T]) {
+        let channels = self.music.channels;
+        let num_frames = self.music.samples.len() / channels;
+        for frame in data.chunks_mut(self.out_channels) {
+            let index = self.position as usize;
+            if index + 1 >= num_frames {
+                self.state.finished.store(true, Ordering::SeqCst);
+                for sample in frame.iter_mut() {
+                    *sample = T::from_sample(0.0);
+                }
+                continue;
+            }
+            let fraction = (self.position - index as f64) as f32;
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let channel = channel.min(channels - 1);
+                let current = self.music.samples[index * channels + channel];
+                let next = self.music.samples[(index + 1) * channels + channel];
+                *sample = T::from_sample(current + (next - current) * fraction);
+            }
+            self.position += self.step;
+        }
+        self.state.frames_played.fetch_add(data.len() / self.out_channels, Ordering::SeqCst);
+    }
+}
+
+/// Picks an output config matching the music's channels and sample rate if the
+/// device has one, or the device's default if not
+fn output_config(device: &cpal::Device, music: &InterleavedAudio) -> Result<(StreamConfig, SampleFormat), Error> {
+    let matching = try!(device.supported_output_configs().map_err(|e| Error::Audio(e.to_string())))
+        .find(|range| {
+            range.channels() as usize == music.channels &&
+                range.min_sample_rate().0 <= music.sample_rate &&
+                range.max_sample_rate().0 >= music.sample_rate
+        });
+    let supported = match matching {
+        Some(range) => range.with_sample_rate(SampleRate(music.sample_rate)),
+        None => try!(device.default_output_config().map_err(|e| Error::Audio(e.to_string()))),
+    };
+    Ok((supported.config(), supported.sample_format()))
+}
+
+/// Builds a stream that plays the source in the device's sample format
+fn build_stream<T: SizedSample + FromSample<f32>>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    mut source: Source
+) -> Result<Stream, Error> {
+
+    let error_state = source.state.clone();
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| source.fill(data),
+        // Once the device fails, shows go on keeping time with the system clock
+        move |_| error_state.finished.store(true, Ordering::SeqCst),
+        None)
+        .map_err(|e| Error::Audio(e.to_string()))
+}
+
+/// Music that cpal plays in its own thread
+struct CpalPlayback {
+    stream: Option<Stream>,
+    state: Arc<PlaybackState>,
+    start_ms: u32,
+    sample_rate: u32, // Of the device
+}
+
+impl Playback for CpalPlayback {
+    fn position_ms(&self) -> Option<u32> {
+        if self.stream.is_none() || self.state.finished.load(Ordering::SeqCst) {
+            return None;
+        }
+        let frames_played = self.state.frames_played.load(Ordering::SeqCst) as u64;
+        Some(self.start_ms + (frames_played * 1000 / self.sample_rate as u64) as u32)
+    }
+
+    fn stop(&mut self) {
+        // Dropping the stream stops it
+        self.stream = None;
+    }
+}
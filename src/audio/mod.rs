@@ -0,0 +1,117 @@
+//! Reading and playing music files. Audio libraries are wrapped in backends
+//! that all implement AudioBackend (and AudioPlayer, if they can play music),
+//! so they can be swapped out.
+
+mod analysis;
+#[cfg(feature = "cpal")]
+mod cpal_player;
+mod silent_player;
+mod symphonia_backend;
+#[cfg(feature = "sfml")]
+mod sfml_backend;
+
+use std::path::Path;
+
+use error::Error;
+
+pub use self::analysis::{analyze, AudioAnalysis, Band};
+#[cfg(feature = "cpal")]
+pub use self::cpal_player::CpalPlayer;
+pub use self::silent_player::SilentPlayer;
+pub use self::symphonia_backend::SymphoniaBackend;
+#[cfg(feature = "sfml")]
+pub use self::sfml_backend::SfmlBackend;
+
+
+/// Extensions of the music files Proton can use
+pub const SUPPORTED_EXTENSIONS: &'static [&'static str] = &["ogg", "wav", "flac", "aiff", "aif", "mp3"];
+
+/// What a backend knows about a music file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioInfo {
+    pub duration_ms: u32,
+    pub sample_rate: u32,
+    pub channels: u32,
+}
+
+/// A music file's samples, mixed down to one channel
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedAudio {
+    pub sample_rate: u32,
+    pub samples: Vec<f32>, // -1.0 to 1.0
+}
+
+/// A music file's samples, with the samples of each of its channels interleaved
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterleavedAudio {
+    pub sample_rate: u32,
+    pub channels: usize,
+    pub samples: Vec<f32>, // -1.0 to 1.0
+}
+
+/// Reads music files
+pub trait AudioBackend {
+    /// Reads a music file's duration, sample rate, and number of channels
+    fn get_info(&self, path: &Path) -> Result<AudioInfo, Error>;
+
+    /// Decodes a whole music file, mixing its channels down to one
+    fn decode(&self, path: &Path) -> Result<DecodedAudio, Error>;
+}
+
+impl<AB: AudioBackend + ?Sized> AudioBackend for Box<AB> {
+    fn get_info(&self, path: &Path) -> Result<AudioInfo, Error> {
+        (**self).get_info(path)
+    }
+
+    fn decode(&self, path: &Path) -> Result<DecodedAudio, Error> {
+        (**self).decode(path)
+    }
+}
+
+/// Plays music files
+pub trait AudioPlayer {
+    /// Starts playing a music file, start_ms into it
+    fn play(&self, path: &Path, start_ms: u32) -> Result<Box<Playback>, Error>;
+}
+
+impl<AP: AudioPlayer + ?Sized> AudioPlayer for Box<AP> {
+    fn play(&self, path: &Path, start_ms: u32) -> Result<Box<Playback>, Error> {
+        (**self).play(path, start_ms)
+    }
+}
+
+/// Music that has started playing
+pub trait Playback {
+    /// How far into the music file playback is, in milliseconds, taken from
+    /// the audio clock. None once the music has finished.
+    fn position_ms(&self) -> Option<u32>;
+
+    /// Stops the music
+    fn stop(&mut self);
+}
+
+/// Averages interleaved samples from each channel into one channel
+pub fn mix_down(samples: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+    samples.chunks(channels)
+        .map(|frame| frame.iter().fold(0.0, |sum, sample| sum + sample) / channels as f32)
+        .collect()
+}
+
+/// Check that the music file is a supported format
+pub fn validate_file_type<P: AsRef<Path>>(music_file_path: P) -> Result<(), Error> {
+    match music_file_path.as_ref().extension() {
+        Some(extension) => {
+            match extension.to_str() {
+                Some(ext) if SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()) => Ok(()),
+                Some(ext) => Err(Error::UnsupportedFileType(ext.to_string())),
+                None => Err(
+                    Error::UnsupportedFileType("Extension is not valid unicode".to_string())
+                    ),
+            }
+        },
+        None => Err(Error::UnsupportedFileType("No file extension".to_string())),
+    }
+}
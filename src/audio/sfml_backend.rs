@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use sfml::audio::{Music, SoundBuffer, SoundStatus};
+use sfml::system::Time;
+
+use audio::{self, AudioBackend, AudioInfo, AudioPlayer, DecodedAudio, Playback};
+use error::Error;
+
+
+/// Backend using SFML, which needs the native SFML and CSFML libraries installed.
+/// Full list of supported formats can be found at
+/// http://www.rust-sfml.org/doc/rsfml/audio/struct.Music.html
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SfmlBackend;
+
+impl AudioBackend for SfmlBackend {
+    fn get_info(&self, path: &Path) -> Result<AudioInfo, Error> {
+        let path_str = try!(path.to_str().ok_or(Error::InvalidFileName));
+        let music = match Music::new_from_file(path_str) {
+            Some(m) => m,
+            None => return Err(Error::Audio("Error reading file.".to_string())),
+        };
+        Ok(AudioInfo {
+            duration_ms: music.get_duration().as_milliseconds() as u32,
+            sample_rate: music.get_sample_rate(),
+            channels: music.get_channel_count()
+        })
+    }
+
+    fn decode(&self, path: &Path) -> Result<DecodedAudio, Error> {
+        let path_str = try!(path.to_str().ok_or(Error::InvalidFileName));
+        let buffer = match SoundBuffer::new(path_str) {
+            Some(b) => b,
+            None => return Err(Error::Audio("Error reading file.".to_string())),
+        };
+        let samples = buffer.get_samples().iter()
+            .map(|sample| *sample as f32 / 32768.0)
+            .collect::<Vec<f32>>();
+        Ok(DecodedAudio {
+            sample_rate: buffer.get_sample_rate(),
+            samples: audio::mix_down(&samples, buffer.get_channel_count() as usize)
+        })
+    }
+}
+
+impl AudioPlayer for SfmlBackend {
+    fn play(&self, path: &Path, start_ms: u32) -> Result<Box<Playback>, Error> {
+        let path_str = try!(path.to_str().ok_or(Error::InvalidFileName));
+        let mut music = match Music::new_from_file(path_str) {
+            Some(m) => m,
+            None => return Err(Error::Audio("Error reading file.".to_string())),
+        };
+        music.set_playing_offset(Time::with_milliseconds(start_ms as i32));
+        music.play();
+        Ok(Box::new(SfmlPlayback { music: music }))
+    }
+}
+
+/// Music streaming from a file, which SFML plays in its own thread
+struct SfmlPlayback {
+    music: Music,
+}
+
+impl Playback for SfmlPlayback {
+    fn position_ms(&self) -> Option<u32> {
+        match self.music.get_status() {
+            SoundStatus::Playing => Some(self.music.get_playing_offset().as_milliseconds() as u32),
+            _ => None,
+        }
+    }
+
+    fn stop(&mut self) {
+        self.music.stop();
+    }
+}
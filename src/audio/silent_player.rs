@@ -0,0 +1,42 @@
+use std::path::Path;
+use std::time::Instant;
+
+use audio::{AudioPlayer, Playback};
+use error::Error;
+
+
+/// Player that doesn't play anything, just keeping time with the system clock.
+/// Used to run shows without sound, e.g. when the music is played by another computer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SilentPlayer;
+
+struct SilentPlayback {
+    started: Instant,
+    start_ms: u32,
+    stopped: bool,
+}
+
+impl AudioPlayer for SilentPlayer {
+    fn play(&self, _path: &Path, start_ms: u32) -> Result<Box<Playback>, Error> {
+        Ok(Box::new(SilentPlayback {
+            started: Instant::now(),
+            start_ms: start_ms,
+            stopped: false
+        }))
+    }
+}
+
+impl Playback for SilentPlayback {
+    fn position_ms(&self) -> Option<u32> {
+        if self.stopped {
+            return None;
+        }
+        let elapsed = self.started.elapsed();
+        let elapsed_ms = elapsed.as_secs() as u32 * 1000 + elapsed.subsec_nanos() / 1_000_000;
+        Some(self.start_ms + elapsed_ms)
+    }
+
+    fn stop(&mut self) {
+        self.stopped = true;
+    }
+}
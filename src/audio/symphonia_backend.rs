@@ -0,0 +1,136 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use symphonia;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CodecParameters, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use audio::{self, AudioBackend, AudioInfo, DecodedAudio, InterleavedAudio};
+use error::Error;
+
+
+/// Pure Rust backend using Symphonia, which needs no native libraries.
+/// Reads ogg (vorbis), wav, flac, aiff and mp3.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SymphoniaBackend;
+
+impl AudioBackend for SymphoniaBackend {
+    fn get_info(&self, path: &Path) -> Result<AudioInfo, Error> {
+        let (mut format, track_id, params) = try!(open(path));
+        let sample_rate = match params.sample_rate {
+            Some(sample_rate) if sample_rate > 0 => sample_rate,
+            _ => return Err(Error::Audio("Unknown sample rate".to_string())),
+        };
+        let channels = params.channels.map(|channels| channels.count() as u32).unwrap_or(0);
+
+        // Files that don't say how long they are (e.g. mp3s without a header
+        // giving their length) are read through to count their samples
+        let num_samples = match params.n_frames {
+            Some(num_samples) => num_samples,
+            None => {
+                let mut num_samples = 0;
+                loop {
+                    match format.next_packet() {
+                        Ok(packet) => if packet.track_id() == track_id {
+                            num_samples += packet.dur;
+                        },
+                        Err(SymphoniaError::IoError(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                        Err(e) => return Err(audio_error(e)),
+                    }
+                }
+                num_samples
+            },
+        };
+
+        Ok(AudioInfo {
+            duration_ms: (num_samples * 1000 / sample_rate as u64) as u32,
+            sample_rate: sample_rate,
+            channels: channels
+        })
+    }
+
+    fn decode(&self, path: &Path) -> Result<DecodedAudio, Error> {
+        let decoded = try!(self.decode_interleaved(path));
+        Ok(DecodedAudio {
+            sample_rate: decoded.sample_rate,
+            samples: audio::mix_down(&decoded.samples, decoded.channels)
+        })
+    }
+}
+
+impl SymphoniaBackend {
+    /// Decodes a whole music file, keeping its channels apart so it can be played
+    pub fn decode_interleaved(&self, path: &Path) -> Result<InterleavedAudio, Error> {
+        let (mut format, track_id, params) = try!(open(path));
+        let mut decoder = try!(symphonia::default::get_codecs()
+            .make(&params, &DecoderOptions::default())
+            .map_err(audio_error));
+
+        let mut sample_rate = params.sample_rate.unwrap_or(0);
+        let mut channels = params.channels.map(|channels| channels.count()).unwrap_or(0);
+        let mut samples = Vec::new();
+        loop {
+            let packet = match format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(audio_error(e)),
+            };
+            if packet.track_id() != track_id {
+                continue;
+            }
+            match decoder.decode(&packet) {
+                Ok(decoded) => {
+                    let spec = *decoded.spec();
+                    let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                    buffer.copy_interleaved_ref(decoded);
+                    sample_rate = spec.rate;
+                    channels = spec.channels.count();
+                    samples.extend_from_slice(buffer.samples());
+                },
+                // A corrupt packet is skipped rather than failing the whole file
+                Err(SymphoniaError::DecodeError(_)) => continue,
+                Err(e) => return Err(audio_error(e)),
+            }
+        }
+
+        if sample_rate == 0 {
+            return Err(Error::Audio("Unknown sample rate".to_string()));
+        }
+        Ok(InterleavedAudio {
+            sample_rate: sample_rate,
+            channels: channels,
+            samples: samples
+        })
+    }
+}
+
+/// Opens a music file, returning its reader and the id and parameters of its audio track
+fn open(path: &Path) -> Result<(Box<FormatReader>, u32, CodecParameters), Error> {
+    let file = try!(File::open(path).map_err(Error::Io));
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(extension);
+    }
+
+    let probed = try!(symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(audio_error));
+    let format = probed.format;
+    let (track_id, params) = match format.default_track() {
+        Some(track) => (track.id, track.codec_params.clone()),
+        None => return Err(Error::Audio("No audio track found".to_string())),
+    };
+    Ok((format, track_id, params))
+}
+
+/// Converts a Symphonia error to a Proton error
+fn audio_error(err: SymphoniaError) -> Error {
+    Error::Audio(err.to_string())
+}
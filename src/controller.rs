@@ -0,0 +1,272 @@
+//! This module handles the controllers of a layout: the physical boxes that
+//! take universes over the network and drive lights from their output ports
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use dao::{ChannelDao, ControllerDao, LayoutDao};
+use error::Error;
+use output::{self, ArtNetDestinations, ArtNetSender, E131Sender, OutputDriver, RoutedDriver};
+use project_types::{Controller, ControllerProtocol, FileTable, IssueLevel, LayoutIssue, PortRange};
+use utils;
+
+
+/// Adds a controller to a layout. Controller names are unique within a layout.
+pub fn add_controller<CTD: ControllerDao, LD: LayoutDao>(
+    controller_dao: &CTD,
+    layout_dao: &LD,
+    controller: &Controller
+) -> Result<Controller, Error> {
+
+    if !try!(layout_dao.layout_exists(controller.layout_id)) {
+        return Err(Error::LayoutNotFound(controller.layout_id));
+    }
+    let controllers = try!(controller_dao.get_controllers(controller.layout_id));
+    if controllers.iter().any(|existing| existing.name == controller.name) {
+        return Err(Error::InvalidController(
+            format!("Layout {} already has a controller named '{}'", controller.layout_id, controller.name)));
+    }
+    controller_dao.new_controller(controller)
+}
+
+/// Removes a controller from a layout. Channels and fixtures wired to it are left unwired.
+pub fn remove_controller<CTD: ControllerDao>(
+    controller_dao: &CTD,
+    layout_id: u32,
+    controllerid: u32
+) -> Result<(), Error> {
+
+    let controllers = try!(controller_dao.get_controllers(layout_id));
+    if !controllers.iter().any(|controller| controller.controllerid == controllerid) {
+        return Err(Error::ControllerNotFound(controllerid));
+    }
+    controller_dao.delete_controller(controllerid)
+}
+
+/// Checks a layout's channels against its controllers. Wiring a port to more
+/// channels than it can drive, or a channel to a controller that isn't sent its
+/// universe, is an error, as is a universe no controller is sent.
+pub fn validate_controllers<CD: ChannelDao, CTD: ControllerDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    controller_dao: &CTD,
+    layout_dao: &LD,
+    layout_id: u32
+) -> Result<Vec<LayoutIssue>, Error> {
+
+    let controllers = try!(controller_dao.get_controllers(layout_id));
+    if controllers.is_empty() {
+        return Ok(vec![LayoutIssue::warning(None, format!("Layout {} has no controllers", layout_id))]);
+    }
+    let chan_ids = try!(layout_dao.get_channel_ids(layout_id));
+    let ports = try!(controller_dao.get_ports(&chan_ids));
+
+    let mut issues = Vec::new();
+    let mut port_channels: BTreeMap<(u32, u32), u32> = BTreeMap::new();
+    let mut missing_universes = BTreeSet::new();
+    let mut num_unwired = 0;
+    for chanid in &chan_ids {
+        let channel = try!(chan_dao.get_channel(*chanid));
+        let universe = match channel.channel_dmx {
+            0 => None,
+            channel_dmx => Some(output::universe_slot(channel_dmx).0),
+        };
+        if let Some(universe) = universe {
+            if !controllers.iter().any(|controller| controller.takes_universe(universe)) {
+                missing_universes.insert(universe);
+            }
+        }
+        let name = format!("Channel {} ({})", channel.channel_internal, channel.name);
+
+        let assignment = match ports.get(chanid) {
+            Some(assignment) => assignment,
+            None => {
+                if universe.is_some() {
+                    num_unwired += 1;
+                }
+                continue;
+            },
+        };
+        let controller = match controllers.iter().find(|controller| controller.controllerid == assignment.controllerid) {
+            Some(controller) => controller,
+            None => {
+                issues.push(LayoutIssue::error(None, format!(
+                    "{} is wired to controller {}, which isn't in the layout", name, assignment.controllerid)));
+                continue;
+            },
+        };
+        if assignment.port == 0 || assignment.port > controller.ports {
+            issues.push(LayoutIssue::error(None, format!(
+                "{} is wired to port {} of controller '{}', which only has ports 1-{}",
+                name, assignment.port, controller.name, controller.ports)));
+            continue;
+        }
+        *port_channels.entry((controller.controllerid, assignment.port)).or_insert(0) += 1;
+
+        match universe {
+            Some(universe) if !controller.takes_universe(universe) => issues.push(LayoutIssue::error(None, format!(
+                "{} is on universe {}, which controller '{}' isn't sent", name, universe, controller.name))),
+            Some(_) => (),
+            None => issues.push(LayoutIssue::warning(None, format!(
+                "{} is wired to controller '{}' but isn't patched", name, controller.name))),
+        }
+    }
+
+    for controller in &controllers {
+        for port in 1..controller.ports + 1 {
+            let num_channels = port_channels.get(&(controller.controllerid, port)).cloned().unwrap_or(0);
+            if num_channels > controller.port_channels {
+                issues.push(LayoutIssue::error(None, format!(
+                    "Port {} of controller '{}' is wired to {} channels, but can only drive {}",
+                    port, controller.name, num_channels, controller.port_channels)));
+            }
+        }
+    }
+    for universe in missing_universes {
+        issues.push(LayoutIssue::error(None, format!(
+            "Universe {} has patched channels, but no controller is sent it", universe)));
+    }
+    if num_unwired > 0 {
+        issues.push(LayoutIssue::warning(None, format!(
+            "{} patched channel(s) aren't wired to a controller port", num_unwired)));
+    }
+    Ok(issues)
+}
+
+/// Works out the dmx channels each controller port drives, one range per
+/// universe, ordered by controller, port and universe. Channels that aren't
+/// patched or are wired to ports that don't exist are left out.
+pub fn get_port_ranges<CD: ChannelDao, CTD: ControllerDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    controller_dao: &CTD,
+    layout_dao: &LD,
+    layout_id: u32
+) -> Result<Vec<PortRange>, Error> {
+
+    let controllers = try!(controller_dao.get_controllers(layout_id));
+    let chan_ids = try!(layout_dao.get_channel_ids(layout_id));
+    let ports = try!(controller_dao.get_ports(&chan_ids));
+
+    // (controllerid, port, universe) -> (first slot, last slot, channels)
+    let mut ranges: BTreeMap<(u32, u32, u16), (u32, u32, u32)> = BTreeMap::new();
+    for (chanid, assignment) in &ports {
+        let channel = try!(chan_dao.get_channel(*chanid));
+        if channel.channel_dmx == 0 {
+            continue;
+        }
+        let (universe, slot) = output::universe_slot(channel.channel_dmx);
+        let slot = slot as u32 + 1;
+        let range = ranges.entry((assignment.controllerid, assignment.port, universe)).or_insert((slot, slot, 0));
+        range.0 = range.0.min(slot);
+        range.1 = range.1.max(slot);
+        range.2 += 1;
+    }
+
+    let mut port_ranges = Vec::new();
+    for controller in &controllers {
+        for (&(controllerid, port, universe), &(start_slot, end_slot, channels)) in &ranges {
+            if controllerid != controller.controllerid || port == 0 || port > controller.ports {
+                continue;
+            }
+            port_ranges.push(PortRange {
+                controller: controller.name.to_owned(),
+                port: port,
+                universe: universe,
+                start_slot: start_slot,
+                end_slot: end_slot,
+                channels: channels
+            });
+        }
+    }
+    Ok(port_ranges)
+}
+
+/// Writes a layout's controllers and the channels each of their ports drives
+/// to a CSV or TSV file (based on its extension), to set the controllers up from
+pub fn export_controllers<P: AsRef<Path>, CD: ChannelDao, CTD: ControllerDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    controller_dao: &CTD,
+    layout_dao: &LD,
+    layout_id: u32,
+    output_path: P
+) -> Result<(), Error> {
+
+    let delimiter = match FileTable::delimiter_for(output_path.as_ref()) {
+        Some(delimiter) => delimiter,
+        None => {
+            let file_name = try!(utils::file_name_from_path(output_path.as_ref()));
+            return Err(Error::UnsupportedFileType(file_name));
+        },
+    };
+
+    let controllers = try!(controller_dao.get_controllers(layout_id));
+    let port_ranges = try!(get_port_ranges(chan_dao, controller_dao, layout_dao, layout_id));
+    let rows = port_ranges.iter()
+        .filter_map(|range| {
+            controllers.iter()
+                .find(|controller| controller.name == range.controller)
+                .map(|controller| vec![
+                    controller.name.to_owned(),
+                    controller.protocol.to_string(),
+                    controller.address.to_owned(),
+                    range.port.to_string(),
+                    range.universe.to_string(),
+                    range.start_slot.to_string(),
+                    range.end_slot.to_string(),
+                    range.channels.to_string(),
+                ])
+        })
+        .collect::<Vec<Vec<String>>>();
+
+    let header = ["controller", "protocol", "address", "port", "universe",
+        "startChannel", "endChannel", "channels"];
+    let text = FileTable::to_text(&header, &rows, delimiter);
+
+    let mut file = try!(File::create(output_path.as_ref()).map_err(Error::Io));
+    try!(file.write_all(text.as_bytes()).map_err(Error::Io));
+    Ok(())
+}
+
+/// Builds an output driver that sends each of a layout's controllers just its
+/// own universes, in its own protocol. Fails if the layout's controllers have
+/// any errors, since some lights might not be sent anything.
+pub fn controller_driver<CD: ChannelDao, CTD: ControllerDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    controller_dao: &CTD,
+    layout_dao: &LD,
+    layout_id: u32,
+    priority: u8,
+    sync: bool
+) -> Result<RoutedDriver, Error> {
+
+    let controllers = try!(controller_dao.get_controllers(layout_id));
+    if controllers.is_empty() {
+        return Err(Error::InvalidController(format!("Layout {} has no controllers", layout_id)));
+    }
+    let issues = try!(validate_controllers(chan_dao, controller_dao, layout_dao, layout_id));
+    let num_errors = issues.iter()
+        .filter(|issue| issue.level == IssueLevel::Error)
+        .count();
+    if num_errors > 0 {
+        return Err(Error::InvalidController(
+            format!("Layout {} has {} controller error(s)", layout_id, num_errors)));
+    }
+
+    let mut driver = RoutedDriver::new();
+    for controller in controllers {
+        let address = try!(output::parse_address(&controller.address, controller.protocol.default_port()));
+        let sender: Box<OutputDriver> = match controller.protocol {
+            ControllerProtocol::E131 => Box::new(try!(E131Sender::new(Some(address), "Proton", priority))),
+            ControllerProtocol::ArtNet => {
+                let destinations = ArtNetDestinations {
+                    universes: BTreeMap::new(),
+                    default: Some(address)
+                };
+                Box::new(try!(ArtNetSender::new(destinations, sync)))
+            },
+        };
+        driver.add(sender, controller.universes);
+    }
+    Ok(driver)
+}
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use dao::{ControllerDao, ControllerDaoPostgres};
+use dao::daos_postgres::resolve_channel_settings;
+use error::Error;
+use project_types::{Controller, ControllerProtocol, PortAssignment};
+
+
+impl ControllerDao for ControllerDaoPostgres {
+
+    fn new_controller(&self, controller: &Controller) -> Result<Controller, Error> {
+        let statement = "INSERT INTO controllers \
+            (layoutid,name,address,protocol,universes,ports,port_channels) \
+            VALUES ($1,$2,$3,$4,$5,$6,$7) RETURNING controllerid";
+        let universes = controller.universes.iter()
+            .map(|universe| *universe as i32)
+            .collect::<Vec<i32>>();
+        let results = try!(
+            self.conn.query(
+                statement,
+                &[
+                    &(controller.layout_id as i32),
+                    &controller.name,
+                    &controller.address,
+                    &controller.protocol.to_string(),
+                    &universes,
+                    &(controller.ports as i32),
+                    &(controller.port_channels as i32)
+                ])
+            .map_err(Error::Postgres));
+        if results.len() != 1 {
+            return Err(Error::InvalidNumResults(results.len()));
+        }
+
+        let controllerid: i32 = results.get(0).get(0);
+        let mut stored = controller.clone();
+        stored.controllerid = controllerid as u32;
+        Ok(stored)
+    }
+
+    fn get_controllers(&self, layoutid: u32) -> Result<Vec<Controller>, Error> {
+        let query = "SELECT controllerid,name,address,protocol,universes,ports,port_channels \
+            FROM controllers WHERE layoutid = $1 ORDER BY controllerid";
+        let results = try!(
+            self.conn.query(query, &[&(layoutid as i32)])
+            .map_err(Error::Postgres));
+
+        let mut controllers = Vec::with_capacity(results.len());
+        for row in results.iter() {
+            let controllerid: i32 = row.get(0);
+            let name: String = row.get(1);
+            let address: String = row.get(2);
+            let protocol: String = row.get(3);
+            let universes: Vec<i32> = row.get(4);
+            let ports: i32 = row.get(5);
+            let port_channels: i32 = row.get(6);
+            controllers.push(Controller {
+                controllerid: controllerid as u32,
+                layout_id: layoutid,
+                name: name,
+                address: address,
+                protocol: try!(protocol.parse::<ControllerProtocol>()),
+                universes: universes.iter().map(|universe| *universe as u16).collect(),
+                ports: ports as u32,
+                port_channels: port_channels as u32
+            });
+        }
+        Ok(controllers)
+    }
+
+    fn delete_controller(&self, controllerid: u32) -> Result<(), Error> {
+        let trans = try!(self.conn.transaction().map_err(Error::Postgres));
+        for statement in &[
+            "DELETE FROM channel_ports WHERE controllerid = $1",
+            "DELETE FROM fixture_ports WHERE controllerid = $1",
+            "DELETE FROM controllers WHERE controllerid = $1",
+        ] {
+            let _ = try!(
+                trans.execute(statement, &[&(controllerid as i32)])
+                .map_err(Error::Postgres));
+        }
+        trans.commit().map_err(Error::Postgres)
+    }
+
+    fn set_channel_port(&self, chanid: u32, port: Option<&PortAssignment>) -> Result<(), Error> {
+        match port {
+            Some(port) => {
+                let statement = "INSERT INTO channel_ports (chanid,controllerid,port) VALUES ($1,$2,$3) \
+                    ON CONFLICT (chanid) DO UPDATE SET controllerid = EXCLUDED.controllerid, port = EXCLUDED.port";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(chanid as i32), &(port.controllerid as i32), &(port.port as i32)])
+                    .map_err(Error::Postgres));
+            },
+            None => {
+                let statement = "DELETE FROM channel_ports WHERE chanid = $1";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(chanid as i32)])
+                    .map_err(Error::Postgres));
+            },
+        }
+        Ok(())
+    }
+
+    fn set_fixture_port(&self, fixid: u32, port: Option<&PortAssignment>) -> Result<(), Error> {
+        match port {
+            Some(port) => {
+                let statement = "INSERT INTO fixture_ports (fixid,controllerid,port) VALUES ($1,$2,$3) \
+                    ON CONFLICT (fixid) DO UPDATE SET controllerid = EXCLUDED.controllerid, port = EXCLUDED.port";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(fixid as i32), &(port.controllerid as i32), &(port.port as i32)])
+                    .map_err(Error::Postgres));
+            },
+            None => {
+                let statement = "DELETE FROM fixture_ports WHERE fixid = $1";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(fixid as i32)])
+                    .map_err(Error::Postgres));
+            },
+        }
+        Ok(())
+    }
+
+    fn get_ports(&self, chan_ids: &Vec<u32>) -> Result<BTreeMap<u32, PortAssignment>, Error> {
+        let query = "SELECT cp.chanid,NULL::integer,cp.controllerid,cp.port \
+            FROM channel_ports cp \
+            WHERE cp.chanid = ANY($1) \
+            UNION ALL \
+            SELECT c.chanid,fp.fixid,fp.controllerid,fp.port \
+            FROM unnest($1::integer[]) AS c(chanid) \
+            INNER JOIN fixtures f ON c.chanid = ANY(f.channels) \
+            INNER JOIN fixture_ports fp ON fp.fixid = f.fixid \
+            ORDER BY 1, 2 NULLS FIRST";
+        let chan_ids = chan_ids.iter()
+            .map(|chanid| *chanid as i32)
+            .collect::<Vec<i32>>();
+        let results = try!(
+            self.conn.query(query, &[&chan_ids])
+            .map_err(Error::Postgres));
+
+        let rows = results.iter()
+            .map(|row| {
+                let chanid: i32 = row.get(0);
+                let fixid: Option<i32> = row.get(1);
+                let controllerid: i32 = row.get(2);
+                let port: i32 = row.get(3);
+                (chanid as u32, fixid.map(|fixid| fixid as u32), PortAssignment {
+                    controllerid: controllerid as u32,
+                    port: port as u32
+                })
+            })
+            .collect::<Vec<(u32, Option<u32>, PortAssignment)>>();
+        resolve_channel_settings(rows, "ports", Error::InvalidController)
+    }
+}
@@ -1,5 +1,7 @@
+use std::collections::BTreeMap;
+
 use error::Error;
-use project_types::{Channel, Fixture, Layout, Permission, Project, Section, Sequence, User};
+use project_types::{Channel, ChannelLoad, Controller, DimmingProfile, Fixture, Layout, LayoutPatch, PatchChange, Permission, PortAssignment, Project, Schedule, Section, Sequence, TimingMark, TimingTrack, User};
 
 
 /// Handles metadata related to channels
@@ -24,6 +26,29 @@ pub trait ChannelDao {
     fn get_last_channel(&self, name: &str) -> Result<Channel, Error>;
 }
 
+/// Handles the controllers of layouts and the controller ports channels are wired to
+pub trait ControllerDao {
+    /// Adds a controller to its layout, returning it with its new id
+    fn new_controller(&self, controller: &Controller) -> Result<Controller, Error>;
+
+    /// Retrieves all of a layout's controllers, ordered by id
+    fn get_controllers(&self, layoutid: u32) -> Result<Vec<Controller>, Error>;
+
+    /// Removes a controller, unwiring any channels and fixtures on its ports
+    fn delete_controller(&self, controllerid: u32) -> Result<(), Error>;
+
+    /// Sets the port a channel is wired to, or removes it if None
+    fn set_channel_port(&self, chanid: u32, port: Option<&PortAssignment>) -> Result<(), Error>;
+
+    /// Sets the port a fixture is wired to, or removes it if None.
+    /// It's used for the fixture's channels that have no port of their own.
+    fn set_fixture_port(&self, fixid: u32, port: Option<&PortAssignment>) -> Result<(), Error>;
+
+    /// Retrieves the port each of the given channels is wired to, by chanid.
+    /// Channels with no port of their own or from their fixture are left out.
+    fn get_ports(&self, chan_ids: &Vec<u32>) -> Result<BTreeMap<u32, PortAssignment>, Error>;
+}
+
 /// Handles the raw output data for specific channel/sequence pairs
 pub trait DataDao {
     /// Add a data entry for all given channels with default data
@@ -49,14 +74,30 @@ pub trait DataDao {
     fn update_data<'a>(&'a self, seqid: u32, chanid: u32, new_data: &'a Vec<u16>) -> Result<(), Error>;
 }
 
+/// Handles the dimming profiles of channels and fixtures
+pub trait DimmingDao {
+    /// Sets a channel's own dimming profile, or removes it if None
+    fn set_channel_dimming(&self, chanid: u32, profile: Option<&DimmingProfile>) -> Result<(), Error>;
+
+    /// Sets a fixture's dimming profile, or removes it if None.
+    /// It's used by the fixture's channels that have no profile of their own.
+    fn set_fixture_dimming(&self, fixid: u32, profile: Option<&DimmingProfile>) -> Result<(), Error>;
+
+    /// Retrieves the dimming profile each of the given channels uses, by chanid.
+    /// Channels with no profile of their own or from their fixture are left out.
+    fn get_dimming(&self, chan_ids: &Vec<u32>) -> Result<BTreeMap<u32, DimmingProfile>, Error>;
+}
+
 /// Handles metadata related to fixtures
 pub trait FixtureDao {
     /// Creates a new fixture made up of some channels
     fn new_fixture(
         &self, 
         name: &str,
+        fixture_type: Option<&str>,
         location: (i32, i32, i32),
         rotation: (i32, i32, i32),
+        size: (i32, i32, i32),
         channels: Vec<u32>
     ) -> Result<Fixture, Error>;
 
@@ -90,6 +131,9 @@ pub trait LayoutDao {
     /// Returns true if the layout exists, false otherwise
     fn layout_exists(&self, layoutid: u32) -> Result<bool, Error>;
 
+    /// Retrieves and returns the ids of all channels in a layout, sorted by dmx channel
+    fn get_channel_ids(&self, layoutid: u32) -> Result<Vec<u32>, Error>;
+
     /// Patch a channel (change a channel's dmx output channel)
     fn patch_channel(
         &self,
@@ -99,6 +143,25 @@ pub trait LayoutDao {
     ) -> Result<u64, Error>;
 }
 
+/// Handles the history of patches applied to layouts
+pub trait PatchDao {
+    /// Patches a layout's channels to their new dmx channels and records the
+    /// changes as the next version of the layout. Nothing is patched or
+    /// recorded unless every channel is.
+    fn new_patch(
+        &self,
+        layoutid: u32,
+        source: &str,
+        changes: &Vec<PatchChange>
+    ) -> Result<LayoutPatch, Error>;
+
+    /// Retrieves and returns a specific version of a layout's patches
+    fn get_patch(&self, layoutid: u32, version: u32) -> Result<LayoutPatch, Error>;
+
+    /// Retrieves and returns all patches applied to a layout, oldest first
+    fn get_patches(&self, layoutid: u32) -> Result<Vec<LayoutPatch>, Error>;
+}
+
 /// [INCOMPLETE] Handles data related to permissions
 pub trait PermissionDao {
     fn add_initial_permission(&self, root_uid: u32) -> Result<(), Error>;
@@ -106,6 +169,26 @@ pub trait PermissionDao {
     fn get_permission(&self, permid: u32) -> Result<Permission, Error>;
 }
 
+/// Handles the power drawn by channels and the limits of the circuits they're on
+pub trait PowerDao {
+    /// Sets the load of a channel, or removes it if None
+    fn set_channel_load(&self, chanid: u32, load: Option<&ChannelLoad>) -> Result<(), Error>;
+
+    /// Sets the load of each of a fixture's channels, or removes it if None.
+    /// It's used for the fixture's channels that have no load of their own.
+    fn set_fixture_load(&self, fixid: u32, load: Option<&ChannelLoad>) -> Result<(), Error>;
+
+    /// Retrieves the load of each of the given channels, by chanid.
+    /// Channels with no load of their own or from their fixture are left out.
+    fn get_loads(&self, chan_ids: &Vec<u32>) -> Result<BTreeMap<u32, ChannelLoad>, Error>;
+
+    /// Sets the most amps a circuit of a layout can draw, or removes its limit if None
+    fn set_circuit_limit(&self, layoutid: u32, circuit: &str, max_amps: Option<f64>) -> Result<(), Error>;
+
+    /// Retrieves the limit in amps of each of a layout's circuits that has one, by name
+    fn get_circuit_limits(&self, layoutid: u32) -> Result<BTreeMap<String, f64>, Error>;
+}
+
 /// Handles project metadata
 pub trait ProjectDao {
     /// Create a new project
@@ -116,6 +199,12 @@ pub trait ProjectDao {
 
     /// Update a project's metadata
     fn update_project(&self, new_project: Project) -> Result<(), Error>;
+
+    /// Set the schedule a project's show runs on
+    fn set_schedule(&self, name: &str, schedule: &Schedule) -> Result<(), Error>;
+
+    /// Fetch a project's schedule
+    fn get_schedule(&self, name: &str) -> Result<Schedule, Error>;
 }
 
 /// Handles metadata about sections of sequences
@@ -129,6 +218,9 @@ pub trait SequenceDao {
     /// Retrieves and returns a sequence
     fn get_sequence(&self, seqid: u32) -> Result<Sequence, Error>;
 
+    /// Retrieves and returns every sequence, ordered by id
+    fn get_all_sequences(&self) -> Result<Vec<Sequence>, Error>;
+
     /// Retrieves and returns the last sequence added with the given name
     fn get_last_sequence(&self, name: &str) -> Result<Sequence, Error>;
 
@@ -138,6 +230,19 @@ pub trait SequenceDao {
     /// Sets a sequence's layout
     fn set_layout(&self, seqid: u32, layout_id: u32) -> Result<(), Error>;
 
+    /// Updates a sequence's metadata
+    fn update_sequence(&self, sequence: &Sequence) -> Result<(), Error>;
+
+    /// Stores a sequence's new frame duration and number of frames along with
+    /// each channel's resampled data, rescaling its sections' frames to match.
+    /// Nothing is changed unless everything is.
+    fn retime_sequence(
+        &self,
+        sequence: &Sequence,
+        old_frame_duration_ms: u32,
+        data: &Vec<(u32, Vec<u16>)>
+    ) -> Result<(), Error>;
+
     /// Returns true if the sequence exists, false otherwise
     fn sequence_exists(&self, seqid: u32) -> Result<bool, Error>;
 
@@ -145,6 +250,24 @@ pub trait SequenceDao {
     fn get_channel_ids(&self, seqid: u32) -> Result<Vec<u32>, Error>;
 }
 
+/// Handles a sequence's timing tracks and the energy envelopes of its music
+pub trait TimingDao {
+    /// Creates a timing track, replacing any the sequence has with the same name
+    fn set_timing_track(&self, seqid: u32, name: &str, marks: &Vec<TimingMark>) -> Result<TimingTrack, Error>;
+
+    /// Retrieves and returns all of a sequence's timing tracks, ordered by name
+    fn get_timing_tracks(&self, seqid: u32) -> Result<Vec<TimingTrack>, Error>;
+
+    /// Deletes one of a sequence's timing tracks
+    fn delete_timing_track(&self, seqid: u32, name: &str) -> Result<(), Error>;
+
+    /// Stores the energy envelope of a band of a sequence's music, replacing any already stored
+    fn set_envelope(&self, seqid: u32, band: &str, data: &Vec<u16>) -> Result<(), Error>;
+
+    /// Retrieves and returns the energy envelope of a band of a sequence's music
+    fn get_envelope(&self, seqid: u32, band: &str) -> Result<Vec<u16>, Error>;
+}
+
 /// Handles user data
 pub trait UserDao {
     /// Creates a project's initial root user
@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+
 use postgres::{Connection, TlsMode};
 
 use error::Error;
@@ -10,13 +13,18 @@ pub struct DaoPostgres {
 }
 
 pub type ChannelDaoPostgres = DaoPostgres;
+pub type ControllerDaoPostgres = DaoPostgres;
 pub type DataDaoPostgres = DaoPostgres;
+pub type DimmingDaoPostgres = DaoPostgres;
 pub type FixtureDaoPostgres = DaoPostgres;
 pub type LayoutDaoPostgres = DaoPostgres;
+pub type PatchDaoPostgres = DaoPostgres;
 pub type PermissionDaoPostgres = DaoPostgres;
+pub type PowerDaoPostgres = DaoPostgres;
 pub type ProjectDaoPostgres = DaoPostgres;
 pub type SectionDaoPostgres = DaoPostgres;
 pub type SequenceDaoPostgres = DaoPostgres;
+pub type TimingDaoPostgres = DaoPostgres;
 pub type UserDaoPostgres = DaoPostgres;
 
 
@@ -35,3 +43,40 @@ fn get_connection() -> Result<Connection, Error> {
         .map_err(Error::PostgresConnection)
 }
 
+/// Picks the setting each channel ends up with from rows of
+/// (chanid, fixid, setting), where a channel's own setting has no fixid.
+/// A channel's own setting is used over its fixture's. A channel without one
+/// whose fixtures have different settings can't be resolved, so fails.
+pub fn resolve_channel_settings<T: PartialEq>(
+    rows: Vec<(u32, Option<u32>, T)>,
+    setting_name: &str,
+    to_error: fn(String) -> Error
+) -> Result<BTreeMap<u32, T>, Error> {
+
+    // Take channels' own settings first, so fixtures never conflict with them
+    let (own_rows, fixture_rows): (Vec<_>, Vec<_>) = rows.into_iter()
+        .partition(|&(_, fixid, _)| fixid.is_none());
+    let mut settings: BTreeMap<u32, (Option<u32>, T)> = own_rows.into_iter()
+        .map(|(chanid, fixid, setting)| (chanid, (fixid, setting)))
+        .collect();
+    for (chanid, fixid, setting) in fixture_rows {
+        match settings.entry(chanid) {
+            Entry::Vacant(entry) => {
+                entry.insert((fixid, setting));
+            },
+            Entry::Occupied(entry) => match (fixid, entry.get().0) {
+                (Some(fixid), Some(other_fixid)) => {
+                    if entry.get().1 != setting {
+                        return Err(to_error(format!(
+                            "Channel {} is in fixtures {} and {}, which have different {}",
+                            chanid, other_fixid, fixid, setting_name)));
+                    }
+                },
+                _ => (),
+            },
+        }
+    }
+    Ok(settings.into_iter()
+        .map(|(chanid, (_, setting))| (chanid, setting))
+        .collect())
+}
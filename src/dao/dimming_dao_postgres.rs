@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use dao::{DimmingDao, DimmingDaoPostgres};
+use dao::daos_postgres::resolve_channel_settings;
+use error::Error;
+use project_types::{DimmingCurve, DimmingProfile};
+
+
+impl DimmingDao for DimmingDaoPostgres {
+
+    fn set_channel_dimming(&self, chanid: u32, profile: Option<&DimmingProfile>) -> Result<(), Error> {
+        match profile {
+            Some(profile) => {
+                let statement = "INSERT INTO channel_dimming (chanid,curve,min_value,max_value) \
+                    VALUES ($1,$2,$3,$4) \
+                    ON CONFLICT (chanid) DO UPDATE SET \
+                        curve = EXCLUDED.curve,\
+                        min_value = EXCLUDED.min_value,\
+                        max_value = EXCLUDED.max_value";
+                let _ = try!(
+                    self.conn.execute(
+                        statement,
+                        &[
+                            &(chanid as i32),
+                            &profile.curve.to_string(),
+                            &(profile.min as i32),
+                            &(profile.max as i32)
+                        ])
+                    .map_err(Error::Postgres));
+            },
+            None => {
+                let statement = "DELETE FROM channel_dimming WHERE chanid = $1";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(chanid as i32)])
+                    .map_err(Error::Postgres));
+            },
+        }
+        Ok(())
+    }
+
+    fn set_fixture_dimming(&self, fixid: u32, profile: Option<&DimmingProfile>) -> Result<(), Error> {
+        match profile {
+            Some(profile) => {
+                let statement = "INSERT INTO fixture_dimming (fixid,curve,min_value,max_value) \
+                    VALUES ($1,$2,$3,$4) \
+                    ON CONFLICT (fixid) DO UPDATE SET \
+                        curve = EXCLUDED.curve,\
+                        min_value = EXCLUDED.min_value,\
+                        max_value = EXCLUDED.max_value";
+                let _ = try!(
+                    self.conn.execute(
+                        statement,
+                        &[
+                            &(fixid as i32),
+                            &profile.curve.to_string(),
+                            &(profile.min as i32),
+                            &(profile.max as i32)
+                        ])
+                    .map_err(Error::Postgres));
+            },
+            None => {
+                let statement = "DELETE FROM fixture_dimming WHERE fixid = $1";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(fixid as i32)])
+                    .map_err(Error::Postgres));
+            },
+        }
+        Ok(())
+    }
+
+    fn get_dimming(&self, chan_ids: &Vec<u32>) -> Result<BTreeMap<u32, DimmingProfile>, Error> {
+        let query = "SELECT cd.chanid,NULL::integer,cd.curve,cd.min_value,cd.max_value \
+            FROM channel_dimming cd \
+            WHERE cd.chanid = ANY($1) \
+            UNION ALL \
+            SELECT c.chanid,fd.fixid,fd.curve,fd.min_value,fd.max_value \
+            FROM unnest($1::integer[]) AS c(chanid) \
+            INNER JOIN fixtures f ON c.chanid = ANY(f.channels) \
+            INNER JOIN fixture_dimming fd ON fd.fixid = f.fixid \
+            ORDER BY 1, 2 NULLS FIRST";
+        let chan_ids = chan_ids.iter()
+            .map(|chanid| *chanid as i32)
+            .collect::<Vec<i32>>();
+        let results = try!(
+            self.conn.query(query, &[&chan_ids])
+            .map_err(Error::Postgres));
+
+        let mut rows = Vec::new();
+        for row in results.iter() {
+            let chanid: i32 = row.get(0);
+            let fixid: Option<i32> = row.get(1);
+            let curve: String = row.get(2);
+            let min: i32 = row.get(3);
+            let max: i32 = row.get(4);
+            let curve = try!(curve.parse::<DimmingCurve>());
+            let profile = try!(DimmingProfile::new(curve, min as u16, max as u16));
+            rows.push((chanid as u32, fixid.map(|fixid| fixid as u32), profile));
+        }
+        resolve_channel_settings(rows, "dimming profiles", Error::InvalidDimming)
+    }
+}
@@ -7,12 +7,15 @@ impl FixtureDao for FixtureDaoPostgres {
     fn new_fixture(
         &self, 
         name: &str,
+        fixture_type: Option<&str>,
         location: (i32, i32, i32),
         rotation: (i32, i32, i32),
+        size: (i32, i32, i32),
         channels: Vec<u32>
     ) -> Result<Fixture, Error> {
-        let statement = "INSERT INTO fixtures (name,location_x,location_y,location_z,\
-            rotation_a,rotation_b,rotation_c,channels) VALUES ($1,$2,$3,$4,$5,$6,$7,$8)";
+        let statement = "INSERT INTO fixtures (name,fixture_type,location_x,location_y,location_z,\
+            rotation_a,rotation_b,rotation_c,size_x,size_y,size_z,channels) \
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12)";
         let channels_i32 = channels.iter()
             .map(|channel| *channel as i32)
             .collect::<Vec<i32>>();
@@ -21,12 +24,16 @@ impl FixtureDao for FixtureDaoPostgres {
                 statement,
                 &[
                     &name.to_owned(),
+                    &fixture_type.map(|fixture_type| fixture_type.to_owned()),
                     &location.0,
                     &location.1,
                     &location.2,
                     &rotation.0,
                     &rotation.1,
                     &rotation.2,
+                    &size.0,
+                    &size.1,
+                    &size.2,
                     &channels_i32
                 ])
             .map_err(Error::Postgres));
@@ -37,7 +44,7 @@ impl FixtureDao for FixtureDaoPostgres {
 
     fn get_last_fixture(&self, name: &str) -> Result<Fixture, Error> {
         let query = "SELECT fixid,location_x,location_y,location_z,rotation_a,rotation_b,\
-            rotation_c,channels FROM fixtures WHERE name = $1 ORDER BY fixid DESC";
+            rotation_c,size_x,size_y,size_z,channels,fixture_type FROM fixtures WHERE name = $1 ORDER BY fixid DESC";
         let results = try!(
             self.conn.query(query, &[&name.to_owned()])
             .map_err(Error::Postgres));
@@ -55,22 +62,62 @@ impl FixtureDao for FixtureDaoPostgres {
         let rotation_a: i32 = row.get(4);
         let rotation_b: i32 = row.get(5);
         let rotation_c: i32 = row.get(6);
-        let channels_i32: Vec<i32> = row.get(7);
+        let size_x: i32 = row.get(7);
+        let size_y: i32 = row.get(8);
+        let size_z: i32 = row.get(9);
+        let channels_i32: Vec<i32> = row.get(10);
+        let fixture_type: Option<String> = row.get(11);
         let channels = channels_i32.iter()
             .map(|channel| *channel as u32)
             .collect::<Vec<u32>>();
         Ok(Fixture {
             fixid: fixid as u32,
             name: name.to_owned(),
+            fixture_type: fixture_type,
             location: (location_x, location_y, location_z),
             rotation: (rotation_a, rotation_b, rotation_c),
+            size: (size_x, size_y, size_z),
             channels: channels
         })
     }
 
-    #[allow(unused_variables)]
     fn get_fixture(&self, fixid: u32) -> Result<Fixture, Error> {
-        Err(Error::TodoErr)
+        let query = "SELECT name,location_x,location_y,location_z,rotation_a,rotation_b,\
+            rotation_c,size_x,size_y,size_z,channels,fixture_type FROM fixtures WHERE fixid = $1";
+        let results = try!(
+            self.conn.query(query, &[&(fixid as i32)])
+            .map_err(Error::Postgres));
+        match results.len() {
+            0 => Err(Error::FixtureNotFound(fixid)),
+            1 => {
+                let row = results.get(0);
+                let name: String = row.get(0);
+                let location_x: i32 = row.get(1);
+                let location_y: i32 = row.get(2);
+                let location_z: i32 = row.get(3);
+                let rotation_a: i32 = row.get(4);
+                let rotation_b: i32 = row.get(5);
+                let rotation_c: i32 = row.get(6);
+                let size_x: i32 = row.get(7);
+                let size_y: i32 = row.get(8);
+                let size_z: i32 = row.get(9);
+                let channels_i32: Vec<i32> = row.get(10);
+                let fixture_type: Option<String> = row.get(11);
+                let channels = channels_i32.iter()
+                    .map(|channel| *channel as u32)
+                    .collect::<Vec<u32>>();
+                Ok(Fixture {
+                    fixid: fixid,
+                    name: name,
+                    fixture_type: fixture_type,
+                    location: (location_x, location_y, location_z),
+                    rotation: (rotation_a, rotation_b, rotation_c),
+                    size: (size_x, size_y, size_z),
+                    channels: channels
+                })
+            },
+            x => Err(Error::InvalidNumResults(x)),
+        }
     }
 
     fn fixture_exists(&self, fixid: u32) -> Result<bool, Error> {
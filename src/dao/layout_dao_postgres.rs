@@ -118,4 +118,22 @@ impl LayoutDao for LayoutDaoPostgres {
             .map_err(Error::Postgres));
         Ok(results.len() > 0)
     }
+
+    fn get_channel_ids(&self, layout_id: u32) -> Result<Vec<u32>, Error> {
+        let query = "SELECT chanid FROM layouts l \
+            INNER JOIN fixtures f ON f.fixid = ANY(l.fixtures) \
+            INNER JOIN channels c ON c.chanid = ANY(f.channels) \
+            WHERE l.layoutid = $1 \
+            ORDER BY c.channel_dmx";
+        let results = try!(
+            self.conn.query(query, &[&(layout_id as i32)])
+            .map_err(Error::Postgres));
+        let chan_ids = results.iter()
+            .map(|row| {
+                let id: i32 = row.get(0);
+                id as u32
+            })
+            .collect::<Vec<u32>>();
+        Ok(chan_ids)
+    }
 }
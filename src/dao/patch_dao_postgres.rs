@@ -0,0 +1,127 @@
+use postgres::rows::Row;
+
+use dao::{PatchDao, PatchDaoPostgres};
+use error::Error;
+use project_types::{LayoutPatch, PatchChange};
+
+
+impl PatchDao for PatchDaoPostgres {
+
+    fn new_patch(
+        &self,
+        layoutid: u32,
+        source: &str,
+        changes: &Vec<PatchChange>
+    ) -> Result<LayoutPatch, Error> {
+        let trans = try!(self.conn.transaction().map_err(Error::Postgres));
+
+        let statement = "UPDATE channels SET channel_dmx=$1 \
+            WHERE chanid = get_internal_chan_id($2, $3)";
+        {
+            let patch_stmt = try!(trans.prepare(statement).map_err(Error::Postgres));
+            for change in changes.iter() {
+                let rows_altered = try!(
+                    patch_stmt.execute(&[
+                        &(change.new_dmx as i32),
+                        &(layoutid as i32),
+                        &(change.channel_internal as i32)
+                    ])
+                    .map_err(Error::Postgres));
+                // Returning early drops the transaction, rolling back channels already patched
+                match rows_altered {
+                    1 => (),
+                    0 => return Err(Error::InvalidPatch(format!(
+                        "Internal channel {} is not in layout {}", change.channel_internal, layoutid))),
+                    x => return Err(Error::InvalidNumResults(x as usize)),
+                }
+            }
+        }
+
+        // The next version is one more than the latest version of this layout
+        let statement = "INSERT INTO layout_patches \
+            (layoutid,version,source,channels_internal,old_dmx,new_dmx) \
+            VALUES ($1,\
+                (SELECT COALESCE(MAX(version),0)+1 FROM layout_patches WHERE layoutid = $1),\
+                $2,$3,$4,$5) \
+            RETURNING patchid,version,source,channels_internal,old_dmx,new_dmx";
+        let channels_internal = changes.iter()
+            .map(|change| change.channel_internal as i32)
+            .collect::<Vec<i32>>();
+        let old_dmx = changes.iter()
+            .map(|change| change.old_dmx as i32)
+            .collect::<Vec<i32>>();
+        let new_dmx = changes.iter()
+            .map(|change| change.new_dmx as i32)
+            .collect::<Vec<i32>>();
+        let results = try!(
+            trans.query(
+                statement,
+                &[
+                    &(layoutid as i32),
+                    &source.to_owned(),
+                    &channels_internal,
+                    &old_dmx,
+                    &new_dmx
+                ])
+            .map_err(Error::Postgres));
+        let patch = match results.len() {
+            1 => patch_from_row(layoutid, &results.get(0)),
+            x => return Err(Error::InvalidNumResults(x)),
+        };
+
+        try!(trans.commit().map_err(Error::Postgres));
+        Ok(patch)
+    }
+
+    fn get_patch(&self, layoutid: u32, version: u32) -> Result<LayoutPatch, Error> {
+        let query = "SELECT patchid,version,source,channels_internal,old_dmx,new_dmx \
+            FROM layout_patches WHERE layoutid = $1 AND version = $2";
+        let results = try!(
+            self.conn.query(query, &[&(layoutid as i32), &(version as i32)])
+            .map_err(Error::Postgres));
+        match results.len() {
+            0 => Err(Error::PatchNotFound(layoutid, version)),
+            1 => Ok(patch_from_row(layoutid, &results.get(0))),
+            x => Err(Error::InvalidNumResults(x)),
+        }
+    }
+
+    fn get_patches(&self, layoutid: u32) -> Result<Vec<LayoutPatch>, Error> {
+        let query = "SELECT patchid,version,source,channels_internal,old_dmx,new_dmx \
+            FROM layout_patches WHERE layoutid = $1 ORDER BY version";
+        let results = try!(
+            self.conn.query(query, &[&(layoutid as i32)])
+            .map_err(Error::Postgres));
+        let patches = results.iter()
+            .map(|row| patch_from_row(layoutid, &row))
+            .collect::<Vec<LayoutPatch>>();
+        Ok(patches)
+    }
+}
+
+/// Builds a LayoutPatch from a row of 
+/// (patchid,version,source,channels_internal,old_dmx,new_dmx)
+fn patch_from_row(layoutid: u32, row: &Row) -> LayoutPatch {
+    let patchid: i32 = row.get(0);
+    let version: i32 = row.get(1);
+    let source: String = row.get(2);
+    let channels_internal: Vec<i32> = row.get(3);
+    let old_dmx: Vec<i32> = row.get(4);
+    let new_dmx: Vec<i32> = row.get(5);
+    let changes = channels_internal.iter()
+        .zip(old_dmx.iter())
+        .zip(new_dmx.iter())
+        .map(|((internal, old), new)| PatchChange {
+            channel_internal: *internal as u32,
+            old_dmx: *old as u32,
+            new_dmx: *new as u32
+        })
+        .collect::<Vec<PatchChange>>();
+    LayoutPatch {
+        patchid: patchid as u32,
+        layout_id: layoutid,
+        version: version as u32,
+        source: source,
+        changes: changes
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use dao::{PowerDao, PowerDaoPostgres};
+use dao::daos_postgres::resolve_channel_settings;
+use error::Error;
+use project_types::ChannelLoad;
+
+
+impl PowerDao for PowerDaoPostgres {
+
+    fn set_channel_load(&self, chanid: u32, load: Option<&ChannelLoad>) -> Result<(), Error> {
+        match load {
+            Some(load) => {
+                let statement = "INSERT INTO channel_loads (chanid,watts,circuit) VALUES ($1,$2,$3) \
+                    ON CONFLICT (chanid) DO UPDATE SET watts = EXCLUDED.watts, circuit = EXCLUDED.circuit";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(chanid as i32), &load.watts, &load.circuit])
+                    .map_err(Error::Postgres));
+            },
+            None => {
+                let statement = "DELETE FROM channel_loads WHERE chanid = $1";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(chanid as i32)])
+                    .map_err(Error::Postgres));
+            },
+        }
+        Ok(())
+    }
+
+    fn set_fixture_load(&self, fixid: u32, load: Option<&ChannelLoad>) -> Result<(), Error> {
+        match load {
+            Some(load) => {
+                let statement = "INSERT INTO fixture_loads (fixid,watts,circuit) VALUES ($1,$2,$3) \
+                    ON CONFLICT (fixid) DO UPDATE SET watts = EXCLUDED.watts, circuit = EXCLUDED.circuit";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(fixid as i32), &load.watts, &load.circuit])
+                    .map_err(Error::Postgres));
+            },
+            None => {
+                let statement = "DELETE FROM fixture_loads WHERE fixid = $1";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(fixid as i32)])
+                    .map_err(Error::Postgres));
+            },
+        }
+        Ok(())
+    }
+
+    fn get_loads(&self, chan_ids: &Vec<u32>) -> Result<BTreeMap<u32, ChannelLoad>, Error> {
+        let query = "SELECT cl.chanid,NULL::integer,cl.watts,cl.circuit \
+            FROM channel_loads cl \
+            WHERE cl.chanid = ANY($1) \
+            UNION ALL \
+            SELECT c.chanid,fl.fixid,fl.watts,fl.circuit \
+            FROM unnest($1::integer[]) AS c(chanid) \
+            INNER JOIN fixtures f ON c.chanid = ANY(f.channels) \
+            INNER JOIN fixture_loads fl ON fl.fixid = f.fixid \
+            ORDER BY 1, 2 NULLS FIRST";
+        let chan_ids = chan_ids.iter()
+            .map(|chanid| *chanid as i32)
+            .collect::<Vec<i32>>();
+        let results = try!(
+            self.conn.query(query, &[&chan_ids])
+            .map_err(Error::Postgres));
+
+        let mut rows = Vec::new();
+        for row in results.iter() {
+            let chanid: i32 = row.get(0);
+            let fixid: Option<i32> = row.get(1);
+            let watts: f64 = row.get(2);
+            let circuit: Option<String> = row.get(3);
+            let load = try!(ChannelLoad::new(watts, circuit));
+            rows.push((chanid as u32, fixid.map(|fixid| fixid as u32), load));
+        }
+        resolve_channel_settings(rows, "loads", Error::InvalidPower)
+    }
+
+    fn set_circuit_limit(&self, layoutid: u32, circuit: &str, max_amps: Option<f64>) -> Result<(), Error> {
+        match max_amps {
+            Some(max_amps) => {
+                let statement = "INSERT INTO circuit_limits (layoutid,circuit,max_amps) VALUES ($1,$2,$3) \
+                    ON CONFLICT (layoutid,circuit) DO UPDATE SET max_amps = EXCLUDED.max_amps";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(layoutid as i32), &circuit.to_owned(), &max_amps])
+                    .map_err(Error::Postgres));
+            },
+            None => {
+                let statement = "DELETE FROM circuit_limits WHERE layoutid = $1 AND circuit = $2";
+                let _ = try!(
+                    self.conn.execute(statement, &[&(layoutid as i32), &circuit.to_owned()])
+                    .map_err(Error::Postgres));
+            },
+        }
+        Ok(())
+    }
+
+    fn get_circuit_limits(&self, layoutid: u32) -> Result<BTreeMap<String, f64>, Error> {
+        let query = "SELECT circuit,max_amps FROM circuit_limits WHERE layoutid = $1";
+        let results = try!(
+            self.conn.query(query, &[&(layoutid as i32)])
+            .map_err(Error::Postgres));
+        let limits = results.iter()
+            .map(|row| {
+                let circuit: String = row.get(0);
+                let max_amps: f64 = row.get(1);
+                (circuit, max_amps)
+            })
+            .collect::<BTreeMap<String, f64>>();
+        Ok(limits)
+    }
+}
@@ -1,4 +1,4 @@
-use project_types::Project;
+use project_types::{Project, Schedule};
 use error::Error;
 use dao::{ProjectDao, ProjectDaoPostgres};
 
@@ -56,4 +56,34 @@ impl ProjectDao for ProjectDaoPostgres {
             .map_err(Error::Postgres));
         Ok(())
     }
+
+    fn set_schedule(&self, name: &str, schedule: &Schedule) -> Result<(), Error> {
+        let statement = "UPDATE projects SET schedule = $1 WHERE name = $2";
+        let schedule_json = try!(schedule.to_json());
+        let num_updated = try!(
+            self.conn.execute(statement, &[&schedule_json, &name.to_owned()])
+            .map_err(Error::Postgres));
+        match num_updated {
+            0 => Err(Error::ProjectNotFound(name.to_owned())),
+            _ => Ok(()),
+        }
+    }
+
+    fn get_schedule(&self, name: &str) -> Result<Schedule, Error> {
+        let query = "SELECT schedule FROM projects WHERE name = $1";
+        let results = try!(
+            self.conn.query(query, &[&name.to_owned()])
+            .map_err(Error::Postgres));
+        match results.len() {
+            0 => Err(Error::ProjectNotFound(name.to_owned())),
+            1 => {
+                let schedule_json: Option<String> = results.get(0).get(0);
+                match schedule_json {
+                    Some(schedule_json) => Schedule::from_json(&schedule_json),
+                    None => Err(Error::ScheduleNotFound(name.to_owned())),
+                }
+            },
+            x => Err(Error::InvalidNumResults(x)),
+        }
+    }
 }
@@ -26,8 +26,8 @@ impl SequenceDao for SequenceDaoPostgres {
     }
 
     fn get_sequence(&self, seqid: u32) -> Result<Sequence, Error> {
-        let query = "SELECT name,music_file_name,music_dur_sec,frame_dur_ms,num_frames,layout_id \
-        FROM sequences WHERE seqid = $1";
+        let query = "SELECT name,music_file_name,music_dur_ms,frame_dur_ms,num_frames,layout_id,\
+        music_offset_ms FROM sequences WHERE seqid = $1";
         let results = try!(
             self.conn.query(query, &[&(seqid as i32)])
             .map_err(Error::Postgres));
@@ -37,27 +37,56 @@ impl SequenceDao for SequenceDaoPostgres {
                 let row = results.get(0);
                 let name: String = row.get(0);
                 let music_file_name: String = row.get(1);
-                let music_dur_sec: i32 = row.get(2);
+                let music_dur_ms: i32 = row.get(2);
                 let frame_dur_ms: i32 = row.get(3);
                 let num_frames: i32 = row.get(4);
                 let layout_id: i32 = row.get(5);
+                let music_offset_ms: i32 = row.get(6);
                 Ok(Sequence {
                     seqid: seqid,
                     name: name,
                     music_file_name: music_file_name,
-                    music_duration_sec: music_dur_sec as u32,
+                    music_duration_ms: music_dur_ms as u32,
                     frame_duration_ms: frame_dur_ms as u32,
                     num_frames: num_frames as u32,
-                    layout_id: layout_id as u32
+                    layout_id: layout_id as u32,
+                    music_offset_ms: music_offset_ms
                 })
             },
             x => Err(Error::InvalidNumResults(x)),
         }
     }
 
+    fn get_all_sequences(&self) -> Result<Vec<Sequence>, Error> {
+        let query = "SELECT seqid,name,music_file_name,music_dur_ms,frame_dur_ms,num_frames,layout_id,\
+        music_offset_ms FROM sequences ORDER BY seqid";
+        let results = try!(
+            self.conn.query(query, &[])
+            .map_err(Error::Postgres));
+        Ok(results.iter()
+            .map(|row| {
+                let seqid: i32 = row.get(0);
+                let music_dur_ms: i32 = row.get(3);
+                let frame_dur_ms: i32 = row.get(4);
+                let num_frames: i32 = row.get(5);
+                let layout_id: i32 = row.get(6);
+                Sequence {
+                    seqid: seqid as u32,
+                    name: row.get(1),
+                    music_file_name: row.get(2),
+                    music_duration_ms: music_dur_ms as u32,
+                    frame_duration_ms: frame_dur_ms as u32,
+                    num_frames: num_frames as u32,
+                    layout_id: layout_id as u32,
+                    music_offset_ms: row.get(7)
+                }
+            })
+            .collect())
+    }
+
     fn get_last_sequence(&self, name: &str) -> Result<Sequence, Error> {
-        let query = "SELECT seqid,music_file_name,music_dur_sec,frame_dur_ms,num_frames,layout_id \
-        FROM sequences WHERE name = $1 ORDER BY seqid DESC";
+        let query = "SELECT seqid,music_file_name,music_dur_ms,frame_dur_ms,num_frames,layout_id,\
+        music_offset_ms FROM sequences WHERE name = $1 ORDER BY seqid DESC";
         let results = try!(
             self.conn.query(query, &[&name.to_owned()])
             .map_err(Error::Postgres));
@@ -68,25 +97,27 @@ impl SequenceDao for SequenceDaoPostgres {
         let row = results.get(0);
         let seqid: i32 = row.get(0);
         let music_file_name: String = row.get(1);
-        let music_dur_sec: i32 = row.get(2);
+        let music_dur_ms: i32 = row.get(2);
         let frame_dur_ms: i32 = row.get(3);
         let num_frames: i32 = row.get(4);
         let layout_id: i32 = row.get(5);
+        let music_offset_ms: i32 = row.get(6);
         Ok(Sequence {
             seqid: seqid as u32,
             name: name.to_owned(),
             music_file_name: music_file_name,
-            music_duration_sec: music_dur_sec as u32,
+            music_duration_ms: music_dur_ms as u32,
             frame_duration_ms: frame_dur_ms as u32,
             num_frames: num_frames as u32,
-            layout_id: layout_id as u32
+            layout_id: layout_id as u32,
+            music_offset_ms: music_offset_ms
         })
     }
 
     fn new_sequence(&self, sequence: &Sequence) -> Result<Sequence, Error> {
-        let statement = "INSERT INTO sequences (name,music_file_name,music_dur_sec,\
-            frame_dur_ms,num_frames,layout_id) VALUES ($1,$2,$3,$4,$5,$6)";
-        let music_dur = sequence.music_duration_sec as i32;
+        let statement = "INSERT INTO sequences (name,music_file_name,music_dur_ms,\
+            frame_dur_ms,num_frames,layout_id,music_offset_ms) VALUES ($1,$2,$3,$4,$5,$6,$7)";
+        let music_dur = sequence.music_duration_ms as i32;
         let frame_dur = sequence.frame_duration_ms as i32;
         let num_frames = sequence.num_frames as i32;
         let layout_id = sequence.layout_id as i32;
@@ -99,7 +130,8 @@ impl SequenceDao for SequenceDaoPostgres {
                     &music_dur,
                     &frame_dur,
                     &num_frames,
-                    &layout_id
+                    &layout_id,
+                    &sequence.music_offset_ms
                 ])
             .map_err(Error::Postgres));
         let sequence = try!(self.get_last_sequence(&sequence.name));
@@ -126,4 +158,74 @@ impl SequenceDao for SequenceDaoPostgres {
             .map_err(Error::Postgres));
         Ok(())
     }
+
+    fn update_sequence(&self, sequence: &Sequence) -> Result<(), Error> {
+        let statement = "UPDATE sequences SET name = $1, music_file_name = $2, music_dur_ms = $3, \
+            frame_dur_ms = $4, num_frames = $5, layout_id = $6, music_offset_ms = $7 WHERE seqid = $8";
+        let _ = try!(
+            self.conn.execute(
+                statement,
+                &[
+                    &sequence.name.to_owned(),
+                    &sequence.music_file_name.to_owned(),
+                    &(sequence.music_duration_ms as i32),
+                    &(sequence.frame_duration_ms as i32),
+                    &(sequence.num_frames as i32),
+                    &(sequence.layout_id as i32),
+                    &sequence.music_offset_ms,
+                    &(sequence.seqid as i32)
+                ])
+            .map_err(Error::Postgres));
+        Ok(())
+    }
+
+    fn retime_sequence(
+        &self,
+        sequence: &Sequence,
+        old_frame_duration_ms: u32,
+        data: &Vec<(u32, Vec<u16>)>
+    ) -> Result<(), Error> {
+        let trans = try!(self.conn.transaction().map_err(Error::Postgres));
+
+        let statement = "UPDATE channel_data SET data = $1 WHERE seqid = $2 AND chanid = $3";
+        {
+            let update_stmt = try!(trans.prepare(statement).map_err(Error::Postgres));
+            for &(chanid, ref new_data) in data {
+                let new_data_i32 = new_data.iter()
+                    .map(|frame| *frame as i32)
+                    .collect::<Vec<i32>>();
+                let _ = try!(
+                    update_stmt.execute(&[&new_data_i32, &(sequence.seqid as i32), &(chanid as i32)])
+                    .map_err(Error::Postgres));
+            }
+        }
+
+        let statement = "UPDATE sequences SET frame_dur_ms = $1, num_frames = $2 WHERE seqid = $3";
+        let _ = try!(
+            trans.execute(
+                statement,
+                &[
+                    &(sequence.frame_duration_ms as i32),
+                    &(sequence.num_frames as i32),
+                    &(sequence.seqid as i32)
+                ])
+            .map_err(Error::Postgres));
+
+        // Sections keep covering the same time, so starts round down and ends up
+        let statement = "UPDATE sections SET \
+                t_start = (t_start * $1) / $2,\
+                t_end = (t_end * $1 + $2 - 1) / $2 \
+            WHERE seqid = $3";
+        let _ = try!(
+            trans.execute(
+                statement,
+                &[
+                    &(old_frame_duration_ms as i32),
+                    &(sequence.frame_duration_ms as i32),
+                    &(sequence.seqid as i32)
+                ])
+            .map_err(Error::Postgres));
+
+        trans.commit().map_err(Error::Postgres)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,124 @@
+use postgres::rows::Row;
+
+use dao::{TimingDao, TimingDaoPostgres};
+use error::Error;
+use project_types::{TimingMark, TimingTrack};
+
+
+impl TimingDao for TimingDaoPostgres {
+
+    fn set_timing_track(&self, seqid: u32, name: &str, marks: &Vec<TimingMark>) -> Result<TimingTrack, Error> {
+        let statement = "INSERT INTO timing_tracks (seqid,name,mark_starts,mark_ends,mark_labels) \
+            VALUES ($1,$2,$3,$4,$5) \
+            ON CONFLICT (seqid,name) DO UPDATE SET \
+                mark_starts = EXCLUDED.mark_starts,\
+                mark_ends = EXCLUDED.mark_ends,\
+                mark_labels = EXCLUDED.mark_labels \
+            RETURNING trackid,name,mark_starts,mark_ends,mark_labels";
+        let starts = marks.iter()
+            .map(|mark| mark.start_ms as i32)
+            .collect::<Vec<i32>>();
+        let ends = marks.iter()
+            .map(|mark| mark.end_ms.unwrap_or(mark.start_ms) as i32)
+            .collect::<Vec<i32>>();
+        let labels = marks.iter()
+            .map(|mark| mark.label.clone().unwrap_or(String::new()))
+            .collect::<Vec<String>>();
+        let results = try!(
+            self.conn.query(
+                statement,
+                &[
+                    &(seqid as i32),
+                    &name.to_owned(),
+                    &starts,
+                    &ends,
+                    &labels
+                ])
+            .map_err(Error::Postgres));
+        match results.len() {
+            1 => Ok(track_from_row(seqid, &results.get(0))),
+            x => Err(Error::InvalidNumResults(x)),
+        }
+    }
+
+    fn get_timing_tracks(&self, seqid: u32) -> Result<Vec<TimingTrack>, Error> {
+        let query = "SELECT trackid,name,mark_starts,mark_ends,mark_labels \
+            FROM timing_tracks WHERE seqid = $1 ORDER BY name";
+        let results = try!(
+            self.conn.query(query, &[&(seqid as i32)])
+            .map_err(Error::Postgres));
+        let tracks = results.iter()
+            .map(|row| track_from_row(seqid, &row))
+            .collect::<Vec<TimingTrack>>();
+        Ok(tracks)
+    }
+
+    fn delete_timing_track(&self, seqid: u32, name: &str) -> Result<(), Error> {
+        let statement = "DELETE FROM timing_tracks WHERE seqid = $1 AND name = $2";
+        let num_deleted = try!(
+            self.conn.execute(statement, &[&(seqid as i32), &name.to_owned()])
+            .map_err(Error::Postgres));
+        match num_deleted {
+            0 => Err(Error::TimingTrackNotFound(seqid, name.to_owned())),
+            _ => Ok(()),
+        }
+    }
+
+    fn set_envelope(&self, seqid: u32, band: &str, data: &Vec<u16>) -> Result<(), Error> {
+        let statement = "INSERT INTO sequence_envelopes (seqid,band,data) VALUES ($1,$2,$3) \
+            ON CONFLICT (seqid,band) DO UPDATE SET data = EXCLUDED.data";
+        let data_i32 = data.iter()
+            .map(|frame| *frame as i32)
+            .collect::<Vec<i32>>();
+        let _ = try!(
+            self.conn.execute(
+                statement,
+                &[
+                    &(seqid as i32),
+                    &band.to_owned(),
+                    &data_i32
+                ])
+            .map_err(Error::Postgres));
+        Ok(())
+    }
+
+    fn get_envelope(&self, seqid: u32, band: &str) -> Result<Vec<u16>, Error> {
+        let query = "SELECT data FROM sequence_envelopes WHERE seqid = $1 AND band = $2";
+        let results = try!(
+            self.conn.query(query, &[&(seqid as i32), &band.to_owned()])
+            .map_err(Error::Postgres));
+        match results.len() {
+            0 => Err(Error::EnvelopeNotFound(seqid, band.to_owned())),
+            1 => {
+                let data: Vec<i32> = results.get(0).get(0);
+                Ok(data.iter().map(|frame| *frame as u16).collect())
+            },
+            x => Err(Error::InvalidNumResults(x)),
+        }
+    }
+}
+
+/// Builds a timing track from a row of
+/// (trackid,name,mark_starts,mark_ends,mark_labels)
+fn track_from_row(seqid: u32, row: &Row) -> TimingTrack {
+    let trackid: i32 = row.get(0);
+    let name: String = row.get(1);
+    let starts: Vec<i32> = row.get(2);
+    let ends: Vec<i32> = row.get(3);
+    let labels: Vec<String> = row.get(4);
+    let marks = starts.iter()
+        .zip(ends.iter())
+        .zip(labels.into_iter())
+        .map(|((start, end), label)| TimingMark {
+            start_ms: *start as u32,
+            end_ms: if end > start { Some(*end as u32) } else { None },
+            label: if label.is_empty() { None } else { Some(label) }
+        })
+        .collect::<Vec<TimingMark>>();
+    TimingTrack {
+        trackid: trackid as u32,
+        seqid: seqid,
+        name: name,
+        marks: marks
+    }
+}
@@ -0,0 +1,46 @@
+use std::str::FromStr;
+
+use effects::MAX_VALUE;
+use error::Error;
+
+
+/// How a layer's values are combined with the values already in a channel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendMode {
+    Normal, // Layer replaces what's below it
+    Add, // Values are added together
+    Subtract, // Layer is taken away from what's below it
+    Multiply, // Layer scales what's below it, like a mask
+    Lighten, // Brighter of the two
+    Darken, // Dimmer of the two
+}
+
+impl BlendMode {
+    /// Combines a layer's value with the value below it
+    pub fn apply(&self, below: u16, layer: u16) -> u16 {
+        match *self {
+            BlendMode::Normal => layer,
+            BlendMode::Add => below.saturating_add(layer).min(MAX_VALUE),
+            BlendMode::Subtract => below.saturating_sub(layer),
+            BlendMode::Multiply => ((below as u32 * layer as u32) / MAX_VALUE as u32) as u16,
+            BlendMode::Lighten => below.max(layer),
+            BlendMode::Darken => below.min(layer),
+        }
+    }
+}
+
+impl FromStr for BlendMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<BlendMode, Error> {
+        match s {
+            "normal" => Ok(BlendMode::Normal),
+            "add" => Ok(BlendMode::Add),
+            "subtract" => Ok(BlendMode::Subtract),
+            "multiply" => Ok(BlendMode::Multiply),
+            "lighten" => Ok(BlendMode::Lighten),
+            "darken" => Ok(BlendMode::Darken),
+            _ => Err(Error::InvalidEffect(format!("Unknown blend mode: {}", s))),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::str::FromStr;
+
+use error::Error;
+
+
+/// An RGB color, with each part from 0 to 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl Color {
+    /// Creates a color, clamping each part to 0-1
+    pub fn new(r: f32, g: f32, b: f32) -> Color {
+        Color {
+            r: r.max(0.0).min(1.0),
+            g: g.max(0.0).min(1.0),
+            b: b.max(0.0).min(1.0)
+        }
+    }
+
+    pub fn black() -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    pub fn white() -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    /// Creates a color from a hue (in turns, so 0.5 is cyan), saturation and value.
+    /// Hues outside 0-1 wrap around.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        let hue = (hue - hue.floor()) * 6.0;
+        let sector = hue.floor();
+        let f = hue - sector;
+        let p = value * (1.0 - saturation);
+        let q = value * (1.0 - saturation * f);
+        let t = value * (1.0 - saturation * (1.0 - f));
+        match sector as u32 {
+            0 => Color::new(value, t, p),
+            1 => Color::new(q, value, p),
+            2 => Color::new(p, value, t),
+            3 => Color::new(p, q, value),
+            4 => Color::new(t, p, value),
+            _ => Color::new(value, p, q),
+        }
+    }
+
+    /// Mixes between this color (amount 0) and another (amount 1)
+    pub fn mix(&self, other: &Color, amount: f32) -> Color {
+        Color::new(
+            self.r + (other.r - self.r) * amount,
+            self.g + (other.g - self.g) * amount,
+            self.b + (other.b - self.b) * amount)
+    }
+
+    /// Scales the color's brightness
+    pub fn scale(&self, amount: f32) -> Color {
+        Color::new(self.r * amount, self.g * amount, self.b * amount)
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    /// Parses a hex color like "#ff8000", or one of a few color names
+    fn from_str(s: &str) -> Result<Color, Error> {
+        let invalid = || Error::InvalidEffect(format!("Invalid color: {}", s));
+        match s {
+            "black" => return Ok(Color::black()),
+            "white" => return Ok(Color::white()),
+            "red" => return Ok(Color::new(1.0, 0.0, 0.0)),
+            "green" => return Ok(Color::new(0.0, 1.0, 0.0)),
+            "blue" => return Ok(Color::new(0.0, 0.0, 1.0)),
+            _ => {},
+        }
+
+        let hex = if s.starts_with('#') { &s[1..] } else { s };
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_digit(16)) {
+            return Err(invalid());
+        }
+        let part = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16)
+            .map(|value| value as f32 / 255.0)
+            .map_err(|_| invalid());
+        Ok(Color::new(try!(part(0)), try!(part(2)), try!(part(4))))
+    }
+}
@@ -0,0 +1,177 @@
+use std::f32::consts::PI;
+
+use effects::{Color, Element};
+
+
+/// Where a frame falls in an effect
+#[derive(Clone, Copy, Debug)]
+pub struct EffectTime {
+    pub ms: u32, // Time since the effect started
+    pub duration_ms: u32, // How long the effect lasts
+    pub progress: f32, // From 0 at the start of the effect to 1 at the end
+}
+
+/// Something that colors elements over time
+pub trait Effect {
+    /// Returns the color an element should be at a time in the effect,
+    /// or None to leave the element alone
+    fn render(&self, element: &Element, time: &EffectTime) -> Option<Color>;
+}
+
+/// Fades every element from one color to another (the same color for a solid wash)
+#[derive(Clone, Debug)]
+pub struct ColorWash {
+    pub start: Color,
+    pub end: Color,
+}
+
+/// Spreads the colors of the rainbow across each fixture's elements.
+/// Fixtures with a single element are spread across fixtures instead.
+#[derive(Clone, Debug)]
+pub struct Rainbow {
+    pub cycles: f32, // Times the rainbow repeats across a fixture
+    pub speed: f32, // Times the colors go all the way around each second
+}
+
+/// Which way a wipe moves across the fixtures
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WipeDirection {
+    LeftToRight,
+    RightToLeft,
+    BottomToTop,
+    TopToBottom,
+}
+
+/// Turns fixtures on one after another, in order of their locations
+#[derive(Clone, Debug)]
+pub struct Wipe {
+    pub color: Color,
+    pub direction: WipeDirection,
+    pub softness: f32, // Length of the fading edge, as a fraction of the way across
+}
+
+/// Flashes elements on and off
+#[derive(Clone, Debug)]
+pub struct Strobe {
+    pub color: Color,
+    pub period_ms: u32, // Time from one flash to the next
+    pub duty: f32, // Fraction of each period the flash is on
+}
+
+/// Randomly flashes elements, leaving them alone in between
+#[derive(Clone, Debug)]
+pub struct Sparkle {
+    pub color: Color,
+    pub density: f32, // Chance an element is sparkling, from 0 to 1
+    pub hold_ms: u32, // How long each sparkle lasts
+    pub seed: u32, // Same seed gives the same sparkles
+}
+
+/// Swirling colors based on where the elements are
+#[derive(Clone, Debug)]
+pub struct Butterfly {
+    pub scale: f32, // Number of swirls across the layout
+    pub speed: f32, // How fast the swirls move
+}
+
+/// Follows a level for each frame, e.g. the energy of a band of music
+#[derive(Clone, Debug)]
+pub struct Envelope {
+    pub color: Color,
+    pub levels: Vec<f32>, // From 0 to 1, starting at the effect's start
+    pub frame_duration_ms: u32,
+}
+
+impl Effect for ColorWash {
+    fn render(&self, _: &Element, time: &EffectTime) -> Option<Color> {
+        Some(self.start.mix(&self.end, time.progress))
+    }
+}
+
+impl Effect for Rainbow {
+    fn render(&self, element: &Element, time: &EffectTime) -> Option<Color> {
+        let place = if element.num_elements > 1 {
+            element.index as f32 / element.num_elements as f32
+        } else {
+            element.fixture as f32 / element.num_fixtures as f32
+        };
+        let hue = place * self.cycles + self.speed * time.ms as f32 / 1000.0;
+        Some(Color::from_hsv(hue, 1.0, 1.0))
+    }
+}
+
+impl Effect for Wipe {
+    fn render(&self, element: &Element, time: &EffectTime) -> Option<Color> {
+        let (x, y) = element.fixture_order;
+        let place = match self.direction {
+            WipeDirection::LeftToRight => x,
+            WipeDirection::RightToLeft => 1.0 - x,
+            WipeDirection::BottomToTop => y,
+            WipeDirection::TopToBottom => 1.0 - y,
+        };
+
+        // The edge moves far enough that the last fixture is fully on at the end
+        let edge = time.progress * (1.0 + self.softness);
+        let amount = if self.softness > 0.0 {
+            ((edge - place) / self.softness).max(0.0).min(1.0)
+        } else if place <= edge {
+            1.0
+        } else {
+            0.0
+        };
+
+        if amount > 0.0 { Some(self.color.scale(amount)) } else { None }
+    }
+}
+
+impl Effect for Strobe {
+    fn render(&self, _: &Element, time: &EffectTime) -> Option<Color> {
+        let period_ms = self.period_ms.max(1);
+        let on_ms = period_ms as f32 * self.duty;
+        if ((time.ms % period_ms) as f32) < on_ms {
+            Some(self.color)
+        } else {
+            Some(Color::black())
+        }
+    }
+}
+
+impl Effect for Sparkle {
+    fn render(&self, element: &Element, time: &EffectTime) -> Option<Color> {
+        let step = time.ms / self.hold_ms.max(1);
+
+        // Hash the seed, element, and step together into a repeatable random number
+        let mut hash = self.seed.wrapping_mul(2654435761)
+            ^ element.seed.wrapping_mul(2246822519)
+            ^ step.wrapping_mul(3266489917);
+        hash ^= hash >> 15;
+        hash = hash.wrapping_mul(668265263);
+        hash ^= hash >> 13;
+
+        if ((hash % 10000) as f32) < self.density * 10000.0 {
+            Some(self.color)
+        } else {
+            None
+        }
+    }
+}
+
+impl Effect for Butterfly {
+    fn render(&self, element: &Element, time: &EffectTime) -> Option<Color> {
+        let (x, y) = element.location;
+        let t = self.speed * time.ms as f32 / 1000.0;
+        let swirl = (2.0 * PI * self.scale * x + t).sin()
+            + (2.0 * PI * self.scale * y - t).sin()
+            + (PI * self.scale * (x + y) + 2.0 * t).sin();
+
+        // Swirl is between -3 and 3
+        Some(Color::from_hsv((swirl + 3.0) / 6.0, 1.0, 1.0))
+    }
+}
+
+impl Effect for Envelope {
+    fn render(&self, _: &Element, time: &EffectTime) -> Option<Color> {
+        let frame = (time.ms / self.frame_duration_ms.max(1)) as usize;
+        self.levels.get(frame).map(|level| self.color.scale(*level))
+    }
+}
@@ -0,0 +1,18 @@
+//! Effects that render colors onto a layout's fixtures and turn them into
+//! channel data. Effects are drawn in layers, each blended onto the data
+//! already in the buffers, so a whole song can be built up one layer at a time.
+
+mod blend;
+mod color;
+mod effect;
+mod params;
+mod scene;
+
+pub use self::blend::BlendMode;
+pub use self::color::Color;
+pub use self::effect::{Butterfly, ColorWash, Effect, Envelope, EffectTime, Rainbow, Sparkle, Strobe, Wipe, WipeDirection};
+pub use self::params::build_effect;
+pub use self::scene::{Element, Scene};
+
+/// Largest value a channel can be set to
+pub const MAX_VALUE: u16 = 255;
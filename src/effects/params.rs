@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use effects::{Butterfly, Color, ColorWash, Effect, Rainbow, Sparkle, Strobe, Wipe, WipeDirection};
+use error::Error;
+
+
+/// Creates an effect from its name and key=value params, as written in sequence scripts.
+/// Params that aren't given get defaults.
+///
+/// - `wash color=<color>` or `wash start=<color> end=<color>`
+/// - `rainbow cycles=1 speed=0`
+/// - `wipe color=white direction=right softness=0` (directions: right, left, up, down)
+/// - `strobe color=white period=100 duty=0.5`
+/// - `sparkle color=white density=0.1 hold=100 seed=1`
+/// - `butterfly scale=1 speed=1`
+pub fn build_effect(name: &str, params: &BTreeMap<String, String>) -> Result<Box<Effect>, Error> {
+    let known: &[&str] = match name {
+        "wash" => &["color", "start", "end"],
+        "rainbow" => &["cycles", "speed"],
+        "wipe" => &["color", "direction", "softness"],
+        "strobe" => &["color", "period", "duty"],
+        "sparkle" => &["color", "density", "hold", "seed"],
+        "butterfly" => &["scale", "speed"],
+        _ => return Err(Error::InvalidEffect(format!("Unknown effect: {}", name))),
+    };
+    for key in params.keys() {
+        if !known.contains(&key.as_str()) {
+            return Err(Error::InvalidEffect(format!("Unknown param for {}: {}", name, key)));
+        }
+    }
+
+    let effect: Box<Effect> = match name {
+        "wash" => {
+            let color = try!(get_param(params, "color", Color::white()));
+            Box::new(ColorWash {
+                start: try!(get_param(params, "start", color)),
+                end: try!(get_param(params, "end", color))
+            })
+        },
+        "rainbow" => Box::new(Rainbow {
+            cycles: try!(get_param(params, "cycles", 1.0)),
+            speed: try!(get_param(params, "speed", 0.0))
+        }),
+        "wipe" => Box::new(Wipe {
+            color: try!(get_param(params, "color", Color::white())),
+            direction: match params.get("direction").map(|direction| direction.as_str()) {
+                Some("right") | None => WipeDirection::LeftToRight,
+                Some("left") => WipeDirection::RightToLeft,
+                Some("up") => WipeDirection::BottomToTop,
+                Some("down") => WipeDirection::TopToBottom,
+                Some(direction) => return Err(Error::InvalidEffect(
+                    format!("Unknown wipe direction '{}' (expected right, left, up, or down)", direction))),
+            },
+            softness: try!(get_param(params, "softness", 0.0))
+        }),
+        "strobe" => Box::new(Strobe {
+            color: try!(get_param(params, "color", Color::white())),
+            period_ms: try!(get_param(params, "period", 100)),
+            duty: try!(get_param(params, "duty", 0.5))
+        }),
+        "sparkle" => Box::new(Sparkle {
+            color: try!(get_param(params, "color", Color::white())),
+            density: try!(get_param(params, "density", 0.1)),
+            hold_ms: try!(get_param(params, "hold", 100)),
+            seed: try!(get_param(params, "seed", 1))
+        }),
+        _ => Box::new(Butterfly {
+            scale: try!(get_param(params, "scale", 1.0)),
+            speed: try!(get_param(params, "speed", 1.0))
+        }),
+    };
+    Ok(effect)
+}
+
+/// Helper function for build_effect() to parse a param, or use its default if missing
+fn get_param<T: FromStr>(params: &BTreeMap<String, String>, key: &str, default: T) -> Result<T, Error> {
+    match params.get(key) {
+        Some(value) => value.parse::<T>()
+            .map_err(|_| Error::InvalidEffect(format!("Invalid value for {}: {}", key, value))),
+        None => Ok(default),
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::BTreeMap;
+
+use effects::{BlendMode, Color, Effect, EffectTime, MAX_VALUE};
+use error::Error;
+use project_types::{Channel, Fixture, Layout};
+
+
+/// What part of an element's color a channel shows, based on the channel's color
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ChannelRole {
+    Red,
+    Green,
+    Blue,
+    White,
+    Single, // Any other color, which just shows brightness
+}
+
+/// A light effects can color: the channels in a fixture with the same name and
+/// numbers, e.g. the R, G and B channels of one pixel
+#[derive(Clone, Debug)]
+pub struct Element {
+    pub fixid: u32,
+    pub fixture: usize, // Index of the element's fixture among those being rendered
+    pub num_fixtures: usize,
+    pub index: usize, // Index of the element within its fixture
+    pub num_elements: usize, // Number of elements in the element's fixture
+    pub location: (f32, f32), // x and y, scaled to 0-1 across everything being rendered
+    pub fixture_order: (f32, f32), // Fixture's place when sorted by x and by y, from 0 to 1
+    pub seed: u32, // Stays the same for an element between renders, for random effects
+    channels: Vec<(u32, ChannelRole)>,
+}
+
+/// A fixture's elements, with locations as given in the layout
+#[derive(Debug)]
+struct SceneFixture {
+    fixid: u32,
+    location: (f32, f32),
+    elements: Vec<(Vec<(u32, ChannelRole)>, (f32, f32))>,
+}
+
+/// A layout's fixtures and channels, ready to have effects rendered onto them
+#[derive(Debug)]
+pub struct Scene {
+    pub frame_duration_ms: u32,
+    pub num_frames: u32,
+    fixtures: Vec<SceneFixture>,
+}
+
+impl Element {
+    /// Works out the value a channel should have to show a color
+    fn channel_value(&self, role: ChannelRole, color: &Color) -> u16 {
+        let has_rgb = self.channels.iter().any(|&(_, role)| {
+            role == ChannelRole::Red || role == ChannelRole::Green || role == ChannelRole::Blue
+        });
+        let brightness = color.r.max(color.g).max(color.b);
+        let amount = match role {
+            ChannelRole::Red => color.r,
+            ChannelRole::Green => color.g,
+            ChannelRole::Blue => color.b,
+            // White channels of RGBW elements show the white part of the color
+            ChannelRole::White if has_rgb => color.r.min(color.g).min(color.b),
+            ChannelRole::White | ChannelRole::Single => brightness,
+        };
+        (amount * MAX_VALUE as f32).round() as u16
+    }
+}
+
+impl Scene {
+    /// Creates a scene from a layout, its fixtures, and their channels.
+    /// Fixtures are kept in layout order, and each fixture's elements in channel order.
+    pub fn new(
+        layout: &Layout,
+        fixtures: &Vec<Fixture>,
+        channels: &Vec<Channel>,
+        frame_duration_ms: u32,
+        num_frames: u32
+    ) -> Result<Scene, Error> {
+
+        let mut scene_fixtures = Vec::new();
+        for fixid in &layout.fixtures {
+            let fixture = match fixtures.iter().find(|fixture| fixture.fixid == *fixid) {
+                Some(fixture) => fixture,
+                None => return Err(Error::FixtureNotFound(*fixid)),
+            };
+            let fixture_location = (fixture.location.0 as f32, fixture.location.1 as f32);
+
+            // Group channels into elements by name and numbers
+            let mut keys: Vec<(&str, (Option<u32>, Option<u32>))> = Vec::new();
+            let mut elements: Vec<(Vec<(u32, ChannelRole)>, (f32, f32))> = Vec::new();
+            for chanid in &fixture.channels {
+                let channel = match channels.iter().find(|channel| channel.chanid == *chanid) {
+                    Some(channel) => channel,
+                    None => return Err(Error::ChannelNotFound(*chanid)),
+                };
+                let role = match channel.color.as_str() {
+                    "R" => ChannelRole::Red,
+                    "G" => ChannelRole::Green,
+                    "B" => ChannelRole::Blue,
+                    "W" | "Ww" | "Cw" => ChannelRole::White,
+                    _ => ChannelRole::Single,
+                };
+                let key = (channel.name.as_str(), channel.numbers);
+                match keys.iter().position(|k| *k == key) {
+                    Some(i) => elements[i].0.push((channel.chanid, role)),
+                    None => {
+                        // Unknown channel locations fall back to the fixture's
+                        let location = (
+                            channel.location.0.map(|x| x as f32).unwrap_or(fixture_location.0),
+                            channel.location.1.map(|y| y as f32).unwrap_or(fixture_location.1));
+                        keys.push(key);
+                        elements.push((vec![(channel.chanid, role)], location));
+                    },
+                }
+            }
+
+            scene_fixtures.push(SceneFixture {
+                fixid: *fixid,
+                location: fixture_location,
+                elements: elements
+            });
+        }
+
+        Ok(Scene {
+            frame_duration_ms: frame_duration_ms,
+            num_frames: num_frames,
+            fixtures: scene_fixtures
+        })
+    }
+
+    /// Creates empty channel data for every channel in the scene
+    pub fn new_buffers(&self) -> BTreeMap<u32, Vec<u16>> {
+        let mut buffers = BTreeMap::new();
+        for fixture in &self.fixtures {
+            for &(ref channels, _) in &fixture.elements {
+                for &(chanid, _) in channels {
+                    buffers.insert(chanid, vec![0; self.num_frames as usize]);
+                }
+            }
+        }
+        buffers
+    }
+
+    /// Returns the elements of the given fixtures (all fixtures if none are given),
+    /// with locations and orders worked out across just those fixtures
+    pub fn elements(&self, fixids: &[u32]) -> Result<Vec<Element>, Error> {
+        let mut fixtures = Vec::new();
+        if fixids.is_empty() {
+            fixtures.extend(self.fixtures.iter());
+        } else {
+            for fixid in fixids {
+                match self.fixtures.iter().find(|fixture| fixture.fixid == *fixid) {
+                    Some(fixture) => fixtures.push(fixture),
+                    None => return Err(Error::FixtureNotFound(*fixid)),
+                }
+            }
+        }
+
+        if fixtures.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Bounds of all element locations, to scale them to 0-1
+        let mut min = (::std::f32::MAX, ::std::f32::MAX);
+        let mut max = (::std::f32::MIN, ::std::f32::MIN);
+        for fixture in &fixtures {
+            for &(_, (x, y)) in &fixture.elements {
+                min = (min.0.min(x), min.1.min(y));
+                max = (max.0.max(x), max.1.max(y));
+            }
+        }
+        let scale = |value: f32, low: f32, high: f32| {
+            if high > low { (value - low) / (high - low) } else { 0.5 }
+        };
+
+        // Place of each fixture when sorted along each axis. Ties keep layout order.
+        let order_by = |axis: &Fn(&SceneFixture) -> f32| {
+            let mut sorted = (0..fixtures.len()).collect::<Vec<usize>>();
+            sorted.sort_by(|a, b| axis(fixtures[*a]).partial_cmp(&axis(fixtures[*b]))
+                .unwrap_or(::std::cmp::Ordering::Equal));
+            let mut places = vec![0.0; fixtures.len()];
+            for (place, i) in sorted.iter().enumerate() {
+                places[*i] = scale(place as f32, 0.0, (fixtures.len() - 1) as f32);
+            }
+            places
+        };
+        let order_x = order_by(&|fixture: &SceneFixture| fixture.location.0);
+        let order_y = order_by(&|fixture: &SceneFixture| fixture.location.1);
+
+        let mut elements = Vec::new();
+        for (i, fixture) in fixtures.iter().enumerate() {
+            for (j, &(ref channels, (x, y))) in fixture.elements.iter().enumerate() {
+                elements.push(Element {
+                    fixid: fixture.fixid,
+                    fixture: i,
+                    num_fixtures: fixtures.len(),
+                    index: j,
+                    num_elements: fixture.elements.len(),
+                    location: (scale(x, min.0, max.0), scale(y, min.1, max.1)),
+                    fixture_order: (order_x[i], order_y[i]),
+                    seed: channels[0].0,
+                    channels: channels.to_owned()
+                });
+            }
+        }
+        Ok(elements)
+    }
+
+    /// Renders an effect onto the given fixtures (all fixtures if none are given)
+    /// from start_ms to end_ms, blending it into the channel buffers.
+    /// Buffers are created for channels that don't have one yet.
+    pub fn render(
+        &self,
+        effect: &Effect,
+        blend: BlendMode,
+        fixids: &[u32],
+        start_ms: u32,
+        end_ms: u32,
+        buffers: &mut BTreeMap<u32, Vec<u16>>
+    ) -> Result<(), Error> {
+
+        let elements = try!(self.elements(fixids));
+        let start_frame = start_ms / self.frame_duration_ms;
+        let end_frame = (end_ms as f32 / self.frame_duration_ms as f32).ceil() as u32;
+        let end_frame = end_frame.min(self.num_frames);
+        let duration_ms = end_ms.saturating_sub(start_ms);
+
+        for frame in start_frame..end_frame {
+            // Progress reaches 1 on the last frame, so effects end where they should
+            let num_frames = end_frame - start_frame;
+            let time = EffectTime {
+                ms: (frame - start_frame) * self.frame_duration_ms,
+                duration_ms: duration_ms,
+                progress: if num_frames > 1 {
+                    (frame - start_frame) as f32 / (num_frames - 1) as f32
+                } else {
+                    1.0
+                }
+            };
+
+            for element in &elements {
+                let color = match effect.render(element, &time) {
+                    Some(color) => color,
+                    None => continue,
+                };
+                for &(chanid, role) in &element.channels {
+                    let value = element.channel_value(role, &color);
+                    let num_frames = self.num_frames as usize;
+                    let buffer = buffers.entry(chanid).or_insert_with(|| vec![0; num_frames]);
+                    if let Some(current) = buffer.get_mut(frame as usize) {
+                        *current = blend.apply(*current, value);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -13,36 +13,64 @@ pub enum Error {
     JsonDecode(json::DecoderError),
     JsonParse(json::ParserError),
     Ssl(openssl_err::ErrorStack),
-    Rsfml(String),
+    Audio(String),
     Postgres(postgres_err::Error),
     PostgresConnection(postgres_err::ConnectError),
     FileNotFound(String),
     FolderNotEmpty(String, usize),
+    IncompatibleLayouts(u32, u32),
+    InvalidController(String),
+    InvalidDataEdit(String),
+    InvalidDimming(String),
+    InvalidDurationPolicy(String),
+    InvalidEffect(String),
     InvalidFileName,
     InvalidFrameDuration(u32),
     InvalidLayout(String),
+    InvalidMusicOffset(i32),
     InvalidNumResults(usize),
+    InvalidOutput(String),
     InvalidPatch(String),
     InvalidPermissionName(String),
+    InvalidPower(String),
     InvalidProjectName(String),
     InvalidPublicKey(String),
+    InvalidResampleMode(String),
+    InvalidSchedule(String),
+    InvalidBand(String),
+    InvalidReactiveMapping(String),
+    InvalidTimeAnchor(String),
+    InvalidTimingFile(String),
+    InvalidScript(usize, String),
+    InvalidSequenceDuration(u32),
     InvalidSequenceName(String),
     InvalidSequenceSection(u32),
+    InvalidSparePolicy(String),
+    InvalidTable(usize, String),
+    InvalidTestPattern(String),
     InvalidVixenData(String),
     LoadProjectError,
     MissingPermissionArg,
     OffsetOutOfBounds(u32, u32),
     DuplicateUser(String, String),
     DuplicateSequence(String),
+    DurationMismatch(String),
+    MusicNotVerified(String),
+    NothingPatched(String),
     UnsupportedFileType(String),
     AdminNotFound,
     ChannelNotFound(u32),
     ChannelDataNotFound(u32, u32),
+    ControllerNotFound(u32),
+    EnvelopeNotFound(u32, String),
     FixtureNotFound(u32),
     LayoutNotFound(u32),
+    PatchNotFound(u32, u32),
     ProjectNotFound(String),
     PublicKeyNotFound(String),
+    ScheduleNotFound(String),
     SequenceNotFound(u32),
+    TimingTrackNotFound(u32, String),
     UserNotFound,
     UnauthorizedAction,
     TodoErr,
@@ -57,36 +85,64 @@ impl error::Error for Error {
             Error::JsonEncode(_) => "Json encoding error occurred",
             Error::JsonParse(_) => "Json parsing error occurred",
             Error::Ssl(_) => "SSL error occured",
-            Error::Rsfml(_) => "Rsfml error occured",
+            Error::Audio(_) => "Audio error occured",
             Error::Postgres(_) => "Postgres error occured",
             Error::PostgresConnection(_) => "Postgres connection error occured",
             Error::FileNotFound(_) => "File not found",
             Error::FolderNotEmpty(_, _) => "Root folder was not empty",
+            Error::IncompatibleLayouts(_, _) => "Incompatible layouts",
+            Error::InvalidController(_) => "Invalid controller",
+            Error::InvalidDataEdit(_) => "Invalid data edit",
+            Error::InvalidDimming(_) => "Invalid dimming",
+            Error::InvalidDurationPolicy(_) => "Invalid duration policy",
+            Error::InvalidEffect(_) => "Invalid effect",
             Error::InvalidFileName => "Invalid file name",
             Error::InvalidFrameDuration(_) => "Invalid frame duration",
             Error::InvalidLayout(_) => "Invalid layout",
+            Error::InvalidMusicOffset(_) => "Invalid music offset",
             Error::InvalidNumResults(_) => "Invalid number of results returned",
+            Error::InvalidOutput(_) => "Invalid output settings",
             Error::InvalidPatch(_) => "Invalid patch file",
             Error::InvalidPermissionName(_) => "Invalid permission name",
+            Error::InvalidPower(_) => "Invalid power settings",
             Error::InvalidProjectName(_) => "Invalid project name",
             Error::InvalidPublicKey(_) => "Invalid public key",
+            Error::InvalidResampleMode(_) => "Invalid resample mode",
+            Error::InvalidSchedule(_) => "Invalid schedule",
+            Error::InvalidBand(_) => "Invalid frequency band",
+            Error::InvalidReactiveMapping(_) => "Invalid reactive mapping",
+            Error::InvalidTimeAnchor(_) => "Invalid time anchor",
+            Error::InvalidTimingFile(_) => "Invalid timing file",
+            Error::InvalidScript(_, _) => "Invalid sequence script",
+            Error::InvalidSequenceDuration(_) => "Invalid sequence duration",
             Error::InvalidSequenceName(_) => "Invalid sequence name",
             Error::InvalidSequenceSection(_) => "Invalid sequence section",
+            Error::InvalidSparePolicy(_) => "Invalid spare channel policy",
+            Error::InvalidTable(_, _) => "Invalid CSV/TSV file",
+            Error::InvalidTestPattern(_) => "Invalid test pattern",
             Error::InvalidVixenData(_) => "Invalid Vixen data",
             Error::LoadProjectError => "Loading project failed",
             Error::MissingPermissionArg => "Permission argument required but missing (seqid or secid)",
             Error::OffsetOutOfBounds(_, _) => "Offset out of bouds",
             Error::DuplicateUser(_, _) => "User already exists",
             Error::DuplicateSequence(_) => "Sequence already exists",
+            Error::DurationMismatch(_) => "Sequence duration doesn't match its music",
+            Error::MusicNotVerified(_) => "Music file missing or changed",
+            Error::NothingPatched(_) => "No patched channels",
             Error::UnsupportedFileType(_) => "Unsupported file type",
             Error::AdminNotFound => "Admin not found",
             Error::ChannelNotFound(_) => "Channel not found",
             Error::ChannelDataNotFound(_, _) => "Channel data not found",
+            Error::ControllerNotFound(_) => "Controller not found",
+            Error::EnvelopeNotFound(_, _) => "Energy envelope not found",
             Error::FixtureNotFound(_) => "Fixture not found",
             Error::LayoutNotFound(_) => "Layout not found",
+            Error::PatchNotFound(_, _) => "Layout patch not found",
             Error::ProjectNotFound(_) => "Project not found",
             Error::PublicKeyNotFound(_) => "Public key not found",
+            Error::ScheduleNotFound(_) => "Schedule not found",
             Error::SequenceNotFound(_) => "Sequence not found",
+            Error::TimingTrackNotFound(_, _) => "Timing track not found",
             Error::UserNotFound => "User not found",
             Error::UnauthorizedAction => "Unauthorized action",
             Error::TodoErr => "Todo",
@@ -102,36 +158,64 @@ impl error::Error for Error {
            Error::JsonEncode(ref err) => Some(err),
            Error::JsonParse(ref err) => Some(err),
            Error::Ssl(ref err) => Some(err),
-           Error::Rsfml(_) => None,
+           Error::Audio(_) => None,
            Error::Postgres(ref err) => Some(err),
            Error::PostgresConnection(ref err) => Some(err),
            Error::FileNotFound(_) => None,
            Error::FolderNotEmpty(_, _) => None,
+           Error::IncompatibleLayouts(_, _) => None,
+           Error::InvalidController(_) => None,
+           Error::InvalidDataEdit(_) => None,
+           Error::InvalidDimming(_) => None,
+           Error::InvalidDurationPolicy(_) => None,
+           Error::InvalidEffect(_) => None,
            Error::InvalidFileName => None,
            Error::InvalidFrameDuration(_) => None,
            Error::InvalidLayout(_) => None,
+           Error::InvalidMusicOffset(_) => None,
            Error::InvalidNumResults(_) => None,
+           Error::InvalidOutput(_) => None,
            Error::InvalidPatch(_) => None,
            Error::InvalidPermissionName(_) => None,
+           Error::InvalidPower(_) => None,
            Error::InvalidProjectName(_) => None,
            Error::InvalidPublicKey(_) => None,
+           Error::InvalidResampleMode(_) => None,
+           Error::InvalidSchedule(_) => None,
+           Error::InvalidBand(_) => None,
+           Error::InvalidReactiveMapping(_) => None,
+           Error::InvalidTimeAnchor(_) => None,
+           Error::InvalidTimingFile(_) => None,
+           Error::InvalidScript(_, _) => None,
+           Error::InvalidSequenceDuration(_) => None,
            Error::InvalidSequenceName(_) => None,
            Error::InvalidSequenceSection(_) => None,
+           Error::InvalidSparePolicy(_) => None,
+           Error::InvalidTable(_, _) => None,
+           Error::InvalidTestPattern(_) => None,
            Error::InvalidVixenData(_) => None,
            Error::LoadProjectError => None,
            Error::MissingPermissionArg => None,
            Error::OffsetOutOfBounds(_, _) => None,
            Error::DuplicateUser(_, _) => None,
            Error::DuplicateSequence(_) => None,
+           Error::DurationMismatch(_) => None,
+           Error::MusicNotVerified(_) => None,
+           Error::NothingPatched(_) => None,
            Error::UnsupportedFileType(_) => None,
            Error::AdminNotFound => None,
            Error::ChannelNotFound(_) => None,
            Error::ChannelDataNotFound(_, _) => None,
+           Error::ControllerNotFound(_) => None,
+           Error::EnvelopeNotFound(_, _) => None,
            Error::FixtureNotFound(_) => None,
            Error::LayoutNotFound(_) => None,
+           Error::PatchNotFound(_, _) => None,
            Error::ProjectNotFound(_) => None,
            Error::PublicKeyNotFound(_) => None,
+           Error::ScheduleNotFound(_) => None,
            Error::SequenceNotFound(_) => None,
+           Error::TimingTrackNotFound(_, _) => None,
            Error::UserNotFound => None,
            Error::UnauthorizedAction => None,
            Error::TodoErr => None,
@@ -153,8 +237,8 @@ impl fmt::Display for Error {
                 "Json parsing error occurred: {}", err),
             Error::Ssl(ref err) => write!(f,
                 "SSL error occured: {}", error::Error::description(err)),
-            Error::Rsfml(ref description) => write!(f, 
-                "Rsfml error: {}", description),
+            Error::Audio(ref description) => write!(f,
+                "Audio error: {}", description),
             Error::Postgres(ref err) => write!(f, 
                 "Postgress error occured: {}", err),
             Error::PostgresConnection(ref err) => write!(f, 
@@ -163,28 +247,68 @@ impl fmt::Display for Error {
                 "File not found at path '{}'", path),
             Error::FolderNotEmpty(ref root, count) => write!(f,
                 "{} was not empty: {} files exist", root, count),
+            Error::IncompatibleLayouts(ref from_layout, ref to_layout) => write!(f,
+                "Channels of layout {} don't match those of layout {}", from_layout, to_layout),
+            Error::InvalidController(ref description) => write!(f,
+                "Invalid controller: {}", description),
+            Error::InvalidDataEdit(ref description) => write!(f,
+                "Invalid data edit: {}", description),
+            Error::InvalidDimming(ref description) => write!(f,
+                "Invalid dimming: {}", description),
+            Error::InvalidDurationPolicy(ref policy) => write!(f,
+                "Invalid duration policy '{}' (expected warn or error)", policy),
+            Error::InvalidEffect(ref description) => write!(f,
+                "Invalid effect: {}", description),
             Error::InvalidFileName => write!(f,
                 "File name provided is invalid and cannot be retrieved"),
             Error::InvalidFrameDuration(ref duration) => write!(f,
                 "Invalid frame duration: {}", duration),
             Error::InvalidLayout(ref description) => write!(f,
                 "The layout being read or decoded is invalid: {}", description),
+            Error::InvalidMusicOffset(ref offset) => write!(f,
+                "Invalid music offset: {}ms is outside the sequence or the music", offset),
             Error::InvalidNumResults(ref num_results) => write!(f,
                 "Invalid number of results returned: {}", num_results),
+            Error::InvalidOutput(ref description) => write!(f,
+                "Invalid output settings: {}", description),
             Error::InvalidPatch(ref description) => write!(f,
                 "Invalid patch file: {}", description),
             Error::InvalidPermissionName(ref name) => write!(f,
                 "Invalid permission name provided: {}", name),
+            Error::InvalidPower(ref description) => write!(f,
+                "Invalid power settings: {}", description),
             Error::InvalidProjectName(ref name) => write!(f,
                 "Invalid project name provided: {}", name),
             Error::InvalidPublicKey(ref key) => write!(f, 
                 "Public key is invalid: {}", key),
+            Error::InvalidResampleMode(ref mode) => write!(f,
+                "Invalid resample mode '{}' (expected nearest, linear, or hold)", mode),
+            Error::InvalidSchedule(ref description) => write!(f,
+                "Invalid schedule: {}", description),
+            Error::InvalidBand(ref band) => write!(f,
+                "Invalid frequency band '{}' (expected bass, mid, or high)", band),
+            Error::InvalidReactiveMapping(ref description) => write!(f,
+                "Invalid reactive mapping: {}", description),
+            Error::InvalidTimeAnchor(ref description) => write!(f,
+                "Invalid time anchor: {}", description),
+            Error::InvalidTimingFile(ref description) => write!(f,
+                "Invalid timing file: {}", description),
+            Error::InvalidScript(ref line, ref description) => write!(f,
+                "Invalid sequence script at line {}: {}", line, description),
+            Error::InvalidSequenceDuration(ref duration) => write!(f,
+                "Invalid sequence duration: {}ms", duration),
             Error::InvalidSequenceName(ref seq_name) => write!(f,
                 "Sequence name had invalid characters: {}", seq_name),
             Error::InvalidVixenData(ref details) => write!(f,
                 "Invalid Vixen data provided: {}", details),
             Error::InvalidSequenceSection(ref section) => write!(f,
                 "Invalid sequence section: {}", section),
+            Error::InvalidSparePolicy(ref policy) => write!(f,
+                "Invalid spare channel policy '{}' (expected reserve or fixture)", policy),
+            Error::InvalidTable(ref line, ref description) => write!(f,
+                "Invalid CSV/TSV file at line {}: {}", line, description),
+            Error::InvalidTestPattern(ref pattern) => write!(f,
+                "Invalid test pattern: {}. Use full, ramp or chase", pattern),
             Error::LoadProjectError => write!(f, "Loading project failed"),
             Error::MissingPermissionArg => write!(f,
               "Permission argument required but missing (did you forget seqid or secid?)"),
@@ -194,6 +318,12 @@ impl fmt::Display for Error {
                 "Duplicate user '{}' or key '{}'", user, key),
             Error::DuplicateSequence(ref name) => write!(f,
                 "Duplicate sequence with name '{}'", name),
+            Error::DurationMismatch(ref description) => write!(f,
+                "Sequence duration doesn't match its music: {}", description),
+            Error::MusicNotVerified(ref description) => write!(f,
+                "Music file missing or changed: {}", description),
+            Error::NothingPatched(ref what) => write!(f,
+                "Nothing to test: {} has no patched channels", what),
             Error::UnsupportedFileType(ref file_type) => write!(f, 
                 "Unsupported file type: {}", file_type),
             Error::AdminNotFound => write!(f, "Admin not found"),
@@ -201,16 +331,26 @@ impl fmt::Display for Error {
                 "Channel not found: {}", chanid),
             Error::ChannelDataNotFound(ref seqid, ref chanid) => write!(f,
                 "Channel data not found. seqid: {}, chanid: {}", seqid, chanid),
+            Error::ControllerNotFound(ref controllerid) => write!(f,
+                "Controller not found: {}", controllerid),
+            Error::EnvelopeNotFound(ref seqid, ref band) => write!(f,
+                "Energy envelope not found. seqid: {}, band: {}", seqid, band),
             Error::FixtureNotFound(ref fix_id) => write!(f,
                 "Fixture not found: {}", fix_id),
             Error::LayoutNotFound(ref layout_id) => write!(f,
                 "Layout not found: {}", layout_id),
+            Error::PatchNotFound(ref layout_id, ref version) => write!(f,
+                "Layout patch not found. layout id: {}, version: {}", layout_id, version),
             Error::ProjectNotFound(ref proj_name) => write!(f,
                 "Project not found: {}", proj_name),
             Error::PublicKeyNotFound(ref key) => write!(f,
                 "PublicKey not found: {}", key),
+            Error::ScheduleNotFound(ref proj_name) => write!(f,
+                "No schedule set for project '{}'", proj_name),
             Error::SequenceNotFound(ref name) => write!(f,
                 "Sequence not found: '{}'", name),
+            Error::TimingTrackNotFound(ref seqid, ref name) => write!(f,
+                "Timing track not found. seqid: {}, name: {}", seqid, name),
             Error::UserNotFound => write!(f, "User not found"),
             Error::UnauthorizedAction => write!(f, "Unauthorized action"),
             Error::TodoErr => write!(f, "TodoErr"),
@@ -1,58 +1,214 @@
 //! Layout-related functionality
 
-use rustc_serialize::json;
+use rustc_serialize::{json, Decodable};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::Write;
 use std::path::Path;
 
-use dao::{ChannelDao, FixtureDao, LayoutDao, SequenceDao};
+use dao::{ChannelDao, ControllerDao, DimmingDao, FixtureDao, LayoutDao, PatchDao, PowerDao, SequenceDao};
 use error::Error;
-use project_types::{FileLayout, FilePatch};
+use project_types::{Channel, ChannelLoad, DimmingProfile, EditTargets, FileLayout, FilePatch, FileTable, IssueLevel, LayoutIssue, LayoutPatch, PatchChange, PortAssignment, SparePolicy, TestStep};
 use utils;
 
 
-/// Patches a layout's channels based on a provided patch file
-pub fn patch_layout<P: AsRef<Path>, LD: LayoutDao> (
+/// Patches a layout's channels based on a provided patch file.
+/// The channels changed are recorded as a new version of the layout, which is returned.
+pub fn patch_layout<P: AsRef<Path>, CD: ChannelDao, LD: LayoutDao, PD: PatchDao> (
+    chan_dao: &CD,
     layout_dao: &LD,
+    patch_dao: &PD,
     layout_id: u32,
     patch_file_path: P
-) -> Result<(), Error> {
+) -> Result<u32, Error> {
 
-    // Load patch file
-    let patch_json = try!(utils::file_as_string(patch_file_path.as_ref()));
-    let patch_file: FilePatch = try!(json::decode(&patch_json).map_err(Error::JsonDecode));
+    // Load patch file, either JSON or a CSV/TSV table
+    let patch_file = match FileTable::delimiter_for(patch_file_path.as_ref()) {
+        Some(delimiter) => {
+            let table = try!(load_table(patch_file_path.as_ref(), delimiter));
+            try!(FilePatch::from_table(&table))
+        },
+        None => {
+            let patch_json = try!(utils::file_as_string(patch_file_path.as_ref()));
+            try!(json::decode(&patch_json).map_err(Error::JsonDecode))
+        },
+    };
     
     // Make sure patch is valid
     try!(patch_file.validate());
 
+    // Patch history refers back to the file the patch came from
+    let source = try!(utils::file_name_from_path(patch_file_path.as_ref()));
+
     // Apply patch
-    for patch in patch_file.patches.iter() {
-        match layout_dao.patch_channel(layout_id, patch.internalChannel, patch.dmxChannel) {
-            Ok(1) => {},
-            Ok(0) => println!("No channels patched. vix: {}, dmx: {}", patch.internalChannel, patch.dmxChannel),
-            Ok(num_ch) => println!("Patched {} channels.", num_ch),
-            Err(e) => println!("Error: {}", e),
+    let patches = patch_file.patches.iter()
+        .map(|patch| (patch.internalChannel, patch.dmxChannel))
+        .collect::<Vec<(u32, u32)>>();
+    apply_patches(chan_dao, layout_dao, patch_dao, layout_id, &source, &patches)
+}
+
+/// Returns every patch applied to a layout, oldest first
+pub fn get_patch_history<LD: LayoutDao, PD: PatchDao>(
+    layout_dao: &LD,
+    patch_dao: &PD,
+    layout_id: u32
+) -> Result<Vec<LayoutPatch>, Error> {
+
+    // Check that layout exists
+    if !try!(layout_dao.layout_exists(layout_id)) {
+        return Err(Error::LayoutNotFound(layout_id));
+    }
+
+    patch_dao.get_patches(layout_id)
+}
+
+/// Returns a layout's mapping of internal channel to dmx channel at the given 
+/// version of the layout, or the current mapping if no version is given
+pub fn get_layout_mapping<CD: ChannelDao, LD: LayoutDao, PD: PatchDao>(
+    chan_dao: &CD,
+    layout_dao: &LD,
+    patch_dao: &PD,
+    layout_id: u32,
+    version: Option<u32>
+) -> Result<BTreeMap<u32, u32>, Error> {
+
+    let history = try!(get_patch_history(layout_dao, patch_dao, layout_id));
+    let current = try!(get_current_mapping(chan_dao, layout_dao, layout_id));
+
+    match version {
+        Some(v) => {
+            // Make sure the version is in the layout's history
+            let latest = history.last().map(|patch| patch.version).unwrap_or(0);
+            if v > latest {
+                return Err(Error::PatchNotFound(layout_id, v));
+            }
+            Ok(LayoutPatch::mapping_at_version(&current, &history, v))
+        },
+        None => Ok(current),
+    }
+}
+
+/// Rolls a layout's channels back to how they were patched at the given version.
+/// The rollback itself is recorded as a new version, which is returned.
+pub fn rollback_layout<CD: ChannelDao, LD: LayoutDao, PD: PatchDao>(
+    chan_dao: &CD,
+    layout_dao: &LD,
+    patch_dao: &PD,
+    layout_id: u32,
+    version: u32
+) -> Result<u32, Error> {
+
+    let target = try!(get_layout_mapping(chan_dao, layout_dao, patch_dao, layout_id, Some(version)));
+    let current = try!(get_current_mapping(chan_dao, layout_dao, layout_id));
+
+    // Only patch channels that differ from the target version
+    let patches = target.iter()
+        .filter(|&(internal, dmx)| current.get(internal) != Some(dmx))
+        .map(|(internal, dmx)| (*internal, *dmx))
+        .collect::<Vec<(u32, u32)>>();
+
+    let source = format!("rollback to version {}", version);
+    apply_patches(chan_dao, layout_dao, patch_dao, layout_id, &source, &patches)
+}
+
+/// Applies (internal channel, dmx channel) patches to a layout and records the
+/// channels that changed as a new version. Returns the layout's latest version.
+/// If any patch fails, none of them are applied.
+fn apply_patches<CD: ChannelDao, LD: LayoutDao, PD: PatchDao>(
+    chan_dao: &CD,
+    layout_dao: &LD,
+    patch_dao: &PD,
+    layout_id: u32,
+    source: &str,
+    patches: &Vec<(u32, u32)>
+) -> Result<u32, Error> {
+
+    // Sources are stored in 64 characters, and are cut short rather than
+    // failing to record a patch that's already been applied
+    let source = source.chars().take(64).collect::<String>();
+
+    // Keep track of the mapping as it changes, so each change knows its old dmx channel
+    let mut mapping = try!(get_current_mapping(chan_dao, layout_dao, layout_id));
+    let mut changes = Vec::new();
+
+    for &(channel_internal, channel_dmx) in patches.iter() {
+        let old_dmx = match mapping.insert(channel_internal, channel_dmx) {
+            Some(old_dmx) => old_dmx,
+            None => return Err(Error::InvalidPatch(
+                format!("Internal channel {} is not in layout {}", channel_internal, layout_id))),
+        };
+        if old_dmx != channel_dmx {
+            changes.push(PatchChange {
+                channel_internal: channel_internal,
+                old_dmx: old_dmx,
+                new_dmx: channel_dmx
+            });
         }
     }
 
-    Ok(())
+    if changes.is_empty() {
+        // Nothing changed, so there is no new version
+        let history = try!(patch_dao.get_patches(layout_id));
+        Ok(history.last().map(|patch| patch.version).unwrap_or(0))
+    } else {
+        // Channels are patched and the version recorded together
+        patch_dao.new_patch(layout_id, &source, &changes).map(|patch| patch.version)
+    }
 }
 
-/// Creates a new layout
+/// Gets a layout's current mapping of internal channel to dmx channel
+fn get_current_mapping<CD: ChannelDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    layout_dao: &LD,
+    layout_id: u32
+) -> Result<BTreeMap<u32, u32>, Error> {
+
+    let chan_ids = try!(layout_dao.get_channel_ids(layout_id));
+    let mut mapping = BTreeMap::new();
+    for chanid in chan_ids {
+        let channel = try!(chan_dao.get_channel(chanid));
+        mapping.insert(channel.channel_internal, channel.channel_dmx);
+    }
+    Ok(mapping)
+}
+
+/// Creates a new layout. Spare channels are handled according to spare_policy.
+/// CSV/TSV layouts are named layout_name, or by their layoutName column.
 pub fn new_layout<P: AsRef<Path>, CD: ChannelDao, FD: FixtureDao, LD: LayoutDao>(
     chan_dao: &CD,
     fix_dao: &FD,
     layout_dao: &LD,
     layout_path: P,
+    layout_name: Option<&str>,
+    spare_policy: SparePolicy,
 ) -> Result<u32, Error> {
 
-    // Load layout from file
-    let layout_json = try!(utils::file_as_string(layout_path.as_ref()));
-    let file_layout: FileLayout = try!(json::decode(&layout_json).map_err(Error::JsonDecode));
+    // Load layout from file, either JSON or a CSV/TSV table
+    let file_layout: FileLayout = match FileTable::delimiter_for(layout_path.as_ref()) {
+        Some(delimiter) => {
+            // Tables are linted first so every bad row is reported at once
+            let (layout, issues) = try!(lint_layout_table(layout_path.as_ref(), delimiter, layout_name));
+            let errors = issues.iter()
+                .filter(|issue| issue.level == IssueLevel::Error)
+                .map(|issue| issue.to_string())
+                .collect::<Vec<String>>();
+            if !errors.is_empty() {
+                return Err(Error::InvalidLayout(errors.join("\n")));
+            }
+            try!(Decodable::decode(&mut json::Decoder::new(layout)).map_err(Error::JsonDecode))
+        },
+        None => {
+            try!(check_json_name(layout_name));
+            let layout_json = try!(utils::file_as_string(layout_path.as_ref()));
+            try!(json::decode(&layout_json).map_err(Error::JsonDecode))
+        },
+    };
     
     // Make sure layout is valid
     try!(file_layout.validate());
 
     // Create new channels and fixtures from layout and add to storage
-    let (_, fixtures) = try!(file_layout.create_new_parts(chan_dao, fix_dao));
+    let (_, fixtures) = try!(file_layout.create_new_parts(chan_dao, fix_dao, spare_policy));
 
     // Create new layout from fixtures
     let fix_ids = fixtures.iter()
@@ -64,6 +220,420 @@ pub fn new_layout<P: AsRef<Path>, CD: ChannelDao, FD: FixtureDao, LD: LayoutDao>
     Ok(layout.layout_id)
 }
 
+/// Checks a layout file and returns every problem found in it. CSV/TSV layouts
+/// are named layout_name, or by their layoutName column.
+pub fn validate_layout<P: AsRef<Path>>(layout_path: P, layout_name: Option<&str>) -> Result<Vec<LayoutIssue>, Error> {
+
+    if let Some(delimiter) = FileTable::delimiter_for(layout_path.as_ref()) {
+        let (_, issues) = try!(lint_layout_table(layout_path.as_ref(), delimiter, layout_name));
+        return Ok(issues);
+    }
+    try!(check_json_name(layout_name));
+
+    // Load layout from file without decoding, so rows that wouldn't decode can be checked
+    let layout_json = try!(utils::file_as_string(layout_path.as_ref()));
+    let layout = try!(json::Json::from_str(&layout_json).map_err(Error::JsonParse));
+
+    Ok(FileLayout::lint(&layout))
+}
+
+/// Writes a layout's channels to a CSV or TSV file (based on its extension),
+/// ordered by internal channel, along with the layout's name and each fixture's
+/// type and size. The file can be read back in with new-layout.
+pub fn export_layout<P: AsRef<Path>, CD: ChannelDao, FD: FixtureDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    fix_dao: &FD,
+    layout_dao: &LD,
+    layout_id: u32,
+    output_path: P
+) -> Result<(), Error> {
+
+    let delimiter = match FileTable::delimiter_for(output_path.as_ref()) {
+        Some(delimiter) => delimiter,
+        None => {
+            let file_name = try!(utils::file_name_from_path(output_path.as_ref()));
+            return Err(Error::UnsupportedFileType(file_name));
+        },
+    };
+
+    // One row per channel, with the name of the fixture it's in
+    let layout = try!(layout_dao.get_layout(layout_id));
+    let mut rows = Vec::new();
+    for fixid in &layout.fixtures {
+        let fixture = try!(fix_dao.get_fixture(*fixid));
+        for chanid in &fixture.channels {
+            let channel = try!(chan_dao.get_channel(*chanid));
+            let number = |num: Option<u32>| num.map(|num| num.to_string()).unwrap_or(String::new());
+            // Triples with nothing known are left blank, which reads back in as 0,0,0
+            let triple = |t: (Option<i32>, Option<i32>, Option<i32>)| {
+                if t == (None, None, None) {
+                    return String::new();
+                }
+                [t.0, t.1, t.2].iter()
+                    .map(|part| part.map(|part| part.to_string()).unwrap_or(String::new()))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            };
+            rows.push((channel.channel_internal, vec![
+                layout.name.to_owned(),
+                channel.channel_internal.to_string(),
+                channel.channel_dmx.to_string(),
+                fixture.name.to_owned(),
+                channel.name.to_owned(),
+                channel.color.to_owned(),
+                number(channel.numbers.0),
+                number(channel.numbers.1),
+                triple(channel.location),
+                triple(channel.rotation),
+                fixture.fixture_type.clone().unwrap_or(String::new()),
+                format!("{},{},{}", fixture.size.0, fixture.size.1, fixture.size.2),
+            ]));
+        }
+    }
+    rows.sort_by_key(|&(channel_internal, _)| channel_internal);
+
+    let header = ["layoutName", "internalChannel", "dmxChannel", "fixtureName", "channelName", "color",
+        "num_primary", "num_secondary", "location", "rotation", "fixtureType", "fixtureSize"];
+    let rows = rows.into_iter()
+        .map(|(_, cells)| cells)
+        .collect::<Vec<Vec<String>>>();
+    let text = FileTable::to_text(&header, &rows, delimiter);
+
+    let mut file = try!(File::create(output_path.as_ref()).map_err(Error::Io));
+    try!(file.write_all(text.as_bytes()).map_err(Error::Io));
+    Ok(())
+}
+
+/// Builds a chase through a layout: each patched channel is lit on its own
+/// for step_ms, in dmx order
+pub fn chase_layout_steps<CD: ChannelDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    layout_dao: &LD,
+    layout_id: u32,
+    step_ms: u32
+) -> Result<Vec<TestStep>, Error> {
+
+    let chan_ids = try!(layout_dao.get_channel_ids(layout_id));
+    let channels = try!(patched_channels(chan_dao, &chan_ids));
+    if channels.is_empty() {
+        return Err(Error::NothingPatched(format!("layout {}", layout_id)));
+    }
+
+    Ok(channels.iter()
+        .map(|channel| TestStep::channel(channel, step_ms))
+        .collect())
+}
+
+/// Builds a test of one of a layout's fixtures: each of its patched channels
+/// on its own, then every channel of each color together, then all of them.
+/// Each step lasts step_ms.
+pub fn test_fixture_steps<CD: ChannelDao, FD: FixtureDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    fix_dao: &FD,
+    layout_dao: &LD,
+    layout_id: u32,
+    fixid: u32,
+    step_ms: u32
+) -> Result<Vec<TestStep>, Error> {
+
+    // Check that the fixture is in the layout
+    let layout = try!(layout_dao.get_layout(layout_id));
+    if !layout.fixtures.contains(&fixid) {
+        return Err(Error::FixtureNotFound(fixid));
+    }
+
+    let fixture = try!(fix_dao.get_fixture(fixid));
+    let channels = try!(patched_channels(chan_dao, &fixture.channels));
+    if channels.is_empty() {
+        return Err(Error::NothingPatched(format!("fixture '{}'", fixture.name)));
+    }
+
+    let mut steps = channels.iter()
+        .map(|channel| TestStep::channel(channel, step_ms))
+        .collect::<Vec<TestStep>>();
+
+    // Colors are tested in the order they first appear
+    let mut colors: Vec<&str> = Vec::new();
+    for channel in &channels {
+        if !colors.contains(&channel.color.as_str()) {
+            colors.push(&channel.color);
+        }
+    }
+    for color in colors {
+        let color_channels = channels.iter()
+            .filter(|channel| channel.color == color)
+            .collect::<Vec<&Channel>>();
+        steps.push(TestStep::group(&format!("All {}", color), &color_channels, step_ms));
+    }
+
+    let all_channels = channels.iter().collect::<Vec<&Channel>>();
+    steps.push(TestStep::group("All on", &all_channels, step_ms));
+    Ok(steps)
+}
+
+/// Builds a test that turns one of a layout's channels, given by its internal
+/// number, fully on for duration_ms
+pub fn test_channel_steps<CD: ChannelDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    layout_dao: &LD,
+    layout_id: u32,
+    channel_internal: u32,
+    duration_ms: u32
+) -> Result<Vec<TestStep>, Error> {
+
+    let chan_ids = try!(layout_dao.get_channel_ids(layout_id));
+    for chanid in chan_ids {
+        let channel = try!(chan_dao.get_channel(chanid));
+        if channel.channel_internal != channel_internal {
+            continue;
+        }
+        if channel.channel_dmx == 0 {
+            return Err(Error::NothingPatched(format!("channel {}", channel_internal)));
+        }
+        return Ok(vec![TestStep::channel(&channel, duration_ms)]);
+    }
+    Err(Error::ChannelNotFound(channel_internal))
+}
+
+/// Sets the dimming profile of some of a layout's channels or fixtures, or
+/// removes it if None. Returns the number of channels or fixtures changed.
+pub fn set_dimming<CD: ChannelDao, DMD: DimmingDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    dimming_dao: &DMD,
+    layout_dao: &LD,
+    layout_id: u32,
+    targets: &EditTargets,
+    profile: Option<&DimmingProfile>
+) -> Result<u32, Error> {
+
+    match *targets {
+        EditTargets::Channels(ref channels_internal) => {
+            let chan_ids = try!(layout_chan_ids(chan_dao, layout_dao, layout_id, channels_internal));
+            for chanid in &chan_ids {
+                try!(dimming_dao.set_channel_dimming(*chanid, profile));
+            }
+            Ok(chan_ids.len() as u32)
+        },
+        EditTargets::Fixtures(ref fixids) => {
+            try!(check_layout_fixtures(layout_dao, layout_id, fixids));
+            for fixid in fixids {
+                try!(dimming_dao.set_fixture_dimming(*fixid, profile));
+            }
+            Ok(fixids.len() as u32)
+        },
+    }
+}
+
+/// Fetches the dimming profile each of a layout's channels uses, in dmx order.
+/// Channels without one are left out.
+pub fn get_dimming<CD: ChannelDao, DMD: DimmingDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    dimming_dao: &DMD,
+    layout_dao: &LD,
+    layout_id: u32
+) -> Result<Vec<(Channel, DimmingProfile)>, Error> {
+
+    let chan_ids = try!(layout_dao.get_channel_ids(layout_id));
+    let mut dimming = try!(dimming_dao.get_dimming(&chan_ids));
+    let mut channels = Vec::with_capacity(dimming.len());
+    for chanid in chan_ids {
+        if let Some(profile) = dimming.remove(&chanid) {
+            channels.push((try!(chan_dao.get_channel(chanid)), profile));
+        }
+    }
+    Ok(channels)
+}
+
+/// Sets the load of some of a layout's channels or fixtures, or removes it if
+/// None. Returns the number of channels or fixtures changed.
+pub fn set_load<CD: ChannelDao, LD: LayoutDao, PWD: PowerDao>(
+    chan_dao: &CD,
+    layout_dao: &LD,
+    power_dao: &PWD,
+    layout_id: u32,
+    targets: &EditTargets,
+    load: Option<&ChannelLoad>
+) -> Result<u32, Error> {
+
+    match *targets {
+        EditTargets::Channels(ref channels_internal) => {
+            let chan_ids = try!(layout_chan_ids(chan_dao, layout_dao, layout_id, channels_internal));
+            for chanid in &chan_ids {
+                try!(power_dao.set_channel_load(*chanid, load));
+            }
+            Ok(chan_ids.len() as u32)
+        },
+        EditTargets::Fixtures(ref fixids) => {
+            try!(check_layout_fixtures(layout_dao, layout_id, fixids));
+            for fixid in fixids {
+                try!(power_dao.set_fixture_load(*fixid, load));
+            }
+            Ok(fixids.len() as u32)
+        },
+    }
+}
+
+/// Sets the most amps one of a layout's circuits can draw, or removes its limit if None
+pub fn set_circuit_limit<LD: LayoutDao, PWD: PowerDao>(
+    layout_dao: &LD,
+    power_dao: &PWD,
+    layout_id: u32,
+    circuit: &str,
+    max_amps: Option<f64>
+) -> Result<(), Error> {
+
+    if !try!(layout_dao.layout_exists(layout_id)) {
+        return Err(Error::LayoutNotFound(layout_id));
+    }
+    if let Some(max_amps) = max_amps {
+        if !(max_amps > 0.0) || max_amps.is_infinite() {
+            return Err(Error::InvalidPower(format!("A circuit's limit must be more than 0 amps, not {}", max_amps)));
+        }
+    }
+    power_dao.set_circuit_limit(layout_id, circuit, max_amps)
+}
+
+/// Wires some of a layout's channels or fixtures to a port of one of its
+/// controllers, or unwires them if None. Returns how many were changed.
+pub fn set_port<CD: ChannelDao, CTD: ControllerDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    controller_dao: &CTD,
+    layout_dao: &LD,
+    layout_id: u32,
+    targets: &EditTargets,
+    port: Option<&PortAssignment>
+) -> Result<u32, Error> {
+
+    if let Some(port) = port {
+        let controllers = try!(controller_dao.get_controllers(layout_id));
+        let controller = try!(controllers.iter()
+            .find(|controller| controller.controllerid == port.controllerid)
+            .ok_or(Error::ControllerNotFound(port.controllerid)));
+        if port.port == 0 || port.port > controller.ports {
+            return Err(Error::InvalidController(
+                format!("Controller '{}' only has ports 1-{}", controller.name, controller.ports)));
+        }
+    }
+
+    match *targets {
+        EditTargets::Channels(ref channels_internal) => {
+            let chan_ids = try!(layout_chan_ids(chan_dao, layout_dao, layout_id, channels_internal));
+            for chanid in &chan_ids {
+                try!(controller_dao.set_channel_port(*chanid, port));
+            }
+            Ok(chan_ids.len() as u32)
+        },
+        EditTargets::Fixtures(ref fixids) => {
+            try!(check_layout_fixtures(layout_dao, layout_id, fixids));
+            for fixid in fixids {
+                try!(controller_dao.set_fixture_port(*fixid, port));
+            }
+            Ok(fixids.len() as u32)
+        },
+    }
+}
+
+/// Finds the ids of a layout's channels from their internal numbers, in the
+/// order they were given. Every channel must be in the layout.
+fn layout_chan_ids<CD: ChannelDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    layout_dao: &LD,
+    layout_id: u32,
+    channels_internal: &[u32]
+) -> Result<Vec<u32>, Error> {
+
+    let mut chan_id_by_internal = HashMap::new();
+    for chanid in try!(layout_dao.get_channel_ids(layout_id)) {
+        let channel = try!(chan_dao.get_channel(chanid));
+        chan_id_by_internal.insert(channel.channel_internal, chanid);
+    }
+
+    let mut chan_ids = Vec::with_capacity(channels_internal.len());
+    for channel_internal in channels_internal {
+        match chan_id_by_internal.get(channel_internal) {
+            Some(chanid) => chan_ids.push(*chanid),
+            None => return Err(Error::ChannelNotFound(*channel_internal)),
+        }
+    }
+    Ok(chan_ids)
+}
+
+/// Checks that every fixture is in a layout
+fn check_layout_fixtures<LD: LayoutDao>(layout_dao: &LD, layout_id: u32, fixids: &[u32]) -> Result<(), Error> {
+    let layout = try!(layout_dao.get_layout(layout_id));
+    match fixids.iter().find(|fixid| !layout.fixtures.contains(fixid)) {
+        Some(fixid) => Err(Error::FixtureNotFound(*fixid)),
+        None => Ok(()),
+    }
+}
+
+/// Fetches the channels that are patched to a dmx channel, in dmx order
+fn patched_channels<CD: ChannelDao>(chan_dao: &CD, chan_ids: &[u32]) -> Result<Vec<Channel>, Error> {
+    let mut channels = Vec::with_capacity(chan_ids.len());
+    for chanid in chan_ids {
+        let channel = try!(chan_dao.get_channel(*chanid));
+        if channel.channel_dmx > 0 {
+            channels.push(channel);
+        }
+    }
+    channels.sort_by_key(|channel| channel.channel_dmx);
+    Ok(channels)
+}
+
+/// Loads a CSV/TSV file
+fn load_table<P: AsRef<Path>>(path: P, delimiter: char) -> Result<FileTable, Error> {
+    let text = try!(utils::file_as_string(path.as_ref()));
+    FileTable::parse(&text, delimiter)
+}
+
+/// Loads a CSV/TSV layout as layout JSON and lints it, numbering rows by their line
+/// in the file. The layout is named layout_name if given, or by its layoutName
+/// column, which has to be the same on every row that fills it in.
+fn lint_layout_table<P: AsRef<Path>>(
+    path: P,
+    delimiter: char,
+    layout_name: Option<&str>
+) -> Result<(json::Json, Vec<LayoutIssue>), Error> {
+
+    let table = try!(load_table(path.as_ref(), delimiter));
+    let column_names = table.rows.iter()
+        .filter_map(|row| table.get(row, "layoutName"))
+        .filter(|name| !name.is_empty())
+        .collect::<BTreeSet<&str>>();
+    let mut name_issue = None;
+    let layout_name = match layout_name {
+        Some(layout_name) => layout_name.to_owned(),
+        None if column_names.len() == 1 => column_names.iter().next().unwrap().to_string(),
+        None => {
+            name_issue = Some(if column_names.is_empty() {
+                LayoutIssue::error(None, "No layout name given; use --name or a layoutName column".to_owned())
+            } else {
+                LayoutIssue::error(None, format!("Rows give different layout names: {}",
+                    column_names.iter().cloned().collect::<Vec<&str>>().join(", ")))
+            });
+            String::new()
+        },
+    };
+
+    let layout = FileLayout::table_to_json(&table, &layout_name);
+    let mut issues = FileLayout::lint(&layout);
+    for issue in issues.iter_mut() {
+        issue.row = issue.row.map(|row| table.rows[row - 1].line);
+    }
+    if let Some(issue) = name_issue {
+        issues.insert(0, issue);
+    }
+    Ok((layout, issues))
+}
+
+/// JSON layouts are named by their layoutName, so they can't be given another name
+fn check_json_name(layout_name: Option<&str>) -> Result<(), Error> {
+    match layout_name {
+        Some(_) => Err(Error::InvalidLayout("Only CSV/TSV layouts can be given a name".to_owned())),
+        None => Ok(()),
+    }
+}
+
 /// Set a layout's sequence
 pub fn set_sequence_layout<LD: LayoutDao, SD: SequenceDao>(
     layout_dao: &LD,
@@ -1,22 +1,42 @@
+extern crate chrono;
+#[cfg(feature = "cpal")]
+extern crate cpal;
+#[cfg(unix)]
+extern crate libc;
 extern crate openssl;
 extern crate postgres;
 extern crate regex;
 extern crate rustc_serialize;
+#[cfg(feature = "sfml")]
 extern crate sfml;
+extern crate symphonia;
 
+mod controller;
 mod layout;
+mod music;
 mod permissions;
+mod playback;
+mod power;
 mod project;
+mod schedule;
 mod sequence;
 mod user;
+pub mod audio;
 pub mod dao;
+pub mod effects;
 pub mod error;
+pub mod output;
 pub mod project_types;
 pub mod utils;
 
 // Re-exports
+pub use controller::*;
 pub use layout::*;
+pub use music::*;
 pub use permissions::*;
+pub use playback::*;
+pub use power::*;
 pub use project::*;
+pub use schedule::*;
 pub use sequence::*;
 pub use user::*;
@@ -9,8 +9,11 @@ use rustc_serialize::json;
 use docopt::Docopt;
 
 use proton_cli::error::Error;
-use proton_cli::dao::{self, LayoutDao};
-use proton_cli::project_types::{Project, Sequence, PermissionEnum};
+use proton_cli::{MusicStore, PlaybackEvent, PlaybackReport, SystemClock};
+use proton_cli::audio::{self, AudioAnalysis, AudioInfo};
+use proton_cli::dao::{self, ControllerDao, LayoutDao, ProjectDao, SequenceDao};
+use proton_cli::output::{self, ArtNetDestinations, ArtNetSender, E131Sender, EnttecDriver, EnttecWidget, OutputDriver, SerialPort};
+use proton_cli::project_types::{Channel, ChannelLoad, Controller, ControllerProtocol, DataEdit, DimmingCurve, DimmingProfile, DurationPolicy, EditTargets, FilePatch, FilePatchRow, IssueLevel, LayoutIssue, LayoutPatch, Project, ReactiveMapping, ResampleMode, ScheduleDecision, Sequence, SequenceIssue, PermissionEnum, PortAssignment, PortRange, PowerReport, SparePolicy, TestPattern, TestStep, TimingTrack};
 use proton_cli::utils;
 
 
@@ -18,68 +21,225 @@ const USAGE: &'static str = "
 Command-line interface for Proton
 
 Usage:
+  ./proton add-controller <admin-key> <layout-id> <name> <address> <universes> [--protocol=<p>] [--ports=<n>] [--port-channels=<n>]
+  ./proton add-music <admin-key> <music-file> [--for-sequence=<seqid>]
+  ./proton analyze-music <admin-key> <seqid>
+  ./proton chase-layout <layout-id> [--step=<ms>] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+  ./proton chase-layout <admin-key> <layout-id> <music-file> --save=<name> [--step=<ms>]
+  ./proton check-power <seqid> [--volts=<v>]
+  ./proton check-sequences <proj-name>
+  ./proton clear-circuit-limit <admin-key> <layout-id> <circuit>
+  ./proton clear-dimming <admin-key> <layout-id> (--channels=<ids> | --fixtures=<ids>)
+  ./proton clear-load <admin-key> <layout-id> (--channels=<ids> | --fixtures=<ids>)
+  ./proton clear-port <admin-key> <layout-id> (--channels=<ids> | --fixtures=<ids>)
+  ./proton clone-sequence <admin-key> <seqid> <name>
+  ./proton compile-script <admin-key> <seqid> <script-file>
+  ./proton concat-sequences <admin-key> <name> <music-file> <seqids>... [--duration-check=<policy>]
+  ./proton copy-data <admin-key> <src-seqid> <t-start> <t-end> <dest-seqid> <t-offset>
   ./proton delete-sequence <admin-key> <seqid>
+  ./proton delete-timing-track <admin-key> <seqid> <track>
+  ./proton edit-data <admin-key> <seqid> <t-start> <t-end> <effect> [<param>...] (--channels=<ids> | --fixtures=<ids>)
+  ./proton export-controllers <layout-id> <output-file>
+  ./proton export-layout <layout-id> <output-file>
+  ./proton gc-music <admin-key> [--dry-run]
+  ./proton generate-reactive <admin-key> <seqid> <mapping>...
+  ./proton get-controllers <layout-id>
+  ./proton get-dimming <layout-id>
   ./proton get-layout-id <proj-name>
+  ./proton get-layout-mapping <layout-id> [<version>]
+  ./proton get-music-info <music-file>
+  ./proton get-patch-history <layout-id>
   ./proton get-playlist-data <proj-name>
   ./proton get-project <proj-name>
+  ./proton get-schedule <proj-name>
   ./proton get-sequence <seqid>
+  ./proton get-timing-tracks <seqid>
   ./proton get-user-id <public-key>
+  ./proton import-timing <admin-key> <seqid> <timing-file> [--name=<name>]
   ./proton insert-sequence <admin-key> <proj-name> <seqid> [<index>]
+  ./proton list-music
   ./proton list-permissions <uid>
-  ./proton new-layout <layout-file>
+  ./proton new-layout <layout-file> [--name=<name>] [--spares=<policy>]
   ./proton new-project <name> <layout-id>
   ./proton new-section <admin-key> <t_start> <t_end> <seqid> <fixid>..
-  ./proton new-sequence <admin-key> <name> <music-file> <seq-duration> <layout-id>
+  ./proton new-sequence <admin-key> <name> <music-file> <seq-duration> <layout-id> [--duration-check=<policy>]
   ./proton new-user <admin-key> <name>
-  ./proton new-vixen-sequence <admin-key> <name> <music-file> <seq-duration> <frame-duration> <data-file> <layout-id>
+  ./proton new-vixen-sequence <admin-key> <name> <music-file> <seq-duration> <frame-duration> <data-file> <layout-id> [--duration-check=<policy>]
   ./proton patch-layout <admin-key> <layout-id> <patch-file>
+  ./proton play <proj-name> [--no-audio] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+  ./proton play-sequence <seqid> [--no-audio] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+  ./proton remove-controller <admin-key> <layout-id> <controller-id>
   ./proton remove-sequence <admin-key> <proj-name> <seqid>
   ./proton remove-user <admin-key> <uid>
+  ./proton resize-sequence <admin-key> <seqid> <seq-duration> [--at-start]
+  ./proton retime-sequence <admin-key> <seqid> <frame-duration> [--resample=<mode>]
+  ./proton rollback-layout <admin-key> <layout-id> <version>
+  ./proton run-schedule <proj-name> [--no-audio] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+  ./proton set-circuit-limit <admin-key> <layout-id> <circuit> <amps>
+  ./proton set-dimming <admin-key> <layout-id> <curve> [--min=<v>] [--max=<v>] (--channels=<ids> | --fixtures=<ids>)
+  ./proton set-load <admin-key> <layout-id> <watts> [--circuit=<name>] (--channels=<ids> | --fixtures=<ids>)
+  ./proton set-music-offset <admin-key> <seqid> --offset=<ms>
   ./proton set-permission <admin-key> (add | remove) <uid> Administrate
   ./proton set-permission <admin-key> (add | remove) <uid> EditSequence <target-sequence>
   ./proton set-permission <admin-key> (add | remove) <uid> EditSection <target-sequence> <target-section>
   ./proton set-permission <admin-key> (add | remove) <name> EditSeqSec <target-section>
+  ./proton set-port <admin-key> <layout-id> <controller-id> <port> (--channels=<ids> | --fixtures=<ids>)
+  ./proton set-schedule <admin-key> <proj-name> <schedule-file>
   ./proton set-sequence-layout <admin-key> <seqid> <layout-id>
+  ./proton test-channel <layout-id> <channel> [--seconds=<s>] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+  ./proton test-channel <admin-key> <layout-id> <channel> <music-file> --save=<name> [--seconds=<s>]
+  ./proton test-fixture <layout-id> <fixid> [--step=<ms>] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+  ./proton test-fixture <admin-key> <layout-id> <fixid> <music-file> --save=<name> [--step=<ms>]
+  ./proton test-pattern <layout-id> <pattern> [--seconds=<s>] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+  ./proton validate-controllers <layout-id>
+  ./proton validate-layout <layout-file> [--name=<name>]
+  ./proton verify-music <proj-name>
   ./proton (-h | --help)
 
 Options:
-  -h --help     Show this screen
+  -h --help                  Show this screen
+  --artnet=<destinations>    Send Art-Net instead of E1.31, e.g. 1=10.0.0.5,2=10.0.0.6 or 10.255.255.255
+  --artsync                  Send an Art-Net sync after each frame
+  --at-start                 Add or remove frames at the start of the sequence instead of the end
+  --channels=<ids>           Internal channels to edit, dim, load or wire to a port, e.g. 1-4,7
+  --circuit=<name>           Circuit the channels or fixtures are wired to
+  --controllers              Send each of the layout's controllers just its own universes
+  --dry-run                  List the files that would be removed without removing them
+  --duration-check=<policy>  What to do if the sequence doesn't match its music: warn or error [default: warn]
+  --fixtures=<ids>           Fixtures to edit, dim, load or wire to a port, e.g. 2,5-6
+  --for-sequence=<seqid>     Sequence to point at the added music, e.g. one whose music isn't stored yet
+  --max=<v>                  Highest value sent to a dimmed channel, from 0 to 255 [default: 255]
+  --min=<v>                  Lowest value sent to a dimmed channel, from 0 to 255 [default: 0]
+  --name=<name>              Name of a CSV/TSV layout, or to store a file's only timing track under
+  --no-audio                 Run shows silently against the system clock instead of playing their music
+  --offset=<ms>              Time the music starts at, negative to skip the start of the music
+  --port-channels=<n>        Most channels each port of a controller can drive [default: 512]
+  --ports=<n>                Number of output ports a controller has [default: 1]
+  --priority=<n>             E1.31 priority, from 0 to 200 [default: 100]
+  --protocol=<p>             Protocol a controller is sent: e131 or artnet [default: e131]
+  --resample=<mode>          How to resample data: nearest, linear or hold [default: linear]
+  --save=<name>              Save a channel test as a new sequence instead of sending it
+  --seconds=<s>              How long to send a test pattern or channel for [default: 10]
+  --serial=<device>          Send to an Enttec USB-DMX interface, e.g. /dev/ttyUSB0
+  --spares=<policy>          What to do with spare channels: reserve or fixture [default: reserve]
+  --step=<ms>                How long each step of a channel test lasts [default: 1000]
+  --unicast=<address>        Send E1.31 to one receiver instead of multicasting, e.g. 10.0.0.5
+  --universe=<n>             Universe to send to a USB-DMX interface [default: 1]
+  --volts=<v>                Voltage of the circuits, to work out amps from watts [default: 120]
+  --widget=<type>            Enttec USB-DMX interface: pro or open [default: pro]
 ";
 
 // Docopt arguments are mapped to this struct
 #[derive(Debug, RustcDecodable)]
 struct Args {
+	arg_address: Option<String>,
 	arg_admin_key: Option<String>,
+	arg_amps: Option<f64>,
+	arg_channel: Option<u32>,
+	arg_circuit: Option<String>,
+	arg_controller_id: Option<u32>,
+	arg_curve: Option<String>,
 	arg_data_file: Option<String>,
+	arg_dest_seqid: Option<u32>,
+	arg_effect: Option<String>,
 	arg_fixid: Option<u32>,
 	arg_frame_duration: Option<u32>,
 	arg_index: Option<u32>,
 	arg_layout_id: Option<u32>,
 	arg_layout_file: Option<String>,
+	arg_mapping: Vec<String>,
 	arg_music_file: Option<String>,
 	arg_name: Option<String>,
+	arg_output_file: Option<String>,
+	arg_param: Vec<String>,
 	arg_patch_file: Option<String>,
+	arg_pattern: Option<String>,
+	arg_port: Option<u32>,
 	arg_proj_name: Option<String>,
 	arg_public_key: Option<String>,
 	arg_root_public_key: Option<String>,
+	arg_schedule_file: Option<String>,
+	arg_script_file: Option<String>,
 	arg_seqid: Option<u32>,
+	arg_seqids: Vec<u32>,
 	arg_seq_duration: Option<u32>,
-	arg_t_start: Option<u32>,
-	arg_t_end: Option<u32>,
+	arg_src_seqid: Option<u32>,
+	arg_t_start: Option<String>,
+	arg_t_end: Option<String>,
+	arg_t_offset: Option<String>,
 	arg_target_sequence: Option<u32>,
 	arg_target_section: Option<u32>,
+	arg_timing_file: Option<String>,
+	arg_track: Option<String>,
 	arg_uid: Option<u32>,
+	arg_universes: Option<String>,
+	arg_version: Option<u32>,
+	arg_watts: Option<f64>,
+	flag_artnet: Option<String>,
+	flag_artsync: bool,
+	flag_at_start: bool,
+	flag_channels: Option<String>,
+	flag_circuit: Option<String>,
+	flag_controllers: bool,
+	flag_dry_run: bool,
+	flag_duration_check: String,
+	flag_fixtures: Option<String>,
+	flag_for_sequence: Option<u32>,
+	flag_max: u16,
+	flag_min: u16,
+	flag_name: Option<String>,
+	flag_no_audio: bool,
+	flag_offset: Option<i32>,
+	flag_port_channels: u32,
+	flag_ports: u32,
+	flag_priority: u8,
+	flag_protocol: String,
+	flag_resample: String,
+	flag_save: Option<String>,
+	flag_seconds: u32,
+	flag_serial: Option<String>,
+	flag_spares: String,
+	flag_step: u32,
+	flag_unicast: Option<String>,
+	flag_universe: u16,
+	flag_volts: f64,
+	flag_widget: String,
 }
 
 // Generic return type of all functions that are called based on cli commands
 enum ProtonReturn {
+	AudioAnalysis(AudioAnalysis),
+	AudioInfo(AudioInfo),
+	ChannelsEdited(u32),
+	ControllerId(u32),
+	Controllers(Vec<Controller>, Vec<PortRange>),
+	CuesCompiled(u32),
+	Dimming(Vec<(Channel, DimmingProfile)>),
+	DimmingChanged(u32),
 	LayoutId(u32),
+	LayoutIssues(Vec<LayoutIssue>),
+	LayoutMapping(FilePatch),
+	LayoutVersion(u32),
+	LoadsChanged(u32),
+	MusicFile(String),
+	MusicFiles(Vec<(String, Vec<u32>)>),
+	NewSequence(u32, Option<SequenceIssue>),
 	NoReturn,
+	NumFrames(u32),
+	PatchHistory(Vec<LayoutPatch>),
+	Playback(PlaybackReport),
 	PlaylistData(String),
+	PortsChanged(u32),
+	Power(PowerReport),
 	Project(Project),
 	PublicKey(String),
+	RemovedMusic(Vec<String>, bool),
+	Schedule(String, ScheduleDecision),
 	Sequence(Sequence),
 	SequenceId(u32),
+	SequenceIssues(Vec<SequenceIssue>),
+	TimingTrackData(Vec<TimingTrack>),
+	TimingTracks(Vec<TimingTrack>),
 	Uid(u32),
 }
 
@@ -94,13 +254,42 @@ fn main() {
 
 	// Every proton command is mapped to a specific function that should be run
 	let command: fn(Args) -> Result<ProtonReturn, Error> = match env::args().nth(1).unwrap().as_ref() {
+		"add-controller" => run_add_controller,
+		"add-music" => run_add_music,
+		"analyze-music" => run_analyze_music,
+		"chase-layout" => run_chase_layout,
+		"check-power" => run_check_power,
+		"check-sequences" => run_check_sequences,
+		"clear-circuit-limit" => run_clear_circuit_limit,
+		"clear-dimming" => run_clear_dimming,
+		"clear-load" => run_clear_load,
+		"clear-port" => run_clear_port,
+		"clone-sequence" => run_clone_sequence,
+		"compile-script" => run_compile_script,
+		"concat-sequences" => run_concat_sequences,
+		"copy-data" => run_copy_data,
 		"delete-sequence" => run_delete_sequence,
+		"delete-timing-track" => run_delete_timing_track,
+		"edit-data" => run_edit_data,
+		"export-controllers" => run_export_controllers,
+		"export-layout" => run_export_layout,
+		"gc-music" => run_gc_music,
+		"generate-reactive" => run_generate_reactive,
+		"get-controllers" => run_get_controllers,
+		"get-dimming" => run_get_dimming,
 		"get-layout-id" => run_get_layout_id,
+		"get-layout-mapping" => run_get_layout_mapping,
+		"get-music-info" => run_get_music_info,
+		"get-patch-history" => run_get_patch_history,
 		"get-playlist-data" => run_get_playlist_data,
 		"get-project" => run_get_project,
+		"get-schedule" => run_get_schedule,
 		"get-sequence" => run_get_sequence,
+		"get-timing-tracks" => run_get_timing_tracks,
 		"get_user_id" => run_get_user_id,
+		"import-timing" => run_import_timing,
 		"insert-sequence" => run_insert_sequence,
+		"list-music" => run_list_music,
 		"list-permissions" => run_list_permissions,
 		"new-layout" => run_new_layout,
 		"new-project" => run_new_project,
@@ -109,10 +298,29 @@ fn main() {
 		"new-user" => run_new_user,
 		"new-vixen-sequence" => run_new_vixen_sequence,
 		"patch-layout" => run_patch_layout,
+		"play" => run_play,
+		"play-sequence" => run_play_sequence,
+		"remove-controller" => run_remove_controller,
 		"remove-sequence" => run_remove_sequence,
 		"remove-user" => run_remove_user,
+		"resize-sequence" => run_resize_sequence,
+		"retime-sequence" => run_retime_sequence,
+		"rollback-layout" => run_rollback_layout,
+		"run-schedule" => run_run_schedule,
+		"set-circuit-limit" => run_set_circuit_limit,
+		"set-dimming" => run_set_dimming,
+		"set-load" => run_set_load,
+		"set-music-offset" => run_set_music_offset,
 		"set-permission" => run_set_permission,
+		"set-port" => run_set_port,
+		"set-schedule" => run_set_schedule,
 		"set-sequence-layout" => run_set_sequence_layout,
+		"test-channel" => run_test_channel,
+		"test-fixture" => run_test_fixture,
+		"test-pattern" => run_test_pattern,
+		"validate-controllers" => run_validate_controllers,
+		"validate-layout" => run_validate_layout,
+		"verify-music" => run_verify_music,
 		_ => panic!("Invalid first argument"),
 	};
 
@@ -120,19 +328,448 @@ fn main() {
 	let result = command(args);
 	match result {
 		Ok(ret) => match ret {
+			ProtonReturn::AudioAnalysis(analysis) => {
+				match analysis.tempo_bpm {
+					Some(bpm) => println!("Tempo: {:.1} BPM", bpm),
+					None => println!("Tempo: unknown"),
+				}
+				println!("Beats: {}", analysis.beats.len());
+				println!("Onsets: {}", analysis.onsets.len());
+			},
+			ProtonReturn::AudioInfo(info) => {
+				println!("Duration: {}ms", info.duration_ms);
+				println!("Sample rate: {}Hz", info.sample_rate);
+				println!("Channels: {}", info.channels);
+			},
+			ProtonReturn::ChannelsEdited(num) => println!("Channels edited: {}", num),
+			ProtonReturn::ControllerId(controllerid) => println!("Controller id: {}", controllerid),
+			ProtonReturn::Controllers(controllers, port_ranges) => {
+				for controller in controllers.iter() {
+					println!("{}", controller);
+					for range in port_ranges.iter().filter(|range| range.controller == controller.name) {
+						println!("  Port {}: universe {}, channels {}-{} ({} channel(s))",
+							range.port,
+							range.universe,
+							range.start_slot,
+							range.end_slot,
+							range.channels);
+					}
+				}
+			},
+			ProtonReturn::CuesCompiled(num) => println!("Cues compiled: {}", num),
+			ProtonReturn::Dimming(channels) => {
+				for (channel, profile) in channels {
+					println!("{} (internal {}, dmx {}): {}",
+						channel.name,
+						channel.channel_internal,
+						channel.channel_dmx,
+						profile);
+				}
+			},
+			ProtonReturn::DimmingChanged(num) => println!("Dimming changed: {}", num),
 			ProtonReturn::LayoutId(lid) => println!("Layout id: {}", lid),
+			ProtonReturn::LayoutIssues(issues) => {
+				for issue in issues.iter() {
+					println!("{}", issue);
+				}
+				let num_errors = issues.iter()
+					.filter(|issue| issue.level == IssueLevel::Error)
+					.count();
+				println!("{} error(s), {} warning(s)", num_errors, issues.len() - num_errors);
+				if num_errors > 0 {
+					std::process::exit(1);
+				}
+			},
+			ProtonReturn::LayoutMapping(patch_file) => println!("{}", json::as_pretty_json(&patch_file)),
+			ProtonReturn::LayoutVersion(version) => println!("Layout version: {}", version),
+			ProtonReturn::LoadsChanged(num) => println!("Loads changed: {}", num),
+			ProtonReturn::MusicFile(file_name) => println!("Music file: {}", file_name),
+			ProtonReturn::MusicFiles(files) => {
+				for (file_name, seqids) in files {
+					if seqids.is_empty() {
+						println!("{}: unused", file_name);
+					} else {
+						let seqids = seqids.iter()
+							.map(|seqid| seqid.to_string())
+							.collect::<Vec<String>>();
+						println!("{}: sequence(s) {}", file_name, seqids.join(", "));
+					}
+				}
+			},
+			ProtonReturn::NewSequence(sid, issue) => {
+				if let Some(issue) = issue {
+					println!("{}", issue);
+				}
+				println!("Sequence id: {}", sid);
+			},
 			ProtonReturn::NoReturn => println!("Worked!"),
+			ProtonReturn::NumFrames(num) => println!("Number of frames: {}", num),
+			ProtonReturn::PatchHistory(history) => println!("{}", json::as_pretty_json(&history)),
+			ProtonReturn::Playback(report) => {
+				println!("Sequences played: {}", report.sequences);
+				println!("Frames sent: {}", report.frames_sent);
+				println!("Frames dropped: {}", report.frames_dropped);
+			},
 			ProtonReturn::PlaylistData(data) => println!("PLAYLIST_DATA:::{}", data),
+			ProtonReturn::PortsChanged(num) => println!("Ports changed: {}", num),
+			ProtonReturn::Power(report) => {
+				for summary in report.summaries.iter() {
+					let limit = match summary.max_amps {
+						Some(max_amps) => format!(", limit {:.1}A", max_amps),
+						None => String::new(),
+					};
+					println!("{}: peak {:.0}W ({:.1}A) at {:.3}s, average {:.0}W ({:.1}A){}",
+						summary.group,
+						summary.peak_watts,
+						summary.peak_watts / report.volts,
+						summary.peak_ms as f64 / 1000.0,
+						summary.average_watts,
+						summary.average_watts / report.volts,
+						limit);
+				}
+				for overload in report.overloads.iter() {
+					println!("Over limit: {} from {:.3}s to {:.3}s, peaking at {:.1}A",
+						overload.group,
+						overload.start_ms as f64 / 1000.0,
+						overload.end_ms as f64 / 1000.0,
+						overload.peak_amps);
+				}
+				if report.unrated_channels > 0 {
+					println!("{} patched channel(s) have no load set and aren't counted", report.unrated_channels);
+				}
+			},
 			ProtonReturn::Project(project) => println!("Project: {:?}", project),
 			ProtonReturn::PublicKey(s) => println!("PubKey: {}", s),
+			ProtonReturn::RemovedMusic(file_names, dry_run) => {
+				let (action, summary) = if dry_run {
+					("Would remove", "would be removed")
+				} else {
+					("Removed", "removed")
+				};
+				for file_name in file_names.iter() {
+					println!("{}: {}", action, file_name);
+				}
+				println!("{} file(s) {}", file_names.len(), summary);
+			},
+			ProtonReturn::Schedule(schedule_json, decision) => {
+				println!("{}", schedule_json);
+				match decision {
+					ScheduleDecision::Play { seqid, position_ms, intermission } => println!(
+						"Playing{} sequence {}, {}ms in",
+						if intermission { " intermission" } else { "" },
+						seqid,
+						position_ms),
+					ScheduleDecision::Wait(start) => println!("Next show starts at {}", start),
+				}
+			},
 			ProtonReturn::Sequence(seq) => println!("Sequence: {:?}", seq),
 			ProtonReturn::SequenceId(sid) => println!("Sequence id: {}", sid),
+			ProtonReturn::SequenceIssues(issues) => {
+				for issue in issues.iter() {
+					println!("{}", issue);
+				}
+				let num_errors = issues.iter()
+					.filter(|issue| issue.level == IssueLevel::Error)
+					.count();
+				println!("{} error(s), {} warning(s)", num_errors, issues.len() - num_errors);
+			},
+			ProtonReturn::TimingTrackData(tracks) => println!("{}", json::as_pretty_json(&tracks)),
+			ProtonReturn::TimingTracks(tracks) => {
+				for track in tracks.iter() {
+					println!("{}: {} mark(s)", track.name, track.marks.len());
+				}
+			},
 			ProtonReturn::Uid(uid) => println!("User id: {}", uid)
 		},
 		Err(e) => println!("{:?}", e.to_string()),
 	};
 }
 
+/// add-controller <admin-key> <layout-id> <name> <address> <universes> [--protocol=<p>] [--ports=<n>] [--port-channels=<n>]
+fn run_add_controller(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let protocol = try!(args.flag_protocol.parse::<ControllerProtocol>());
+	let universes = try!(Controller::parse_universes(&args.arg_universes.unwrap()));
+	let controller = try!(Controller::new(
+		args.arg_layout_id.unwrap(),
+		&args.arg_name.unwrap(),
+		&args.arg_address.unwrap(),
+		protocol,
+		universes,
+		args.flag_ports,
+		args.flag_port_channels));
+	let controller_dao = try!(dao::ControllerDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let controller = try!(proton_cli::add_controller(&controller_dao, &layout_dao, &controller));
+	Ok(ProtonReturn::ControllerId(controller.controllerid))
+}
+
+/// add-music <admin-key> <music-file> [--for-sequence=<seqid>]
+fn run_add_music(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let music_file = args.arg_music_file.unwrap();
+	let music_file_path = Path::new(&music_file);
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let music_store = MusicStore::new(proton_cli::MUSIC_DIR);
+	let file_name = match args.flag_for_sequence {
+		Some(seqid) => {
+			let seq_dao = try!(dao::SequenceDaoPostgres::new());
+			try!(proton_cli::add_music_for_sequence(
+				&audio_backend(), &music_store, &seq_dao, seqid, &music_file_path))
+		},
+		None => try!(proton_cli::add_music(&audio_backend(), &music_store, &music_file_path)),
+	};
+	Ok(ProtonReturn::MusicFile(file_name))
+}
+
+/// analyze-music <admin-key> <seqid>
+fn run_analyze_music(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let seqid = args.arg_seqid.unwrap();
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let timing_dao = try!(dao::TimingDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the user can edit the sequence
+	let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(seqid)];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let analysis = try!(proton_cli::analyze_music(
+		&audio_backend(),
+		&MusicStore::new(proton_cli::MUSIC_DIR),
+		&sequence_dao,
+		&timing_dao,
+		seqid));
+	Ok(ProtonReturn::AudioAnalysis(analysis))
+}
+
+/// chase-layout <layout-id> [--step=<ms>] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+/// chase-layout <admin-key> <layout-id> <music-file> --save=<name> [--step=<ms>]
+fn run_chase_layout(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_id = args.arg_layout_id.unwrap();
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let steps = try!(proton_cli::chase_layout_steps(&channel_dao, &layout_dao, layout_id, args.flag_step));
+	send_or_save_test_steps(&args, layout_id, &steps)
+}
+
+/// check-power <seqid> [--volts=<v>]
+fn run_check_power(args: Args) -> Result<ProtonReturn, Error> {
+	let seqid = args.arg_seqid.unwrap();
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let controller_dao = try!(dao::ControllerDaoPostgres::new());
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let dimming_dao = try!(dao::DimmingDaoPostgres::new());
+	let power_dao = try!(dao::PowerDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let report = try!(proton_cli::check_power(
+		&channel_dao,
+		&controller_dao,
+		&data_dao,
+		&dimming_dao,
+		&power_dao,
+		&sequence_dao,
+		seqid,
+		args.flag_volts));
+	Ok(ProtonReturn::Power(report))
+}
+
+/// check-sequences <proj-name>
+fn run_check_sequences(args: Args) -> Result<ProtonReturn, Error> {
+	let proj_name = args.arg_proj_name.unwrap();
+	let proj_dao = try!(dao::ProjectDaoPostgres::new());
+	let seq_dao = try!(dao::SequenceDaoPostgres::new());
+	let issues = try!(proton_cli::check_sequences(&proj_dao, &seq_dao, &proj_name));
+	Ok(ProtonReturn::SequenceIssues(issues))
+}
+
+/// clear-circuit-limit <admin-key> <layout-id> <circuit>
+fn run_clear_circuit_limit(args: Args) -> Result<ProtonReturn, Error> {
+	run_set_or_clear_circuit_limit(args, None)
+}
+
+/// clear-dimming <admin-key> <layout-id> (--channels=<ids> | --fixtures=<ids>)
+fn run_clear_dimming(args: Args) -> Result<ProtonReturn, Error> {
+	run_set_or_clear_dimming(args, None)
+}
+
+/// clear-load <admin-key> <layout-id> (--channels=<ids> | --fixtures=<ids>)
+fn run_clear_load(args: Args) -> Result<ProtonReturn, Error> {
+	run_set_or_clear_load(args, None)
+}
+
+/// clear-port <admin-key> <layout-id> (--channels=<ids> | --fixtures=<ids>)
+fn run_clear_port(args: Args) -> Result<ProtonReturn, Error> {
+	run_set_or_clear_port(args, None)
+}
+
+/// clone-sequence <admin-key> <seqid> <name>
+fn run_clone_sequence(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let seqid = args.arg_seqid.unwrap();
+	let name = args.arg_name.unwrap();
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let new_seqid = try!(proton_cli::clone_sequence(
+		&data_dao,
+		&sequence_dao,
+		seqid,
+		&name));
+	Ok(ProtonReturn::SequenceId(new_seqid))
+}
+
+/// compile-script <admin-key> <seqid> <script-file>
+fn run_compile_script(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let seqid = args.arg_seqid.unwrap();
+	let script_file = args.arg_script_file.unwrap();
+	let script_path = Path::new(&script_file);
+
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let fixture_dao = try!(dao::FixtureDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let timing_dao = try!(dao::TimingDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the user can edit the sequence
+	let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(seqid)];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let num_cues = try!(proton_cli::compile_script(
+		&channel_dao,
+		&data_dao,
+		&fixture_dao,
+		&layout_dao,
+		&sequence_dao,
+		&timing_dao,
+		seqid,
+		script_path));
+	Ok(ProtonReturn::CuesCompiled(num_cues))
+}
+
+/// concat-sequences <admin-key> <name> <music-file> <seqids>... [--duration-check=<policy>]
+fn run_concat_sequences(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let name = args.arg_name.unwrap();
+	let music_file = args.arg_music_file.unwrap();
+	let music_file_path = Path::new(&music_file);
+	let seqids = args.arg_seqids;
+	let duration_policy = try!(args.flag_duration_check.parse::<DurationPolicy>());
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let (seqid, issue) = try!(proton_cli::concat_sequences(
+		&audio_backend(),
+		&MusicStore::new(proton_cli::MUSIC_DIR),
+		&channel_dao,
+		&data_dao,
+		&sequence_dao,
+		&name,
+		&music_file_path,
+		&seqids,
+		duration_policy));
+	Ok(ProtonReturn::NewSequence(seqid, issue))
+}
+
+/// copy-data <admin-key> <src-seqid> <t-start> <t-end> <dest-seqid> <t-offset>
+fn run_copy_data(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let src_seqid = args.arg_src_seqid.unwrap();
+	let dest_seqid = args.arg_dest_seqid.unwrap();
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let timing_dao = try!(dao::TimingDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// The range is in the source sequence's time, the offset in the destination's
+	let t_start = try!(proton_cli::resolve_time(&timing_dao, src_seqid, &args.arg_t_start.unwrap()));
+	let t_end = try!(proton_cli::resolve_time(&timing_dao, src_seqid, &args.arg_t_end.unwrap()));
+	let t_offset = try!(proton_cli::resolve_time(&timing_dao, dest_seqid, &args.arg_t_offset.unwrap()));
+
+	// Check that the user can edit the sequence being copied into
+	let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(dest_seqid)];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let num_copied = try!(proton_cli::copy_sequence_data(
+		&channel_dao,
+		&data_dao,
+		&sequence_dao,
+		src_seqid,
+		t_start,
+		t_end,
+		dest_seqid,
+		t_offset));
+	Ok(ProtonReturn::ChannelsEdited(num_copied))
+}
+
 fn run_delete_sequence(args: Args) -> Result<ProtonReturn, Error> {
 	let admin_key = args.arg_admin_key.unwrap();
 	let admin_key_path = Path::new(&admin_key);
@@ -151,6 +788,184 @@ fn run_delete_sequence(args: Args) -> Result<ProtonReturn, Error> {
 	Ok(ProtonReturn::NoReturn)
 }
 
+/// delete-timing-track <admin-key> <seqid> <track>
+fn run_delete_timing_track(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let seqid = args.arg_seqid.unwrap();
+	let track = args.arg_track.unwrap();
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let timing_dao = try!(dao::TimingDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the user can edit the sequence
+	let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(seqid)];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	try!(proton_cli::delete_timing_track(&sequence_dao, &timing_dao, seqid, &track));
+	Ok(ProtonReturn::NoReturn)
+}
+
+/// edit-data <admin-key> <seqid> <t-start> <t-end> <effect> [<param>...] (--channels=<ids> | --fixtures=<ids>)
+fn run_edit_data(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let seqid = args.arg_seqid.unwrap();
+	let effect = args.arg_effect.unwrap();
+	let edit = try!(DataEdit::from_params(&effect, &args.arg_param));
+
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let fixture_dao = try!(dao::FixtureDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let layout_id = try!(sequence_dao.get_sequence(seqid)).layout_id;
+	let targets = try!(edit_targets(&args.flag_channels, &args.flag_fixtures, layout_id));
+	let timing_dao = try!(dao::TimingDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the user can edit the sequence
+	let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(seqid)];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let t_start = try!(proton_cli::resolve_time(&timing_dao, seqid, &args.arg_t_start.unwrap()));
+	let t_end = try!(proton_cli::resolve_time(&timing_dao, seqid, &args.arg_t_end.unwrap()));
+	let num_edited = try!(proton_cli::edit_data(
+		&channel_dao,
+		&data_dao,
+		&fixture_dao,
+		&sequence_dao,
+		seqid,
+		t_start,
+		t_end,
+		&edit,
+		&targets));
+	Ok(ProtonReturn::ChannelsEdited(num_edited))
+}
+
+/// export-controllers <layout-id> <output-file>
+fn run_export_controllers(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_id = args.arg_layout_id.unwrap();
+	let output_file = args.arg_output_file.unwrap();
+	let output_path = Path::new(&output_file);
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let controller_dao = try!(dao::ControllerDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	try!(proton_cli::export_controllers(
+		&channel_dao,
+		&controller_dao,
+		&layout_dao,
+		layout_id,
+		&output_path));
+	Ok(ProtonReturn::NoReturn)
+}
+
+/// export-layout <layout-id> <output-file>
+fn run_export_layout(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_id = args.arg_layout_id.unwrap();
+	let output_file = args.arg_output_file.unwrap();
+	let output_path = Path::new(&output_file);
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let fixture_dao = try!(dao::FixtureDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	try!(proton_cli::export_layout(
+		&channel_dao,
+		&fixture_dao,
+		&layout_dao,
+		layout_id,
+		&output_path));
+	Ok(ProtonReturn::NoReturn)
+}
+
+/// gc-music <admin-key> [--dry-run]
+fn run_gc_music(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let seq_dao = try!(dao::SequenceDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let music_store = MusicStore::new(proton_cli::MUSIC_DIR);
+	let removed = try!(proton_cli::gc_music(&seq_dao, &music_store, args.flag_dry_run));
+	Ok(ProtonReturn::RemovedMusic(removed, args.flag_dry_run))
+}
+
+/// generate-reactive <admin-key> <seqid> <mapping>...
+fn run_generate_reactive(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let seqid = args.arg_seqid.unwrap();
+	let mut mappings = Vec::with_capacity(args.arg_mapping.len());
+	for mapping in &args.arg_mapping {
+		mappings.push(try!(mapping.parse::<ReactiveMapping>()));
+	}
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let fixture_dao = try!(dao::FixtureDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the user can edit the sequence
+	let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(seqid)];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let num_channels = try!(proton_cli::generate_reactive(
+		&audio_backend(),
+		&MusicStore::new(proton_cli::MUSIC_DIR),
+		&channel_dao,
+		&data_dao,
+		&fixture_dao,
+		&layout_dao,
+		&sequence_dao,
+		seqid,
+		&mappings));
+	Ok(ProtonReturn::ChannelsEdited(num_channels))
+}
+
+/// get-controllers <layout-id>
+fn run_get_controllers(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_id = args.arg_layout_id.unwrap();
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let controller_dao = try!(dao::ControllerDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let controllers = try!(controller_dao.get_controllers(layout_id));
+	let port_ranges = try!(proton_cli::get_port_ranges(&channel_dao, &controller_dao, &layout_dao, layout_id));
+	Ok(ProtonReturn::Controllers(controllers, port_ranges))
+}
+
+/// get-dimming <layout-id>
+fn run_get_dimming(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_id = args.arg_layout_id.unwrap();
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let dimming_dao = try!(dao::DimmingDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let channels = try!(proton_cli::get_dimming(&channel_dao, &dimming_dao, &layout_dao, layout_id));
+	Ok(ProtonReturn::Dimming(channels))
+}
+
 /// get-layout-id <proj-name>
 fn run_get_layout_id(args: Args) -> Result<ProtonReturn, Error> {
 	let proj_name = args.arg_proj_name.unwrap();
@@ -159,18 +974,64 @@ fn run_get_layout_id(args: Args) -> Result<ProtonReturn, Error> {
 	Ok(ProtonReturn::LayoutId(layout_id))
 }
 
+/// get-layout-mapping <layout-id> [<version>]
+fn run_get_layout_mapping(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_id = args.arg_layout_id.unwrap();
+	let version = args.arg_version;
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let patch_dao = try!(dao::PatchDaoPostgres::new());
+	let mapping = try!(proton_cli::get_layout_mapping(
+		&channel_dao,
+		&layout_dao,
+		&patch_dao,
+		layout_id,
+		version));
+
+	// Output in patch file format, so it can be reapplied with patch-layout
+	let patch_file = FilePatch {
+		patches: mapping.iter()
+			.map(|(internal, dmx)| FilePatchRow {
+				internalChannel: *internal,
+				dmxChannel: *dmx
+			})
+			.collect::<Vec<FilePatchRow>>()
+	};
+	Ok(ProtonReturn::LayoutMapping(patch_file))
+}
+
+/// get-music-info <music-file>
+fn run_get_music_info(args: Args) -> Result<ProtonReturn, Error> {
+	let music_file = args.arg_music_file.unwrap();
+	let music_file_path = Path::new(&music_file);
+	let info = try!(proton_cli::get_music_info(&audio_backend(), &music_file_path));
+	Ok(ProtonReturn::AudioInfo(info))
+}
+
+/// get-patch-history <layout-id>
+fn run_get_patch_history(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_id = args.arg_layout_id.unwrap();
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let patch_dao = try!(dao::PatchDaoPostgres::new());
+	let history = try!(proton_cli::get_patch_history(&layout_dao, &patch_dao, layout_id));
+	Ok(ProtonReturn::PatchHistory(history))
+}
+
 /// get-playlist-data <proj-name>
 fn run_get_playlist_data(args: Args) -> Result<ProtonReturn, Error> {
 	let proj_name = args.arg_proj_name.unwrap();
 	let channel_dao = try!(dao::ChannelDaoPostgres::new());
 	let data_dao = try!(dao::DataDaoPostgres::new());
+	let dimming_dao = try!(dao::DimmingDaoPostgres::new());
 	let proj_dao = try!(dao::ProjectDaoPostgres::new());
 	let seq_dao = try!(dao::SequenceDaoPostgres::new());
 	let data = try!(proton_cli::get_playlist_data(
 		&channel_dao,
 		&data_dao,
+		&dimming_dao,
 		&proj_dao,
 		&seq_dao,
+		&MusicStore::new(proton_cli::MUSIC_DIR),
 		&proj_name));
 	Ok(ProtonReturn::PlaylistData(data))
 }
@@ -183,6 +1044,17 @@ fn run_get_project(args: Args) -> Result<ProtonReturn, Error> {
 	Ok(ProtonReturn::Project(project))
 }
 
+/// get-schedule <proj-name>
+fn run_get_schedule(args: Args) -> Result<ProtonReturn, Error> {
+	let proj_name = args.arg_proj_name.unwrap();
+	let proj_dao = try!(dao::ProjectDaoPostgres::new());
+	let seq_dao = try!(dao::SequenceDaoPostgres::new());
+	let schedule = try!(proton_cli::get_schedule(&proj_dao, &proj_name));
+	let schedule_json = try!(schedule.to_json());
+	let decision = try!(proton_cli::get_schedule_decision(&SystemClock, &proj_dao, &seq_dao, &proj_name));
+	Ok(ProtonReturn::Schedule(schedule_json, decision))
+}
+
 /// get-sequence <seqid>
 fn run_get_sequence(args: Args) -> Result<ProtonReturn, Error> {
 	let seqid = args.arg_seqid.unwrap();
@@ -191,6 +1063,15 @@ fn run_get_sequence(args: Args) -> Result<ProtonReturn, Error> {
 	Ok(ProtonReturn::Sequence(sequence))
 }
 
+/// get-timing-tracks <seqid>
+fn run_get_timing_tracks(args: Args) -> Result<ProtonReturn, Error> {
+	let seqid = args.arg_seqid.unwrap();
+	let seq_dao = try!(dao::SequenceDaoPostgres::new());
+	let timing_dao = try!(dao::TimingDaoPostgres::new());
+	let tracks = try!(proton_cli::get_timing_tracks(&seq_dao, &timing_dao, seqid));
+	Ok(ProtonReturn::TimingTrackData(tracks))
+}
+
 /// get-user-id <public-key>
 fn run_get_user_id(args: Args) -> Result<ProtonReturn, Error> {
 	let public_key = args.arg_public_key.unwrap();
@@ -200,6 +1081,35 @@ fn run_get_user_id(args: Args) -> Result<ProtonReturn, Error> {
 	Ok(ProtonReturn::Uid(uid))
 }
 
+/// import-timing <admin-key> <seqid> <timing-file> [--name=<name>]
+fn run_import_timing(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let seqid = args.arg_seqid.unwrap();
+	let timing_file = args.arg_timing_file.unwrap();
+	let timing_file_path = Path::new(&timing_file);
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let timing_dao = try!(dao::TimingDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the user can edit the sequence
+	let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(seqid)];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let tracks = try!(proton_cli::import_timing(
+		&sequence_dao,
+		&timing_dao,
+		seqid,
+		&timing_file_path,
+		args.flag_name.as_ref().map(|name| name.as_str())));
+	Ok(ProtonReturn::TimingTracks(tracks))
+}
+
 /// insert-sequence <admin-key> <proj-name> <seqid> [<index>]
 fn run_insert_sequence(args: Args) -> Result<ProtonReturn, Error> {
 	let admin_key = args.arg_admin_key.unwrap();
@@ -224,6 +1134,15 @@ fn run_insert_sequence(args: Args) -> Result<ProtonReturn, Error> {
 	Ok(ProtonReturn::NoReturn)
 }
 
+/// list-music
+#[allow(unused_variables)]
+fn run_list_music(args: Args) -> Result<ProtonReturn, Error> {
+	let seq_dao = try!(dao::SequenceDaoPostgres::new());
+	let music_store = MusicStore::new(proton_cli::MUSIC_DIR);
+	let files = try!(proton_cli::list_music(&seq_dao, &music_store));
+	Ok(ProtonReturn::MusicFiles(files))
+}
+
 /// list-permissions <uid>
 fn run_list_permissions(args: Args) -> Result<ProtonReturn, Error> {
 	let uid = args.arg_uid.unwrap();
@@ -234,10 +1153,11 @@ fn run_list_permissions(args: Args) -> Result<ProtonReturn, Error> {
 	Ok(ProtonReturn::NoReturn)
 }
 
-/// new-layout <layout-file>
+/// new-layout <layout-file> [--name=<name>] [--spares=<policy>]
 fn run_new_layout(args: Args) -> Result<ProtonReturn, Error> {
 	let layout_file = args.arg_layout_file.unwrap();
 	let layout_file_path = Path::new(&layout_file);
+	let spare_policy = try!(args.flag_spares.parse::<SparePolicy>());
 	let channel_dao = try!(dao::ChannelDaoPostgres::new());
 	let fixture_dao = try!(dao::FixtureDaoPostgres::new());
 	let layout_dao = try!(dao::LayoutDaoPostgres::new());
@@ -245,7 +1165,9 @@ fn run_new_layout(args: Args) -> Result<ProtonReturn, Error> {
 		&channel_dao,
 		&fixture_dao,
 		&layout_dao,
-		&layout_file_path));
+		&layout_file_path,
+		args.flag_name.as_ref().map(String::as_str),
+		spare_policy));
 	Ok(ProtonReturn::LayoutId(layout_id))
 }
 
@@ -273,7 +1195,7 @@ fn run_new_section(args: Args) -> Result<ProtonReturn, Error> {
 	Err(Error::TodoErr)
 }
 
-/// new-sequence <admin-key> <name> <music-file> <seq-duration> <layout-id>
+/// new-sequence <admin-key> <name> <music-file> <seq-duration> <layout-id> [--duration-check=<policy>]
 fn run_new_sequence(args: Args) -> Result<ProtonReturn, Error> {
 	let admin_key = args.arg_admin_key.unwrap();
 	let admin_key_path = Path::new(&admin_key);
@@ -282,6 +1204,7 @@ fn run_new_sequence(args: Args) -> Result<ProtonReturn, Error> {
 	let music_file_path = Path::new(&music_file);
 	let seq_duration = args.arg_seq_duration.unwrap();
 	let layout_id = args.arg_layout_id;
+	let duration_policy = try!(args.flag_duration_check.parse::<DurationPolicy>());
 	let data_dao = try!(dao::DataDaoPostgres::new());
 	let layout_dao = try!(dao::LayoutDaoPostgres::new());
 	let perm_dao = try!(dao::PermissionDaoPostgres::new());
@@ -296,7 +1219,9 @@ fn run_new_sequence(args: Args) -> Result<ProtonReturn, Error> {
         admin_key_path,
         &valid_permissions));
 
-	let seqid = try!(proton_cli::new_sequence(
+	let (seqid, issue) = try!(proton_cli::new_sequence(
+		&audio_backend(),
+		&MusicStore::new(proton_cli::MUSIC_DIR),
 		&data_dao,
 		&layout_dao,
 		&sequence_dao,
@@ -304,8 +1229,9 @@ fn run_new_sequence(args: Args) -> Result<ProtonReturn, Error> {
 		&music_file_path,
 		seq_duration,
 		None::<u32>,
-		layout_id));
-	Ok(ProtonReturn::SequenceId(seqid))
+		layout_id,
+		duration_policy));
+	Ok(ProtonReturn::NewSequence(seqid, issue))
 }
 
 /// new-user <admin-key> <name>
@@ -324,67 +1250,259 @@ fn run_new_user(args: Args) -> Result<ProtonReturn, Error> {
         admin_key_path,
         &valid_permissions));
 
-	let public_key = try!(proton_cli::new_user(user_dao, &name));
-	Ok(ProtonReturn::PublicKey(public_key))
+	let public_key = try!(proton_cli::new_user(user_dao, &name));
+	Ok(ProtonReturn::PublicKey(public_key))
+}
+
+/// new-vixen-sequence <admin-key> <name> <music-file> <seq-duration> <frame-duration> <data-file> <layout-id> [--duration-check=<policy>]
+fn run_new_vixen_sequence(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let name = args.arg_name.unwrap();
+	let music_file = args.arg_music_file.unwrap();
+	let music_file_path = Path::new(&music_file);
+	let seq_duration = args.arg_seq_duration.unwrap();
+	let frame_duration = args.arg_frame_duration.unwrap();
+	let data_file = args.arg_data_file.unwrap();
+	let data_file_path = Path::new(&data_file);
+	let duration_policy = try!(args.flag_duration_check.parse::<DurationPolicy>());
+	let layout_id = match args.arg_layout_id {
+		Some(lid) => lid,
+		None => {
+			let layout_dao = try!(dao::LayoutDaoPostgres::new());
+			let default_layout = try!(layout_dao.get_default_layout());
+			default_layout.layout_id
+		},
+	};
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+    let valid_permissions = vec![PermissionEnum::Administrate];
+    let _ = try!(utils::check_valid_permission(
+        &perm_dao,
+        &user_dao,
+        admin_key_path,
+        &valid_permissions));
+
+	let (seqid, issue) = try!(proton_cli::new_vixen_sequence(
+		&audio_backend(),
+		&MusicStore::new(proton_cli::MUSIC_DIR),
+		&channel_dao,
+		&data_dao,
+		&layout_dao,
+		&sequence_dao,
+		&name,
+		&music_file_path,
+		seq_duration,
+		frame_duration,
+		&data_file_path,
+		layout_id,
+		duration_policy));
+	Ok(ProtonReturn::NewSequence(seqid, issue))
+}
+
+/// patch-layout <admin-key> <layout-id> <patch-file>
+fn run_patch_layout(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let layout_id = args.arg_layout_id.unwrap();
+	let patch_file = args.arg_patch_file.unwrap();
+	let patch_file_path = Path::new(&patch_file);
+
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let patch_dao = try!(dao::PatchDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+    let valid_permissions = vec![PermissionEnum::Administrate];
+    let _ = try!(utils::check_valid_permission(
+        &perm_dao,
+        &user_dao,
+        admin_key_path,
+        &valid_permissions));
+
+	let version = try!(proton_cli::patch_layout(
+		&channel_dao,
+		&layout_dao,
+		&patch_dao,
+		layout_id,
+		&patch_file_path));
+	
+	Ok(ProtonReturn::LayoutVersion(version))
+}
+
+/// play <proj-name> [--no-audio] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+fn run_play(args: Args) -> Result<ProtonReturn, Error> {
+	let proj_name = args.arg_proj_name.clone().unwrap();
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let dimming_dao = try!(dao::DimmingDaoPostgres::new());
+	let project_dao = try!(dao::ProjectDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let layout_id = try!(project_dao.get_project(&proj_name)).layout_id;
+	let mut output = try!(output_driver(&args, layout_id));
+	let music_store = MusicStore::new(proton_cli::MUSIC_DIR);
+	let report = try!(proton_cli::play_project(
+		&audio_player(args.flag_no_audio),
+		&mut output,
+		&channel_dao,
+		&data_dao,
+		&dimming_dao,
+		&project_dao,
+		&sequence_dao,
+		&music_store,
+		&proj_name,
+		&print_playback_event));
+	Ok(ProtonReturn::Playback(report))
+}
+
+/// play-sequence <seqid> [--no-audio] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+fn run_play_sequence(args: Args) -> Result<ProtonReturn, Error> {
+	let seqid = args.arg_seqid.unwrap();
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let dimming_dao = try!(dao::DimmingDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let layout_id = try!(sequence_dao.get_sequence(seqid)).layout_id;
+	let mut output = try!(output_driver(&args, layout_id));
+	let music_store = MusicStore::new(proton_cli::MUSIC_DIR);
+	let report = try!(proton_cli::play_sequence(
+		&audio_player(args.flag_no_audio),
+		&mut output,
+		&channel_dao,
+		&data_dao,
+		&dimming_dao,
+		&sequence_dao,
+		&music_store,
+		seqid));
+	Ok(ProtonReturn::Playback(report))
+}
+
+/// remove-controller <admin-key> <layout-id> <controller-id>
+fn run_remove_controller(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let layout_id = args.arg_layout_id.unwrap();
+	let controllerid = args.arg_controller_id.unwrap();
+	let controller_dao = try!(dao::ControllerDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	try!(proton_cli::remove_controller(&controller_dao, layout_id, controllerid));
+	Ok(ProtonReturn::NoReturn)
+}
+
+/// remove-sequence <admin-key> <proj-name> <seqid>
+fn run_remove_sequence(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let proj_name = args.arg_proj_name.unwrap();
+	let seqid = args.arg_seqid.unwrap();
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let project_dao = try!(dao::ProjectDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+    let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(seqid)];
+    let _ = try!(utils::check_valid_permission(
+        &perm_dao,
+        &user_dao,
+        admin_key_path,
+        &valid_permissions));
+    
+	try!(proton_cli::remove_sequence(&project_dao, &proj_name, seqid));
+	Ok(ProtonReturn::NoReturn)
+}
+
+/// remove-user <admin-key> <uid>
+fn run_remove_user(args: Args) -> Result<ProtonReturn, Error> {
+	let uid = args.arg_uid.unwrap();
+	try!(proton_cli::remove_user(uid));
+	Ok(ProtonReturn::NoReturn)
+}
+
+/// resize-sequence <admin-key> <seqid> <seq-duration> [--at-start]
+fn run_resize_sequence(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let seqid = args.arg_seqid.unwrap();
+	let seq_duration = args.arg_seq_duration.unwrap();
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the user can edit the sequence
+	let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(seqid)];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let num_frames = try!(proton_cli::resize_sequence(
+		&data_dao,
+		&sequence_dao,
+		seqid,
+		seq_duration,
+		args.flag_at_start));
+	Ok(ProtonReturn::NumFrames(num_frames))
 }
 
-/// new-vixen-sequence <admin-key> <name> <music-file> <seq-duration> <frame-duration> <data-file> <layout-id>
-fn run_new_vixen_sequence(args: Args) -> Result<ProtonReturn, Error> {
+/// retime-sequence <admin-key> <seqid> <frame-duration> [--resample=<mode>]
+fn run_retime_sequence(args: Args) -> Result<ProtonReturn, Error> {
 	let admin_key = args.arg_admin_key.unwrap();
 	let admin_key_path = Path::new(&admin_key);
-	let name = args.arg_name.unwrap();
-	let music_file = args.arg_music_file.unwrap();
-	let music_file_path = Path::new(&music_file);
-	let seq_duration = args.arg_seq_duration.unwrap();
+	let seqid = args.arg_seqid.unwrap();
 	let frame_duration = args.arg_frame_duration.unwrap();
-	let data_file = args.arg_data_file.unwrap();
-	let data_file_path = Path::new(&data_file);
-	let layout_id = match args.arg_layout_id {
-		Some(lid) => lid,
-		None => {
-			let layout_dao = try!(dao::LayoutDaoPostgres::new());
-			let default_layout = try!(layout_dao.get_default_layout());
-			default_layout.layout_id
-		},
-	};
-	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let mode = try!(args.flag_resample.parse::<ResampleMode>());
 	let data_dao = try!(dao::DataDaoPostgres::new());
-	let layout_dao = try!(dao::LayoutDaoPostgres::new());
 	let perm_dao = try!(dao::PermissionDaoPostgres::new());
 	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
 	let user_dao = try!(dao::UserDaoPostgres::new());
 
-	// Check that the admin has sufficient privileges
-    let valid_permissions = vec![PermissionEnum::Administrate];
-    let _ = try!(utils::check_valid_permission(
-        &perm_dao,
-        &user_dao,
-        admin_key_path,
-        &valid_permissions));
+	// Check that the user can edit the sequence
+	let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(seqid)];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
 
-	let seqid = try!(proton_cli::new_vixen_sequence(
-		&channel_dao,
+	let num_frames = try!(proton_cli::retime_sequence(
 		&data_dao,
-		&layout_dao,
 		&sequence_dao,
-		&name,
-		&music_file_path,
-		seq_duration,
+		seqid,
 		frame_duration,
-		&data_file_path,
-		layout_id));
-	Ok(ProtonReturn::SequenceId(seqid))
+		mode));
+	Ok(ProtonReturn::NumFrames(num_frames))
 }
 
-/// patch-layout <admin-key> <layout-id> <patch-file>
-fn run_patch_layout(args: Args) -> Result<ProtonReturn, Error> {
+/// rollback-layout <admin-key> <layout-id> <version>
+fn run_rollback_layout(args: Args) -> Result<ProtonReturn, Error> {
 	let admin_key = args.arg_admin_key.unwrap();
 	let admin_key_path = Path::new(&admin_key);
 	let layout_id = args.arg_layout_id.unwrap();
-	let patch_file = args.arg_patch_file.unwrap();
-	let patch_file_path = Path::new(&patch_file);
+	let version = args.arg_version.unwrap();
 
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
 	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let patch_dao = try!(dao::PatchDaoPostgres::new());
 	let perm_dao = try!(dao::PermissionDaoPostgres::new());
 	let user_dao = try!(dao::UserDaoPostgres::new());
 
@@ -396,40 +1514,88 @@ fn run_patch_layout(args: Args) -> Result<ProtonReturn, Error> {
         admin_key_path,
         &valid_permissions));
 
-	try!(proton_cli::patch_layout(
+	let new_version = try!(proton_cli::rollback_layout(
+		&channel_dao,
 		&layout_dao,
+		&patch_dao,
 		layout_id,
-		&patch_file_path));
-	
-	Ok(ProtonReturn::NoReturn)	
+		version));
+
+	Ok(ProtonReturn::LayoutVersion(new_version))
 }
 
-/// remove-sequence <admin-key> <proj-name> <seqid>
-fn run_remove_sequence(args: Args) -> Result<ProtonReturn, Error> {
+/// run-schedule <proj-name> [--no-audio] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+fn run_run_schedule(args: Args) -> Result<ProtonReturn, Error> {
+	let proj_name = args.arg_proj_name.clone().unwrap();
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let dimming_dao = try!(dao::DimmingDaoPostgres::new());
+	let project_dao = try!(dao::ProjectDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let layout_id = try!(project_dao.get_project(&proj_name)).layout_id;
+	let mut output = try!(output_driver(&args, layout_id));
+	let music_store = MusicStore::new(proton_cli::MUSIC_DIR);
+	let audio_player = audio_player(args.flag_no_audio);
+
+	// Play show after show until stopped. The schedule and sequences are
+	// reloaded before each show, so changes are picked up the next night.
+	loop {
+		let report = try!(proton_cli::play_schedule(
+			&SystemClock,
+			&audio_player,
+			&mut output,
+			&channel_dao,
+			&data_dao,
+			&dimming_dao,
+			&project_dao,
+			&sequence_dao,
+			&music_store,
+			&proj_name,
+			&print_playback_event));
+		println!("Show over: {} sequence(s) played, {} frame(s) dropped",
+			report.sequences,
+			report.frames_dropped);
+	}
+}
+
+/// set-circuit-limit <admin-key> <layout-id> <circuit> <amps>
+fn run_set_circuit_limit(args: Args) -> Result<ProtonReturn, Error> {
+	let max_amps = args.arg_amps;
+	run_set_or_clear_circuit_limit(args, max_amps)
+}
+
+/// set-dimming <admin-key> <layout-id> <curve> [--min=<v>] [--max=<v>] (--channels=<ids> | --fixtures=<ids>)
+fn run_set_dimming(args: Args) -> Result<ProtonReturn, Error> {
+	let curve = try!(args.arg_curve.clone().unwrap().parse::<DimmingCurve>());
+	let profile = try!(DimmingProfile::new(curve, args.flag_min, args.flag_max));
+	run_set_or_clear_dimming(args, Some(profile))
+}
+
+/// set-load <admin-key> <layout-id> <watts> [--circuit=<name>] (--channels=<ids> | --fixtures=<ids>)
+fn run_set_load(args: Args) -> Result<ProtonReturn, Error> {
+	let load = try!(ChannelLoad::new(args.arg_watts.unwrap(), args.flag_circuit.clone()));
+	run_set_or_clear_load(args, Some(load))
+}
+
+/// set-music-offset <admin-key> <seqid> --offset=<ms>
+fn run_set_music_offset(args: Args) -> Result<ProtonReturn, Error> {
 	let admin_key = args.arg_admin_key.unwrap();
 	let admin_key_path = Path::new(&admin_key);
-	let proj_name = args.arg_proj_name.unwrap();
 	let seqid = args.arg_seqid.unwrap();
+	let offset = args.flag_offset.unwrap();
 	let perm_dao = try!(dao::PermissionDaoPostgres::new());
-	let project_dao = try!(dao::ProjectDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
 	let user_dao = try!(dao::UserDaoPostgres::new());
 
-	// Check that the admin has sufficient privileges
-    let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(seqid)];
-    let _ = try!(utils::check_valid_permission(
-        &perm_dao,
-        &user_dao,
-        admin_key_path,
-        &valid_permissions));
-    
-	try!(proton_cli::remove_sequence(&project_dao, &proj_name, seqid));
-	Ok(ProtonReturn::NoReturn)
-}
+	// Check that the user can edit the sequence
+	let valid_permissions = vec![PermissionEnum::Administrate, PermissionEnum::EditSequence(seqid)];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
 
-/// remove-user <admin-key> <uid>
-fn run_remove_user(args: Args) -> Result<ProtonReturn, Error> {
-	let uid = args.arg_uid.unwrap();
-	try!(proton_cli::remove_user(uid));
+	try!(proton_cli::set_music_offset(&sequence_dao, seqid, offset));
 	Ok(ProtonReturn::NoReturn)
 }
 
@@ -456,6 +1622,39 @@ fn run_set_permission(args: Args) -> Result<ProtonReturn, Error> {
 	Ok(ProtonReturn::NoReturn)
 }
 
+/// set-port <admin-key> <layout-id> <controller-id> <port> (--channels=<ids> | --fixtures=<ids>)
+fn run_set_port(args: Args) -> Result<ProtonReturn, Error> {
+	let port = PortAssignment {
+		controllerid: args.arg_controller_id.unwrap(),
+		port: args.arg_port.unwrap()
+	};
+	run_set_or_clear_port(args, Some(port))
+}
+
+/// set-schedule <admin-key> <proj-name> <schedule-file>
+fn run_set_schedule(args: Args) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let proj_name = args.arg_proj_name.unwrap();
+	let schedule_file = args.arg_schedule_file.unwrap();
+	let schedule_file_path = Path::new(&schedule_file);
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let project_dao = try!(dao::ProjectDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let _ = try!(proton_cli::set_schedule(&project_dao, &sequence_dao, &proj_name, &schedule_file_path));
+	Ok(ProtonReturn::NoReturn)
+}
+
 /// set-sequence-layout <admin-key> <seqid> <layout-id>
 fn run_set_sequence_layout(args: Args) -> Result<ProtonReturn, Error> {
 	let admin_key = args.arg_admin_key.unwrap();
@@ -482,3 +1681,343 @@ fn run_set_sequence_layout(args: Args) -> Result<ProtonReturn, Error> {
 		seqid));
 	Ok(ProtonReturn::NoReturn)
 }
+
+/// test-channel <layout-id> <channel> [--seconds=<s>] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+/// test-channel <admin-key> <layout-id> <channel> <music-file> --save=<name> [--seconds=<s>]
+fn run_test_channel(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_id = args.arg_layout_id.unwrap();
+	let channel = args.arg_channel.unwrap();
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let steps = try!(proton_cli::test_channel_steps(
+		&channel_dao,
+		&layout_dao,
+		layout_id,
+		channel,
+		args.flag_seconds * 1000));
+	send_or_save_test_steps(&args, layout_id, &steps)
+}
+
+/// test-fixture <layout-id> <fixid> [--step=<ms>] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+/// test-fixture <admin-key> <layout-id> <fixid> <music-file> --save=<name> [--step=<ms>]
+fn run_test_fixture(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_id = args.arg_layout_id.unwrap();
+	let fixid = args.arg_fixid.unwrap();
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let fixture_dao = try!(dao::FixtureDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let steps = try!(proton_cli::test_fixture_steps(
+		&channel_dao,
+		&fixture_dao,
+		&layout_dao,
+		layout_id,
+		fixid,
+		args.flag_step));
+	send_or_save_test_steps(&args, layout_id, &steps)
+}
+
+/// test-pattern <layout-id> <pattern> [--seconds=<s>] [--controllers] [--artnet=<destinations>] [--artsync] [--unicast=<address>] [--priority=<n>] [--serial=<device>] [--widget=<type>] [--universe=<n>]
+fn run_test_pattern(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_id = args.arg_layout_id.unwrap();
+	let pattern = try!(args.arg_pattern.clone().unwrap().parse::<TestPattern>());
+	let mut output = try!(output_driver(&args, layout_id));
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let dimming_dao = try!(dao::DimmingDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let report = try!(proton_cli::play_test_pattern(
+		&mut output,
+		&channel_dao,
+		&dimming_dao,
+		&layout_dao,
+		layout_id,
+		pattern,
+		args.flag_seconds * 1000));
+	Ok(ProtonReturn::Playback(report))
+}
+
+/// validate-controllers <layout-id>
+fn run_validate_controllers(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_id = args.arg_layout_id.unwrap();
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let controller_dao = try!(dao::ControllerDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let issues = try!(proton_cli::validate_controllers(&channel_dao, &controller_dao, &layout_dao, layout_id));
+	Ok(ProtonReturn::LayoutIssues(issues))
+}
+
+/// validate-layout <layout-file> [--name=<name>]
+fn run_validate_layout(args: Args) -> Result<ProtonReturn, Error> {
+	let layout_file = args.arg_layout_file.unwrap();
+	let layout_file_path = Path::new(&layout_file);
+	let issues = try!(proton_cli::validate_layout(&layout_file_path, args.flag_name.as_ref().map(String::as_str)));
+	Ok(ProtonReturn::LayoutIssues(issues))
+}
+
+/// verify-music <proj-name>
+fn run_verify_music(args: Args) -> Result<ProtonReturn, Error> {
+	let proj_name = args.arg_proj_name.unwrap();
+	let proj_dao = try!(dao::ProjectDaoPostgres::new());
+	let seq_dao = try!(dao::SequenceDaoPostgres::new());
+	let music_store = MusicStore::new(proton_cli::MUSIC_DIR);
+	let issues = try!(proton_cli::verify_music(&proj_dao, &seq_dao, &music_store, &proj_name));
+	Ok(ProtonReturn::SequenceIssues(issues))
+}
+
+/// Sets the dimming profile of a layout's channels or fixtures, or clears it if None
+fn run_set_or_clear_dimming(args: Args, profile: Option<DimmingProfile>) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let layout_id = args.arg_layout_id.unwrap();
+	let targets = try!(edit_targets(&args.flag_channels, &args.flag_fixtures, layout_id));
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let dimming_dao = try!(dao::DimmingDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let num_changed = try!(proton_cli::set_dimming(
+		&channel_dao,
+		&dimming_dao,
+		&layout_dao,
+		layout_id,
+		&targets,
+		profile.as_ref()));
+	Ok(ProtonReturn::DimmingChanged(num_changed))
+}
+
+/// Sets the load of a layout's channels or fixtures, or clears it if None
+fn run_set_or_clear_load(args: Args, load: Option<ChannelLoad>) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let layout_id = args.arg_layout_id.unwrap();
+	let targets = try!(edit_targets(&args.flag_channels, &args.flag_fixtures, layout_id));
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let power_dao = try!(dao::PowerDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let num_changed = try!(proton_cli::set_load(
+		&channel_dao,
+		&layout_dao,
+		&power_dao,
+		layout_id,
+		&targets,
+		load.as_ref()));
+	Ok(ProtonReturn::LoadsChanged(num_changed))
+}
+
+/// Sets the limit of one of a layout's circuits, or clears it if None
+fn run_set_or_clear_circuit_limit(args: Args, max_amps: Option<f64>) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let layout_id = args.arg_layout_id.unwrap();
+	let circuit = args.arg_circuit.unwrap();
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let power_dao = try!(dao::PowerDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	try!(proton_cli::set_circuit_limit(&layout_dao, &power_dao, layout_id, &circuit, max_amps));
+	Ok(ProtonReturn::NoReturn)
+}
+
+/// Wires a layout's channels or fixtures to a controller port, or unwires them if None
+fn run_set_or_clear_port(args: Args, port: Option<PortAssignment>) -> Result<ProtonReturn, Error> {
+	let admin_key = args.arg_admin_key.unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let layout_id = args.arg_layout_id.unwrap();
+	let targets = try!(edit_targets(&args.flag_channels, &args.flag_fixtures, layout_id));
+	let channel_dao = try!(dao::ChannelDaoPostgres::new());
+	let controller_dao = try!(dao::ControllerDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let num_changed = try!(proton_cli::set_port(
+		&channel_dao,
+		&controller_dao,
+		&layout_dao,
+		layout_id,
+		&targets,
+		port.as_ref()));
+	Ok(ProtonReturn::PortsChanged(num_changed))
+}
+
+/// Parses the channels or fixtures of a layout to edit. A range can't cover
+/// more ids than the layout has channels or fixtures.
+fn edit_targets(
+	channels: &Option<String>,
+	fixtures: &Option<String>,
+	layout_id: u32
+) -> Result<EditTargets, Error> {
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	match *channels {
+		Some(ref ids) => {
+			let num_channels = try!(LayoutDao::get_channel_ids(&layout_dao, layout_id)).len();
+			EditTargets::parse(ids, false, num_channels as u32)
+		},
+		None => {
+			let num_fixtures = try!(layout_dao.get_layout(layout_id)).fixtures.len();
+			EditTargets::parse(fixtures.as_ref().unwrap(), true, num_fixtures as u32)
+		},
+	}
+}
+
+/// Sends channel test steps to the output, or with --save, saves them as a new
+/// sequence on the layout with the given music
+fn send_or_save_test_steps(args: &Args, layout_id: u32, steps: &[TestStep]) -> Result<ProtonReturn, Error> {
+	let name = match args.flag_save {
+		Some(ref name) => name,
+		None => {
+			let mut output = try!(output_driver(args, layout_id));
+			let dimming_dao = try!(dao::DimmingDaoPostgres::new());
+			let report = try!(proton_cli::play_test_steps(&mut output, &dimming_dao, steps, &print_playback_event));
+			return Ok(ProtonReturn::Playback(report));
+		},
+	};
+
+	let admin_key = args.arg_admin_key.clone().unwrap();
+	let admin_key_path = Path::new(&admin_key);
+	let music_file = args.arg_music_file.clone().unwrap();
+	let music_file_path = Path::new(&music_file);
+	let data_dao = try!(dao::DataDaoPostgres::new());
+	let layout_dao = try!(dao::LayoutDaoPostgres::new());
+	let perm_dao = try!(dao::PermissionDaoPostgres::new());
+	let sequence_dao = try!(dao::SequenceDaoPostgres::new());
+	let timing_dao = try!(dao::TimingDaoPostgres::new());
+	let user_dao = try!(dao::UserDaoPostgres::new());
+
+	// Check that the admin has sufficient privileges
+	let valid_permissions = vec![PermissionEnum::Administrate];
+	let _ = try!(utils::check_valid_permission(
+		&perm_dao,
+		&user_dao,
+		admin_key_path,
+		&valid_permissions));
+
+	let (seqid, issue) = try!(proton_cli::save_test_steps(
+		&audio_backend(),
+		&MusicStore::new(proton_cli::MUSIC_DIR),
+		&data_dao,
+		&layout_dao,
+		&sequence_dao,
+		&timing_dao,
+		name,
+		&music_file_path,
+		layout_id,
+		steps));
+	Ok(ProtonReturn::NewSequence(seqid, issue))
+}
+
+/// Creates a driver for the layout's controllers if --controllers is given, a
+/// USB-DMX driver if --serial is given, an Art-Net sender if --artnet is given,
+/// and otherwise an E1.31 sender
+fn output_driver(args: &Args, layout_id: u32) -> Result<Box<OutputDriver>, Error> {
+	if args.flag_controllers {
+		if args.flag_artnet.is_some() || args.flag_unicast.is_some() || args.flag_serial.is_some() {
+			return Err(Error::InvalidOutput("--controllers can't be used with --artnet, --unicast or --serial".to_owned()));
+		}
+		let channel_dao = try!(dao::ChannelDaoPostgres::new());
+		let controller_dao = try!(dao::ControllerDaoPostgres::new());
+		let layout_dao = try!(dao::LayoutDaoPostgres::new());
+		return Ok(Box::new(try!(proton_cli::controller_driver(
+			&channel_dao,
+			&controller_dao,
+			&layout_dao,
+			layout_id,
+			args.flag_priority,
+			args.flag_artsync))));
+	}
+
+	if let Some(ref device) = args.flag_serial {
+		if args.flag_artnet.is_some() || args.flag_artsync || args.flag_unicast.is_some() {
+			return Err(Error::InvalidOutput("--serial can't be used with network output options".to_owned()));
+		}
+		let widget = try!(args.flag_widget.parse::<EnttecWidget>());
+		let port = try!(SerialPort::open(device));
+		return Ok(Box::new(EnttecDriver::new(port, widget, args.flag_universe)));
+	}
+
+	match args.flag_artnet {
+		Some(ref destinations) => {
+			if args.flag_unicast.is_some() {
+				return Err(Error::InvalidOutput("--unicast is only used with E1.31".to_owned()));
+			}
+			let destinations = try!(destinations.parse::<ArtNetDestinations>());
+			Ok(Box::new(try!(ArtNetSender::new(destinations, args.flag_artsync))))
+		},
+		None => {
+			if args.flag_artsync {
+				return Err(Error::InvalidOutput("--artsync is only used with Art-Net".to_owned()));
+			}
+			let destination = match args.flag_unicast {
+				Some(ref address) => Some(try!(output::parse_address(address, output::E131_PORT))),
+				None => None,
+			};
+			Ok(Box::new(try!(E131Sender::new(destination, "Proton", args.flag_priority))))
+		},
+	}
+}
+
+/// Reads and decodes music files
+fn audio_backend() -> Box<audio::AudioBackend> {
+	Box::new(audio::SymphoniaBackend)
+}
+
+/// Plays music during shows, or with --no-audio runs them silently
+#[cfg(feature = "cpal")]
+fn audio_player(no_audio: bool) -> Box<audio::AudioPlayer> {
+	if no_audio {
+		Box::new(audio::SilentPlayer)
+	} else {
+		Box::new(audio::CpalPlayer)
+	}
+}
+
+/// Built without cpal there's no audio output, so shows always run silently
+#[cfg(not(feature = "cpal"))]
+fn audio_player(_no_audio: bool) -> Box<audio::AudioPlayer> {
+	Box::new(audio::SilentPlayer)
+}
+
+/// Prints progress through a show as it's played
+fn print_playback_event(event: PlaybackEvent) {
+	match event {
+		PlaybackEvent::ShowTimes(start, end) => println!("Show runs from {} to {}", start, end),
+		PlaybackEvent::Playing(name) => println!("Playing '{}'", name),
+		PlaybackEvent::TestStep(step, num_steps, label) => println!("Step {}/{}: {}", step, num_steps, label),
+	}
+}
@@ -0,0 +1,284 @@
+//! This module manages the music files sequences play
+
+use openssl::hash::{hash, MessageDigest};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use audio::{self, AudioAnalysis, AudioBackend};
+use dao::{ProjectDao, SequenceDao, TimingDao};
+use error::Error;
+use project_types::{IssueLevel, SequenceIssue, TimingMark};
+
+
+/// Directory music files are stored in
+pub const MUSIC_DIR: &'static str = "Music";
+
+/// A directory music files are copied into. Files are named by a hash of their
+/// contents, so each song is only stored once and changed files can be found.
+#[derive(Clone, Debug)]
+pub struct MusicStore {
+    dir: PathBuf,
+}
+
+impl MusicStore {
+    /// Creates a store for the given directory. The directory is created when
+    /// the first file is added.
+    pub fn new<P: AsRef<Path>>(dir: P) -> MusicStore {
+        MusicStore { dir: dir.as_ref().to_path_buf() }
+    }
+
+    /// Returns the path of a stored file
+    pub fn path(&self, file_name: &str) -> PathBuf {
+        self.dir.join(file_name)
+    }
+
+    /// Returns true if the file is in the store
+    pub fn contains(&self, file_name: &str) -> bool {
+        self.path(file_name).is_file()
+    }
+
+    /// Copies a music file into the store, unless it's already there.
+    /// Returns the name of the stored file.
+    pub fn add<P: AsRef<Path>>(&self, music_file_path: P) -> Result<String, Error> {
+        try!(audio::validate_file_type(&music_file_path));
+        let extension = music_file_path.as_ref().extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let data = try!(read_file(music_file_path.as_ref()));
+        let file_name = format!("{}.{}", try!(hash_data(&data)), extension);
+
+        if !self.contains(&file_name) {
+            try!(fs::create_dir_all(&self.dir).map_err(Error::Io));
+            let mut file = try!(File::create(self.path(&file_name)).map_err(Error::Io));
+            try!(file.write_all(&data).map_err(Error::Io));
+        }
+        Ok(file_name)
+    }
+
+    /// Lists the names of every stored file, sorted
+    pub fn list(&self) -> Result<Vec<String>, Error> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut file_names = Vec::new();
+        for entry in try!(fs::read_dir(&self.dir).map_err(Error::Io)) {
+            let entry = try!(entry.map_err(Error::Io));
+            if entry.path().is_file() {
+                if let Some(file_name) = entry.file_name().to_str() {
+                    file_names.push(file_name.to_owned());
+                }
+            }
+        }
+        file_names.sort();
+        Ok(file_names)
+    }
+
+    /// Checks that a file is in the store and hasn't changed since it was added.
+    /// Files that aren't named by their hash (added before the store) are only
+    /// checked for.
+    pub fn verify(&self, file_name: &str) -> Result<bool, Error> {
+        if !self.contains(file_name) {
+            return Ok(false);
+        }
+        if !MusicStore::is_hash_name(file_name) {
+            return Ok(true);
+        }
+        let stem = file_name.split('.').next().unwrap_or("");
+        let data = try!(read_file(&self.path(file_name)));
+        Ok(try!(hash_data(&data)) == stem)
+    }
+
+    /// Returns true if a file is named by its hash, as files added to the store are
+    pub fn is_hash_name(file_name: &str) -> bool {
+        let stem = file_name.split('.').next().unwrap_or("");
+        stem.len() == 64 && stem.chars().all(|c| c.is_digit(16))
+    }
+
+    /// Deletes a file from the store
+    pub fn remove(&self, file_name: &str) -> Result<(), Error> {
+        fs::remove_file(self.path(file_name)).map_err(Error::Io)
+    }
+}
+
+/// Copies a music file into the store, checking it can be read first.
+/// Returns the name of the stored file.
+pub fn add_music<P: AsRef<Path>, AB: AudioBackend>(
+    audio_backend: &AB,
+    music_store: &MusicStore,
+    music_file_path: P
+) -> Result<String, Error> {
+    try!(audio::validate_file_type(&music_file_path));
+    let _ = try!(audio_backend.get_info(music_file_path.as_ref()));
+    music_store.add(music_file_path)
+}
+
+/// Copies a music file into the store and points a sequence at it, taking the
+/// duration from the file. Used to move sequences made before the store, whose
+/// music is named by its original file name, into it. Returns the name of the stored file.
+pub fn add_music_for_sequence<P: AsRef<Path>, AB: AudioBackend, SD: SequenceDao>(
+    audio_backend: &AB,
+    music_store: &MusicStore,
+    seq_dao: &SD,
+    seqid: u32,
+    music_file_path: P
+) -> Result<String, Error> {
+
+    let mut sequence = try!(seq_dao.get_sequence(seqid));
+    try!(audio::validate_file_type(&music_file_path));
+    let info = try!(audio_backend.get_info(music_file_path.as_ref()));
+    let file_name = try!(music_store.add(music_file_path));
+    sequence.music_file_name = file_name.to_owned();
+    sequence.music_duration_ms = info.duration_ms;
+    try!(seq_dao.update_sequence(&sequence));
+    Ok(file_name)
+}
+
+/// Lists the stored music files, along with the ids of the sequences that use each
+pub fn list_music<SD: SequenceDao>(
+    seq_dao: &SD,
+    music_store: &MusicStore
+) -> Result<Vec<(String, Vec<u32>)>, Error> {
+
+    let sequences = try!(seq_dao.get_all_sequences());
+    let file_names = try!(music_store.list());
+    Ok(file_names.into_iter()
+        .map(|file_name| {
+            let seqids = sequences.iter()
+                .filter(|sequence| sequence.music_file_name == file_name)
+                .map(|sequence| sequence.seqid)
+                .collect::<Vec<u32>>();
+            (file_name, seqids)
+        })
+        .collect())
+}
+
+/// Deletes stored music files that no sequence uses. Nothing is deleted if
+/// dry_run is true. Returns the names of the files deleted (or that would be).
+pub fn gc_music<SD: SequenceDao>(
+    seq_dao: &SD,
+    music_store: &MusicStore,
+    dry_run: bool
+) -> Result<Vec<String>, Error> {
+
+    let sequences = try!(seq_dao.get_all_sequences());
+    let used = sequences.iter()
+        .map(|sequence| sequence.music_file_name.as_str())
+        .collect::<HashSet<&str>>();
+
+    let mut removed = Vec::new();
+    for file_name in try!(music_store.list()) {
+        if used.contains(file_name.as_str()) {
+            continue;
+        }
+        if !dry_run {
+            try!(music_store.remove(&file_name));
+        }
+        removed.push(file_name);
+    }
+    Ok(removed)
+}
+
+/// Checks that the music of every sequence in a project's playlist is stored
+/// and unchanged
+pub fn verify_music<PD: ProjectDao, SD: SequenceDao>(
+    proj_dao: &PD,
+    seq_dao: &SD,
+    music_store: &MusicStore,
+    proj_name: &str
+) -> Result<Vec<SequenceIssue>, Error> {
+
+    let project = try!(proj_dao.get_project(proj_name));
+    let mut issues = Vec::new();
+    for seqid in &project.playlist {
+        let sequence = try!(seq_dao.get_sequence(*seqid));
+        let message = if !music_store.contains(&sequence.music_file_name) {
+            if MusicStore::is_hash_name(&sequence.music_file_name) {
+                format!("Music file '{}' is missing", sequence.music_file_name)
+            } else {
+                format!("Music file '{}' was never added to the store; add it with add-music --for-sequence={}",
+                    sequence.music_file_name, sequence.seqid)
+            }
+        } else if !try!(music_store.verify(&sequence.music_file_name)) {
+            format!("Music file '{}' has changed since it was added", sequence.music_file_name)
+        } else {
+            continue;
+        };
+        issues.push(SequenceIssue {
+            seqid: sequence.seqid,
+            name: sequence.name,
+            level: IssueLevel::Error,
+            message: message
+        });
+    }
+    Ok(issues)
+}
+
+/// Makes sure every sequence in a project's playlist has its music stored and
+/// unchanged, so it can be played
+pub fn require_verified_music<PD: ProjectDao, SD: SequenceDao>(
+    proj_dao: &PD,
+    seq_dao: &SD,
+    music_store: &MusicStore,
+    proj_name: &str
+) -> Result<(), Error> {
+
+    let issues = try!(verify_music(proj_dao, seq_dao, music_store, proj_name));
+    if issues.is_empty() {
+        return Ok(());
+    }
+    let description = issues.iter()
+        .map(|issue| issue.to_string())
+        .collect::<Vec<String>>()
+        .join("; ");
+    Err(Error::MusicNotVerified(description))
+}
+
+/// Finds the beats, onsets and band energy of a sequence's music, and stores them
+/// with the sequence as the "beats" and "onsets" timing tracks and an envelope per band
+pub fn analyze_music<AB: AudioBackend, SD: SequenceDao, TD: TimingDao>(
+    audio_backend: &AB,
+    music_store: &MusicStore,
+    seq_dao: &SD,
+    timing_dao: &TD,
+    seqid: u32
+) -> Result<AudioAnalysis, Error> {
+
+    let sequence = try!(seq_dao.get_sequence(seqid));
+    if !music_store.contains(&sequence.music_file_name) {
+        return Err(Error::MusicNotVerified(
+            format!("Music file '{}' is missing", sequence.music_file_name)));
+    }
+    let decoded = try!(audio_backend.decode(&music_store.path(&sequence.music_file_name)));
+    let analysis = audio::analyze(
+        &decoded,
+        sequence.frame_duration_ms,
+        sequence.num_frames,
+        sequence.music_offset_ms);
+
+    let to_marks = |times: &Vec<u32>| -> Vec<TimingMark> {
+        times.iter().map(|time| TimingMark::at(*time)).collect()
+    };
+    try!(timing_dao.set_timing_track(seqid, "beats", &to_marks(&analysis.beats)));
+    try!(timing_dao.set_timing_track(seqid, "onsets", &to_marks(&analysis.onsets)));
+    for &(band, ref envelope) in &analysis.envelopes {
+        try!(timing_dao.set_envelope(seqid, band.name(), envelope));
+    }
+    Ok(analysis)
+}
+
+/// Reads a whole file
+fn read_file(path: &Path) -> Result<Vec<u8>, Error> {
+    let mut file = try!(File::open(path).map_err(Error::Io));
+    let mut data = Vec::new();
+    try!(file.read_to_end(&mut data).map_err(Error::Io));
+    Ok(data)
+}
+
+/// Hashes data with SHA-256, returning the hash as lowercase hex
+fn hash_data(data: &[u8]) -> Result<String, Error> {
+    let digest = try!(hash(MessageDigest::sha256(), data).map_err(Error::Ssl));
+    Ok(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
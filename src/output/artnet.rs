@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::net::{SocketAddr, UdpSocket};
+use std::str::FromStr;
+
+use error::Error;
+use output::{self, DmxFrame, OutputDriver};
+
+
+/// Port Art-Net nodes listen on
+pub const ARTNET_PORT: u16 = 6454;
+
+/// Art-Net protocol version sent in packets
+const PROTOCOL_VERSION: u16 = 14;
+
+const OP_DMX: u16 = 0x5000;
+const OP_SYNC: u16 = 0x5200;
+
+/// Where each universe's Art-Net packets are sent, e.g. `1=10.0.0.5,2=10.0.0.6`.
+/// An address on its own is used for every universe not given one, and can be
+/// a broadcast address like 10.255.255.255.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArtNetDestinations {
+    pub universes: BTreeMap<u16, SocketAddr>,
+    pub default: Option<SocketAddr>,
+}
+
+impl ArtNetDestinations {
+    /// Returns where a universe's packets are sent
+    pub fn address(&self, universe: u16) -> Option<SocketAddr> {
+        self.universes.get(&universe).cloned().or(self.default)
+    }
+}
+
+impl FromStr for ArtNetDestinations {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<ArtNetDestinations, Error> {
+        let mut destinations = ArtNetDestinations {
+            universes: BTreeMap::new(),
+            default: None
+        };
+        for destination in s.split(',').map(|destination| destination.trim()) {
+            match destination.find('=') {
+                Some(equals) => {
+                    let universe = match destination[..equals].trim().parse::<u16>() {
+                        Ok(universe) if universe > 0 => universe,
+                        _ => return Err(Error::InvalidOutput(
+                            format!("Invalid universe in Art-Net destination: {}", destination))),
+                    };
+                    let address = try!(output::parse_address(destination[equals + 1..].trim(), ARTNET_PORT));
+                    destinations.universes.insert(universe, address);
+                },
+                None => {
+                    if destinations.default.is_some() {
+                        return Err(Error::InvalidOutput(
+                            "Only one Art-Net destination can be used for all universes".to_owned()));
+                    }
+                    destinations.default = Some(try!(output::parse_address(destination, ARTNET_PORT)));
+                },
+            }
+        }
+        Ok(destinations)
+    }
+}
+
+/// Sends frames as Art-Net ArtDmx packets, one per universe. With sync on, an
+/// ArtSync packet follows each frame so nodes output every universe at once.
+pub struct ArtNetSender {
+    socket: UdpSocket,
+    destinations: ArtNetDestinations,
+    sync: bool,
+    sequence: u8,
+}
+
+impl ArtNetSender {
+    /// Creates a sender
+    pub fn new(destinations: ArtNetDestinations, sync: bool) -> Result<ArtNetSender, Error> {
+        let socket = try!(UdpSocket::bind("0.0.0.0:0").map_err(Error::Io));
+        try!(socket.set_broadcast(true).map_err(Error::Io));
+        Ok(ArtNetSender {
+            socket: socket,
+            destinations: destinations,
+            sync: sync,
+            sequence: 0
+        })
+    }
+}
+
+impl OutputDriver for ArtNetSender {
+    fn send(&mut self, frame: &DmxFrame) -> Result<(), Error> {
+        // Sequence numbers go from 1 to 255; 0 means they aren't used
+        self.sequence = if self.sequence == 255 { 1 } else { self.sequence + 1 };
+
+        let mut sent_to = Vec::new();
+        for (universe, data) in frame.universes.iter() {
+            let address = try!(self.destinations.address(*universe)
+                .ok_or(Error::InvalidOutput(format!("No Art-Net destination for universe {}", universe))));
+            let packet = artdmx_packet(self.sequence, artnet_port_address(*universe), data);
+            let _ = try!(self.socket.send_to(&packet, address).map_err(Error::Io));
+            if !sent_to.contains(&address) {
+                sent_to.push(address);
+            }
+        }
+
+        if self.sync {
+            let packet = artsync_packet();
+            for address in sent_to {
+                let _ = try!(self.socket.send_to(&packet, address).map_err(Error::Io));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the Art-Net port-address of a universe (starting from 1). Port-addresses
+/// start from 0, with the net in bits 8-14, the sub-net in bits 4-7 and the
+/// universe in bits 0-3, so universe 1 is net 0, sub-net 0, universe 0 and
+/// universe 17 is net 0, sub-net 1, universe 0.
+pub fn artnet_port_address(universe: u16) -> u16 {
+    (universe - 1) & 0x7fff
+}
+
+/// Builds an ArtDmx packet (Art-Net 4, OpDmx) for one universe
+pub fn artdmx_packet(sequence: u8, port_address: u16, data: &[u8]) -> Vec<u8> {
+    let mut packet = artnet_header(OP_DMX, 18 + data.len());
+    packet.push(sequence);
+    packet.push(0); // Physical port
+    packet.push(port_address as u8); // SubUni
+    packet.push((port_address >> 8) as u8); // Net
+    packet.push((data.len() >> 8) as u8);
+    packet.push(data.len() as u8);
+    packet.extend_from_slice(data);
+    packet
+}
+
+/// Builds an ArtSync packet (Art-Net 4, OpSync)
+pub fn artsync_packet() -> Vec<u8> {
+    let mut packet = artnet_header(OP_SYNC, 14);
+    packet.push(0); // Aux1
+    packet.push(0); // Aux2
+    packet
+}
+
+/// ID, op code (little endian) and protocol version (big endian) that start every packet
+fn artnet_header(op_code: u16, length: usize) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(length);
+    packet.extend_from_slice(b"Art-Net\0");
+    packet.push(op_code as u8);
+    packet.push((op_code >> 8) as u8);
+    packet.push((PROTOCOL_VERSION >> 8) as u8);
+    packet.push(PROTOCOL_VERSION as u8);
+    packet
+}
@@ -0,0 +1,152 @@
+use openssl::hash::{hash, MessageDigest};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+
+use error::Error;
+use output::{DmxFrame, OutputDriver};
+
+
+/// Port E1.31 receivers listen on
+pub const E131_PORT: u16 = 5568;
+
+/// Highest priority a source can send at
+const MAX_PRIORITY: u8 = 200;
+
+/// Option bit telling receivers a source has stopped sending a universe
+const STREAM_TERMINATED: u8 = 0x40;
+
+/// Sends frames as E1.31 (sACN) packets, one per universe, either multicast
+/// to each universe's group or unicast to a single receiver
+pub struct E131Sender {
+    socket: UdpSocket,
+    destination: Option<SocketAddr>, // None to multicast
+    source_name: String,
+    cid: [u8; 16],
+    priority: u8,
+    sequences: HashMap<u16, u8>,
+}
+
+impl E131Sender {
+    /// Creates a sender. The source's CID (its unique id) comes from its name,
+    /// so receivers see the same source each time a show is played.
+    pub fn new(destination: Option<SocketAddr>, source_name: &str, priority: u8) -> Result<E131Sender, Error> {
+        if priority > MAX_PRIORITY {
+            return Err(Error::InvalidOutput(
+                format!("E1.31 priority must be from 0 to {}, not {}", MAX_PRIORITY, priority)));
+        }
+        let socket = try!(UdpSocket::bind("0.0.0.0:0").map_err(Error::Io));
+        let digest = try!(hash(MessageDigest::sha256(), source_name.as_bytes()).map_err(Error::Ssl));
+        let mut cid = [0; 16];
+        cid.copy_from_slice(&digest[..16]);
+        Ok(E131Sender {
+            socket: socket,
+            destination: destination,
+            source_name: source_name.to_owned(),
+            cid: cid,
+            priority: priority,
+            sequences: HashMap::new()
+        })
+    }
+
+    /// Returns where a universe's packets are sent
+    pub fn address(&self, universe: u16) -> SocketAddr {
+        match self.destination {
+            Some(address) => address,
+            None => SocketAddr::V4(SocketAddrV4::new(
+                Ipv4Addr::new(239, 255, (universe >> 8) as u8, universe as u8),
+                E131_PORT)),
+        }
+    }
+
+    fn send_universe(&mut self, universe: u16, data: &[u8], options: u8) -> Result<(), Error> {
+        // Receivers use sequence numbers to throw away packets that arrive out of order
+        let sequence = {
+            let sequence = self.sequences.entry(universe).or_insert(0);
+            *sequence = sequence.wrapping_add(1);
+            *sequence
+        };
+        let packet = e131_packet(&self.cid, &self.source_name, self.priority, sequence, options, universe, data);
+        let address = self.address(universe);
+        let _ = try!(self.socket.send_to(&packet, address).map_err(Error::Io));
+        Ok(())
+    }
+}
+
+impl OutputDriver for E131Sender {
+    fn send(&mut self, frame: &DmxFrame) -> Result<(), Error> {
+        for (universe, data) in frame.universes.iter() {
+            try!(self.send_universe(*universe, data, 0));
+        }
+        Ok(())
+    }
+
+    /// Sends each universe three stream terminated packets, as the standard asks
+    fn finish(&mut self) -> Result<(), Error> {
+        let universes = self.sequences.keys().cloned().collect::<Vec<u16>>();
+        for universe in universes {
+            for _ in 0..3 {
+                try!(self.send_universe(universe, &[], STREAM_TERMINATED));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builds an E1.31 data packet (ANSI E1.31-2016, section 4) for one universe.
+/// Data starts at dmx channel 1 and is sent after a null start code.
+pub fn e131_packet(
+    cid: &[u8; 16],
+    source_name: &str,
+    priority: u8,
+    sequence: u8,
+    options: u8,
+    universe: u16,
+    data: &[u8]
+) -> Vec<u8> {
+
+    let length = 126 + data.len();
+    let mut packet = Vec::with_capacity(length);
+
+    // Root layer
+    push_u16(&mut packet, 0x0010); // Preamble size
+    push_u16(&mut packet, 0x0000); // Postamble size
+    packet.extend_from_slice(b"ASC-E1.17\0\0\0");
+    push_u16(&mut packet, 0x7000 | (length - 16) as u16);
+    push_u32(&mut packet, 0x00000004); // VECTOR_ROOT_E131_DATA
+    packet.extend_from_slice(cid);
+
+    // Framing layer
+    push_u16(&mut packet, 0x7000 | (length - 38) as u16);
+    push_u32(&mut packet, 0x00000002); // VECTOR_E131_DATA_PACKET
+    let mut name = [0; 64];
+    for (byte, name_byte) in source_name.bytes().take(63).zip(name.iter_mut()) {
+        *name_byte = byte;
+    }
+    packet.extend_from_slice(&name);
+    packet.push(priority);
+    push_u16(&mut packet, 0); // Synchronization address (none)
+    packet.push(sequence);
+    packet.push(options);
+    push_u16(&mut packet, universe);
+
+    // DMP layer
+    push_u16(&mut packet, 0x7000 | (length - 115) as u16);
+    packet.push(0x02); // VECTOR_DMP_SET_PROPERTY
+    packet.push(0xa1); // Address and data type
+    push_u16(&mut packet, 0x0000); // First property address
+    push_u16(&mut packet, 0x0001); // Address increment
+    push_u16(&mut packet, (data.len() + 1) as u16);
+    packet.push(0x00); // Start code
+    packet.extend_from_slice(data);
+    packet
+}
+
+fn push_u16(packet: &mut Vec<u8>, value: u16) {
+    packet.push((value >> 8) as u8);
+    packet.push(value as u8);
+}
+
+fn push_u32(packet: &mut Vec<u8>, value: u32) {
+    push_u16(packet, (value >> 16) as u16);
+    push_u16(packet, value as u16);
+}
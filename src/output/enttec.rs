@@ -0,0 +1,265 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use error::Error;
+use output::{DmxFrame, OutputDriver, UNIVERSE_SIZE};
+
+
+/// Speed DMX is sent at: 250k baud, 8 data bits, no parity and 2 stop bits
+const DMX_BAUD: u32 = 250000;
+
+/// Length of the break and mark after break that start each DMX packet
+const BREAK_US: u32 = 110;
+const MARK_AFTER_BREAK_US: u32 = 16;
+
+/// Start and end of a DMX USB Pro message, and the label of an "Output Only
+/// Send DMX Packet Request"
+const PRO_START: u8 = 0x7e;
+const PRO_END: u8 = 0xe7;
+const PRO_SEND_DMX_LABEL: u8 = 6;
+
+/// Enttec USB-DMX interfaces
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnttecWidget {
+    Pro, // DMX USB Pro, which times DMX itself and is sent whole packets
+    Open, // Open DMX USB, which is a plain serial port that the DMX is timed over
+}
+
+impl FromStr for EnttecWidget {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<EnttecWidget, Error> {
+        match s {
+            "pro" => Ok(EnttecWidget::Pro),
+            "open" => Ok(EnttecWidget::Open),
+            _ => Err(Error::InvalidOutput(format!("Unknown Enttec widget: {}. Use pro or open", s))),
+        }
+    }
+}
+
+/// A serial device DMX is written to
+pub trait SerialDevice: Write {
+    /// Holds the line low for a time, then releases it
+    fn send_break(&mut self, duration_us: u32) -> io::Result<()>;
+}
+
+/// A serial port, e.g. /dev/ttyUSB0, set up to send DMX. Anything that isn't
+/// a terminal (e.g. a plain file) is written to as is.
+pub struct SerialPort {
+    file: File,
+}
+
+impl SerialPort {
+    /// Opens a serial port and sets it to raw 250k baud, 8N2
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<SerialPort, Error> {
+        let file = try!(OpenOptions::new()
+            .write(true)
+            .open(path.as_ref())
+            .map_err(Error::Io));
+        try!(configure(&file).map_err(Error::Io));
+        Ok(SerialPort { file: file })
+    }
+}
+
+impl Write for SerialPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    /// Waits until everything written has been sent down the line
+    fn flush(&mut self) -> io::Result<()> {
+        try!(self.file.flush());
+        drain(&self.file)
+    }
+}
+
+impl SerialDevice for SerialPort {
+    fn send_break(&mut self, duration_us: u32) -> io::Result<()> {
+        // A break sent while the last frame is still going out would cut it short
+        try!(drain(&self.file));
+        try!(set_break(&self.file, true));
+        thread::sleep(Duration::new(0, duration_us * 1000));
+        set_break(&self.file, false)
+    }
+}
+
+/// Sends one universe to an Enttec USB-DMX interface
+pub struct EnttecDriver<S: SerialDevice> {
+    device: S,
+    widget: EnttecWidget,
+    universe: u16,
+}
+
+impl<S: SerialDevice> EnttecDriver<S> {
+    /// Creates a driver sending the given universe (starting from 1) to a device
+    pub fn new(device: S, widget: EnttecWidget, universe: u16) -> EnttecDriver<S> {
+        EnttecDriver {
+            device: device,
+            widget: widget,
+            universe: universe
+        }
+    }
+
+    /// Returns the device, e.g. to look at what was written to it
+    pub fn into_device(self) -> S {
+        self.device
+    }
+}
+
+impl<S: SerialDevice> OutputDriver for EnttecDriver<S> {
+    fn send(&mut self, frame: &DmxFrame) -> Result<(), Error> {
+        let off = vec![0; UNIVERSE_SIZE as usize];
+        let data = frame.universes.get(&self.universe).unwrap_or(&off);
+        match self.widget {
+            EnttecWidget::Pro => {
+                try!(self.device.write_all(&enttec_pro_packet(data)).map_err(Error::Io));
+            },
+            EnttecWidget::Open => {
+                // Break, mark after break, then the start code and data. The
+                // last frame has to finish going out before the break starts.
+                try!(self.device.flush().map_err(Error::Io));
+                try!(self.device.send_break(BREAK_US).map_err(Error::Io));
+                thread::sleep(Duration::new(0, MARK_AFTER_BREAK_US * 1000));
+                let mut packet = Vec::with_capacity(data.len() + 1);
+                packet.push(0);
+                packet.extend_from_slice(data);
+                try!(self.device.write_all(&packet).map_err(Error::Io));
+            },
+        }
+        self.device.flush().map_err(Error::Io)
+    }
+}
+
+/// Builds a DMX USB Pro "Output Only Send DMX Packet Request" (label 6), with
+/// a null start code before the data
+pub fn enttec_pro_packet(data: &[u8]) -> Vec<u8> {
+    let length = data.len() + 1;
+    let mut packet = Vec::with_capacity(length + 5);
+    packet.push(PRO_START);
+    packet.push(PRO_SEND_DMX_LABEL);
+    packet.push(length as u8);
+    packet.push((length >> 8) as u8);
+    packet.push(0); // Start code
+    packet.extend_from_slice(data);
+    packet.push(PRO_END);
+    packet
+}
+
+#[cfg(unix)]
+fn configure(file: &File) -> io::Result<()> {
+    use libc;
+    use std::mem;
+    use std::os::unix::io::AsRawFd;
+
+    let fd = file.as_raw_fd();
+    unsafe {
+        if libc::isatty(fd) != 1 {
+            return Ok(());
+        }
+        let mut settings: libc::termios = mem::zeroed();
+        if libc::tcgetattr(fd, &mut settings) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        libc::cfmakeraw(&mut settings);
+        settings.c_cflag |= libc::CSTOPB | libc::CLOCAL | libc::CREAD;
+        settings.c_cflag &= !(libc::PARENB | libc::CRTSCTS);
+        if libc::tcsetattr(fd, libc::TCSANOW, &settings) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    set_baud(fd, DMX_BAUD)
+}
+
+/// 250k isn't a standard baud rate, so Linux needs it set with termios2
+#[cfg(target_os = "linux")]
+fn set_baud(fd: i32, baud: u32) -> io::Result<()> {
+    use libc;
+    use std::mem;
+
+    unsafe {
+        let mut settings: libc::termios2 = mem::zeroed();
+        if libc::ioctl(fd, libc::TCGETS2, &mut settings) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        settings.c_cflag &= !libc::CBAUD;
+        settings.c_cflag |= libc::BOTHER as libc::tcflag_t;
+        settings.c_ispeed = baud;
+        settings.c_ospeed = baud;
+        if libc::ioctl(fd, libc::TCSETS2, &settings) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn set_baud(fd: i32, baud: u32) -> io::Result<()> {
+    use libc;
+    use std::mem;
+
+    unsafe {
+        let mut settings: libc::termios = mem::zeroed();
+        if libc::tcgetattr(fd, &mut settings) != 0 ||
+            libc::cfsetspeed(&mut settings, baud as libc::speed_t) != 0 ||
+            libc::tcsetattr(fd, libc::TCSANOW, &settings) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_break(file: &File, on: bool) -> io::Result<()> {
+    use libc;
+    use std::os::unix::io::AsRawFd;
+
+    let fd = file.as_raw_fd();
+    unsafe {
+        // Plain files can't send breaks, and don't need to
+        if libc::isatty(fd) != 1 {
+            return Ok(());
+        }
+        let request = if on { libc::TIOCSBRK } else { libc::TIOCCBRK };
+        if libc::ioctl(fd, request) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn drain(file: &File) -> io::Result<()> {
+    use libc;
+    use std::os::unix::io::AsRawFd;
+
+    let fd = file.as_raw_fd();
+    unsafe {
+        // Plain files are written straight away
+        if libc::isatty(fd) != 1 {
+            return Ok(());
+        }
+        if libc::tcdrain(fd) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn configure(_file: &File) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Other, "Serial output is only supported on Unix"))
+}
+
+#[cfg(not(unix))]
+fn drain(_file: &File) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Other, "Serial output is only supported on Unix"))
+}
+
+#[cfg(not(unix))]
+fn set_break(_file: &File, _on: bool) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Other, "Serial output is only supported on Unix"))
+}
@@ -0,0 +1,95 @@
+//! Sending sequence data to lights. Protocols and interfaces are wrapped in
+//! drivers that all implement OutputDriver, so they can be swapped out.
+
+mod artnet;
+mod e131;
+mod enttec;
+mod routed;
+
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+
+use effects::MAX_VALUE;
+use error::Error;
+
+pub use self::artnet::{artdmx_packet, artnet_port_address, artsync_packet, ArtNetDestinations, ArtNetSender, ARTNET_PORT};
+pub use self::e131::{e131_packet, E131Sender, E131_PORT};
+pub use self::enttec::{enttec_pro_packet, EnttecDriver, EnttecWidget, SerialDevice, SerialPort};
+pub use self::routed::RoutedDriver;
+
+
+/// Number of channels in a DMX universe
+pub const UNIVERSE_SIZE: u32 = 512;
+
+/// Sends frames of DMX data
+pub trait OutputDriver {
+    /// Sends one frame to every universe in it
+    fn send(&mut self, frame: &DmxFrame) -> Result<(), Error>;
+
+    /// Called once the last frame has been sent, e.g. to let receivers know the
+    /// show is over rather than waiting for them to time out
+    fn finish(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Lets the driver used be picked when Proton runs
+impl OutputDriver for Box<OutputDriver> {
+    fn send(&mut self, frame: &DmxFrame) -> Result<(), Error> {
+        (**self).send(frame)
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        (**self).finish()
+    }
+}
+
+/// One frame of DMX data for each universe in use. A layout's dmx channels
+/// run on from one universe to the next: channels 1-512 are universe 1,
+/// 513-1024 universe 2, and so on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DmxFrame {
+    pub universes: BTreeMap<u16, Vec<u8>>, // 512 values each
+}
+
+impl DmxFrame {
+    /// Creates a frame with every channel off
+    pub fn new() -> DmxFrame {
+        DmxFrame { universes: BTreeMap::new() }
+    }
+
+    /// Sets a dmx channel (starting from 1) to a value from 0-255.
+    /// Channel 0 (unpatched) is ignored.
+    pub fn set(&mut self, channel_dmx: u32, value: u16) {
+        if channel_dmx == 0 {
+            return;
+        }
+        let (universe, slot) = universe_slot(channel_dmx);
+        let data = self.universes.entry(universe).or_insert_with(|| vec![0; UNIVERSE_SIZE as usize]);
+        data[slot] = value.min(MAX_VALUE) as u8;
+    }
+
+    /// Makes sure a universe is sent, even if all its channels are off
+    pub fn add_universe(&mut self, universe: u16) {
+        self.universes.entry(universe).or_insert_with(|| vec![0; UNIVERSE_SIZE as usize]);
+    }
+}
+
+/// Returns the universe (starting from 1) a dmx channel is in, and its index in that universe
+pub fn universe_slot(channel_dmx: u32) -> (u16, usize) {
+    let universe = (channel_dmx - 1) / UNIVERSE_SIZE + 1;
+    let slot = (channel_dmx - 1) % UNIVERSE_SIZE;
+    (universe as u16, slot as usize)
+}
+
+/// Parses an IP address, with or without a port. The default port is used if
+/// none is given.
+pub fn parse_address(address: &str, default_port: u16) -> Result<SocketAddr, Error> {
+    if let Ok(socket_address) = address.parse::<SocketAddr>() {
+        return Ok(socket_address);
+    }
+    match address.parse::<IpAddr>() {
+        Ok(ip) => Ok(SocketAddr::new(ip, default_port)),
+        Err(_) => Err(Error::InvalidOutput(format!("Invalid address: {}", address))),
+    }
+}
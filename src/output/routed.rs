@@ -0,0 +1,45 @@
+use error::Error;
+use output::{DmxFrame, OutputDriver};
+
+
+/// Sends each universe only to the drivers that take it, e.g. so each of a
+/// layout's controllers is sent just its own universes
+pub struct RoutedDriver {
+    routes: Vec<(Box<OutputDriver>, Vec<u16>)>,
+}
+
+impl RoutedDriver {
+    /// Creates a driver with nowhere to send to
+    pub fn new() -> RoutedDriver {
+        RoutedDriver { routes: Vec::new() }
+    }
+
+    /// Sends the given universes to a driver as well
+    pub fn add(&mut self, driver: Box<OutputDriver>, universes: Vec<u16>) {
+        self.routes.push((driver, universes));
+    }
+}
+
+impl OutputDriver for RoutedDriver {
+    fn send(&mut self, frame: &DmxFrame) -> Result<(), Error> {
+        for &mut (ref mut driver, ref universes) in self.routes.iter_mut() {
+            let mut routed = DmxFrame::new();
+            for universe in universes {
+                if let Some(data) = frame.universes.get(universe) {
+                    routed.universes.insert(*universe, data.clone());
+                }
+            }
+            if !routed.universes.is_empty() {
+                try!(driver.send(&routed));
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        for &mut (ref mut driver, _) in self.routes.iter_mut() {
+            try!(driver.finish());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,448 @@
+//! This module plays shows, sending sequence data to lights in time with the music
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{self, NaiveDateTime};
+
+use audio::{AudioPlayer, Playback, SilentPlayer};
+use dao::{ChannelDao, DataDao, DimmingDao, LayoutDao, ProjectDao, SequenceDao};
+use error::Error;
+use music::{self, MusicStore};
+use output::{self, DmxFrame, OutputDriver};
+use effects::MAX_VALUE;
+use project_types::{DimmingProfile, TestPattern, TestStep};
+use schedule::Clock;
+
+/// Frame duration test patterns are sent at
+const TEST_PATTERN_FRAME_MS: u32 = 25;
+
+/// How late a scheduled sequence can start before it's skipped
+const LATE_START_MS: i64 = 5000;
+
+
+/// How a show went. Frames are dropped when sending falls behind the music.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlaybackReport {
+    pub sequences: u32,
+    pub frames_sent: u32,
+    pub frames_dropped: u32,
+}
+
+/// Progress through a show, reported as it happens
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlaybackEvent {
+    /// A scheduled show's start and end times
+    ShowTimes(NaiveDateTime, NaiveDateTime),
+    /// A sequence starting, by name
+    Playing(String),
+    /// A channel test step starting: its number (from 1), the number of steps, and its label
+    TestStep(usize, usize, String),
+}
+
+/// Plays every sequence in a project's playlist, in order, reporting each one
+/// as it starts. Lights are turned off once the last sequence ends.
+pub fn play_project<AP: AudioPlayer, OD: OutputDriver, CD: ChannelDao, DD: DataDao, DMD: DimmingDao, PD: ProjectDao, SD: SequenceDao>(
+    audio_player: &AP,
+    output: &mut OD,
+    chan_dao: &CD,
+    data_dao: &DD,
+    dimming_dao: &DMD,
+    proj_dao: &PD,
+    seq_dao: &SD,
+    music_store: &MusicStore,
+    proj_name: &str,
+    on_event: &Fn(PlaybackEvent)
+) -> Result<PlaybackReport, Error> {
+
+    let project = try!(proj_dao.get_project(proj_name));
+    try!(music::require_verified_music(proj_dao, seq_dao, music_store, proj_name));
+
+    // Load everything up front, so there are no gaps between sequences
+    let mut shows = Vec::with_capacity(project.playlist.len());
+    for seqid in &project.playlist {
+        shows.push(try!(load_show(chan_dao, data_dao, dimming_dao, seq_dao, music_store, *seqid)));
+    }
+
+    let mut report = PlaybackReport::default();
+    for show in &shows {
+        on_event(PlaybackEvent::Playing(show.name.clone()));
+        try!(play_show(audio_player, output, show, &mut report));
+    }
+    try!(end_show(output, &shows));
+    Ok(report)
+}
+
+/// Plays a single sequence
+pub fn play_sequence<AP: AudioPlayer, OD: OutputDriver, CD: ChannelDao, DD: DataDao, DMD: DimmingDao, SD: SequenceDao>(
+    audio_player: &AP,
+    output: &mut OD,
+    chan_dao: &CD,
+    data_dao: &DD,
+    dimming_dao: &DMD,
+    seq_dao: &SD,
+    music_store: &MusicStore,
+    seqid: u32
+) -> Result<PlaybackReport, Error> {
+
+    let show = try!(load_show(chan_dao, data_dao, dimming_dao, seq_dao, music_store, seqid));
+    if !try!(music_store.verify(&show.music_file_name)) {
+        return Err(Error::MusicNotVerified(
+            format!("Music file '{}' has changed since it was added", show.music_file_name)));
+    }
+
+    let mut report = PlaybackReport::default();
+    try!(play_show(audio_player, output, &show, &mut report));
+    try!(end_show(output, &[show]));
+    Ok(report)
+}
+
+/// Plays a project's show on its schedule. Waits for the next show to start
+/// (or joins the one running now), plays its running order and returns once
+/// the show is over. Sequences that should have started a while ago are
+/// skipped, so a show joined part way through keeps to its schedule.
+pub fn play_schedule<C: Clock, AP: AudioPlayer, OD: OutputDriver, CD: ChannelDao, DD: DataDao, DMD: DimmingDao, PD: ProjectDao, SD: SequenceDao>(
+    clock: &C,
+    audio_player: &AP,
+    output: &mut OD,
+    chan_dao: &CD,
+    data_dao: &DD,
+    dimming_dao: &DMD,
+    proj_dao: &PD,
+    seq_dao: &SD,
+    music_store: &MusicStore,
+    proj_name: &str,
+    on_event: &Fn(PlaybackEvent)
+) -> Result<PlaybackReport, Error> {
+
+    let project = try!(proj_dao.get_project(proj_name));
+    let schedule = try!(proj_dao.get_schedule(proj_name));
+    try!(music::require_verified_music(proj_dao, seq_dao, music_store, proj_name));
+
+    // Load the playlist and the intermission up front
+    let mut seqids = project.playlist.clone();
+    if let Some(ref intermission) = schedule.intermission {
+        if !seqids.contains(&intermission.seqid) {
+            seqids.push(intermission.seqid);
+        }
+    }
+    let mut shows = Vec::with_capacity(seqids.len());
+    let mut durations = BTreeMap::new();
+    for seqid in seqids {
+        let show = try!(load_show(chan_dao, data_dao, dimming_dao, seq_dao, music_store, seqid));
+        if !project.playlist.contains(&seqid) && !try!(music_store.verify(&show.music_file_name)) {
+            return Err(Error::MusicNotVerified(
+                format!("Music file '{}' has changed since it was added", show.music_file_name)));
+        }
+        durations.insert(seqid, show.num_frames * show.frame_duration_ms);
+        shows.push((seqid, show));
+    }
+
+    let now = clock.now();
+    let (start, end) = match schedule.show_at(now).or_else(|| schedule.next_show(now)) {
+        Some(show) => show,
+        None => return Err(Error::InvalidSchedule("no shows in the next week".to_owned())),
+    };
+    let order = try!(schedule.running_order(&project.playlist, &durations, start, end));
+    on_event(PlaybackEvent::ShowTimes(start, end));
+
+    let mut report = PlaybackReport::default();
+    for scheduled in &order {
+        if clock.now() - scheduled.start > chrono::Duration::milliseconds(LATE_START_MS) {
+            continue;
+        }
+        clock.sleep_until(scheduled.start);
+        let show = match shows.iter().find(|&&(seqid, _)| seqid == scheduled.seqid) {
+            Some(&(_, ref show)) => show,
+            None => return Err(Error::SequenceNotFound(scheduled.seqid)),
+        };
+        on_event(PlaybackEvent::Playing(show.name.clone()));
+        try!(play_show(audio_player, output, show, &mut report));
+    }
+    let shows = shows.into_iter()
+        .map(|(_, show)| show)
+        .collect::<Vec<Show>>();
+    try!(end_show(output, &shows));
+
+    // Wait for the show to end, so the next call plays the next show
+    clock.sleep_until(end);
+    Ok(report)
+}
+
+/// Sends a test pattern to every patched channel in a layout for the given time.
+/// Channels' dimming profiles are applied, so limits hold while testing.
+pub fn play_test_pattern<OD: OutputDriver, CD: ChannelDao, DMD: DimmingDao, LD: LayoutDao>(
+    output: &mut OD,
+    chan_dao: &CD,
+    dimming_dao: &DMD,
+    layout_dao: &LD,
+    layout_id: u32,
+    pattern: TestPattern,
+    duration_ms: u32
+) -> Result<PlaybackReport, Error> {
+
+    let chan_ids = try!(layout_dao.get_channel_ids(layout_id));
+    let dimming = try!(dimming_dao.get_dimming(&chan_ids));
+    let mut patched = Vec::with_capacity(chan_ids.len());
+    for chanid in chan_ids {
+        let channel = try!(chan_dao.get_channel(chanid));
+        if channel.channel_dmx > 0 {
+            patched.push((chanid, channel.channel_dmx));
+        }
+    }
+
+    // Build the pattern as a show with no music
+    let num_frames = (duration_ms as f32 / TEST_PATTERN_FRAME_MS as f32).ceil() as u32;
+    let channels = patched.iter()
+        .enumerate()
+        .map(|(i, &(chanid, channel_dmx))| {
+            let data = (0..num_frames)
+                .map(|frame| pattern.value(i, patched.len(), frame * TEST_PATTERN_FRAME_MS))
+                .collect::<Vec<u16>>();
+            (channel_dmx, dim(&dimming, chanid, data))
+        })
+        .collect::<Vec<(u32, Vec<u16>)>>();
+    let mut universes = patched.iter()
+        .map(|&(_, channel_dmx)| output::universe_slot(channel_dmx).0)
+        .collect::<Vec<u16>>();
+    universes.sort();
+    universes.dedup();
+    let show = Show {
+        name: format!("{:?} test pattern", pattern),
+        music_file_name: String::new(),
+        music_path: None,
+        music_offset_ms: 0,
+        frame_duration_ms: TEST_PATTERN_FRAME_MS,
+        num_frames: num_frames,
+        channels: channels,
+        universes: universes
+    };
+
+    let mut report = PlaybackReport::default();
+    try!(play_show(&SilentPlayer, output, &show, &mut report));
+    try!(end_show(output, &[show]));
+    Ok(report)
+}
+
+/// Sends channel test steps one after another, reporting each step as it
+/// starts. Every universe the steps use is sent throughout, so channels are
+/// off when their step is over.
+pub fn play_test_steps<OD: OutputDriver, DMD: DimmingDao>(
+    output: &mut OD,
+    dimming_dao: &DMD,
+    steps: &[TestStep],
+    on_event: &Fn(PlaybackEvent)
+) -> Result<PlaybackReport, Error> {
+
+    let mut chan_ids = steps.iter()
+        .flat_map(|step| step.channels.iter())
+        .map(|&(chanid, _)| chanid)
+        .collect::<Vec<u32>>();
+    chan_ids.sort();
+    chan_ids.dedup();
+    let dimming = try!(dimming_dao.get_dimming(&chan_ids));
+
+    let mut universes = steps.iter()
+        .flat_map(|step| step.channels.iter())
+        .map(|&(_, channel_dmx)| output::universe_slot(channel_dmx).0)
+        .collect::<Vec<u16>>();
+    universes.sort();
+    universes.dedup();
+
+    // Each step is a show of its own with no music
+    let shows = steps.iter()
+        .map(|step| {
+            let num_frames = (step.duration_ms as f32 / TEST_PATTERN_FRAME_MS as f32).ceil() as u32;
+            Show {
+                name: step.label.clone(),
+                music_file_name: String::new(),
+                music_path: None,
+                music_offset_ms: 0,
+                frame_duration_ms: TEST_PATTERN_FRAME_MS,
+                num_frames: num_frames,
+                channels: step.channels.iter()
+                    .map(|&(chanid, channel_dmx)| {
+                        (channel_dmx, dim(&dimming, chanid, vec![MAX_VALUE; num_frames as usize]))
+                    })
+                    .collect(),
+                universes: universes.clone()
+            }
+        })
+        .collect::<Vec<Show>>();
+
+    let mut report = PlaybackReport::default();
+    for (i, show) in shows.iter().enumerate() {
+        on_event(PlaybackEvent::TestStep(i + 1, shows.len(), show.name.clone()));
+        try!(play_show(&SilentPlayer, output, show, &mut report));
+    }
+    try!(end_show(output, &shows));
+    Ok(report)
+}
+
+/// A sequence's data, ready to play
+struct Show {
+    name: String,
+    music_file_name: String,
+    music_path: Option<PathBuf>, // None to play without music
+    music_offset_ms: i32,
+    frame_duration_ms: u32,
+    num_frames: u32,
+    channels: Vec<(u32, Vec<u16>)>, // (channel_dmx, data)
+    universes: Vec<u16>,
+}
+
+impl Show {
+    /// Builds one frame of DMX data, with every universe the sequence uses
+    fn frame(&self, frame: usize) -> DmxFrame {
+        let mut dmx_frame = DmxFrame::new();
+        for universe in &self.universes {
+            dmx_frame.add_universe(*universe);
+        }
+        for &(channel_dmx, ref data) in &self.channels {
+            dmx_frame.set(channel_dmx, data.get(frame).cloned().unwrap_or(0));
+        }
+        dmx_frame
+    }
+}
+
+/// Loads a sequence's data, with dimming profiles applied, and checks its music is in the store
+fn load_show<CD: ChannelDao, DD: DataDao, DMD: DimmingDao, SD: SequenceDao>(
+    chan_dao: &CD,
+    data_dao: &DD,
+    dimming_dao: &DMD,
+    seq_dao: &SD,
+    music_store: &MusicStore,
+    seqid: u32
+) -> Result<Show, Error> {
+
+    let sequence = try!(seq_dao.get_sequence(seqid));
+    if !music_store.contains(&sequence.music_file_name) {
+        return Err(Error::MusicNotVerified(
+            format!("Music file '{}' is missing", sequence.music_file_name)));
+    }
+
+    let chan_ids = try!(seq_dao.get_channel_ids(seqid));
+    let dimming = try!(dimming_dao.get_dimming(&chan_ids));
+    let mut channels = Vec::with_capacity(chan_ids.len());
+    let mut universes = Vec::new();
+    for chanid in chan_ids {
+        let channel = try!(chan_dao.get_channel(chanid));
+        if channel.channel_dmx == 0 {
+            continue;
+        }
+        let universe = output::universe_slot(channel.channel_dmx).0;
+        if !universes.contains(&universe) {
+            universes.push(universe);
+        }
+        let data = try!(data_dao.get_data(seqid, chanid));
+        channels.push((channel.channel_dmx, dim(&dimming, chanid, data)));
+    }
+    universes.sort();
+
+    Ok(Show {
+        name: sequence.name,
+        music_path: Some(music_store.path(&sequence.music_file_name)),
+        music_file_name: sequence.music_file_name,
+        music_offset_ms: sequence.music_offset_ms,
+        frame_duration_ms: sequence.frame_duration_ms,
+        num_frames: sequence.num_frames,
+        channels: channels,
+        universes: universes
+    })
+}
+
+/// Applies a channel's dimming profile to its data, if it has one
+fn dim(dimming: &BTreeMap<u32, DimmingProfile>, chanid: u32, data: Vec<u16>) -> Vec<u16> {
+    match dimming.get(&chanid) {
+        Some(profile) => profile.apply_all(&data),
+        None => data,
+    }
+}
+
+/// Sends a sequence's frames in time with its music. The time is read from
+/// the audio clock, so frames stay in sync even if the music drifts from the
+/// system clock; frames that are already late are skipped.
+fn play_show<AP: AudioPlayer, OD: OutputDriver>(
+    audio_player: &AP,
+    output: &mut OD,
+    show: &Show,
+    report: &mut PlaybackReport
+) -> Result<(), Error> {
+
+    let duration_ms = (show.num_frames * show.frame_duration_ms) as i64;
+    let offset_ms = show.music_offset_ms as i64;
+    let started = Instant::now();
+
+    // The music starts offset_ms into the sequence, or that far into the
+    // music if the offset is negative
+    let mut playback: Option<Box<Playback>> = None;
+    if offset_ms <= 0 {
+        if let Some(ref music_path) = show.music_path {
+            playback = Some(try!(audio_player.play(music_path, (-offset_ms) as u32)));
+        }
+    }
+
+    // Last time read from the audio clock, to keep time from once the music ends
+    let mut last_audio: Option<(Instant, i64)> = None;
+    let mut next_frame = 0;
+    loop {
+        if playback.is_none() && elapsed_ms(started) >= offset_ms {
+            if let Some(ref music_path) = show.music_path {
+                playback = Some(try!(audio_player.play(music_path, 0)));
+            }
+        }
+        let time_ms = match playback.as_ref().and_then(|playback| playback.position_ms()) {
+            Some(position_ms) => {
+                let time_ms = position_ms as i64 + offset_ms;
+                last_audio = Some((Instant::now(), time_ms));
+                time_ms
+            },
+            None => match last_audio {
+                Some((at, time_ms)) => time_ms + elapsed_ms(at),
+                None => elapsed_ms(started),
+            },
+        };
+        if time_ms >= duration_ms {
+            break;
+        }
+
+        let frame = (time_ms.max(0) / show.frame_duration_ms as i64) as u32;
+        if frame >= next_frame {
+            try!(output.send(&show.frame(frame as usize)));
+            report.frames_sent += 1;
+            report.frames_dropped += frame - next_frame;
+            next_frame = frame + 1;
+        }
+
+        // Sleep until the next frame is due
+        let wait_ms = (next_frame as i64 * show.frame_duration_ms as i64 - time_ms).max(1);
+        thread::sleep(Duration::from_millis(wait_ms.min(show.frame_duration_ms as i64) as u64));
+    }
+
+    if let Some(mut playback) = playback {
+        playback.stop();
+    }
+    report.frames_dropped += show.num_frames - next_frame.min(show.num_frames);
+    report.sequences += 1;
+    Ok(())
+}
+
+/// Turns off every universe used in the show and lets the output know it's over
+fn end_show<OD: OutputDriver>(output: &mut OD, shows: &[Show]) -> Result<(), Error> {
+    let mut blackout = DmxFrame::new();
+    for show in shows {
+        for universe in &show.universes {
+            blackout.add_universe(*universe);
+        }
+    }
+    try!(output.send(&blackout));
+    output.finish()
+}
+
+/// Milliseconds since an instant
+fn elapsed_ms(instant: Instant) -> i64 {
+    let elapsed = instant.elapsed();
+    elapsed.as_secs() as i64 * 1000 + (elapsed.subsec_nanos() / 1_000_000) as i64
+}
@@ -0,0 +1,122 @@
+//! This module estimates the power a sequence draws, to check it against the
+//! limits of the circuits its lights are on
+
+use std::collections::BTreeMap;
+
+use dao::{ChannelDao, ControllerDao, DataDao, DimmingDao, PowerDao, SequenceDao};
+use effects::MAX_VALUE;
+use error::Error;
+use output;
+use project_types::{LoadGroup, LoadSummary, Overload, PowerReport};
+
+
+/// Walks a sequence's data frame by frame, adding up the power drawn on each
+/// circuit, each controller, each universe and in total. Channels draw their load in proportion
+/// to the value sent to them, after their dimming profile. Runs of frames where
+/// a circuit draws more than its limit are reported as overloads.
+pub fn check_power<CD: ChannelDao, CTD: ControllerDao, DD: DataDao, DMD: DimmingDao, PWD: PowerDao, SD: SequenceDao>(
+    chan_dao: &CD,
+    controller_dao: &CTD,
+    data_dao: &DD,
+    dimming_dao: &DMD,
+    power_dao: &PWD,
+    seq_dao: &SD,
+    seqid: u32,
+    volts: f64
+) -> Result<PowerReport, Error> {
+
+    if !(volts > 0.0) || volts.is_infinite() {
+        return Err(Error::InvalidPower(format!("Volts must be more than 0, not {}", volts)));
+    }
+
+    let sequence = try!(seq_dao.get_sequence(seqid));
+    let chan_ids = try!(seq_dao.get_channel_ids(seqid));
+    let loads = try!(power_dao.get_loads(&chan_ids));
+    let dimming = try!(dimming_dao.get_dimming(&chan_ids));
+    let limits = try!(power_dao.get_circuit_limits(sequence.layout_id));
+    let ports = try!(controller_dao.get_ports(&chan_ids));
+    let controller_names = try!(controller_dao.get_controllers(sequence.layout_id)).into_iter()
+        .map(|controller| (controller.controllerid, controller.name))
+        .collect::<BTreeMap<u32, String>>();
+
+    // Watts drawn in each frame
+    let num_frames = sequence.num_frames as usize;
+    let mut circuits: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    let mut controllers: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    let mut universes: BTreeMap<u16, Vec<f64>> = BTreeMap::new();
+    let mut total = vec![0.0; num_frames];
+    let mut unrated_channels = 0;
+    for chanid in &chan_ids {
+        // Channels that aren't patched are never sent anything
+        let channel = try!(chan_dao.get_channel(*chanid));
+        if channel.channel_dmx == 0 {
+            continue;
+        }
+        let load = match loads.get(chanid) {
+            Some(load) => load,
+            None => {
+                unrated_channels += 1;
+                continue;
+            },
+        };
+
+        let mut data = try!(data_dao.get_data(seqid, *chanid));
+        if let Some(profile) = dimming.get(chanid) {
+            data = profile.apply_all(&data);
+        }
+        let frame_watts = (0..num_frames)
+            .map(|frame| {
+                let value = data.get(frame).cloned().unwrap_or(0).min(MAX_VALUE);
+                load.watts * value as f64 / MAX_VALUE as f64
+            })
+            .collect::<Vec<f64>>();
+
+        add_watts(&mut total, &frame_watts);
+        if let Some(ref circuit) = load.circuit {
+            let circuit_watts = circuits.entry(circuit.clone()).or_insert_with(|| vec![0.0; num_frames]);
+            add_watts(circuit_watts, &frame_watts);
+        }
+        if let Some(name) = ports.get(chanid).and_then(|port| controller_names.get(&port.controllerid)) {
+            let controller_watts = controllers.entry(name.clone()).or_insert_with(|| vec![0.0; num_frames]);
+            add_watts(controller_watts, &frame_watts);
+        }
+        let universe = output::universe_slot(channel.channel_dmx).0;
+        let universe_watts = universes.entry(universe).or_insert_with(|| vec![0.0; num_frames]);
+        add_watts(universe_watts, &frame_watts);
+    }
+
+    let frame_duration_ms = sequence.frame_duration_ms;
+    let mut summaries = Vec::with_capacity(circuits.len() + controllers.len() + universes.len() + 1);
+    let mut overloads = Vec::new();
+    for (circuit, frame_watts) in circuits {
+        let max_amps = limits.get(&circuit).cloned();
+        let group = LoadGroup::Circuit(circuit);
+        if let Some(max_amps) = max_amps {
+            overloads.extend(Overload::find(&group, &frame_watts, frame_duration_ms, volts, max_amps));
+        }
+        summaries.push(LoadSummary::from_frames(group, &frame_watts, frame_duration_ms, max_amps));
+    }
+    for (controller, frame_watts) in controllers {
+        summaries.push(LoadSummary::from_frames(LoadGroup::Controller(controller), &frame_watts, frame_duration_ms, None));
+    }
+    for (universe, frame_watts) in universes {
+        summaries.push(LoadSummary::from_frames(LoadGroup::Universe(universe), &frame_watts, frame_duration_ms, None));
+    }
+    summaries.push(LoadSummary::from_frames(LoadGroup::Total, &total, frame_duration_ms, None));
+    overloads.sort_by_key(|overload| overload.start_ms);
+
+    Ok(PowerReport {
+        seqid: seqid,
+        volts: volts,
+        summaries: summaries,
+        overloads: overloads,
+        unrated_channels: unrated_channels
+    })
+}
+
+/// Adds a channel's watts to a group's, frame by frame
+fn add_watts(group_watts: &mut Vec<f64>, frame_watts: &[f64]) {
+    for (sum, watts) in group_watts.iter_mut().zip(frame_watts.iter()) {
+        *sum += *watts;
+    }
+}
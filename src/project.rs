@@ -1,7 +1,8 @@
 use rustc_serialize::json;
 
-use dao::{ChannelDao, DataDao, LayoutDao, PermissionDao, ProjectDao, SequenceDao, UserDao};
+use dao::{ChannelDao, DataDao, DimmingDao, LayoutDao, PermissionDao, ProjectDao, SequenceDao, UserDao};
 use error::Error;
+use music::{self, MusicStore};
 use project_types::{Project, SequenceData};
 use utils;
 
@@ -61,18 +62,23 @@ pub fn get_layout_id<PD: ProjectDao>(
     Ok(project.layout_id)
 }
 
-/// Gets all sequence data in the project's playlist
-pub fn get_playlist_data<CD: ChannelDao, DD: DataDao, PD: ProjectDao, SD: SequenceDao>(
+/// Gets all sequence data in the project's playlist, with channels' dimming profiles applied
+pub fn get_playlist_data<CD: ChannelDao, DD: DataDao, DMD: DimmingDao, PD: ProjectDao, SD: SequenceDao>(
     chan_dao: &CD,
     data_dao: &DD,
+    dimming_dao: &DMD,
     proj_dao: &PD,
     seq_dao: &SD,
+    music_store: &MusicStore,
     proj_name: &str
 ) -> Result<String, Error> {
 
     // Check that project exists
     let project = try!(proj_dao.get_project(proj_name));
 
+    // Make sure every sequence's music is stored and unchanged
+    try!(music::require_verified_music(proj_dao, seq_dao, music_store, proj_name));
+
     let mut playlist_data = Vec::with_capacity(project.playlist.len());
 
     // Go through each sequence in the playlist
@@ -102,17 +108,22 @@ pub fn get_playlist_data<CD: ChannelDao, DD: DataDao, PD: ProjectDao, SD: Sequen
         // Up to 512 channels per universe, plus one because DMX starts at 1
         let mut seq_data = vec![vec![0; sequence.num_frames as usize]; 513];
 
-        // Get each channel's data and put it in the correct vector slot
+        // Get each channel's data, with its dimming profile applied, and put it in the correct vector slot
+        let dimming = try!(dimming_dao.get_dimming(&chan_ids));
         for chanid in chan_ids {
             let channel = try!(chan_dao.get_channel(chanid));
             let chan_data = try!(data_dao.get_data(seqid.to_owned(), chanid.to_owned()));
-            seq_data[channel.channel_dmx as usize] = chan_data;
+            seq_data[channel.channel_dmx as usize] = match dimming.get(&chanid) {
+                Some(profile) => profile.apply_all(&chan_data),
+                None => chan_data,
+            };
         }
 
         let sequence_data = SequenceData {
             name: sequence.name,
             frame_dur_ms: sequence.frame_duration_ms,
             music_file: sequence.music_file_name,
+            music_offset_ms: sequence.music_offset_ms,
             num_frames: sequence.num_frames,
             data: seq_data
         };
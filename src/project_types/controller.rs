@@ -0,0 +1,172 @@
+use std::fmt;
+use std::str::FromStr;
+
+use error::Error;
+use output::{self, ARTNET_PORT, E131_PORT};
+
+
+/// How a controller is sent its universes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ControllerProtocol {
+    E131,
+    ArtNet,
+}
+
+/// A physical controller in a layout. It takes some universes over the network
+/// and drives its lights from output ports, each of which can only drive so
+/// many channels (e.g. 510 for 170 RGB pixels).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Controller {
+    pub controllerid: u32,
+    pub layout_id: u32,
+    pub name: String,
+    pub address: String, // IP address, with or without a port
+    pub protocol: ControllerProtocol,
+    pub universes: Vec<u16>,
+    pub ports: u32, // Ports are numbered from 1
+    pub port_channels: u32, // Most channels each port can drive
+}
+
+/// The controller output port a channel is wired to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PortAssignment {
+    pub controllerid: u32,
+    pub port: u32,
+}
+
+/// The dmx channels of one universe that a controller port drives, as they
+/// would be set up on the controller. Slots are numbered from 1 to 512.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PortRange {
+    pub controller: String,
+    pub port: u32,
+    pub universe: u16,
+    pub start_slot: u32,
+    pub end_slot: u32,
+    pub channels: u32,
+}
+
+impl ControllerProtocol {
+    /// Port the protocol is sent to if the controller's address doesn't give one
+    pub fn default_port(&self) -> u16 {
+        match *self {
+            ControllerProtocol::E131 => E131_PORT,
+            ControllerProtocol::ArtNet => ARTNET_PORT,
+        }
+    }
+
+    /// Highest universe the protocol can address. Art-Net port-addresses are 15 bits.
+    pub fn max_universe(&self) -> u16 {
+        match *self {
+            ControllerProtocol::E131 => 63999,
+            ControllerProtocol::ArtNet => 32768,
+        }
+    }
+}
+
+impl FromStr for ControllerProtocol {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<ControllerProtocol, Error> {
+        match s {
+            "e131" => Ok(ControllerProtocol::E131),
+            "artnet" => Ok(ControllerProtocol::ArtNet),
+            _ => Err(Error::InvalidController(
+                format!("Unknown protocol '{}' (expected e131 or artnet)", s))),
+        }
+    }
+}
+
+impl fmt::Display for ControllerProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ControllerProtocol::E131 => write!(f, "e131"),
+            ControllerProtocol::ArtNet => write!(f, "artnet"),
+        }
+    }
+}
+
+impl Controller {
+    /// Creates a controller for a layout, checking its settings make sense.
+    /// Its controllerid is 0 until it's stored.
+    pub fn new(
+        layout_id: u32,
+        name: &str,
+        address: &str,
+        protocol: ControllerProtocol,
+        universes: Vec<u16>,
+        ports: u32,
+        port_channels: u32
+    ) -> Result<Controller, Error> {
+
+        if name.trim().is_empty() || name.len() > 64 {
+            return Err(Error::InvalidController("Names must be from 1 to 64 characters".to_owned()));
+        }
+        if output::parse_address(address, protocol.default_port()).is_err() {
+            return Err(Error::InvalidController(format!("Invalid address: {}", address)));
+        }
+        if universes.is_empty() {
+            return Err(Error::InvalidController("A controller needs at least one universe".to_owned()));
+        }
+        for (i, universe) in universes.iter().enumerate() {
+            if *universe == 0 || *universe > protocol.max_universe() || universes[..i].contains(universe) {
+                return Err(Error::InvalidController(format!(
+                    "Universes must be from 1 to {} and only given once: {}", protocol.max_universe(), universe)));
+            }
+        }
+        if ports == 0 || port_channels == 0 {
+            return Err(Error::InvalidController(
+                "A controller needs at least one port, and ports at least one channel".to_owned()));
+        }
+
+        Ok(Controller {
+            controllerid: 0,
+            layout_id: layout_id,
+            name: name.to_owned(),
+            address: address.to_owned(),
+            protocol: protocol,
+            universes: universes,
+            ports: ports,
+            port_channels: port_channels
+        })
+    }
+
+    /// Parses a list of universes like "1-4,7"
+    pub fn parse_universes(universes: &str) -> Result<Vec<u16>, Error> {
+        let mut parsed = Vec::new();
+        for part in universes.split(',').map(|part| part.trim()) {
+            let range = match part.find('-') {
+                Some(dash) => (part[..dash].trim().parse::<u16>(), part[dash + 1..].trim().parse::<u16>()),
+                None => (part.parse::<u16>(), part.parse::<u16>()),
+            };
+            match range {
+                (Ok(first), Ok(last)) if first <= last => {
+                    parsed.extend((first as u32..last as u32 + 1).map(|universe| universe as u16))
+                },
+                _ => return Err(Error::InvalidController(format!("Invalid universes: {}", part))),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Returns true if the controller is sent the universe
+    pub fn takes_universe(&self, universe: u16) -> bool {
+        self.universes.contains(&universe)
+    }
+}
+
+impl fmt::Display for Controller {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let universes = self.universes.iter()
+            .map(|universe| universe.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "{} (id {}): {} at {}, universe(s) {}, {} port(s) of {} channels",
+            self.name,
+            self.controllerid,
+            self.protocol,
+            self.address,
+            universes.join(","),
+            self.ports,
+            self.port_channels)
+    }
+}
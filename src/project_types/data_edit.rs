@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+
+use error::Error;
+
+
+/// Shape of a fade between two values
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FadeCurve {
+    Linear,
+    EaseIn, // Starts slow, ends fast
+    EaseOut, // Starts fast, ends slow
+    EaseInOut, // Slow at both ends
+}
+
+/// A change to make to channel data over a range of frames.
+/// Times are in milliseconds, and are converted to frames when applied.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DataEdit {
+    /// Sets every frame to a value
+    Set { value: u16 },
+    /// Fades from one value to another
+    Fade { from: u16, to: u16, curve: FadeCurve },
+    /// Turns the channels on one at a time, in order, for step_ms each
+    Chase { value: u16, step_ms: u32 },
+    /// Randomly turns each channel on or off every step_ms. Density is the
+    /// percent chance a channel is on, and the same seed gives the same twinkle.
+    Twinkle { value: u16, step_ms: u32, density: u32, seed: u32 },
+}
+
+/// Which channels of a sequence an edit applies to
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditTargets {
+    Channels(Vec<u32>), // Internal channel numbers
+    Fixtures(Vec<u32>), // Fixture ids
+}
+
+impl DataEdit {
+    /// Creates an edit from an effect name and its key=value params, e.g.
+    /// "fade" with ["from=0", "to=255", "curve=in"]. Missing params get defaults.
+    pub fn from_params(effect: &str, params: &Vec<String>) -> Result<DataEdit, Error> {
+        let mut values: BTreeMap<&str, &str> = BTreeMap::new();
+        for param in params {
+            let mut parts = param.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => { values.insert(key, value); },
+                _ => return Err(Error::InvalidDataEdit(
+                    format!("Params must be of the form key=value: {}", param))),
+            }
+        }
+
+        let known: &[&str] = match effect {
+            "set" => &["value"],
+            "fade" => &["from", "to", "curve"],
+            "chase" => &["value", "step"],
+            "twinkle" => &["value", "step", "density", "seed"],
+            _ => return Err(Error::InvalidDataEdit(format!("Unknown effect: {}", effect))),
+        };
+        for key in values.keys() {
+            if !known.contains(key) {
+                return Err(Error::InvalidDataEdit(format!("Unknown param for {}: {}", effect, key)));
+            }
+        }
+
+        let edit = match effect {
+            "set" => DataEdit::Set {
+                value: try!(DataEdit::get_param(&values, "value", 255))
+            },
+            "fade" => DataEdit::Fade {
+                from: try!(DataEdit::get_param(&values, "from", 0)),
+                to: try!(DataEdit::get_param(&values, "to", 255)),
+                curve: match values.get("curve").cloned().unwrap_or("linear") {
+                    "linear" => FadeCurve::Linear,
+                    "in" => FadeCurve::EaseIn,
+                    "out" => FadeCurve::EaseOut,
+                    "inout" => FadeCurve::EaseInOut,
+                    curve => return Err(Error::InvalidDataEdit(format!(
+                        "Unknown curve '{}' (expected linear, in, out, or inout)", curve))),
+                }
+            },
+            "chase" => DataEdit::Chase {
+                value: try!(DataEdit::get_param(&values, "value", 255)),
+                step_ms: try!(DataEdit::get_param(&values, "step", 250))
+            },
+            _ => DataEdit::Twinkle {
+                value: try!(DataEdit::get_param(&values, "value", 255)),
+                step_ms: try!(DataEdit::get_param(&values, "step", 100)),
+                density: try!(DataEdit::get_param(&values, "density", 30)),
+                seed: try!(DataEdit::get_param(&values, "seed", 1))
+            },
+        };
+
+        if let DataEdit::Twinkle { density, .. } = edit {
+            if density > 100 {
+                return Err(Error::InvalidDataEdit(format!("Density is a percent, not {}", density)));
+            }
+        }
+        Ok(edit)
+    }
+
+    /// Helper function for from_params() to parse a param, or use its default if missing
+    fn get_param<T: ::std::str::FromStr>(
+        values: &BTreeMap<&str, &str>,
+        key: &str,
+        default: T
+    ) -> Result<T, Error> {
+        match values.get(key) {
+            Some(value) => value.parse::<T>()
+                .map_err(|_| Error::InvalidDataEdit(format!("Invalid value for {}: {}", key, value))),
+            None => Ok(default),
+        }
+    }
+
+    /// Applies the edit to one channel's data, from start_frame up to (not including)
+    /// end_frame. The channel belongs to target number index of num_targets being
+    /// edited together (a channel or a fixture), which chases use to take turns.
+    pub fn apply(
+        &self,
+        data: &mut Vec<u16>,
+        start_frame: usize,
+        end_frame: usize,
+        index: usize,
+        num_targets: usize,
+        frame_duration_ms: u32
+    ) {
+        let end_frame = end_frame.min(data.len());
+        if start_frame >= end_frame {
+            return;
+        }
+        let num_frames = end_frame - start_frame;
+
+        // Steps are at least a frame long
+        let step_frames = |step_ms: u32| {
+            (((step_ms as f32 / frame_duration_ms as f32).round()) as usize).max(1)
+        };
+
+        match *self {
+            DataEdit::Set { value } => {
+                for frame in data[start_frame..end_frame].iter_mut() {
+                    *frame = value;
+                }
+            },
+            DataEdit::Fade { from, to, curve } => {
+                for (i, frame) in data[start_frame..end_frame].iter_mut().enumerate() {
+                    // Position in the fade, from 0 at the first frame to 1 at the last
+                    let p = if num_frames > 1 { i as f32 / (num_frames - 1) as f32 } else { 1.0 };
+                    let p = match curve {
+                        FadeCurve::Linear => p,
+                        FadeCurve::EaseIn => p * p,
+                        FadeCurve::EaseOut => 1.0 - (1.0 - p) * (1.0 - p),
+                        FadeCurve::EaseInOut => p * p * (3.0 - 2.0 * p),
+                    };
+                    *frame = (from as f32 + (to as f32 - from as f32) * p).round() as u16;
+                }
+            },
+            DataEdit::Chase { value, step_ms } => {
+                let step = step_frames(step_ms);
+                for (i, frame) in data[start_frame..end_frame].iter_mut().enumerate() {
+                    *frame = if (i / step) % num_targets.max(1) == index { value } else { 0 };
+                }
+            },
+            DataEdit::Twinkle { value, step_ms, density, seed } => {
+                let step = step_frames(step_ms);
+                // Each target gets its own xorshift sequence, so targets twinkle independently
+                let mut state = seed.wrapping_mul(2654435761) ^ ((index as u32 + 1).wrapping_mul(40503));
+                if state == 0 {
+                    state = 1;
+                }
+                let mut on = false;
+                for (i, frame) in data[start_frame..end_frame].iter_mut().enumerate() {
+                    if i % step == 0 {
+                        state ^= state << 13;
+                        state ^= state >> 17;
+                        state ^= state << 5;
+                        on = state % 100 < density;
+                    }
+                    *frame = if on { value } else { 0 };
+                }
+            },
+        }
+    }
+}
+
+impl EditTargets {
+    /// Parses a list of ids and ranges, like "1-4,7", into targets. No range
+    /// can cover more than max_range ids, e.g. the number of channels in a layout.
+    pub fn parse(ids: &str, fixtures: bool, max_range: u32) -> Result<EditTargets, Error> {
+        let mut parsed = Vec::new();
+        for part in ids.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let invalid = || Error::InvalidDataEdit(format!("Invalid id or range: {}", part));
+            let mut bounds = part.splitn(2, '-');
+            let low = try!(bounds.next().unwrap_or("").trim().parse::<u32>().map_err(|_| invalid()));
+            let high = match bounds.next() {
+                Some(high) => try!(high.trim().parse::<u32>().map_err(|_| invalid())),
+                None => low,
+            };
+            if high < low {
+                return Err(invalid());
+            }
+            let size = high as u64 - low as u64 + 1;
+            if size > 1 && size > max_range as u64 {
+                return Err(Error::InvalidDataEdit(
+                    format!("Range {} covers more than the {} there are", part, max_range)));
+            }
+            parsed.extend((low as u64..high as u64 + 1).map(|id| id as u32));
+        }
+        if parsed.is_empty() {
+            return Err(Error::InvalidDataEdit("No channels or fixtures given".to_owned()));
+        }
+
+        Ok(if fixtures { EditTargets::Fixtures(parsed) } else { EditTargets::Channels(parsed) })
+    }
+}
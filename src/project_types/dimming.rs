@@ -0,0 +1,120 @@
+use std::fmt;
+use std::str::FromStr;
+
+use effects::MAX_VALUE;
+use error::Error;
+
+
+/// How a channel's value is mapped to the value sent to its lights
+#[derive(Clone, Debug, PartialEq)]
+pub enum DimmingCurve {
+    Linear, // Sent as is, e.g. for incandescent strings
+    Square, // Low values are dimmer, so LEDs fade evenly to the eye
+    Table(Vec<u16>), // Values spread evenly from 0 to 255, blended between
+}
+
+/// The curve and limits applied to a channel's data as it's played or exported.
+/// Output is clamped between min and max, so a circuit never goes above max.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DimmingProfile {
+    pub curve: DimmingCurve,
+    pub min: u16,
+    pub max: u16,
+}
+
+impl DimmingCurve {
+    /// Maps a value from 0 to 255 through the curve
+    pub fn apply(&self, value: u16) -> u16 {
+        let value = value.min(MAX_VALUE);
+        match *self {
+            DimmingCurve::Linear => value,
+            DimmingCurve::Square => {
+                (value as f32 * value as f32 / MAX_VALUE as f32).round() as u16
+            },
+            DimmingCurve::Table(ref points) => {
+                let position = value as f32 * (points.len() - 1) as f32 / MAX_VALUE as f32;
+                let index = position.floor() as usize;
+                let from = points[index] as f32;
+                let to = *points.get(index + 1).unwrap_or(&points[index]) as f32;
+                (from + (to - from) * (position - index as f32)).round() as u16
+            },
+        }
+    }
+}
+
+impl FromStr for DimmingCurve {
+    type Err = Error;
+
+    /// Parses "linear", "square", or a table like "table:0,10,40,255"
+    fn from_str(s: &str) -> Result<DimmingCurve, Error> {
+        match s {
+            "linear" => return Ok(DimmingCurve::Linear),
+            "square" => return Ok(DimmingCurve::Square),
+            _ => (),
+        }
+        if !s.starts_with("table:") {
+            return Err(Error::InvalidDimming(
+                format!("Unknown curve '{}' (expected linear, square, or table:<values>)", s)));
+        }
+        let mut points = Vec::new();
+        for point in s["table:".len()..].split(',') {
+            match point.trim().parse::<u16>() {
+                Ok(value) if value <= MAX_VALUE => points.push(value),
+                _ => return Err(Error::InvalidDimming(
+                    format!("Table values must be from 0 to {}: {}", MAX_VALUE, point))),
+            }
+        }
+        if points.len() < 2 {
+            return Err(Error::InvalidDimming("A table needs at least 2 values".to_owned()));
+        }
+        Ok(DimmingCurve::Table(points))
+    }
+}
+
+impl fmt::Display for DimmingCurve {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DimmingCurve::Linear => write!(f, "linear"),
+            DimmingCurve::Square => write!(f, "square"),
+            DimmingCurve::Table(ref points) => {
+                let points = points.iter()
+                    .map(|point| point.to_string())
+                    .collect::<Vec<String>>();
+                write!(f, "table:{}", points.join(","))
+            },
+        }
+    }
+}
+
+impl DimmingProfile {
+    /// Creates a profile, checking its limits are in order
+    pub fn new(curve: DimmingCurve, min: u16, max: u16) -> Result<DimmingProfile, Error> {
+        if max > MAX_VALUE || min > max {
+            return Err(Error::InvalidDimming(
+                format!("Limits must be 0 <= min <= max <= {}, not {}-{}", MAX_VALUE, min, max)));
+        }
+        Ok(DimmingProfile {
+            curve: curve,
+            min: min,
+            max: max
+        })
+    }
+
+    /// Value sent to the lights for a value of the channel's data
+    pub fn apply(&self, value: u16) -> u16 {
+        self.curve.apply(value).max(self.min).min(self.max)
+    }
+
+    /// Applies the profile to every frame of a channel's data
+    pub fn apply_all(&self, data: &[u16]) -> Vec<u16> {
+        data.iter()
+            .map(|value| self.apply(*value))
+            .collect()
+    }
+}
+
+impl fmt::Display for DimmingProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}-{}", self.curve, self.min, self.max)
+    }
+}
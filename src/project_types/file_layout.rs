@@ -1,17 +1,84 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+
+use rustc_serialize::json::{Json, Object};
 
 use dao::{ChannelDao, FixtureDao};
 use error::Error;
-use project_types::{Channel, Fixture};
+use project_types::{Channel, ChannelPlacement, FileTable, Fixture, LayoutIssue};
+
+
+/// Fields of a layout row that are read into a FileLayoutRow
+const ROW_FIELDS: [&'static str; 13] = [
+    "internalChannel",
+    "dmxChannel",
+    "fixtureName",
+    "channelName",
+    "color",
+    "num_primary",
+    "num_secondary",
+    "location",
+    "rotation",
+    "width",
+    "height",
+    "fixtureType",
+    "fixtureSize",
+];
+
+/// Fields of a layout row that hold triples, which are "0,0,0" if left blank in a table
+const TRIPLE_FIELDS: [&'static str; 2] = ["location", "rotation"];
+
+/// Fields of a layout row that hold numbers rather than text
+const NUMBER_FIELDS: [&'static str; 4] = ["internalChannel", "dmxChannel", "num_primary", "num_secondary"];
+
+/// Color tokens that stand for a single color of light
+const COLOR_TOKENS: [&'static str; 6] = ["R", "G", "B", "W", "Ww", "Cw"];
+
+/// Fixture and channel names used for channels that aren't hooked up to anything
+const SPARE_NAMES: [&'static str; 2] = ["Spare", "X"];
+
+/// Name of the fixture spare channels are put in when they are reserved
+const SPARE_FIXTURE_NAME: &'static str = "Spare";
+
+/// What to do with spare channels when creating a layout.
+/// Either way the channels are created, so their DMX addresses stay reserved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SparePolicy {
+    Reserve, // All spare channels go in one fixture named "Spare"
+    Fixture, // Spare channels are grouped by fixtureName like any other channel
+}
+
+impl FromStr for SparePolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<SparePolicy, Error> {
+        match s {
+            "reserve" => Ok(SparePolicy::Reserve),
+            "fixture" => Ok(SparePolicy::Fixture),
+            _ => Err(Error::InvalidSparePolicy(s.to_owned())),
+        }
+    }
+}
 
 /// Mapping for a layout JSON object
 #[derive(Debug, RustcDecodable)]
 #[allow(non_snake_case)]
 pub struct FileLayout {
     pub layoutName: String,
+    pub fixtures: Option<Vec<FileFixture>>, // Default is no fixture definitions
     pub channels: Vec<FileLayoutRow>,
 }
 
+/// Mapping for a fixture definition in a JSON layout. Gives the fixture's type and
+/// the colors of the channels that make up each of its elements.
+#[derive(Debug, RustcDecodable)]
+#[allow(non_snake_case)]
+pub struct FileFixture {
+    pub fixtureName: String,
+    pub fixtureType: String, // e.g. "RGB pixel", "Single color string"
+    pub roles: Vec<String>, // Colors of each element's channels, e.g. ["R", "G", "B"]
+}
+
 /// Mapping for a row (channel) in a JSON layout
 #[derive(Debug, RustcDecodable)]
 #[allow(non_snake_case)]
@@ -25,11 +92,15 @@ pub struct FileLayoutRow {
     pub num_secondary: Option<u32>, // Default is 0
     pub location: String, // Default is "0,0,0"
     pub rotation: String, // Default is "0,0,0"
+    pub width: Option<String>, // Default is ""
+    pub height: Option<String>, // Default is ""
+    pub fixtureType: Option<String>, // Type of the row's fixture if it has no definition
+    pub fixtureSize: Option<String>, // "w,h,d" of the row's fixture, instead of working it out
 }
 
 impl FileLayout {
 
-    /// Helper function to convert a string to i32, making blank strings map to None 
+    /// Helper function to convert a string to i32, making blank strings map to None
     /// and return an Error if the conversion fails.
     fn layout_str_to_i32<'a>(s: &'a str, err_msg: &'a str) -> Result<Option<i32>, Error> {
         match s.is_empty() {
@@ -53,6 +124,56 @@ impl FileLayout {
         Ok((x,y,z))
     }
 
+    /// Helper function to parse a fixture size of the form "w,h,d", where w, h and d are integers
+    fn parse_size(s: &str) -> Result<(i32, i32, i32), Error> {
+        match try!(FileLayout::layout_get_i32_tuple(s)) {
+            (Some(width), Some(height), Some(depth)) => Ok((width, height, depth)),
+            _ => Err(Error::InvalidLayout(String::from("Fixture sizes must be of the form w,h,d"))),
+        }
+    }
+
+    /// Helper function to check that a name is not too long and only has alphanumerics or spaces
+    fn check_text(what: &str, text: &str, max_len: usize) -> Result<(), String> {
+        if text.len() > max_len {
+            return Err(format!("{} cannot be longer than {} characters", what, max_len));
+        }
+        if !text.chars().all(|c| c.is_alphanumeric() || c == ' ') {
+            return Err(format!("{} has to be alphanumeric: {}", what, text));
+        }
+        Ok(())
+    }
+
+    /// Converts a CSV/TSV layout into the same JSON a layout file would have, so it
+    /// can be linted and decoded the same way. Each column in the header is a row
+    /// field, except layoutName. Blank cells in number columns are left out, and
+    /// cells that aren't numbers are kept as text for lint() to report.
+    pub fn table_to_json(table: &FileTable, layout_name: &str) -> Json {
+        let channels = table.rows.iter()
+            .map(|row| {
+                let mut channel = Object::new();
+                for (column, cell) in table.header.iter().zip(row.cells.iter()) {
+                    if column == "layoutName" {
+                        continue;
+                    } else if TRIPLE_FIELDS.contains(&column.as_str()) && cell.is_empty() {
+                        channel.insert(column.to_owned(), Json::String("0,0,0".to_owned()));
+                    } else if !NUMBER_FIELDS.contains(&column.as_str()) {
+                        channel.insert(column.to_owned(), Json::String(cell.to_owned()));
+                    } else if let Ok(num) = cell.parse::<u64>() {
+                        channel.insert(column.to_owned(), Json::U64(num));
+                    } else if !cell.is_empty() {
+                        channel.insert(column.to_owned(), Json::String(cell.to_owned()));
+                    }
+                }
+                Json::Object(channel)
+            })
+            .collect::<Vec<Json>>();
+
+        let mut layout = Object::new();
+        layout.insert("layoutName".to_owned(), Json::String(layout_name.to_owned()));
+        layout.insert("channels".to_owned(), Json::Array(channels));
+        Json::Object(layout)
+    }
+
     /// Check that all channels in the layout are valid
     pub fn validate(&self) -> Result<(), Error> {
 
@@ -82,76 +203,553 @@ impl FileLayout {
             // Validate rotations and each piece
             let _ = try!(FileLayout::layout_get_i32_tuple(&channel.rotation));
 
-            // Validate channel name not too long and only alphanumerics or spaces
-            if channel.channelName.len() > 40 {
-                return Err(Error::InvalidLayout(String::from("Channel name cannot be longer than 40 characters")));
+            // Validate width and height, and the fixture's type and size if given
+            let _ = try!(channel.get_size());
+            let _ = try!(channel.get_fixture_size());
+            if let Some(ref fixture_type) = channel.fixtureType {
+                try!(FileLayout::check_text("Fixture type", fixture_type, 40)
+                    .map_err(Error::InvalidLayout));
+            }
+
+            // Validate names and color not too long and only alphanumerics or spaces
+            try!(FileLayout::check_text("Channel name", &channel.channelName, 40)
+                .map_err(Error::InvalidLayout));
+            try!(FileLayout::check_text("Fixture name", &channel.fixtureName, 40)
+                .map_err(Error::InvalidLayout));
+            try!(FileLayout::check_text("Color", &channel.color, 16)
+                .map_err(Error::InvalidLayout));
+        }
+
+        // Validate fixture definitions against the channels in each fixture
+        if let Some(ref definitions) = self.fixtures {
+            let mut defined = HashSet::new();
+            for definition in definitions {
+                try!(FileLayout::check_text("Fixture name", &definition.fixtureName, 40)
+                    .map_err(Error::InvalidLayout));
+                try!(FileLayout::check_text("Fixture type", &definition.fixtureType, 40)
+                    .map_err(Error::InvalidLayout));
+                if !defined.insert(definition.fixtureName.as_str()) {
+                    return Err(Error::InvalidLayout(
+                        format!("Fixture '{}' is defined more than once", definition.fixtureName)));
+                }
+                if definition.roles.is_empty() {
+                    return Err(Error::InvalidLayout(
+                        format!("Fixture '{}' has no channel roles", definition.fixtureName)));
+                }
+
+                let elements = self.get_fixture_elements(&definition.fixtureName);
+                if elements.is_empty() {
+                    return Err(Error::InvalidLayout(
+                        format!("Fixture '{}' is defined but has no channels", definition.fixtureName)));
+                }
+
+                // Every element has to have exactly one channel per role
+                let mut roles = definition.roles.clone();
+                roles.sort();
+                for (&(channel_name, _, _), colors) in elements.iter() {
+                    if *colors != roles {
+                        return Err(Error::InvalidLayout(format!(
+                            "Fixture '{}' element '{}' has colors ({}), but type '{}' has roles ({})",
+                            definition.fixtureName, channel_name, colors.join(","),
+                            definition.fixtureType, roles.join(","))));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the definition given for a fixture, if any
+    pub fn get_fixture_definition(&self, fixture_name: &str) -> Option<&FileFixture> {
+        match self.fixtures {
+            Some(ref definitions) => definitions.iter()
+                .find(|definition| definition.fixtureName == fixture_name),
+            None => None,
+        }
+    }
+
+    /// Helper function to group a fixture's channels by element.
+    /// Returns (channel name, primary, secondary) -> sorted colors of the element's channels
+    fn get_fixture_elements(&self, fixture_name: &str) -> BTreeMap<(&str, u32, u32), Vec<String>> {
+        let mut elements: BTreeMap<(&str, u32, u32), Vec<String>> = BTreeMap::new();
+        for channel in self.channels.iter().filter(|channel| channel.fixtureName == fixture_name) {
+            elements.entry((
+                    &channel.channelName,
+                    channel.num_primary.unwrap_or(0),
+                    channel.num_secondary.unwrap_or(0)))
+                .or_insert_with(Vec::new)
+                .push(channel.color.to_owned());
+        }
+        for colors in elements.values_mut() {
+            colors.sort();
+        }
+        elements
+    }
+
+    /// Checks a layout's JSON for every problem at once, instead of stopping at the
+    /// first one like validate(). Works on the raw JSON so that rows which wouldn't
+    /// decode, and fields the decoder would ignore, can be reported too.
+    pub fn lint(layout_json: &Json) -> Vec<LayoutIssue> {
+        let mut issues = Vec::new();
+
+        let layout = match layout_json.as_object() {
+            Some(layout) => layout,
+            None => {
+                issues.push(LayoutIssue::error(None, "Layout must be a JSON object".to_owned()));
+                return issues;
+            }
+        };
+
+        // Validate layout name not too long and only alphanumerics
+        match layout.get("layoutName").and_then(|name| name.as_string()) {
+            Some(name) => {
+                if name.len() > 64 {
+                    issues.push(LayoutIssue::error(None,
+                        "Layout name cannot be longer than 64 characters".to_owned()));
+                }
+                if !name.chars().all(char::is_alphanumeric) {
+                    issues.push(LayoutIssue::error(None,
+                        format!("Layout name has to be alphanumeric: {}", name)));
+                }
+            },
+            None => issues.push(LayoutIssue::error(None, "Missing layoutName".to_owned())),
+        }
+
+        // Fixture name -> (type, sorted roles) of each fixture definition
+        let definitions = FileLayout::lint_fixture_definitions(&mut issues, layout);
+
+        let rows = match layout.get("channels").and_then(|channels| channels.as_array()) {
+            Some(rows) => rows,
+            None => {
+                issues.push(LayoutIssue::error(None, "Missing channels list".to_owned()));
+                return issues;
+            }
+        };
+
+        // First row each channel number was seen on
+        let mut internal_rows: BTreeMap<u32, usize> = BTreeMap::new();
+        let mut dmx_rows: BTreeMap<u32, usize> = BTreeMap::new();
+
+        // Fixture name -> (channel name, primary, secondary) -> (row, color) of each channel
+        let mut fixtures: BTreeMap<String, BTreeMap<(String, u64, u64), Vec<(usize, String)>>> = BTreeMap::new();
+
+        // (Fixture name, fixtureType or fixtureSize) -> (first row, value) given for the fixture
+        let mut fixture_values: BTreeMap<(String, &str), (usize, String)> = BTreeMap::new();
+
+        for (i, row_json) in rows.iter().enumerate() {
+            let row = i + 1;
+            let channel = match row_json.as_object() {
+                Some(channel) => channel,
+                None => {
+                    issues.push(LayoutIssue::error(Some(row), "Row must be a JSON object".to_owned()));
+                    continue;
+                }
+            };
+
+            // Fields the decoder doesn't know about are silently dropped
+            for field in channel.keys() {
+                if !ROW_FIELDS.contains(&field.as_str()) {
+                    issues.push(LayoutIssue::warning(Some(row),
+                        format!("Unknown field '{}' is ignored", field)));
+                }
+            }
+
+            // Channel numbers start at 1 and can only be used once
+            FileLayout::lint_channel_number(&mut issues, row, channel, "internalChannel",
+                "Internal channel", &mut internal_rows);
+            FileLayout::lint_channel_number(&mut issues, row, channel, "dmxChannel",
+                "DMX channel", &mut dmx_rows);
+
+            let primary = FileLayout::lint_u64(&mut issues, row, channel, "num_primary", false);
+            let secondary = FileLayout::lint_u64(&mut issues, row, channel, "num_secondary", false);
+
+            let fixture_name = FileLayout::lint_text(&mut issues, row, channel, "fixtureName", "Fixture name", 40);
+            let channel_name = FileLayout::lint_text(&mut issues, row, channel, "channelName", "Channel name", 40);
+            let color = FileLayout::lint_text(&mut issues, row, channel, "color", "Color", 16);
+
+            // Colors should be something an effect knows how to drive
+            match color {
+                Some(ref color) if color.is_empty() => issues.push(LayoutIssue::warning(Some(row),
+                    "No color given".to_owned())),
+                Some(ref color) if !COLOR_TOKENS.contains(&color.as_str()) => {
+                    issues.push(LayoutIssue::warning(Some(row),
+                        format!("Color '{}' is not an RGB or white color token", color)))
+                },
+                _ => {},
             }
-            if !channel.channelName.chars().all(|c| c.is_alphanumeric() || c == ' ') {
-                return Err(Error::InvalidLayout(String::from("Channel name has to be alphanumeric: ") + &channel.channelName));
+
+            // Locations and rotations must be of the form x,y,z
+            for &field in ["location", "rotation"].iter() {
+                match channel.get(field) {
+                    Some(&Json::String(ref triple)) => {
+                        if let Err(e) = FileLayout::layout_get_i32_tuple(triple) {
+                            issues.push(LayoutIssue::error(Some(row),
+                                format!("Can't parse {} '{}': {}", field, triple, e)));
+                        }
+                    },
+                    Some(_) => issues.push(LayoutIssue::error(Some(row),
+                        format!("Field '{}' must be a string", field))),
+                    None => issues.push(LayoutIssue::error(Some(row),
+                        format!("Missing field '{}'", field))),
+                }
             }
 
-            // Validate name not too long and only alphanumerics or spaces
-            if channel.fixtureName.len() > 40 {
-                return Err(Error::InvalidLayout(String::from("Fixture name cannot be longer than 40 characters")))
+            // Widths and heights are integers or blank
+            for &field in ["width", "height"].iter() {
+                match channel.get(field) {
+                    Some(&Json::String(ref size)) => {
+                        if FileLayout::layout_str_to_i32(size, "").is_err() {
+                            issues.push(LayoutIssue::error(Some(row),
+                                format!("Can't parse {} '{}': not an i32", field, size)));
+                        }
+                    },
+                    Some(_) => issues.push(LayoutIssue::error(Some(row),
+                        format!("Field '{}' must be a string", field))),
+                    None => {},
+                }
             }
-            if !channel.fixtureName.chars().all(|c| c.is_alphanumeric() || c == ' ') {
-                return Err(Error::InvalidLayout(String::from("Fixture name has to be alphanumeric: ") + &channel.fixtureName))   
+
+            // Fixture types and sizes are text, and sizes are full triples
+            for &field in ["fixtureType", "fixtureSize"].iter() {
+                let text = match channel.get(field) {
+                    Some(&Json::String(ref text)) if !text.is_empty() => text,
+                    Some(&Json::String(_)) | None => continue,
+                    Some(_) => {
+                        issues.push(LayoutIssue::error(Some(row), format!("Field '{}' must be a string", field)));
+                        continue;
+                    },
+                };
+                let result = if field == "fixtureType" {
+                    FileLayout::check_text("Fixture type", text, 40)
+                } else {
+                    FileLayout::parse_size(text).map(|_| ()).map_err(|e| e.to_string())
+                };
+                if let Err(msg) = result {
+                    issues.push(LayoutIssue::error(Some(row), format!("Can't use {} '{}': {}", field, text, msg)));
+                }
+                if let Some(ref fixture_name) = fixture_name {
+                    match fixture_values.get(&(fixture_name.to_owned(), field)) {
+                        Some(&(first_row, ref first)) if first != text => {
+                            issues.push(LayoutIssue::error(Some(row), format!(
+                                "Fixture '{}' has {} '{}', but row {} gave '{}'",
+                                fixture_name, field, text, first_row, first)));
+                        },
+                        Some(_) => {},
+                        None => {
+                            fixture_values.insert((fixture_name.to_owned(), field), (row, text.to_owned()));
+                        },
+                    }
+                }
             }
 
-            // Validate color not too long and only alphanumerics or spaces
-            if channel.color.len() > 16 {
-                return Err(Error::InvalidLayout(String::from("Color cannot be longer than 16 characters")))
+            // Group channels by fixture and element to check consistency afterwards
+            if let (Some(fixture_name), Some(channel_name), Some(color)) = (fixture_name, channel_name, color) {
+                fixtures.entry(fixture_name)
+                    .or_insert_with(BTreeMap::new)
+                    .entry((channel_name, primary.unwrap_or(0), secondary.unwrap_or(0)))
+                    .or_insert_with(Vec::new)
+                    .push((row, color));
             }
-            if !channel.color.chars().all(|c| c.is_alphanumeric() || c == ' ') {
-                return Err(Error::InvalidLayout(String::from("Color has to be alphanumeric")))   
+        }
+
+        // Report unused channel numbers between the lowest and highest used
+        FileLayout::lint_gaps(&mut issues, "Internal channel", &internal_rows);
+        FileLayout::lint_gaps(&mut issues, "DMX channel", &dmx_rows);
+
+        // Defined fixtures have to have channels
+        for fixture_name in definitions.keys() {
+            if !fixtures.contains_key(fixture_name) {
+                issues.push(LayoutIssue::error(None,
+                    format!("Fixture '{}' is defined but has no channels", fixture_name)));
+            }
+        }
+
+        // Every element of a fixture should have the same colors
+        for (fixture_name, elements) in fixtures.iter() {
+            let definition = definitions.get(fixture_name);
+            if definition.is_none() && SPARE_NAMES.contains(&fixture_name.as_str()) {
+                continue;
+            }
+
+            let colors_of = |channels: &Vec<(usize, String)>| {
+                let mut colors = channels.iter()
+                    .map(|&(_, ref color)| color.to_owned())
+                    .collect::<Vec<String>>();
+                colors.sort();
+                colors.join(",")
+            };
+
+            // A defined fixture's elements have to match its roles
+            if let Some(&(ref fixture_type, ref roles)) = definition {
+                for (&(ref channel_name, _, _), channels) in elements.iter() {
+                    let colors = colors_of(channels);
+                    if colors != *roles {
+                        issues.push(LayoutIssue::error(Some(channels[0].0), format!(
+                            "Fixture '{}' element '{}' has colors ({}), but type '{}' has roles ({})",
+                            fixture_name, channel_name, colors, fixture_type, roles)));
+                    }
+                }
+                continue;
+            }
+
+            // Otherwise the most common color set is taken as what the fixture should have
+            let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+            for channels in elements.values() {
+                *counts.entry(colors_of(channels)).or_insert(0) += 1;
+            }
+            let expected = match counts.iter().max_by_key(|&(_, count)| *count) {
+                Some((colors, _)) => colors.to_owned(),
+                None => continue,
+            };
+
+            for (&(ref channel_name, _, _), channels) in elements.iter() {
+                let colors = colors_of(channels);
+                if colors != expected {
+                    issues.push(LayoutIssue::warning(Some(channels[0].0), format!(
+                        "Fixture '{}' element '{}' has {} channel(s) ({}), but most of its elements have ({})",
+                        fixture_name, channel_name, channels.len(), colors, expected)));
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Helper function for lint() to check the optional fixtures section.
+    /// Returns fixture name -> (type, sorted and joined roles) for each usable definition
+    fn lint_fixture_definitions(
+        issues: &mut Vec<LayoutIssue>,
+        layout: &Object
+    ) -> BTreeMap<String, (String, String)> {
+        let mut definitions = BTreeMap::new();
+        let fixtures = match layout.get("fixtures") {
+            Some(&Json::Array(ref fixtures)) => fixtures,
+            Some(_) => {
+                issues.push(LayoutIssue::error(None, "Field 'fixtures' must be a list".to_owned()));
+                return definitions;
+            },
+            None => return definitions,
+        };
+
+        for (i, fixture_json) in fixtures.iter().enumerate() {
+            let what = format!("Fixture definition {}", i + 1);
+            let fixture = match fixture_json.as_object() {
+                Some(fixture) => fixture,
+                None => {
+                    issues.push(LayoutIssue::error(None, format!("{} must be a JSON object", what)));
+                    continue;
+                }
+            };
+
+            let mut texts = Vec::new();
+            for &(field, max_len) in [("fixtureName", 40), ("fixtureType", 40)].iter() {
+                match fixture.get(field) {
+                    Some(&Json::String(ref text)) => {
+                        if let Err(msg) = FileLayout::check_text(field, text, max_len) {
+                            issues.push(LayoutIssue::error(None, format!("{}: {}", what, msg)));
+                        }
+                        texts.push(text.to_owned());
+                    },
+                    Some(_) => issues.push(LayoutIssue::error(None,
+                        format!("{}: field '{}' must be a string", what, field))),
+                    None => issues.push(LayoutIssue::error(None,
+                        format!("{}: missing field '{}'", what, field))),
+                }
+            }
+
+            let roles = match fixture.get("roles").and_then(|roles| roles.as_array()) {
+                Some(roles) => roles.iter().filter_map(|role| role.as_string()).collect::<Vec<&str>>(),
+                None => {
+                    issues.push(LayoutIssue::error(None, format!("{}: missing roles list", what)));
+                    continue;
+                }
+            };
+            if roles.is_empty() {
+                issues.push(LayoutIssue::error(None, format!("{}: has no channel roles", what)));
+                continue;
+            }
+
+            if texts.len() == 2 {
+                let mut roles = roles.iter().map(|role| role.to_string()).collect::<Vec<String>>();
+                roles.sort();
+                let (fixture_name, fixture_type) = (texts[0].to_owned(), texts[1].to_owned());
+                if definitions.contains_key(&fixture_name) {
+                    issues.push(LayoutIssue::error(None,
+                        format!("Fixture '{}' is defined more than once", fixture_name)));
+                }
+                definitions.insert(fixture_name, (fixture_type, roles.join(",")));
+            }
+        }
+        definitions
+    }
+
+    /// Helper function for lint() to read an unsigned integer field from a row
+    fn lint_u64(
+        issues: &mut Vec<LayoutIssue>,
+        row: usize,
+        channel: &Object,
+        field: &str,
+        required: bool
+    ) -> Option<u64> {
+        match channel.get(field) {
+            Some(value) => match value.as_u64() {
+                Some(num) => Some(num),
+                None => {
+                    issues.push(LayoutIssue::error(Some(row),
+                        format!("Field '{}' must be a whole number", field)));
+                    None
+                }
+            },
+            None => {
+                if required {
+                    issues.push(LayoutIssue::error(Some(row), format!("Missing field '{}'", field)));
+                }
+                None
+            },
+        }
+    }
+
+    /// Helper function for lint() to check a row's channel number against those already seen
+    fn lint_channel_number(
+        issues: &mut Vec<LayoutIssue>,
+        row: usize,
+        channel: &Object,
+        field: &str,
+        what: &str,
+        seen: &mut BTreeMap<u32, usize>
+    ) {
+        match FileLayout::lint_u64(issues, row, channel, field, true) {
+            Some(0) => issues.push(LayoutIssue::error(Some(row),
+                format!("{}s start at 1, not 0", what))),
+            Some(num) => {
+                let num = num as u32;
+                if let Some(first_row) = seen.get(&num) {
+                    issues.push(LayoutIssue::error(Some(row),
+                        format!("{} {} is already used on row {}", what, num, first_row)));
+                }
+                seen.entry(num).or_insert(row);
+            },
+            None => {},
+        }
+    }
+
+    /// Helper function for lint() to read and check a text field from a row
+    fn lint_text(
+        issues: &mut Vec<LayoutIssue>,
+        row: usize,
+        channel: &Object,
+        field: &str,
+        what: &str,
+        max_len: usize
+    ) -> Option<String> {
+        match channel.get(field) {
+            Some(&Json::String(ref text)) => {
+                if let Err(msg) = FileLayout::check_text(what, text, max_len) {
+                    issues.push(LayoutIssue::error(Some(row), msg));
+                }
+                Some(text.to_owned())
+            },
+            Some(_) => {
+                issues.push(LayoutIssue::error(Some(row), format!("Field '{}' must be a string", field)));
+                None
+            },
+            None => {
+                issues.push(LayoutIssue::error(Some(row), format!("Missing field '{}'", field)));
+                None
+            },
+        }
+    }
+
+    /// Helper function for lint() to report ranges of unused channel numbers
+    fn lint_gaps(issues: &mut Vec<LayoutIssue>, what: &str, used: &BTreeMap<u32, usize>) {
+        let numbers = used.keys().cloned().collect::<Vec<u32>>();
+        for pair in numbers.windows(2) {
+            let (low, high) = (pair[0] + 1, pair[1] - 1);
+            if low == high {
+                issues.push(LayoutIssue::warning(None, format!("{} {} is unused", what, low)));
+            } else if low < high {
+                issues.push(LayoutIssue::warning(None, format!("{}s {}-{} are unused", what, low, high)));
             }
         }
-        Ok(())
     }
 
-    /// Creates new channels and fixtures based on this FileLayout's data. Part of 
-    /// this process is grouping the channels into fixtures.
+    /// Creates new channels and fixtures based on this FileLayout's data. Part of
+    /// this process is grouping the channels into fixtures by fixtureName. Spare
+    /// channels are still created, so their DMX addresses stay reserved, and are
+    /// grouped according to spare_policy.
     pub fn create_new_parts<CD: ChannelDao, FD: FixtureDao>(
         &self,
         chan_dao: &CD,
-        fix_dao: &FD
+        fix_dao: &FD,
+        spare_policy: SparePolicy
     ) -> Result<(Vec<Channel>, Vec<Fixture>), Error> {
     
         let mut channels = Vec::new();
-        let mut fixture_names = HashMap::new();
+        // Fixture names in the order they first show up, and each one's channel ids and placements
+        let mut fixture_order: Vec<String> = Vec::new();
+        let mut fixture_names: HashMap<String, (Vec<u32>, Vec<ChannelPlacement>)> = HashMap::new();
+        // Types and sizes rows gave their fixtures, first one wins
+        let mut given_types: HashMap<String, &str> = HashMap::new();
+        let mut given_sizes: HashMap<String, (i32, i32, i32)> = HashMap::new();
+
         // Create channels and add to vec. Place ids in fixture buckets
-        // Ignore channels with name of "Spare" or "X"
         for c in &self.channels {
-            if c.channelName != "Spare" && c.channelName != "X" {
-                let location = try!(FileLayout::layout_get_i32_tuple(&c.location));
-                let rotation = try!(FileLayout::layout_get_i32_tuple(&c.rotation));
-                let channel = try!(chan_dao.new_channel(
-                    &c.channelName,
-                    c.num_primary,
-                    c.num_secondary,
-                    &c.color,
-                    c.internalChannel,
-                    c.dmxChannel,
-                    location,
-                    rotation));
-                let fix_name = channel.name.clone();
-                if !fixture_names.contains_key(&fix_name) {
-                    fixture_names.insert(fix_name, vec![channel.chanid]);
-                } else {
-                    fixture_names.get_mut(&fix_name).unwrap().push(channel.chanid);
-                }
-                channels.push(channel);
+            let location = try!(FileLayout::layout_get_i32_tuple(&c.location));
+            let rotation = try!(FileLayout::layout_get_i32_tuple(&c.rotation));
+            let (width, height) = try!(c.get_size());
+            let channel = try!(chan_dao.new_channel(
+                &c.channelName,
+                c.num_primary,
+                c.num_secondary,
+                &c.color,
+                c.internalChannel,
+                c.dmxChannel,
+                location,
+                rotation));
+            let fix_name = if c.is_spare() && spare_policy == SparePolicy::Reserve {
+                SPARE_FIXTURE_NAME.to_owned()
+            } else {
+                c.fixtureName.clone()
+            };
+            let placement = ChannelPlacement {
+                location: location,
+                rotation: rotation,
+                width: width,
+                height: height
+            };
+            if !fixture_names.contains_key(&fix_name) {
+                fixture_order.push(fix_name.clone());
+            }
+            match c.fixtureType {
+                Some(ref fixture_type) if !fixture_type.is_empty() => {
+                    given_types.entry(fix_name.clone()).or_insert(fixture_type);
+                },
+                _ => {},
             }
+            if let Some(size) = try!(c.get_fixture_size()) {
+                given_sizes.entry(fix_name.clone()).or_insert(size);
+            }
+            let fixture_parts = fixture_names.entry(fix_name).or_insert((Vec::new(), Vec::new()));
+            fixture_parts.0.push(channel.chanid);
+            fixture_parts.1.push(placement);
+            channels.push(channel);
         }
 
-        // Create fixtures
+        // Create fixtures, placed around their channels
         let mut fixtures = Vec::new();
-        for (fix_name, fix_chan_ids) in &fixture_names {
-            // TODO: Calculate center and width/height of fixture
+        for fix_name in &fixture_order {
+            let (ref fix_chan_ids, ref placements) = fixture_names[fix_name];
+            let (location, rotation, size) = Fixture::geometry(placements);
+            let size = given_sizes.get(fix_name).cloned().unwrap_or(size);
+            let fixture_type = self.get_fixture_definition(fix_name)
+                .map(|definition| definition.fixtureType.as_str())
+                .or(given_types.get(fix_name).cloned());
             let fixture = try!(fix_dao.new_fixture(
                 fix_name,
-                (0,0,0),
-                (0,0,0),
+                fixture_type,
+                location,
+                rotation,
+                size,
                 fix_chan_ids.to_owned()
             ));
             fixtures.push(fixture);
@@ -160,3 +758,32 @@ impl FileLayout {
         Ok((channels, fixtures))
     }
 }
+
+impl FileLayoutRow {
+    /// Returns true if the row is a channel that isn't hooked up to anything
+    pub fn is_spare(&self) -> bool {
+        SPARE_NAMES.contains(&self.fixtureName.as_str()) ||
+            SPARE_NAMES.contains(&self.channelName.as_str())
+    }
+
+    /// Parses the size given for the row's fixture, if any
+    pub fn get_fixture_size(&self) -> Result<Option<(i32, i32, i32)>, Error> {
+        match self.fixtureSize {
+            Some(ref size) if !size.is_empty() => FileLayout::parse_size(size).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Parses the row's width and height. Missing or blank sizes are None.
+    pub fn get_size(&self) -> Result<(Option<i32>, Option<i32>), Error> {
+        let width = match self.width {
+            Some(ref width) => try!(FileLayout::layout_str_to_i32(width, "width is not an i32")),
+            None => None,
+        };
+        let height = match self.height {
+            Some(ref height) => try!(FileLayout::layout_str_to_i32(height, "height is not an i32")),
+            None => None,
+        };
+        Ok((width, height))
+    }
+}
@@ -1,13 +1,14 @@
 use error::Error;
+use project_types::FileTable;
 
 /// Mapping for patch JSON object
-#[derive(Debug, RustcDecodable)]
+#[derive(Debug, RustcDecodable, RustcEncodable)]
 pub struct FilePatch {
     pub patches: Vec<FilePatchRow>,
 }
 
 /// Mapping for one row (patch) in the patch JSON object
-#[derive(Debug, RustcDecodable)]
+#[derive(Debug, RustcDecodable, RustcEncodable)]
 #[allow(non_snake_case)]
 pub struct FilePatchRow {
     pub internalChannel: u32,
@@ -15,6 +16,38 @@ pub struct FilePatchRow {
 }
 
 impl FilePatch {
+    /// Reads a patch from a CSV/TSV table with internalChannel and dmxChannel columns.
+    /// Other columns are ignored. Every bad row is reported, by its line in the file.
+    pub fn from_table(table: &FileTable) -> Result<FilePatch, Error> {
+        for column in ["internalChannel", "dmxChannel"].iter() {
+            if !table.header.iter().any(|name| name == *column) {
+                return Err(Error::InvalidPatch(format!("Missing column '{}'", column)));
+            }
+        }
+
+        let mut patches = Vec::new();
+        let mut problems = Vec::new();
+        for row in &table.rows {
+            let internal = table.get(row, "internalChannel").unwrap_or("");
+            let dmx = table.get(row, "dmxChannel").unwrap_or("");
+            match (internal.parse::<u32>(), dmx.parse::<u32>()) {
+                (Ok(internal), Ok(dmx)) => patches.push(FilePatchRow {
+                    internalChannel: internal,
+                    dmxChannel: dmx
+                }),
+                (Err(_), _) => problems.push(format!(
+                    "line {}: internalChannel '{}' is not a whole number", row.line, internal)),
+                (_, Err(_)) => problems.push(format!(
+                    "line {}: dmxChannel '{}' is not a whole number", row.line, dmx)),
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(Error::InvalidPatch(problems.join("\n")));
+        }
+        Ok(FilePatch { patches: patches })
+    }
+
     /// Check that all channels are valid
     pub fn validate(&self) -> Result<(), Error> {
 
@@ -0,0 +1,154 @@
+use std::path::Path;
+
+use error::Error;
+
+
+/// A spreadsheet saved as CSV or TSV. The first row is a header naming each column,
+/// and the rest are data rows.
+#[derive(Debug)]
+pub struct FileTable {
+    pub header: Vec<String>,
+    pub rows: Vec<FileTableRow>,
+}
+
+/// One data row of a FileTable, along with the line of the file it starts on
+#[derive(Debug)]
+pub struct FileTableRow {
+    pub line: usize,
+    pub cells: Vec<String>,
+}
+
+impl FileTable {
+
+    /// Returns the delimiter to use for a file based on its extension:
+    /// commas for .csv and tabs for .tsv. Other files aren't tables.
+    pub fn delimiter_for<P: AsRef<Path>>(path: P) -> Option<char> {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Some(','),
+            Some(ext) if ext.eq_ignore_ascii_case("tsv") => Some('\t'),
+            _ => None,
+        }
+    }
+
+    /// Parses CSV/TSV text. Cells can be quoted to hold delimiters, quotes ("") or
+    /// line breaks. Unquoted cells are trimmed, and blank lines are skipped.
+    pub fn parse(text: &str, delimiter: char) -> Result<FileTable, Error> {
+        let mut records: Vec<FileTableRow> = Vec::new();
+        let mut cells: Vec<String> = Vec::new();
+        let mut cell = String::new();
+        let mut quoted = false; // Inside a quoted cell
+        let mut was_quoted = false; // Current cell was quoted, so don't trim it
+        let mut line = 1;
+        let mut record_line = 1;
+
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if quoted {
+                match c {
+                    '"' if chars.peek() == Some(&'"') => {
+                        cell.push('"');
+                        chars.next();
+                    },
+                    '"' => quoted = false,
+                    _ => {
+                        if c == '\n' {
+                            line += 1;
+                        }
+                        cell.push(c);
+                    },
+                }
+                continue;
+            }
+
+            match c {
+                '"' if cell.trim().is_empty() => {
+                    cell.clear();
+                    quoted = true;
+                    was_quoted = true;
+                },
+                '\r' => {},
+                '\n' => {
+                    cells.push(FileTable::finish_cell(&mut cell, was_quoted));
+                    was_quoted = false;
+                    if cells.len() > 1 || !cells[0].is_empty() {
+                        records.push(FileTableRow { line: record_line, cells: cells });
+                    }
+                    cells = Vec::new();
+                    line += 1;
+                    record_line = line;
+                },
+                _ if c == delimiter => {
+                    cells.push(FileTable::finish_cell(&mut cell, was_quoted));
+                    was_quoted = false;
+                },
+                _ => cell.push(c),
+            }
+        }
+
+        if quoted {
+            return Err(Error::InvalidTable(record_line, "Quoted cell is never closed".to_owned()));
+        }
+        if !cell.is_empty() || was_quoted || !cells.is_empty() {
+            cells.push(FileTable::finish_cell(&mut cell, was_quoted));
+            records.push(FileTableRow { line: record_line, cells: cells });
+        }
+
+        // First record is the header
+        if records.is_empty() {
+            return Err(Error::InvalidTable(1, "Missing header row".to_owned()));
+        }
+        let header = records.remove(0).cells;
+        for row in &records {
+            if row.cells.len() > header.len() {
+                return Err(Error::InvalidTable(row.line, format!(
+                    "Row has {} cells, but the header only has {}", row.cells.len(), header.len())));
+            }
+        }
+
+        Ok(FileTable {
+            header: header,
+            rows: records,
+        })
+    }
+
+    /// Helper function for parse() to take the finished cell's text
+    fn finish_cell(cell: &mut String, was_quoted: bool) -> String {
+        let text = if was_quoted { cell.to_owned() } else { cell.trim().to_owned() };
+        cell.clear();
+        text
+    }
+
+    /// Returns the row's cell in the given column. Short rows are missing their last cells.
+    pub fn get<'a>(&self, row: &'a FileTableRow, column: &str) -> Option<&'a str> {
+        self.header.iter()
+            .position(|name| name == column)
+            .and_then(|i| row.cells.get(i))
+            .map(|cell| cell.as_str())
+    }
+
+    /// Writes a header and rows out as CSV/TSV text, quoting cells where needed
+    pub fn to_text(header: &[&str], rows: &Vec<Vec<String>>, delimiter: char) -> String {
+        let quote = |cell: &str| {
+            if cell.contains(delimiter) || cell.contains('"') || cell.contains('\n') || cell.contains('\r') {
+                format!("\"{}\"", cell.replace("\"", "\"\""))
+            } else {
+                cell.to_owned()
+            }
+        };
+
+        let delimiter = delimiter.to_string();
+        let mut text = header.iter()
+            .map(|name| quote(name))
+            .collect::<Vec<String>>()
+            .join(&delimiter);
+        text.push('\n');
+        for row in rows {
+            text.push_str(&row.iter()
+                .map(|cell| quote(cell))
+                .collect::<Vec<String>>()
+                .join(&delimiter));
+            text.push('\n');
+        }
+        text
+    }
+}
@@ -5,24 +5,96 @@
 pub struct Fixture {
     pub fixid: u32,
     pub name: String,
-    pub location: (i32, i32, i32),
+    pub fixture_type: Option<String>, // e.g. "RGB pixel", if the layout defined one
+    pub location: (i32, i32, i32), // Center of the fixture
     pub rotation: (i32, i32, i32),
+    pub size: (i32, i32, i32), // Width, height, and depth of the fixture's bounding box
     pub channels: Vec<u32>,
 }
 
+/// Where one of a fixture's channels is placed, as given in a layout.
+/// Used to work out the fixture's geometry.
+#[derive(Debug)]
+pub struct ChannelPlacement {
+    pub location: (Option<i32>, Option<i32>, Option<i32>),
+    pub rotation: (Option<i32>, Option<i32>, Option<i32>),
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
 impl Fixture {
     pub fn new(
         name: &str,
+        fixture_type: Option<&str>,
         location: (i32, i32, i32),
         rotation: (i32, i32, i32),
+        size: (i32, i32, i32),
         channels: Vec<u32>
     ) -> Fixture {
         Fixture {
             fixid: 0, // Default, set by dao
             name: name.to_owned(),
+            fixture_type: fixture_type.map(|fixture_type| fixture_type.to_owned()),
             location: location,
             rotation: rotation,
+            size: size,
             channels: channels
         }
     }
+
+    /// Works out a fixture's location, rotation and size from where its channels are.
+    /// Each channel is a box centered on its location, width wide and height tall.
+    /// The fixture's location is the center of the box around all of its channels,
+    /// its size is the size of that box, and its rotation is the channels' average.
+    /// Unknown values are left out; axes with nothing known are 0.
+    /// Returns (location, rotation, size)
+    pub fn geometry(
+        placements: &Vec<ChannelPlacement>
+    ) -> ((i32, i32, i32), (i32, i32, i32), (i32, i32, i32)) {
+
+        // Bounds along each axis as (min, max)
+        let mut bounds: [Option<(i32, i32)>; 3] = [None, None, None];
+        let mut rotation_sums = [(0i64, 0i64); 3];
+
+        for placement in placements {
+            let (x, y, z) = placement.location;
+            let half_width = placement.width.unwrap_or(0) / 2;
+            let half_height = placement.height.unwrap_or(0) / 2;
+            let extents = [
+                x.map(|x| (x - half_width, x + placement.width.unwrap_or(0) - half_width)),
+                y.map(|y| (y - half_height, y + placement.height.unwrap_or(0) - half_height)),
+                z.map(|z| (z, z)),
+            ];
+            for (bound, extent) in bounds.iter_mut().zip(extents.iter()) {
+                if let Some((low, high)) = *extent {
+                    *bound = Some(match *bound {
+                        Some((min, max)) => (min.min(low), max.max(high)),
+                        None => (low, high),
+                    });
+                }
+            }
+
+            let (a, b, c) = placement.rotation;
+            for (sum, angle) in rotation_sums.iter_mut().zip([a, b, c].iter()) {
+                if let Some(angle) = *angle {
+                    sum.0 += angle as i64;
+                    sum.1 += 1;
+                }
+            }
+        }
+
+        let center = |bound: Option<(i32, i32)>| bound.map(|(min, max)| min + (max - min) / 2).unwrap_or(0);
+        let extent = |bound: Option<(i32, i32)>| bound.map(|(min, max)| max - min).unwrap_or(0);
+        let average = |sum: (i64, i64)| if sum.1 > 0 {
+            (sum.0 as f64 / sum.1 as f64).round() as i32
+        } else {
+            0
+        };
+
+        (
+            (center(bounds[0]), center(bounds[1]), center(bounds[2])),
+            (average(rotation_sums[0]), average(rotation_sums[1]), average(rotation_sums[2])),
+            (extent(bounds[0]), extent(bounds[1]), extent(bounds[2]))
+        )
+    }
 }
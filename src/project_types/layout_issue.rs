@@ -0,0 +1,52 @@
+use std::fmt;
+
+
+/// How serious a problem found in a layout file is
+#[derive(Clone, Copy, Debug, PartialEq, RustcEncodable)]
+pub enum IssueLevel {
+    Error,
+    Warning,
+}
+
+/// A problem found while checking a layout file.
+/// Rows are numbered from 1, in the order they appear in the file.
+/// CSV/TSV rows are numbered by the line they're on, like in a spreadsheet.
+#[derive(Clone, Debug, PartialEq, RustcEncodable)]
+pub struct LayoutIssue {
+    pub row: Option<usize>,
+    pub level: IssueLevel,
+    pub message: String,
+}
+
+impl LayoutIssue {
+    /// Creates an issue that would make the layout fail to load or behave incorrectly
+    pub fn error(row: Option<usize>, message: String) -> LayoutIssue {
+        LayoutIssue {
+            row: row,
+            level: IssueLevel::Error,
+            message: message
+        }
+    }
+
+    /// Creates an issue that is suspicious, but won't stop the layout from loading
+    pub fn warning(row: Option<usize>, message: String) -> LayoutIssue {
+        LayoutIssue {
+            row: row,
+            level: IssueLevel::Warning,
+            message: message
+        }
+    }
+}
+
+impl fmt::Display for LayoutIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = match self.level {
+            IssueLevel::Error => "error",
+            IssueLevel::Warning => "warning",
+        };
+        match self.row {
+            Some(row) => write!(f, "row {}: {}: {}", row, level, self.message),
+            None => write!(f, "layout: {}: {}", level, self.message),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+
+
+/// A set of channel patches applied to a layout at the same time.
+/// Each patch set is a new version of the layout (starting at 1); version 0
+/// is the layout as it was originally created.
+#[derive(Clone, Debug, RustcEncodable)]
+pub struct LayoutPatch {
+    pub patchid: u32,
+    pub layout_id: u32,
+    pub version: u32,
+    pub source: String,
+    pub changes: Vec<PatchChange>,
+}
+
+/// A single channel's dmx change within a layout patch
+#[derive(Clone, Debug, PartialEq, RustcEncodable)]
+pub struct PatchChange {
+    pub channel_internal: u32,
+    pub old_dmx: u32,
+    pub new_dmx: u32,
+}
+
+impl LayoutPatch {
+    /// Rebuilds a layout's mapping (internal channel -> dmx channel) as it was at 
+    /// the given version, by undoing every newer patch on top of the current mapping
+    pub fn mapping_at_version(
+        current: &BTreeMap<u32, u32>,
+        history: &Vec<LayoutPatch>,
+        version: u32
+    ) -> BTreeMap<u32, u32> {
+
+        let mut mapping = current.clone();
+        let mut newer = history.iter()
+            .filter(|patch| patch.version > version)
+            .collect::<Vec<&LayoutPatch>>();
+
+        // Undo newest first
+        newer.sort_by(|a, b| b.version.cmp(&a.version));
+        for patch in newer {
+            for change in patch.changes.iter().rev() {
+                mapping.insert(change.channel_internal, change.old_dmx);
+            }
+        }
+
+        mapping
+    }
+}
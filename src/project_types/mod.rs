@@ -1,28 +1,60 @@
 
 mod channel;
+mod controller;
+mod data_edit;
+mod dimming;
 mod file_layout;
 mod file_patch;
+mod file_table;
 mod fixture;
 mod layout;
+mod layout_issue;
+mod layout_patch;
 mod permissions;
 mod permission_enum;
+mod power;
 mod project;
+mod reactive_mapping;
+mod resample_mode;
+mod schedule;
 mod section;
 mod sequence;
 mod sequence_data;
+mod sequence_issue;
+mod sequence_script;
+mod test_pattern;
+mod time_anchor;
+mod timing_file;
+mod timing_track;
 mod user;
 
 pub use self::channel::Channel;
+pub use self::controller::{Controller, ControllerProtocol, PortAssignment, PortRange};
+pub use self::data_edit::{DataEdit, EditTargets, FadeCurve};
+pub use self::dimming::{DimmingCurve, DimmingProfile};
 pub use self::file_patch::{FilePatch, FilePatchRow};
-pub use self::file_layout::FileLayout;
-pub use self::fixture::Fixture;
+pub use self::file_layout::{FileFixture, FileLayout, SparePolicy};
+pub use self::file_table::{FileTable, FileTableRow};
+pub use self::fixture::{ChannelPlacement, Fixture};
 pub use self::layout::Layout;
+pub use self::layout_issue::{IssueLevel, LayoutIssue};
+pub use self::layout_patch::{LayoutPatch, PatchChange};
 pub use self::permissions::Permission;
 pub use self::permission_enum::PermissionEnum;
+pub use self::power::{ChannelLoad, LoadGroup, LoadSummary, Overload, PowerReport};
 pub use self::project::Project;
+pub use self::reactive_mapping::ReactiveMapping;
+pub use self::resample_mode::ResampleMode;
+pub use self::schedule::{Intermission, Schedule, ScheduleDecision, ScheduledSequence, ShowWindow};
 pub use self::section::Section;
 pub use self::sequence::Sequence;
 pub use self::sequence_data::SequenceData;
+pub use self::sequence_issue::{DurationPolicy, SequenceIssue};
+pub use self::sequence_script::{ScriptCue, SequenceScript};
+pub use self::test_pattern::{TestPattern, TestStep};
+pub use self::time_anchor::TimeAnchor;
+pub use self::timing_file::TimingFile;
+pub use self::timing_track::{TimingMark, TimingTrack};
 pub use self::user::User;
 
 pub use self::permission_enum::get_permission_enum;
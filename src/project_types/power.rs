@@ -0,0 +1,144 @@
+use std::fmt;
+
+use error::Error;
+
+
+/// Power a channel draws when fully on, and the circuit it's wired to
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelLoad {
+    pub watts: f64,
+    pub circuit: Option<String>,
+}
+
+/// What a load is summed over
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadGroup {
+    Circuit(String),
+    Controller(String),
+    Universe(u16),
+    Total,
+}
+
+/// Peak and average load of a group over a sequence. Times are in milliseconds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoadSummary {
+    pub group: LoadGroup,
+    pub peak_watts: f64,
+    pub peak_ms: u32,
+    pub average_watts: f64,
+    pub max_amps: Option<f64>, // Limit of the group, if it has one
+}
+
+/// A run of frames where a group draws more than its limit
+#[derive(Clone, Debug, PartialEq)]
+pub struct Overload {
+    pub group: LoadGroup,
+    pub start_ms: u32,
+    pub end_ms: u32,
+    pub peak_amps: f64,
+}
+
+/// How much power a sequence draws, frame by frame
+#[derive(Clone, Debug, PartialEq)]
+pub struct PowerReport {
+    pub seqid: u32,
+    pub volts: f64,
+    pub summaries: Vec<LoadSummary>,
+    pub overloads: Vec<Overload>,
+    pub unrated_channels: u32, // Channels with no load set, which aren't counted
+}
+
+impl ChannelLoad {
+    /// Creates a load, checking its wattage makes sense
+    pub fn new(watts: f64, circuit: Option<String>) -> Result<ChannelLoad, Error> {
+        if !(watts >= 0.0) || watts.is_infinite() {
+            return Err(Error::InvalidPower(format!("Watts must be 0 or more, not {}", watts)));
+        }
+        if circuit.as_ref().map_or(false, |circuit| circuit.trim().is_empty()) {
+            return Err(Error::InvalidPower("Circuit names can't be blank".to_owned()));
+        }
+        Ok(ChannelLoad {
+            watts: watts,
+            circuit: circuit
+        })
+    }
+}
+
+impl LoadSummary {
+    /// Summarizes a group's load in each frame
+    pub fn from_frames(
+        group: LoadGroup,
+        frame_watts: &[f64],
+        frame_duration_ms: u32,
+        max_amps: Option<f64>
+    ) -> LoadSummary {
+        let mut peak_watts = 0.0;
+        let mut peak_frame = 0;
+        for (frame, watts) in frame_watts.iter().enumerate() {
+            if *watts > peak_watts {
+                peak_watts = *watts;
+                peak_frame = frame as u32;
+            }
+        }
+        let average_watts = if frame_watts.is_empty() {
+            0.0
+        } else {
+            frame_watts.iter().fold(0.0, |sum, watts| sum + watts) / frame_watts.len() as f64
+        };
+        LoadSummary {
+            group: group,
+            peak_watts: peak_watts,
+            peak_ms: peak_frame * frame_duration_ms,
+            average_watts: average_watts,
+            max_amps: max_amps
+        }
+    }
+}
+
+impl Overload {
+    /// Finds the runs of frames where a group draws more than max_amps
+    pub fn find(
+        group: &LoadGroup,
+        frame_watts: &[f64],
+        frame_duration_ms: u32,
+        volts: f64,
+        max_amps: f64
+    ) -> Vec<Overload> {
+        let mut overloads: Vec<Overload> = Vec::new();
+        let mut last_frame = None;
+        for (frame, watts) in frame_watts.iter().enumerate() {
+            let amps = watts / volts;
+            if amps <= max_amps {
+                continue;
+            }
+            let start_ms = frame as u32 * frame_duration_ms;
+            let end_ms = start_ms + frame_duration_ms;
+            match overloads.last_mut() {
+                // Frames in a row are one overload
+                Some(ref mut overload) if last_frame.map(|last| last + 1) == Some(frame) => {
+                    overload.end_ms = end_ms;
+                    overload.peak_amps = overload.peak_amps.max(amps);
+                },
+                _ => overloads.push(Overload {
+                    group: group.clone(),
+                    start_ms: start_ms,
+                    end_ms: end_ms,
+                    peak_amps: amps
+                }),
+            }
+            last_frame = Some(frame);
+        }
+        overloads
+    }
+}
+
+impl fmt::Display for LoadGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadGroup::Circuit(ref name) => write!(f, "Circuit '{}'", name),
+            LoadGroup::Controller(ref name) => write!(f, "Controller '{}'", name),
+            LoadGroup::Universe(universe) => write!(f, "Universe {}", universe),
+            LoadGroup::Total => write!(f, "Total"),
+        }
+    }
+}
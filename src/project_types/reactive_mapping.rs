@@ -0,0 +1,51 @@
+use std::str::FromStr;
+
+use audio::Band;
+use effects::Color;
+use error::Error;
+
+
+/// Which fixtures follow a band of the music, and in what color.
+/// Written as band:fixtures[:color], e.g. `bass:Megatree:red` or
+/// `high:Net 1,Net 2`. Fixtures are comma separated, or * for all.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReactiveMapping {
+    pub band: Band,
+    pub fixtures: Vec<String>, // Fixture names, or empty for all fixtures
+    pub color: Color,
+}
+
+impl FromStr for ReactiveMapping {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<ReactiveMapping, Error> {
+        let parts = s.split(':').collect::<Vec<&str>>();
+        if parts.len() < 2 || parts.len() > 3 {
+            return Err(Error::InvalidReactiveMapping(
+                format!("Mappings must be of the form band:fixtures[:color]: {}", s)));
+        }
+
+        let band = try!(parts[0].trim().parse::<Band>());
+        let fixtures = if parts[1].trim() == "*" {
+            Vec::new()
+        } else {
+            let names = parts[1].split(',')
+                .map(|name| name.trim().to_owned())
+                .collect::<Vec<String>>();
+            if names.iter().any(|name| name.is_empty()) {
+                return Err(Error::InvalidReactiveMapping(format!("Invalid fixture list: {}", parts[1])));
+            }
+            names
+        };
+        let color = match parts.get(2) {
+            Some(color) => try!(color.trim().parse::<Color>()),
+            None => Color::white(),
+        };
+
+        Ok(ReactiveMapping {
+            band: band,
+            fixtures: fixtures,
+            color: color
+        })
+    }
+}
@@ -0,0 +1,58 @@
+use std::str::FromStr;
+
+use error::Error;
+
+
+/// How channel data is sampled when changing its frame duration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResampleMode {
+    Nearest, // Value of the closest frame
+    Linear, // Blends between the two closest frames, for smoother fades
+    Hold, // Value of the frame playing at the time
+}
+
+impl ResampleMode {
+    /// Samples data at a position given in frames, which can fall between frames.
+    /// Returns None if the position is past the end of the data.
+    pub fn sample(&self, data: &[u16], position: f64) -> Option<u16> {
+        let frame = position.floor() as usize;
+        if position < 0.0 || frame >= data.len() {
+            return None;
+        }
+        let value = match *self {
+            ResampleMode::Nearest => data[(position.round() as usize).min(data.len() - 1)],
+            ResampleMode::Linear => {
+                let from = data[frame] as f64;
+                let to = *data.get(frame + 1).unwrap_or(&data[frame]) as f64;
+                (from + (to - from) * (position - frame as f64)).round() as u16
+            },
+            ResampleMode::Hold => data[frame],
+        };
+        Some(value)
+    }
+
+    /// Resamples a channel's data from one frame duration to another
+    pub fn resample(&self, data: &[u16], frame_duration_ms: u32, new_frame_duration_ms: u32) -> Vec<u16> {
+        let duration_ms = data.len() as u32 * frame_duration_ms;
+        let num_frames = (duration_ms as f64 / new_frame_duration_ms as f64).ceil() as u32;
+        (0..num_frames)
+            .map(|frame| {
+                let position = (frame * new_frame_duration_ms) as f64 / frame_duration_ms as f64;
+                self.sample(data, position).unwrap_or(0)
+            })
+            .collect()
+    }
+}
+
+impl FromStr for ResampleMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<ResampleMode, Error> {
+        match s {
+            "nearest" => Ok(ResampleMode::Nearest),
+            "linear" => Ok(ResampleMode::Linear),
+            "hold" => Ok(ResampleMode::Hold),
+            _ => Err(Error::InvalidResampleMode(s.to_owned())),
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use rustc_serialize::json;
+
+use error::Error;
+
+
+/// Days a show window runs on if none are given
+const ALL_DAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Format show window times are written in, e.g. 17:30
+const TIME_FORMAT: &'static str = "%H:%M";
+
+/// How many days ahead to look for the next show. Every window runs at least
+/// once a week, so there's always one in this time.
+const DAYS_AHEAD: i64 = 8;
+
+/// When a project's show runs, e.g. 5:30-10pm every day, and how its playlist
+/// is played during that time
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schedule {
+    pub windows: Vec<ShowWindow>,
+    pub repeat: bool, // Loop the playlist until the window ends, instead of playing it once
+    pub shuffle: bool, // Play the playlist in a different order each time through
+    pub intermission: Option<Intermission>,
+}
+
+/// Time of day a show runs on some days of the week. Windows that end at or
+/// before the time they start run past midnight, and belong to the day they start on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShowWindow {
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+/// A sequence played between playlist sequences every so often
+#[derive(Clone, Debug, PartialEq, RustcEncodable, RustcDecodable)]
+pub struct Intermission {
+    pub seqid: u32,
+    pub every_minutes: u32,
+}
+
+/// A sequence in a show's running order
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduledSequence {
+    pub seqid: u32,
+    pub start: NaiveDateTime,
+    pub duration_ms: u32,
+    pub intermission: bool,
+}
+
+/// What should be playing at a given time
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScheduleDecision {
+    Play {
+        seqid: u32,
+        position_ms: u32, // How far into the sequence it should be
+        intermission: bool,
+    },
+    Wait(NaiveDateTime), // Nothing is playing until the next show starts at this time
+}
+
+/// Mapping for a schedule JSON object, e.g.
+/// {"windows": [{"days": ["fri", "sat"], "start": "17:30", "end": "22:00"}],
+///  "repeat": true, "shuffle": false, "intermission": {"seqid": 4, "every_minutes": 60}}
+#[derive(Debug, RustcEncodable, RustcDecodable)]
+struct FileSchedule {
+    windows: Vec<FileShowWindow>,
+    repeat: Option<bool>, // Default is to play the playlist once
+    shuffle: Option<bool>, // Default is playlist order
+    intermission: Option<Intermission>,
+}
+
+/// Mapping for a show window in a JSON schedule
+#[derive(Debug, RustcEncodable, RustcDecodable)]
+struct FileShowWindow {
+    days: Option<Vec<String>>, // Default is every day
+    start: String,
+    end: String,
+}
+
+impl ShowWindow {
+    /// The show this window has on a date, if it runs that day
+    pub fn show_on(&self, date: NaiveDate) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        if !self.days.contains(&date.weekday()) {
+            return None;
+        }
+        let end_date = if self.end > self.start { date } else { date + Duration::days(1) };
+        Some((date.and_time(self.start), end_date.and_time(self.end)))
+    }
+}
+
+impl ScheduledSequence {
+    /// Time the sequence finishes
+    pub fn end(&self) -> NaiveDateTime {
+        self.start + Duration::milliseconds(self.duration_ms as i64)
+    }
+}
+
+impl Schedule {
+    /// Reads a schedule from a JSON file
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Schedule, Error> {
+        let mut file = try!(File::open(path.as_ref()).map_err(Error::Io));
+        let mut text = String::new();
+        try!(file.read_to_string(&mut text).map_err(Error::Io));
+        Schedule::from_json(&text)
+    }
+
+    /// Parses and checks a JSON schedule
+    pub fn from_json(text: &str) -> Result<Schedule, Error> {
+        let file_schedule: FileSchedule = try!(json::decode(text).map_err(Error::JsonDecode));
+        if file_schedule.windows.is_empty() {
+            return Err(Error::InvalidSchedule("no show windows given".to_owned()));
+        }
+
+        let mut windows = Vec::with_capacity(file_schedule.windows.len());
+        for file_window in file_schedule.windows {
+            let days = match file_window.days {
+                Some(ref days) if days.is_empty() => {
+                    return Err(Error::InvalidSchedule("show window has no days".to_owned()));
+                },
+                Some(days) => {
+                    let mut weekdays = Vec::with_capacity(days.len());
+                    for day in days {
+                        let weekday = try!(day.parse::<Weekday>()
+                            .map_err(|_| Error::InvalidSchedule(format!("unknown day '{}'", day))));
+                        weekdays.push(weekday);
+                    }
+                    weekdays
+                },
+                None => ALL_DAYS.to_vec(),
+            };
+            let start = try!(parse_time(&file_window.start));
+            let end = try!(parse_time(&file_window.end));
+            if start == end {
+                return Err(Error::InvalidSchedule(format!("show window starts and ends at {}", file_window.start)));
+            }
+            windows.push(ShowWindow {
+                days: days,
+                start: start,
+                end: end
+            });
+        }
+
+        if let Some(ref intermission) = file_schedule.intermission {
+            if intermission.every_minutes == 0 {
+                return Err(Error::InvalidSchedule("intermission must be at least a minute apart".to_owned()));
+            }
+        }
+
+        Ok(Schedule {
+            windows: windows,
+            repeat: file_schedule.repeat.unwrap_or(false),
+            shuffle: file_schedule.shuffle.unwrap_or(false),
+            intermission: file_schedule.intermission
+        })
+    }
+
+    /// Writes the schedule as JSON, in the form it's read in
+    pub fn to_json(&self) -> Result<String, Error> {
+        let file_schedule = FileSchedule {
+            windows: self.windows.iter()
+                .map(|window| FileShowWindow {
+                    days: Some(window.days.iter()
+                        .map(|day| format!("{:?}", day).to_lowercase())
+                        .collect()),
+                    start: window.start.format(TIME_FORMAT).to_string(),
+                    end: window.end.format(TIME_FORMAT).to_string()
+                })
+                .collect(),
+            repeat: Some(self.repeat),
+            shuffle: Some(self.shuffle),
+            intermission: self.intermission.clone()
+        };
+        json::encode(&file_schedule).map_err(Error::JsonEncode)
+    }
+
+    /// Every show starting between two dates (inclusive), in order
+    pub fn shows(&self, from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+        let mut shows = Vec::new();
+        let mut date = from;
+        while date <= to {
+            for window in &self.windows {
+                if let Some(show) = window.show_on(date) {
+                    shows.push(show);
+                }
+            }
+            date = date + Duration::days(1);
+        }
+        shows.sort();
+        shows
+    }
+
+    /// The show running at a time, if any
+    pub fn show_at(&self, now: NaiveDateTime) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let today = now.date();
+        self.shows(today - Duration::days(1), today)
+            .into_iter()
+            .find(|&(start, end)| start <= now && now < end)
+    }
+
+    /// The first show starting after a time
+    pub fn next_show(&self, now: NaiveDateTime) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let today = now.date();
+        self.shows(today, today + Duration::days(DAYS_AHEAD))
+            .into_iter()
+            .find(|&(start, _)| start > now)
+    }
+
+    /// Works out the order sequences play in during a show, given the
+    /// playlist and each sequence's duration. Sequences play back to back from
+    /// the start of the show, and the last one starts before the show ends and
+    /// is played to the end. The intermission plays between two sequences once
+    /// its time has come.
+    pub fn running_order(
+        &self,
+        playlist: &[u32],
+        durations: &BTreeMap<u32, u32>,
+        start: NaiveDateTime,
+        end: NaiveDateTime
+    ) -> Result<Vec<ScheduledSequence>, Error> {
+
+        let duration_of = |seqid: u32| durations.get(&seqid)
+            .cloned()
+            .ok_or(Error::SequenceNotFound(seqid));
+        let intermission = match self.intermission {
+            Some(ref intermission) => Some((
+                intermission.seqid,
+                try!(duration_of(intermission.seqid)),
+                Duration::minutes(intermission.every_minutes as i64))),
+            None => None,
+        };
+
+        let mut order: Vec<ScheduledSequence> = Vec::new();
+        let mut time = start;
+        let mut intermission_due = intermission.map(|(_, _, every)| start + every);
+        let mut pass = 0;
+        loop {
+            let pass_start = time;
+            let previous = order.last().map(|scheduled| scheduled.seqid);
+            for seqid in self.pass_order(playlist, start, pass, previous) {
+                if time >= end {
+                    return Ok(order);
+                }
+                if let (Some((intermission_seqid, duration_ms, every)), Some(due)) = (intermission, intermission_due) {
+                    if time >= due && !order.is_empty() {
+                        order.push(ScheduledSequence {
+                            seqid: intermission_seqid,
+                            start: time,
+                            duration_ms: duration_ms,
+                            intermission: true
+                        });
+                        time = time + Duration::milliseconds(duration_ms as i64);
+                        let mut next_due = due;
+                        while next_due <= time {
+                            next_due = next_due + every;
+                        }
+                        intermission_due = Some(next_due);
+                        if time >= end {
+                            return Ok(order);
+                        }
+                    }
+                }
+                let duration_ms = try!(duration_of(seqid));
+                order.push(ScheduledSequence {
+                    seqid: seqid,
+                    start: time,
+                    duration_ms: duration_ms,
+                    intermission: false
+                });
+                time = time + Duration::milliseconds(duration_ms as i64);
+            }
+
+            // Stop if the playlist only plays once, or would never fill the show
+            if !self.repeat || time == pass_start {
+                return Ok(order);
+            }
+            pass += 1;
+        }
+    }
+
+    /// Decides what should be playing at a time, given the project's playlist
+    /// and the duration of each sequence in it and the intermission
+    pub fn decide(
+        &self,
+        playlist: &[u32],
+        durations: &BTreeMap<u32, u32>,
+        now: NaiveDateTime
+    ) -> Result<ScheduleDecision, Error> {
+
+        // A show's last sequence can run past its end, so yesterday's shows are checked too
+        let today = now.date();
+        let shows = self.shows(today - Duration::days(1), today);
+        for &(start, end) in shows.iter().rev().filter(|&&(start, _)| start <= now) {
+            let order = try!(self.running_order(playlist, durations, start, end));
+            if let Some(scheduled) = order.iter().find(|scheduled| scheduled.start <= now && now < scheduled.end()) {
+                return Ok(ScheduleDecision::Play {
+                    seqid: scheduled.seqid,
+                    position_ms: (now - scheduled.start).num_milliseconds() as u32,
+                    intermission: scheduled.intermission
+                });
+            }
+        }
+
+        match self.next_show(now) {
+            Some((start, _)) => Ok(ScheduleDecision::Wait(start)),
+            None => Err(Error::InvalidSchedule("no shows in the next week".to_owned())),
+        }
+    }
+
+    /// Orders the playlist for one time through it. Shuffled orders only
+    /// depend on the show and how many times the playlist has been played, so
+    /// the same time always gets the same decision. A shuffle doesn't start
+    /// with the sequence that was just played.
+    fn pass_order(&self, playlist: &[u32], show_start: NaiveDateTime, pass: u32, previous: Option<u32>) -> Vec<u32> {
+        let mut order = playlist.to_vec();
+        if !self.shuffle || order.len() < 2 {
+            return order;
+        }
+
+        let mut state = (show_start.date().num_days_from_ce() as u64) << 32
+            ^ (show_start.time().num_seconds_from_midnight() as u64) << 8
+            ^ pass as u64
+            ^ 0x9e37_79b9_7f4a_7c15;
+        for i in (1..order.len()).rev() {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let j = (state % (i as u64 + 1)) as usize;
+            order.swap(i, j);
+        }
+        if previous == Some(order[0]) {
+            let last = order.len() - 1;
+            order.swap(0, last);
+        }
+        order
+    }
+}
+
+/// Parses a time of day, e.g. 17:30
+fn parse_time(time: &str) -> Result<NaiveTime, Error> {
+    NaiveTime::parse_from_str(time, TIME_FORMAT)
+        .map_err(|_| Error::InvalidSchedule(format!("invalid time '{}', expected e.g. 17:30", time)))
+}
@@ -1,5 +1,8 @@
 use error::Error;
-use project_types::Layout;
+use project_types::{IssueLevel, Layout, SequenceIssue};
+
+/// How far a sequence can run past the end of its music before it's worth a warning
+pub const MAX_EXTRA_DURATION_MS: i64 = 5000;
 
 /// Structure to hold sequence metadata
 #[derive(Clone, Debug)]
@@ -7,10 +10,11 @@ pub struct Sequence {
     pub seqid: u32,
     pub name: String,
     pub music_file_name: String, // found in Music/
-    pub music_duration_sec: u32,
+    pub music_duration_ms: u32,
     pub frame_duration_ms: u32,
     pub num_frames: u32,
     pub layout_id: u32,
+    pub music_offset_ms: i32, // Time in the sequence the music starts at. Negative skips the start of the music.
 }
 
 impl Sequence {
@@ -18,7 +22,7 @@ impl Sequence {
     pub fn new(
         name: &str,
         music_file_name: &str,
-        music_duration_sec: u32,
+        music_duration_ms: u32,
         seq_duration_ms: u32,
         frame_duration_ms: Option<u32>,
         layout: &Layout
@@ -45,12 +49,38 @@ impl Sequence {
             seqid: seqid,
             name: name.to_string(),
             music_file_name: music_file_name.to_string(),
-            music_duration_sec: music_duration_sec,
+            music_duration_ms: music_duration_ms,
             frame_duration_ms: frame_dur_ms,
             num_frames: num_frames,
-            layout_id: layout_id
+            layout_id: layout_id,
+            music_offset_ms: 0
         };
 
         Ok(sequence)
     }
+
+    /// Checks the sequence's duration against its music. Sequences that end before
+    /// their music does cut it off, and ones that run well past it are suspicious.
+    pub fn check_duration(&self) -> Option<SequenceIssue> {
+        let seq_duration_ms = self.num_frames as i64 * self.frame_duration_ms as i64;
+        let music_end_ms = self.music_offset_ms as i64 + self.music_duration_ms as i64;
+        let (level, message) = if seq_duration_ms < music_end_ms {
+            (IssueLevel::Error, format!(
+                "Sequence is {}ms long, but its music ends at {}ms and will be cut off",
+                seq_duration_ms, music_end_ms))
+        } else if seq_duration_ms > music_end_ms + MAX_EXTRA_DURATION_MS {
+            (IssueLevel::Warning, format!(
+                "Sequence runs {}ms past the end of its music",
+                seq_duration_ms - music_end_ms))
+        } else {
+            return None;
+        };
+
+        Some(SequenceIssue {
+            seqid: self.seqid,
+            name: self.name.to_owned(),
+            level: level,
+            message: message
+        })
+    }
 }
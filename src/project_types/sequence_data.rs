@@ -4,6 +4,7 @@
 pub struct SequenceData {
     pub name: String,
     pub music_file: String,
+    pub music_offset_ms: i32,
     pub frame_dur_ms: u32,
     pub num_frames: u32,
     pub data: Vec<Vec<u16>>
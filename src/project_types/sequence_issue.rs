@@ -0,0 +1,44 @@
+use std::fmt;
+use std::str::FromStr;
+
+use error::Error;
+use project_types::IssueLevel;
+
+
+/// What to do when a new sequence's duration doesn't match its music
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DurationPolicy {
+    Warn, // Report the problem and create the sequence anyway
+    Error, // Refuse to create the sequence
+}
+
+/// A problem found while checking a sequence
+#[derive(Clone, Debug, PartialEq, RustcEncodable)]
+pub struct SequenceIssue {
+    pub seqid: u32,
+    pub name: String,
+    pub level: IssueLevel,
+    pub message: String,
+}
+
+impl FromStr for DurationPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<DurationPolicy, Error> {
+        match s {
+            "warn" => Ok(DurationPolicy::Warn),
+            "error" => Ok(DurationPolicy::Error),
+            _ => Err(Error::InvalidDurationPolicy(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for SequenceIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = match self.level {
+            IssueLevel::Error => "error",
+            IssueLevel::Warning => "warning",
+        };
+        write!(f, "sequence {} ({}): {}: {}", self.seqid, self.name, level, self.message)
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+
+use effects::{self, BlendMode, Effect};
+use error::Error;
+use project_types::TimeAnchor;
+
+
+/// A sequence written as text, one cue per line:
+///
+/// ```text
+/// # Comments start with a hash
+/// 00:12.500-00:15.000 Megatree rainbow speed=2
+/// 00:15-00:20 "Bush 1",Arch wash color=#ff0000 blend=add
+/// 0:00-1:00 * sparkle density=0.2
+/// beats#4-beats#8.end Arch strobe
+/// ```
+///
+/// Each cue has a time range, the fixtures it lights (comma separated, quoted if
+/// they have spaces, or * for all), an effect, and the effect's params. Times are
+/// clock times or marks in the sequence's timing tracks (see TimeAnchor).
+/// Later cues are layered on top of earlier ones.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SequenceScript {
+    pub cues: Vec<ScriptCue>,
+}
+
+/// One line of a SequenceScript
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptCue {
+    pub line: usize,
+    pub start: TimeAnchor,
+    pub end: TimeAnchor,
+    pub fixtures: Vec<String>, // Fixture names, or empty for all fixtures
+    pub effect: String,
+    pub params: BTreeMap<String, String>,
+    pub blend: BlendMode,
+}
+
+impl SequenceScript {
+    /// Parses a script, checking every cue's times and effect
+    pub fn parse(text: &str) -> Result<SequenceScript, Error> {
+        let mut cues = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_num = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            cues.push(try!(ScriptCue::parse(line, line_num)));
+        }
+        Ok(SequenceScript { cues: cues })
+    }
+
+    /// Parses a time like 1:02:03.500, 02:03.5 or 3.5 into milliseconds.
+    /// Errors give the line of the script the time is on.
+    pub fn parse_time(time: &str, line_num: usize) -> Result<u32, Error> {
+        let invalid = || Error::InvalidScript(line_num, format!("Invalid time: {}", time));
+        let mut parts = time.rsplitn(3, ':');
+        let seconds = match parts.next().and_then(|seconds| seconds.parse::<f64>().ok()) {
+            Some(seconds) if seconds >= 0.0 => seconds,
+            _ => return Err(invalid()),
+        };
+        let mut minutes = 0;
+        let mut hours = 0;
+        if let Some(part) = parts.next() {
+            minutes = try!(part.parse::<u32>().map_err(|_| invalid()));
+            if seconds >= 60.0 {
+                return Err(invalid());
+            }
+        }
+        if let Some(part) = parts.next() {
+            hours = try!(part.parse::<u32>().map_err(|_| invalid()));
+            if minutes >= 60 {
+                return Err(invalid());
+            }
+        }
+
+        let too_long = || Error::InvalidScript(line_num, format!("Time is too long: {}", time));
+        let seconds_ms = (seconds * 1000.0).round();
+        if seconds_ms > ::std::u32::MAX as f64 {
+            return Err(too_long());
+        }
+        hours.checked_mul(3600)
+            .and_then(|hours_s| minutes.checked_mul(60).and_then(|minutes_s| hours_s.checked_add(minutes_s)))
+            .and_then(|whole_s| whole_s.checked_mul(1000))
+            .and_then(|whole_ms| whole_ms.checked_add(seconds_ms as u32))
+            .ok_or_else(too_long)
+    }
+}
+
+impl ScriptCue {
+    /// Creates the cue's effect
+    pub fn build_effect(&self) -> Result<Box<Effect>, Error> {
+        effects::build_effect(&self.effect, &self.params)
+    }
+
+    /// Helper function for SequenceScript::parse() to parse one cue
+    fn parse(line: &str, line_num: usize) -> Result<ScriptCue, Error> {
+        let invalid = |description: String| Error::InvalidScript(line_num, description);
+        let words = try!(ScriptCue::split_words(line).map_err(&invalid));
+        if words.len() < 3 {
+            return Err(invalid("Cues need a time range, fixtures, and an effect".to_owned()));
+        }
+
+        // Time range
+        let mut times = words[0].splitn(2, '-');
+        let (start, end) = match (times.next(), times.next()) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Err(invalid(format!("Time range must be of the form start-end: {}", words[0]))),
+        };
+        let start_anchor = try!(ScriptCue::parse_anchor(start, line_num));
+        let end_anchor = try!(ScriptCue::parse_anchor(end, line_num));
+        if let (&TimeAnchor::Ms(start_ms), &TimeAnchor::Ms(end_ms)) = (&start_anchor, &end_anchor) {
+            if end_ms <= start_ms {
+                return Err(invalid(format!("End time {} must be after start time {}", end, start)));
+            }
+        }
+
+        // Fixtures
+        let fixtures = if words[1] == "*" {
+            Vec::new()
+        } else {
+            let names = words[1].split(',')
+                .map(|name| name.trim().to_owned())
+                .collect::<Vec<String>>();
+            if names.iter().any(|name| name.is_empty()) {
+                return Err(invalid(format!("Invalid fixture list: {}", words[1])));
+            }
+            names
+        };
+
+        // Effect and params. Blend is for the cue, not the effect.
+        let mut params = BTreeMap::new();
+        let mut blend = BlendMode::Normal;
+        for param in &words[3..] {
+            let mut parts = param.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some("blend"), Some(value)) => {
+                    blend = try!(value.parse::<BlendMode>().map_err(|e| invalid(e.to_string())));
+                },
+                (Some(key), Some(value)) if !key.is_empty() => {
+                    params.insert(key.to_owned(), value.to_owned());
+                },
+                _ => return Err(invalid(format!("Params must be of the form key=value: {}", param))),
+            }
+        }
+
+        let cue = ScriptCue {
+            line: line_num,
+            start: start_anchor,
+            end: end_anchor,
+            fixtures: fixtures,
+            effect: words[2].to_owned(),
+            params: params,
+            blend: blend
+        };
+        try!(cue.build_effect().map_err(|e| invalid(e.to_string())));
+        Ok(cue)
+    }
+
+    /// Helper function for parse() to read a clock time or a timing mark
+    fn parse_anchor(time: &str, line_num: usize) -> Result<TimeAnchor, Error> {
+        if time.contains('#') {
+            time.parse::<TimeAnchor>().map_err(|e| Error::InvalidScript(line_num, e.to_string()))
+        } else {
+            SequenceScript::parse_time(time, line_num).map(TimeAnchor::Ms)
+        }
+    }
+
+    /// Helper function for parse() to split a line on whitespace, keeping quoted text together
+    fn split_words(line: &str) -> Result<Vec<String>, String> {
+        let mut words = Vec::new();
+        let mut word = String::new();
+        let mut in_word = false;
+        let mut quoted = false;
+        for c in line.chars() {
+            match c {
+                '"' => {
+                    quoted = !quoted;
+                    in_word = true;
+                },
+                c if c.is_whitespace() && !quoted => {
+                    if in_word {
+                        words.push(word.clone());
+                        word.clear();
+                        in_word = false;
+                    }
+                },
+                c => {
+                    word.push(c);
+                    in_word = true;
+                },
+            }
+        }
+        if quoted {
+            return Err("Unclosed quote".to_owned());
+        }
+        if in_word {
+            words.push(word);
+        }
+        Ok(words)
+    }
+}
@@ -0,0 +1,85 @@
+use std::str::FromStr;
+
+use effects::MAX_VALUE;
+use error::Error;
+use project_types::Channel;
+
+
+/// How long the ramp pattern takes to fade up
+const RAMP_MS: u32 = 2000;
+
+/// How long the chase pattern lights each channel for
+const CHASE_STEP_MS: u32 = 250;
+
+/// Patterns sent to every channel in a layout, for checking wiring and output
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TestPattern {
+    Full, // Every channel on
+    Ramp, // Every channel fading up together, over and over
+    Chase, // One channel on at a time, in dmx order
+}
+
+/// One step of a channel test, e.g. a single channel of a chase, and the channels it lights
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestStep {
+    pub label: String,
+    pub duration_ms: u32,
+    pub channels: Vec<(u32, u32)>, // (chanid, channel_dmx) of each channel lit
+}
+
+impl TestStep {
+    /// A step that lights one channel, labeled with what the channel is
+    pub fn channel(channel: &Channel, duration_ms: u32) -> TestStep {
+        TestStep {
+            label: format!("{} ({}, internal {}, dmx {})",
+                channel.name,
+                channel.color,
+                channel.channel_internal,
+                channel.channel_dmx),
+            duration_ms: duration_ms,
+            channels: vec![(channel.chanid, channel.channel_dmx)]
+        }
+    }
+
+    /// A step that lights some channels together, labeled with their internal numbers
+    pub fn group(name: &str, channels: &[&Channel], duration_ms: u32) -> TestStep {
+        let internals = channels.iter()
+            .map(|channel| channel.channel_internal.to_string())
+            .collect::<Vec<String>>();
+        TestStep {
+            label: format!("{} (internal {})", name, internals.join(", ")),
+            duration_ms: duration_ms,
+            channels: channels.iter()
+                .map(|channel| (channel.chanid, channel.channel_dmx))
+                .collect()
+        }
+    }
+}
+
+impl TestPattern {
+    /// Value of a channel at a time in the pattern. Channels are numbered by
+    /// their place in the layout, in dmx order.
+    pub fn value(&self, channel: usize, num_channels: usize, time_ms: u32) -> u16 {
+        match *self {
+            TestPattern::Full => MAX_VALUE,
+            TestPattern::Ramp => ((time_ms % RAMP_MS) * MAX_VALUE as u32 / (RAMP_MS - 1)) as u16,
+            TestPattern::Chase => {
+                let step = (time_ms / CHASE_STEP_MS) as usize;
+                if num_channels > 0 && step % num_channels == channel { MAX_VALUE } else { 0 }
+            },
+        }
+    }
+}
+
+impl FromStr for TestPattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TestPattern, Error> {
+        match s {
+            "full" => Ok(TestPattern::Full),
+            "ramp" => Ok(TestPattern::Ramp),
+            "chase" => Ok(TestPattern::Chase),
+            _ => Err(Error::InvalidTestPattern(s.to_owned())),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::str::FromStr;
+
+use error::Error;
+use project_types::TimingTrack;
+
+
+/// A time in a sequence, given in milliseconds or as a mark in one of the
+/// sequence's timing tracks: `beats#4` is the start of the 4th beat, and
+/// `phrases#2.end` is the end of the 2nd phrase.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimeAnchor {
+    Ms(u32),
+    Mark {
+        track: String,
+        index: usize, // Starting from 1
+        end: bool,
+    },
+}
+
+impl TimeAnchor {
+    /// Works out the time in milliseconds, using the sequence's timing tracks.
+    /// Marks without an end time end where they start.
+    pub fn resolve(&self, tracks: &Vec<TimingTrack>) -> Result<u32, Error> {
+        match *self {
+            TimeAnchor::Ms(ms) => Ok(ms),
+            TimeAnchor::Mark { ref track, index, end } => {
+                let timing_track = try!(tracks.iter()
+                    .find(|timing_track| timing_track.name == *track)
+                    .ok_or(Error::InvalidTimeAnchor(format!("No timing track named '{}'", track))));
+                let mark = try!(timing_track.marks.get(index - 1)
+                    .ok_or(Error::InvalidTimeAnchor(format!("Timing track '{}' has {} marks, not {}",
+                        track, timing_track.marks.len(), index))));
+                Ok(if end { mark.end_ms.unwrap_or(mark.start_ms) } else { mark.start_ms })
+            },
+        }
+    }
+}
+
+impl FromStr for TimeAnchor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TimeAnchor, Error> {
+        if let Ok(ms) = s.parse::<u32>() {
+            return Ok(TimeAnchor::Ms(ms));
+        }
+        let invalid = || Error::InvalidTimeAnchor(
+            format!("Expected milliseconds or track#index[.end]: {}", s));
+
+        let hash = try!(s.rfind('#').ok_or(invalid()));
+        let (track, mark) = (&s[..hash], &s[hash + 1..]);
+        let (index, end) = if mark.ends_with(".end") {
+            (&mark[..mark.len() - 4], true)
+        } else {
+            (mark, false)
+        };
+        match index.parse::<usize>() {
+            Ok(index) if index > 0 && !track.is_empty() => Ok(TimeAnchor::Mark {
+                track: track.to_owned(),
+                index: index,
+                end: end
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use regex::Regex;
+use std::path::Path;
+
+use error::Error;
+use project_types::TimingMark;
+use utils;
+
+/// An XML attribute and its value
+const ATTRIBUTE_RE: &'static str = r#"([\w:]+)\s*=\s*"([^"]*)""#;
+
+/// An ISO 8601 duration, like PT1M2.5S
+const ISO_DURATION_RE: &'static str = r"^P(?:(\d+)D)?(?:T(?:(\d+)H)?(?:(\d+)M)?(?:(\d+(?:\.\d+)?)S)?)?$";
+
+
+/// Timing tracks read from another program's files:
+///
+/// - Audacity label tracks (.txt): start, end and label on each line, tab separated,
+///   with times in seconds. One track, named after the file.
+/// - xLights timing exports (.xtiming): each `<timing>` is a track, with a mark for
+///   each `<Effect>` (times in milliseconds). Extra layers (e.g. the words and
+///   phonemes of lyrics) become tracks of their own, numbered after the first.
+/// - Vixen 3 mark collections (.xml, .tim or .vmc): each `<MarkCollection>` is a
+///   track, with times as ISO 8601 durations like PT1.5S.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimingFile {
+    pub tracks: Vec<(String, Vec<TimingMark>)>,
+}
+
+impl TimingFile {
+    /// Reads a timing file, picking the format by its extension
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<TimingFile, Error> {
+        let text = try!(utils::file_as_string(path.as_ref()));
+        let extension = path.as_ref().extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match extension.as_str() {
+            "txt" => {
+                let name = try!(path.as_ref().file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .ok_or(Error::InvalidFileName));
+                TimingFile::parse_audacity(&text, name)
+            },
+            "xtiming" => TimingFile::parse_xlights(&text),
+            "xml" | "tim" | "vmc" => TimingFile::parse_vixen(&text),
+            _ => Err(Error::UnsupportedFileType(extension)),
+        }
+    }
+
+    /// Parses an Audacity label track into a track with the given name
+    pub fn parse_audacity(text: &str, name: &str) -> Result<TimingFile, Error> {
+        let mut marks = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            // Lines starting with a backslash hold the frequencies of spectral labels
+            if line.trim().is_empty() || line.starts_with('\\') {
+                continue;
+            }
+            let fields = line.splitn(3, '\t').collect::<Vec<&str>>();
+            let invalid = |description: &str| Error::InvalidTimingFile(
+                format!("{} on line {}: {}", description, i + 1, line));
+            if fields.len() < 2 {
+                return Err(invalid("Expected start and end times"));
+            }
+            let start_ms = try!(parse_seconds(fields[0]).ok_or(invalid("Invalid start time")));
+            let end_ms = try!(parse_seconds(fields[1]).ok_or(invalid("Invalid end time")));
+            if end_ms < start_ms {
+                return Err(invalid("End time is before start time"));
+            }
+            marks.push(TimingMark {
+                start_ms: start_ms,
+                end_ms: if end_ms > start_ms { Some(end_ms) } else { None },
+                label: fields.get(2).map(|label| label.trim()).and_then(non_empty)
+            });
+        }
+        Ok(TimingFile { tracks: vec![(name.to_owned(), marks)] })
+    }
+
+    /// Parses an xLights timing export
+    pub fn parse_xlights(text: &str) -> Result<TimingFile, Error> {
+        let timing_re = Regex::new(r"(?s)<timing\s([^>]*)>(.*?)</timing>").unwrap();
+        let layer_re = Regex::new(r"(?s)<EffectLayer[^>]*>(.*?)</EffectLayer>").unwrap();
+        let effect_re = Regex::new(r"<Effect\s([^>]*?)/?>").unwrap();
+        let attribute_re = Regex::new(ATTRIBUTE_RE).unwrap();
+
+        let mut tracks = Vec::new();
+        for timing in timing_re.captures_iter(text) {
+            let name = try!(attribute(&attribute_re, &timing[1], "name")
+                .ok_or(Error::InvalidTimingFile("Timing has no name".to_owned())));
+            for (i, layer) in layer_re.captures_iter(&timing[2]).enumerate() {
+                let mut marks = Vec::new();
+                for effect in effect_re.captures_iter(&layer[1]) {
+                    let time = |key: &str| -> Result<u32, Error> {
+                        attribute(&attribute_re, &effect[1], key)
+                            .and_then(|value| value.parse::<u32>().ok())
+                            .ok_or(Error::InvalidTimingFile(
+                                format!("Invalid {} in timing '{}': {}", key, name, &effect[0])))
+                    };
+                    let start_ms = try!(time("starttime"));
+                    let end_ms = try!(time("endtime"));
+                    marks.push(TimingMark {
+                        start_ms: start_ms,
+                        end_ms: if end_ms > start_ms { Some(end_ms) } else { None },
+                        label: attribute(&attribute_re, &effect[1], "label").and_then(|label| non_empty(&label))
+                    });
+                }
+                let track_name = if i == 0 { name.clone() } else { format!("{} {}", name, i + 1) };
+                tracks.push((track_name, marks));
+            }
+        }
+
+        if tracks.is_empty() {
+            return Err(Error::InvalidTimingFile("No timings found".to_owned()));
+        }
+        Ok(TimingFile { tracks: tracks })
+    }
+
+    /// Parses Vixen 3 mark collections. Older versions stored just a list of
+    /// times in `<Marks>`, newer ones a `<Mark>` with a start, duration and text.
+    pub fn parse_vixen(text: &str) -> Result<TimingFile, Error> {
+        let collection_re = Regex::new(r"(?s)<(?:\w+:)?MarkCollection(?:\s[^>]*)?>(.*?)</(?:\w+:)?MarkCollection>").unwrap();
+        let name_re = Regex::new(r"<(?:\w+:)?Name>([^<]*)</").unwrap();
+        let mark_re = Regex::new(r"(?s)<(?:\w+:)?Mark(?:\s[^>]*)?>(.*?)</(?:\w+:)?Mark>").unwrap();
+        let start_re = Regex::new(r"<(?:\w+:)?StartTime>([^<]*)</").unwrap();
+        let duration_re = Regex::new(r"<(?:\w+:)?Duration>([^<]*)</").unwrap();
+        let text_re = Regex::new(r"<(?:\w+:)?Text>([^<]*)</").unwrap();
+        let marks_re = Regex::new(r"(?s)<(?:\w+:)?Marks(?:\s[^>]*)?>(.*?)</(?:\w+:)?Marks>").unwrap();
+        let time_re = Regex::new(r"<(?:\w+:)?duration>([^<]*)</").unwrap();
+        let iso_duration_re = Regex::new(ISO_DURATION_RE).unwrap();
+
+        let mut tracks = Vec::new();
+        for collection in collection_re.captures_iter(text) {
+            let name = match name_re.captures(&collection[1]) {
+                Some(name) => unescape(&name[1]),
+                None => return Err(Error::InvalidTimingFile("Mark collection has no name".to_owned())),
+            };
+            let invalid = |time: &str| Error::InvalidTimingFile(
+                format!("Invalid time in mark collection '{}': {}", name, time));
+
+            let mut marks = Vec::new();
+            for mark in mark_re.captures_iter(&collection[1]) {
+                let start = match start_re.captures(&mark[1]) {
+                    Some(start) => start[1].to_owned(),
+                    None => return Err(invalid(&mark[0])),
+                };
+                let start_ms = try!(parse_iso_duration(&iso_duration_re, &start).ok_or(invalid(&start)));
+                let duration_ms = match duration_re.captures(&mark[1]) {
+                    Some(duration) => try!(parse_iso_duration(&iso_duration_re, &duration[1]).ok_or(invalid(&duration[1]))),
+                    None => 0,
+                };
+                marks.push(TimingMark {
+                    start_ms: start_ms,
+                    end_ms: if duration_ms > 0 { Some(start_ms + duration_ms) } else { None },
+                    label: text_re.captures(&mark[1]).and_then(|label| non_empty(&unescape(&label[1])))
+                });
+            }
+            if marks.is_empty() {
+                if let Some(times) = marks_re.captures(&collection[1]) {
+                    for time in time_re.captures_iter(&times[1]) {
+                        let start_ms = try!(parse_iso_duration(&iso_duration_re, &time[1]).ok_or(invalid(&time[1])));
+                        marks.push(TimingMark::at(start_ms));
+                    }
+                }
+            }
+            tracks.push((name, marks));
+        }
+
+        if tracks.is_empty() {
+            return Err(Error::InvalidTimingFile("No mark collections found".to_owned()));
+        }
+        Ok(TimingFile { tracks: tracks })
+    }
+}
+
+/// Parses a time in seconds, like 1.5, into milliseconds
+fn parse_seconds(text: &str) -> Option<u32> {
+    match text.trim().parse::<f64>() {
+        Ok(seconds) if seconds >= 0.0 => Some((seconds * 1000.0).round() as u32),
+        _ => None,
+    }
+}
+
+/// Parses an ISO 8601 duration, like PT1M2.5S, into milliseconds, given
+/// ISO_DURATION_RE compiled
+fn parse_iso_duration(duration_re: &Regex, text: &str) -> Option<u32> {
+    let captures = match duration_re.captures(text.trim()) {
+        Some(captures) => captures,
+        None => return None,
+    };
+    let part = |i: usize| -> f64 {
+        captures.get(i).and_then(|part| part.as_str().parse::<f64>().ok()).unwrap_or(0.0)
+    };
+    let seconds = ((part(1) * 24.0 + part(2)) * 60.0 + part(3)) * 60.0 + part(4);
+    Some((seconds * 1000.0).round() as u32)
+}
+
+/// Finds the value of an XML attribute, given ATTRIBUTE_RE compiled
+fn attribute(attribute_re: &Regex, attributes: &str, key: &str) -> Option<String> {
+    attribute_re.captures_iter(attributes)
+        .find(|captures| &captures[1] == key)
+        .map(|captures| unescape(&captures[2]))
+}
+
+/// Replaces XML entities with the characters they stand for
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    if text.is_empty() { None } else { Some(text.to_owned()) }
+}
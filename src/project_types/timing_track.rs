@@ -0,0 +1,28 @@
+/// Named times in a sequence, e.g. its beats or lyric phrases, in order
+#[derive(Clone, Debug, PartialEq, RustcEncodable)]
+pub struct TimingTrack {
+    pub trackid: u32,
+    pub seqid: u32,
+    pub name: String,
+    pub marks: Vec<TimingMark>,
+}
+
+/// A time in a timing track, in milliseconds from the start of the sequence.
+/// Marks can cover a span of time (e.g. a phrase) and have a label.
+#[derive(Clone, Debug, PartialEq, RustcEncodable)]
+pub struct TimingMark {
+    pub start_ms: u32,
+    pub end_ms: Option<u32>,
+    pub label: Option<String>,
+}
+
+impl TimingMark {
+    /// Creates a mark at a single time, with no label
+    pub fn at(start_ms: u32) -> TimingMark {
+        TimingMark {
+            start_ms: start_ms,
+            end_ms: None,
+            label: None
+        }
+    }
+}
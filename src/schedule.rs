@@ -0,0 +1,99 @@
+//! This module stores project schedules and decides what should be playing when
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::thread;
+use std::time;
+
+use chrono::{Duration, Local, NaiveDateTime};
+
+use dao::{ProjectDao, SequenceDao};
+use error::Error;
+use project_types::{Schedule, ScheduleDecision};
+
+/// Longest time the system clock sleeps for before checking the time again
+const MAX_SLEEP_MS: i64 = 60000;
+
+
+/// Tells the time for schedules. Shows run on local time.
+pub trait Clock {
+    /// Current local date and time
+    fn now(&self) -> NaiveDateTime;
+
+    /// Waits until a local time. Returns straight away if it has passed.
+    fn sleep_until(&self, time: NaiveDateTime);
+}
+
+/// The system's clock, in its time zone
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+
+    fn sleep_until(&self, time: NaiveDateTime) {
+        // Sleep in steps, so changes to the clock (e.g. daylight saving) are noticed
+        loop {
+            let remaining = time - self.now();
+            if remaining <= Duration::zero() {
+                return;
+            }
+            let sleep_ms = remaining.num_milliseconds().min(MAX_SLEEP_MS).max(1);
+            thread::sleep(time::Duration::from_millis(sleep_ms as u64));
+        }
+    }
+}
+
+/// Reads a schedule file and sets it as a project's schedule
+pub fn set_schedule<P: AsRef<Path>, PD: ProjectDao, SD: SequenceDao>(
+    proj_dao: &PD,
+    seq_dao: &SD,
+    proj_name: &str,
+    schedule_path: P
+) -> Result<Schedule, Error> {
+
+    // Check that project exists
+    let _ = try!(proj_dao.get_project(proj_name));
+
+    let schedule = try!(Schedule::read(schedule_path));
+
+    // Check that the intermission sequence exists
+    if let Some(ref intermission) = schedule.intermission {
+        let _ = try!(seq_dao.get_sequence(intermission.seqid));
+    }
+
+    try!(proj_dao.set_schedule(proj_name, &schedule));
+    Ok(schedule)
+}
+
+/// Fetches a project's schedule
+pub fn get_schedule<PD: ProjectDao>(
+    proj_dao: &PD,
+    proj_name: &str
+) -> Result<Schedule, Error> {
+    proj_dao.get_schedule(proj_name)
+}
+
+/// Decides what a project should be playing now
+pub fn get_schedule_decision<C: Clock, PD: ProjectDao, SD: SequenceDao>(
+    clock: &C,
+    proj_dao: &PD,
+    seq_dao: &SD,
+    proj_name: &str
+) -> Result<ScheduleDecision, Error> {
+
+    let project = try!(proj_dao.get_project(proj_name));
+    let schedule = try!(proj_dao.get_schedule(proj_name));
+
+    // Get the duration of every sequence that could play
+    let mut durations = BTreeMap::new();
+    let intermission_seqid = schedule.intermission.as_ref().map(|intermission| intermission.seqid);
+    for seqid in project.playlist.iter().chain(intermission_seqid.iter()) {
+        let sequence = try!(seq_dao.get_sequence(*seqid));
+        durations.insert(*seqid, sequence.num_frames * sequence.frame_duration_ms);
+    }
+
+    schedule.decide(&project.playlist, &durations, clock.now())
+}
@@ -1,17 +1,28 @@
 //! This module manages project sequences
 
 use rustc_serialize::json;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 
-use sfml::audio::Music;
-
+use audio::{self, AudioBackend};
 use error::Error;
-use project_types::{Sequence};
-use dao::{ChannelDao, DataDao, LayoutDao, PermissionDao, ProjectDao, SequenceDao, UserDao};
+use effects::{BlendMode, Envelope, Scene, MAX_VALUE};
+use music::MusicStore;
+use project_types::{DataEdit, DurationPolicy, EditTargets, Fixture, ReactiveMapping, ResampleMode, Sequence, SequenceIssue, SequenceScript, TestStep, TimeAnchor, TimingFile, TimingMark, TimingTrack};
+use dao::{ChannelDao, DataDao, FixtureDao, LayoutDao, PermissionDao, ProjectDao, SequenceDao, TimingDao, UserDao};
 use utils;
 
-/// Creates a new sequence based on proton-vixen-converter data
-pub fn new_vixen_sequence<P: AsRef<Path>, CD: ChannelDao, DD: DataDao, LD: LayoutDao, SD: SequenceDao>(
+/// How long reactive lights take to fade out after the music gets quieter
+const REACTIVE_DECAY_MS: u32 = 200;
+
+/// Name of the timing track that labels the steps of a saved channel test
+pub const TEST_STEPS_TRACK: &'static str = "test steps";
+
+/// Creates a new sequence based on proton-vixen-converter data. Returns its id,
+/// and how its duration doesn't match its music if the policy is to warn.
+pub fn new_vixen_sequence<P: AsRef<Path>, AB: AudioBackend, CD: ChannelDao, DD: DataDao, LD: LayoutDao, SD: SequenceDao>(
+    audio_backend: &AB,
+    music_store: &MusicStore,
     chan_dao: &CD,
     data_dao: &DD,
     layout_dao: &LD,
@@ -21,32 +32,32 @@ pub fn new_vixen_sequence<P: AsRef<Path>, CD: ChannelDao, DD: DataDao, LD: Layou
     seq_duration_ms: u32,
     frame_duration_ms: u32,
     data_file_path: P,
-    layout_id: u32
-) -> Result<u32, Error> {
+    layout_id: u32,
+    duration_policy: DurationPolicy
+) -> Result<(u32, Option<SequenceIssue>), Error> {
 
     // Get layout (also checks if it exists)
     let layout = try!(layout_dao.get_layout(layout_id));
 
     // Make sure the music file is a valid format
-    try!(validate_file_type(&music_file_path));
-
-    // Get name of music file from path
-    let music_file_name = try!(utils::file_name_from_path(&music_file_path));
+    try!(audio::validate_file_type(&music_file_path));
 
     // Get duration of music file
-    let music_duration_sec = try!(get_music_duration_sec(&music_file_path));
-    
-    // Create sequence
-    let sequence = try!(
+    let music_duration_ms = try!(audio_backend.get_info(music_file_path.as_ref())).duration_ms;
+
+    // Create sequence, and only copy the music file into the store once it's valid
+    let mut sequence = try!(
         Sequence::new(
             name,
-            &music_file_name,
-            music_duration_sec,
+            "",
+            music_duration_ms,
             seq_duration_ms,
             Some(frame_duration_ms),
             &layout
         )
     );
+    let duration_issue = try!(check_new_duration(&sequence, duration_policy));
+    sequence.music_file_name = try!(music_store.add(&music_file_path));
 
     // Try to add sequence
     let seq = try!(seq_dao.new_sequence(&sequence));
@@ -71,11 +82,14 @@ pub fn new_vixen_sequence<P: AsRef<Path>, CD: ChannelDao, DD: DataDao, LD: Layou
         try!(data_dao.new_data(seq.seqid, chanid, chan_data));
     }
 
-    Ok(seq.seqid)
+    Ok((seq.seqid, duration_issue.map(|issue| SequenceIssue { seqid: seq.seqid, ..issue })))
 }
 
-/// Creates a new sequence
-pub fn new_sequence<P: AsRef<Path>, DD: DataDao, LD: LayoutDao, SD: SequenceDao>(
+/// Creates a new sequence with no data. Returns its id, and how its duration
+/// doesn't match its music if the policy is to warn.
+pub fn new_sequence<P: AsRef<Path>, AB: AudioBackend, DD: DataDao, LD: LayoutDao, SD: SequenceDao>(
+    audio_backend: &AB,
+    music_store: &MusicStore,
     data_dao: &DD,
     layout_dao: &LD,
     seq_dao: &SD,
@@ -83,8 +97,9 @@ pub fn new_sequence<P: AsRef<Path>, DD: DataDao, LD: LayoutDao, SD: SequenceDao>
     music_file_path: P,
     seq_duration_ms: u32,
     frame_duration_ms: Option<u32>,
-    layout_id: Option<u32>
-) -> Result<u32, Error> {
+    layout_id: Option<u32>,
+    duration_policy: DurationPolicy
+) -> Result<(u32, Option<SequenceIssue>), Error> {
 
     // Get layout (also checks if it exists)
     let lid = match layout_id {
@@ -98,25 +113,24 @@ pub fn new_sequence<P: AsRef<Path>, DD: DataDao, LD: LayoutDao, SD: SequenceDao>
     let layout = try!(layout_dao.get_layout(lid));
 
     // Make sure the music file is a valid format
-    try!(validate_file_type(&music_file_path));
-
-    // Get name of music file from path
-    let music_file_name = try!(utils::file_name_from_path(&music_file_path));
+    try!(audio::validate_file_type(&music_file_path));
 
     // Get duration of music file
-    let music_duration_sec = try!(get_music_duration_sec(&music_file_path));
-    
-    // Create sequence with no data
-    let sequence = try!(
+    let music_duration_ms = try!(audio_backend.get_info(music_file_path.as_ref())).duration_ms;
+
+    // Create sequence with no data, and only copy the music file into the store once it's valid
+    let mut sequence = try!(
         Sequence::new(
             name,
-            &music_file_name,
-            music_duration_sec,
+            "",
+            music_duration_ms,
             seq_duration_ms,
             frame_duration_ms,
             &layout
         )
     );
+    let duration_issue = try!(check_new_duration(&sequence, duration_policy));
+    sequence.music_file_name = try!(music_store.add(&music_file_path));
 
     // Try to add sequence
     let seq = try!(seq_dao.new_sequence(&sequence));
@@ -128,7 +142,7 @@ pub fn new_sequence<P: AsRef<Path>, DD: DataDao, LD: LayoutDao, SD: SequenceDao>
     let seq_data = vec![0; sequence.num_frames as usize];
     let _ = try!(data_dao.new_data_default(seq.seqid, channel_ids, seq_data));
 
-    Ok(seq.seqid)
+    Ok((seq.seqid, duration_issue.map(|issue| SequenceIssue { seqid: seq.seqid, ..issue })))
 }
 
 /// Adds a sequence to the project's playlist at the given index
@@ -166,8 +180,7 @@ pub fn remove_sequence<PD: ProjectDao>(
     let new_project = try!(project.remove_sequence(seqid));
     project_dao.update_project(new_project)
 
-    // TODO: Remove sequence's music file if not used elsewhere in playlist
-
+    // The sequence's music stays in the store until gc-music removes it
 }
 
 /// Deletes sequence from storage
@@ -186,41 +199,785 @@ pub fn delete_sequence<P: AsRef<Path>, PD: PermissionDao, UD: UserDao, SD: Seque
     Err(Error::TodoErr)
 }
 
+/// Checks the durations of the sequences in a project's playlist against their music
+pub fn check_sequences<PD: ProjectDao, SD: SequenceDao>(
+    proj_dao: &PD,
+    seq_dao: &SD,
+    proj_name: &str
+) -> Result<Vec<SequenceIssue>, Error> {
+
+    let project = try!(proj_dao.get_project(proj_name));
+    let mut issues = Vec::new();
+    for seqid in &project.playlist {
+        let sequence = try!(seq_dao.get_sequence(*seqid));
+        if let Some(issue) = sequence.check_duration() {
+            issues.push(issue);
+        }
+    }
+    Ok(issues)
+}
+
+/// Checks that a music file is supported and reads its duration, sample rate, and channels
+pub fn get_music_info<P: AsRef<Path>, AB: AudioBackend>(
+    audio_backend: &AB,
+    music_file_path: P
+) -> Result<audio::AudioInfo, Error> {
+    try!(audio::validate_file_type(&music_file_path));
+    audio_backend.get_info(music_file_path.as_ref())
+}
+
 /// Fetches and returns a sequence
 pub fn get_sequence<SD: SequenceDao>(seq_dao: &SD, seqid: u32) -> Result<Sequence, Error> {
     seq_dao.get_sequence(seqid)
 }
 
-/// Check that the music file is a valid format
-/// Full list of supported formats can be found at
-/// http://www.rust-sfml.org/doc/rsfml/audio/struct.Music.html
-fn validate_file_type<P: AsRef<Path>>(music_file_path: P) -> Result<(), Error> {
-    match music_file_path.as_ref().extension() {
-        Some(extension) => {
-            match extension.to_str() {
-                Some("ogg")  |
-                Some("wav")  |
-                Some("flac") |
-                Some("aiff") |
-                Some("raw") => Ok(()),
-                None => Err(
-                    Error::UnsupportedFileType("Extension is not valid unicode".to_string())
-                    ),
-                Some(ext) => Err(Error::UnsupportedFileType(ext.to_string())),
+/// Fetches and returns a sequence's timing tracks
+pub fn get_timing_tracks<SD: SequenceDao, TD: TimingDao>(
+    seq_dao: &SD,
+    timing_dao: &TD,
+    seqid: u32
+) -> Result<Vec<TimingTrack>, Error> {
+
+    // Check that sequence exists
+    let _ = try!(seq_dao.get_sequence(seqid));
+    timing_dao.get_timing_tracks(seqid)
+}
+
+/// Imports the timing tracks in an Audacity, xLights or Vixen timing file into
+/// a sequence, replacing any tracks with the same names. A file with a single
+/// track can have it stored under a different name. Returns the tracks imported.
+pub fn import_timing<P: AsRef<Path>, SD: SequenceDao, TD: TimingDao>(
+    seq_dao: &SD,
+    timing_dao: &TD,
+    seqid: u32,
+    timing_file_path: P,
+    name: Option<&str>
+) -> Result<Vec<TimingTrack>, Error> {
+
+    let sequence = try!(seq_dao.get_sequence(seqid));
+    let timing_file = try!(TimingFile::read(timing_file_path));
+    if name.is_some() && timing_file.tracks.len() != 1 {
+        return Err(Error::InvalidTimingFile(format!(
+            "File has {} timing tracks, so they can't be renamed", timing_file.tracks.len())));
+    }
+
+    // Check every track before storing any of them
+    let sequence_duration_ms = sequence.num_frames * sequence.frame_duration_ms;
+    let mut tracks = Vec::with_capacity(timing_file.tracks.len());
+    for (track_name, mut marks) in timing_file.tracks {
+        let track_name = name.map(|name| name.to_owned()).unwrap_or(track_name);
+        if track_name.is_empty() || track_name.contains('#') {
+            return Err(Error::InvalidTimingFile(
+                format!("Invalid timing track name: '{}'", track_name)));
+        }
+        if let Some(mark) = marks.iter().find(|mark| mark.start_ms >= sequence_duration_ms) {
+            return Err(Error::InvalidTimingFile(format!(
+                "Mark at {}ms in timing track '{}' is past the end of the sequence ({}ms)",
+                mark.start_ms, track_name, sequence_duration_ms)));
+        }
+        marks.sort_by_key(|mark| mark.start_ms);
+        tracks.push((track_name, marks));
+    }
+
+    let mut imported = Vec::with_capacity(tracks.len());
+    for (track_name, marks) in tracks {
+        imported.push(try!(timing_dao.set_timing_track(seqid, &track_name, &marks)));
+    }
+    Ok(imported)
+}
+
+/// Deletes one of a sequence's timing tracks
+pub fn delete_timing_track<SD: SequenceDao, TD: TimingDao>(
+    seq_dao: &SD,
+    timing_dao: &TD,
+    seqid: u32,
+    name: &str
+) -> Result<(), Error> {
+
+    // Check that sequence exists
+    let _ = try!(seq_dao.get_sequence(seqid));
+    timing_dao.delete_timing_track(seqid, name)
+}
+
+/// Works out a time in a sequence, given in milliseconds or as a mark in one
+/// of its timing tracks (see TimeAnchor)
+pub fn resolve_time<TD: TimingDao>(
+    timing_dao: &TD,
+    seqid: u32,
+    anchor: &str
+) -> Result<u32, Error> {
+
+    let anchor = try!(anchor.parse::<TimeAnchor>());
+    let tracks = try!(get_anchor_tracks(timing_dao, seqid, &[&anchor]));
+    anchor.resolve(&tracks)
+}
+
+/// Gets the timing tracks needed to resolve the anchors, which are only looked
+/// up if some anchor is a mark
+fn get_anchor_tracks<TD: TimingDao>(
+    timing_dao: &TD,
+    seqid: u32,
+    anchors: &[&TimeAnchor]
+) -> Result<Vec<TimingTrack>, Error> {
+
+    let has_marks = anchors.iter().any(|anchor| match **anchor {
+        TimeAnchor::Ms(_) => false,
+        TimeAnchor::Mark { .. } => true,
+    });
+    if has_marks {
+        timing_dao.get_timing_tracks(seqid)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Edits a sequence's data over a time range (in milliseconds) for the given
+/// channels or fixtures. Returns the number of channels edited.
+pub fn edit_data<CD: ChannelDao, DD: DataDao, FD: FixtureDao, SD: SequenceDao>(
+    chan_dao: &CD,
+    data_dao: &DD,
+    fix_dao: &FD,
+    seq_dao: &SD,
+    seqid: u32,
+    t_start_ms: u32,
+    t_end_ms: u32,
+    edit: &DataEdit,
+    targets: &EditTargets
+) -> Result<u32, Error> {
+
+    let sequence = try!(seq_dao.get_sequence(seqid));
+
+    // Convert times to frames, covering any frame the range touches
+    if t_end_ms <= t_start_ms {
+        return Err(Error::InvalidDataEdit(
+            format!("End time {} must be after start time {}", t_end_ms, t_start_ms)));
+    }
+    let start_frame = t_start_ms / sequence.frame_duration_ms;
+    let end_frame = (t_end_ms as f32 / sequence.frame_duration_ms as f32).ceil() as u32;
+    if start_frame >= sequence.num_frames {
+        return Err(Error::InvalidDataEdit(
+            format!("Start time {} is past the end of the sequence", t_start_ms)));
+    }
+    let end_frame = end_frame.min(sequence.num_frames);
+
+    // Edit each channel's data and write it back. A fixture's channels all get
+    // the fixture's index, so chases step fixture by fixture and a fixture
+    // twinkles as one.
+    let groups = try!(get_target_channels(chan_dao, fix_dao, seq_dao, seqid, targets));
+    let mut num_edited = 0;
+    for (i, chan_ids) in groups.iter().enumerate() {
+        for chanid in chan_ids {
+            let mut data = try!(data_dao.get_data(seqid, *chanid));
+            edit.apply(
+                &mut data,
+                start_frame as usize,
+                end_frame as usize,
+                i,
+                groups.len(),
+                sequence.frame_duration_ms);
+            try!(data_dao.update_data(seqid, *chanid, &data));
+            num_edited += 1;
+        }
+    }
+
+    Ok(num_edited)
+}
+
+/// Finds the ids of a sequence's channels to edit, grouped by the target they
+/// belong to (one group per channel, or per fixture), in the order they were given
+fn get_target_channels<CD: ChannelDao, FD: FixtureDao, SD: SequenceDao>(
+    chan_dao: &CD,
+    fix_dao: &FD,
+    seq_dao: &SD,
+    seqid: u32,
+    targets: &EditTargets
+) -> Result<Vec<Vec<u32>>, Error> {
+
+    let seq_chan_ids = try!(seq_dao.get_channel_ids(seqid));
+    let mut groups: Vec<Vec<u32>> = Vec::new();
+
+    match *targets {
+        EditTargets::Channels(ref channels_internal) => {
+            let mut chan_id_by_internal = HashMap::new();
+            for chanid in &seq_chan_ids {
+                let channel = try!(chan_dao.get_channel(*chanid));
+                chan_id_by_internal.insert(channel.channel_internal, *chanid);
+            }
+            for channel_internal in channels_internal {
+                match chan_id_by_internal.get(channel_internal) {
+                    Some(chanid) => groups.push(vec![*chanid]),
+                    None => return Err(Error::InvalidDataEdit(
+                        format!("Sequence has no internal channel {}", channel_internal))),
+                }
             }
         },
-        None => Err(Error::UnsupportedFileType("No file extension".to_string())),
+        EditTargets::Fixtures(ref fixids) => {
+            for fixid in fixids {
+                let fixture = try!(fix_dao.get_fixture(*fixid));
+                if fixture.channels.iter().any(|chanid| !seq_chan_ids.contains(chanid)) {
+                    return Err(Error::InvalidDataEdit(
+                        format!("Fixture {} is not in the sequence's layout", fixid)));
+                }
+                groups.push(fixture.channels.clone());
+            }
+        },
+    }
+
+    // A channel given twice is only edited once, with the first target it's in
+    let mut seen = HashSet::new();
+    for chan_ids in groups.iter_mut() {
+        chan_ids.retain(|chanid| seen.insert(*chanid));
     }
+    groups.retain(|chan_ids| !chan_ids.is_empty());
+    Ok(groups)
 }
 
-/// Extracts the duration of a music file
-fn get_music_duration_sec<P: AsRef<Path>>(path: P) -> Result<u32, Error> {
-    let path_str = &path.as_ref().to_str().expect("Path is invalid");
-    let music = match Music::new_from_file(&path_str) {
-        Some(m) => m,
-        None => return Err(Error::Rsfml("Error reading file.".to_string())),
+/// Creates a copy of a sequence and its data with a new name. Returns the new sequence's id.
+pub fn clone_sequence<DD: DataDao, SD: SequenceDao>(
+    data_dao: &DD,
+    seq_dao: &SD,
+    seqid: u32,
+    name: &str
+) -> Result<u32, Error> {
+
+    let sequence = try!(seq_dao.get_sequence(seqid));
+    let mut clone = sequence.clone();
+    clone.seqid = 0;
+    clone.name = name.to_owned();
+    let clone = try!(seq_dao.new_sequence(&clone));
+
+    // Both sequences use the same layout, so they have the same channels
+    let chan_ids = try!(seq_dao.get_channel_ids(seqid));
+    for chanid in chan_ids {
+        let data = try!(data_dao.get_data(seqid, chanid));
+        try!(data_dao.new_data(clone.seqid, chanid, &data));
+    }
+
+    Ok(clone.seqid)
+}
+
+/// Copies a time range (in milliseconds) of one sequence's data into another
+/// sequence, starting at t_offset_ms. Data is resampled if the sequences' frame
+/// durations differ. Returns the number of channels copied.
+pub fn copy_sequence_data<CD: ChannelDao, DD: DataDao, SD: SequenceDao>(
+    chan_dao: &CD,
+    data_dao: &DD,
+    seq_dao: &SD,
+    src_seqid: u32,
+    t_start_ms: u32,
+    t_end_ms: u32,
+    dest_seqid: u32,
+    t_offset_ms: u32
+) -> Result<u32, Error> {
+
+    let src = try!(seq_dao.get_sequence(src_seqid));
+    let dest = try!(seq_dao.get_sequence(dest_seqid));
+    if t_end_ms <= t_start_ms {
+        return Err(Error::InvalidDataEdit(
+            format!("End time {} must be after start time {}", t_end_ms, t_start_ms)));
+    }
+    if t_start_ms >= src.num_frames * src.frame_duration_ms {
+        return Err(Error::InvalidDataEdit(
+            format!("Start time {} is past the end of sequence {}", t_start_ms, src_seqid)));
+    }
+    if t_offset_ms >= dest.num_frames * dest.frame_duration_ms {
+        return Err(Error::InvalidDataEdit(
+            format!("Offset {} is past the end of sequence {}", t_offset_ms, dest_seqid)));
+    }
+
+    let chan_pairs = try!(get_channel_pairs(chan_dao, seq_dao, &src, &dest));
+    for &(src_chanid, dest_chanid) in &chan_pairs {
+        let src_data = try!(data_dao.get_data(src_seqid, src_chanid));
+        let mut dest_data = try!(data_dao.get_data(dest_seqid, dest_chanid));
+        resample_into(
+            &src_data,
+            src.frame_duration_ms,
+            t_start_ms,
+            &mut dest_data,
+            dest.frame_duration_ms,
+            t_offset_ms,
+            t_end_ms - t_start_ms);
+        try!(data_dao.update_data(dest_seqid, dest_chanid, &dest_data));
+    }
+
+    Ok(chan_pairs.len() as u32)
+}
+
+/// Creates a new sequence made of the given sequences played one after another,
+/// set to the given music. The new sequence uses the first sequence's layout and
+/// frame duration, and the rest are resampled to match. Returns the new sequence's
+/// id, and how its duration doesn't match its music if the policy is to warn.
+pub fn concat_sequences<P: AsRef<Path>, AB: AudioBackend, CD: ChannelDao, DD: DataDao, SD: SequenceDao>(
+    audio_backend: &AB,
+    music_store: &MusicStore,
+    chan_dao: &CD,
+    data_dao: &DD,
+    seq_dao: &SD,
+    name: &str,
+    music_file_path: P,
+    seqids: &Vec<u32>,
+    duration_policy: DurationPolicy
+) -> Result<(u32, Option<SequenceIssue>), Error> {
+
+    let mut sequences = Vec::new();
+    for seqid in seqids {
+        sequences.push(try!(seq_dao.get_sequence(*seqid)));
+    }
+    let first = match sequences.first() {
+        Some(first) => first.clone(),
+        None => return Err(Error::SequenceNotFound(0)),
     };
-    let duration_time = music.get_duration();
-    let duration = duration_time.as_seconds() as u32;
-    Ok(duration)
+
+    let duration_ms = sequences.iter()
+        .map(|sequence| sequence.num_frames * sequence.frame_duration_ms)
+        .fold(0, |total, duration| total + duration);
+    let mut concat = first.clone();
+    concat.seqid = 0;
+    concat.name = name.to_owned();
+    concat.num_frames = (duration_ms as f32 / first.frame_duration_ms as f32).ceil() as u32;
+
+    // The sequences' own music doesn't carry over, so the new music has to fit them all
+    try!(audio::validate_file_type(&music_file_path));
+    concat.music_duration_ms = try!(audio_backend.get_info(music_file_path.as_ref())).duration_ms;
+    concat.music_offset_ms = 0;
+    let duration_issue = try!(check_new_duration(&concat, duration_policy));
+    concat.music_file_name = try!(music_store.add(&music_file_path));
+    let concat = try!(seq_dao.new_sequence(&concat));
+
+    // Fill in each channel's data a sequence at a time
+    let chan_ids = try!(seq_dao.get_channel_ids(concat.seqid));
+    let mut concat_data: HashMap<u32, Vec<u16>> = chan_ids.iter()
+        .map(|chanid| (*chanid, vec![0; concat.num_frames as usize]))
+        .collect();
+    let mut offset_ms = 0;
+    for sequence in &sequences {
+        let chan_pairs = try!(get_channel_pairs(chan_dao, seq_dao, sequence, &concat));
+        let sequence_duration_ms = sequence.num_frames * sequence.frame_duration_ms;
+        for &(src_chanid, dest_chanid) in &chan_pairs {
+            let src_data = try!(data_dao.get_data(sequence.seqid, src_chanid));
+            if let Some(dest_data) = concat_data.get_mut(&dest_chanid) {
+                resample_into(
+                    &src_data,
+                    sequence.frame_duration_ms,
+                    0,
+                    dest_data,
+                    concat.frame_duration_ms,
+                    offset_ms,
+                    sequence_duration_ms);
+            }
+        }
+        offset_ms += sequence_duration_ms;
+    }
+
+    for chanid in &chan_ids {
+        try!(data_dao.new_data(concat.seqid, *chanid, &concat_data[chanid]));
+    }
+
+    Ok((concat.seqid, duration_issue.map(|issue| SequenceIssue { seqid: concat.seqid, ..issue })))
+}
+
+/// Saves channel test steps as a new sequence on a layout, so they can be played
+/// or exported like any other sequence. Each step's channels are fully on for
+/// its duration, and a timing track labels the steps. Returns the new sequence's
+/// id, and how its duration doesn't match the music if it doesn't.
+pub fn save_test_steps<P: AsRef<Path>, AB: AudioBackend, DD: DataDao, LD: LayoutDao, SD: SequenceDao, TD: TimingDao>(
+    audio_backend: &AB,
+    music_store: &MusicStore,
+    data_dao: &DD,
+    layout_dao: &LD,
+    seq_dao: &SD,
+    timing_dao: &TD,
+    name: &str,
+    music_file_path: P,
+    layout_id: u32,
+    steps: &[TestStep]
+) -> Result<(u32, Option<SequenceIssue>), Error> {
+
+    let duration_ms = steps.iter().fold(0, |total, step| total + step.duration_ms);
+    let (seqid, duration_issue) = try!(new_sequence(
+        audio_backend,
+        music_store,
+        data_dao,
+        layout_dao,
+        seq_dao,
+        name,
+        music_file_path,
+        duration_ms,
+        None,
+        Some(layout_id),
+        DurationPolicy::Warn));
+    let sequence = try!(seq_dao.get_sequence(seqid));
+    let frame_ms = sequence.frame_duration_ms;
+    let num_frames = sequence.num_frames as usize;
+
+    // Light each step's channels for the frames it covers
+    let mut data: BTreeMap<u32, Vec<u16>> = BTreeMap::new();
+    let mut marks = Vec::with_capacity(steps.len());
+    let mut start_ms = 0;
+    for step in steps {
+        let end_ms = start_ms + step.duration_ms;
+        let first_frame = (start_ms / frame_ms) as usize;
+        let last_frame = ((end_ms / frame_ms) as usize).min(num_frames);
+        for &(chanid, _) in &step.channels {
+            let chan_data = data.entry(chanid).or_insert_with(|| vec![0; num_frames]);
+            for frame in first_frame..last_frame {
+                chan_data[frame] = MAX_VALUE;
+            }
+        }
+        marks.push(TimingMark {
+            start_ms: start_ms,
+            end_ms: Some(end_ms),
+            label: Some(step.label.clone())
+        });
+        start_ms = end_ms;
+    }
+
+    for (chanid, chan_data) in data {
+        try!(data_dao.update_data(seqid, chanid, &chan_data));
+    }
+    let _ = try!(timing_dao.set_timing_track(seqid, TEST_STEPS_TRACK, &marks));
+    Ok((seqid, duration_issue))
+}
+
+/// Changes a sequence's frame duration, resampling every channel's data to match.
+/// The data, sequence and its sections are all stored at once, so a failure
+/// can't leave them out of step. Returns the sequence's new number of frames.
+pub fn retime_sequence<DD: DataDao, SD: SequenceDao>(
+    data_dao: &DD,
+    seq_dao: &SD,
+    seqid: u32,
+    frame_duration_ms: u32,
+    mode: ResampleMode
+) -> Result<u32, Error> {
+
+    if frame_duration_ms < 25 {
+        return Err(Error::InvalidFrameDuration(frame_duration_ms));
+    }
+    let mut sequence = try!(seq_dao.get_sequence(seqid));
+    let duration_ms = sequence.num_frames * sequence.frame_duration_ms;
+    let num_frames = (duration_ms as f32 / frame_duration_ms as f32).ceil() as u32;
+
+    let chan_ids = try!(seq_dao.get_channel_ids(seqid));
+    let mut new_data = Vec::with_capacity(chan_ids.len());
+    for chanid in chan_ids {
+        let data = try!(data_dao.get_data(seqid, chanid));
+        let mut resampled = mode.resample(&data, sequence.frame_duration_ms, frame_duration_ms);
+        resampled.resize(num_frames as usize, 0);
+        new_data.push((chanid, resampled));
+    }
+
+    let old_frame_duration_ms = sequence.frame_duration_ms;
+    sequence.frame_duration_ms = frame_duration_ms;
+    sequence.num_frames = num_frames;
+    try!(seq_dao.retime_sequence(&sequence, old_frame_duration_ms, &new_data));
+
+    Ok(num_frames)
+}
+
+/// Changes a sequence's duration, padding its data with zeros or trimming it.
+/// Frames are added or removed at the end, or at the start if at_start is true,
+/// in which case the music offset moves with them so the data stays in time with
+/// the music. Returns the sequence's new number of frames.
+pub fn resize_sequence<DD: DataDao, SD: SequenceDao>(
+    data_dao: &DD,
+    seq_dao: &SD,
+    seqid: u32,
+    seq_duration_ms: u32,
+    at_start: bool
+) -> Result<u32, Error> {
+
+    let mut sequence = try!(seq_dao.get_sequence(seqid));
+    let num_frames = (seq_duration_ms as f32 / sequence.frame_duration_ms as f32).ceil() as u32;
+    if num_frames == 0 {
+        return Err(Error::InvalidSequenceDuration(seq_duration_ms));
+    }
+
+    let chan_ids = try!(seq_dao.get_channel_ids(seqid));
+    for chanid in chan_ids {
+        let mut data = try!(data_dao.get_data(seqid, chanid));
+        // Data that doesn't match the sequence's length is fixed up first, so
+        // frames are added or removed from where the sequence says they are
+        data.resize(sequence.num_frames as usize, 0);
+        if at_start && num_frames > sequence.num_frames {
+            let mut padded = vec![0; (num_frames - sequence.num_frames) as usize];
+            padded.extend(data);
+            data = padded;
+        } else if at_start {
+            data = data.split_off((sequence.num_frames - num_frames) as usize);
+        }
+        data.resize(num_frames as usize, 0);
+        try!(data_dao.update_data(seqid, chanid, &data));
+    }
+
+    if at_start {
+        let frames_added = num_frames as i32 - sequence.num_frames as i32;
+        sequence.music_offset_ms += frames_added * sequence.frame_duration_ms as i32;
+    }
+    sequence.num_frames = num_frames;
+    try!(seq_dao.update_sequence(&sequence));
+
+    Ok(num_frames)
+}
+
+/// Sets the time in a sequence its music starts at. Positive offsets leave room for
+/// a lighting intro, and negative offsets skip the start of the music.
+pub fn set_music_offset<SD: SequenceDao>(
+    seq_dao: &SD,
+    seqid: u32,
+    music_offset_ms: i32
+) -> Result<(), Error> {
+
+    let mut sequence = try!(seq_dao.get_sequence(seqid));
+    let seq_duration_ms = (sequence.num_frames * sequence.frame_duration_ms) as i32;
+    let music_duration_ms = sequence.music_duration_ms as i32;
+    if music_offset_ms >= seq_duration_ms || (music_offset_ms < 0 && -(music_offset_ms as i64) >= music_duration_ms as i64) {
+        return Err(Error::InvalidMusicOffset(music_offset_ms));
+    }
+
+    sequence.music_offset_ms = music_offset_ms;
+    seq_dao.update_sequence(&sequence)
+}
+
+/// Compiles a sequence script into a sequence's data, replacing what was there.
+/// Cues are rendered onto the fixtures of the sequence's layout in order,
+/// with later cues layered on top. Returns the number of cues compiled.
+pub fn compile_script<P: AsRef<Path>, CD: ChannelDao, DD: DataDao, FD: FixtureDao, LD: LayoutDao, SD: SequenceDao, TD: TimingDao>(
+    chan_dao: &CD,
+    data_dao: &DD,
+    fix_dao: &FD,
+    layout_dao: &LD,
+    seq_dao: &SD,
+    timing_dao: &TD,
+    seqid: u32,
+    script_path: P
+) -> Result<u32, Error> {
+
+    let script_text = try!(utils::file_as_string(script_path));
+    let script = try!(SequenceScript::parse(&script_text));
+
+    let sequence = try!(seq_dao.get_sequence(seqid));
+    let (scene, fixtures) = try!(load_scene(chan_dao, fix_dao, layout_dao, &sequence));
+
+    let anchors = script.cues.iter()
+        .flat_map(|cue| vec![&cue.start, &cue.end])
+        .collect::<Vec<&TimeAnchor>>();
+    let tracks = try!(get_anchor_tracks(timing_dao, seqid, &anchors));
+
+    // Check every cue before rendering anything
+    let sequence_duration_ms = sequence.num_frames * sequence.frame_duration_ms;
+    let mut cues = Vec::new();
+    for cue in &script.cues {
+        let start_ms = try!(cue.start.resolve(&tracks)
+            .map_err(|e| Error::InvalidScript(cue.line, e.to_string())));
+        let end_ms = try!(cue.end.resolve(&tracks)
+            .map_err(|e| Error::InvalidScript(cue.line, e.to_string())));
+        if end_ms <= start_ms {
+            return Err(Error::InvalidScript(cue.line,
+                format!("Cue ends at {}ms, which isn't after its start at {}ms", end_ms, start_ms)));
+        }
+        if start_ms >= sequence_duration_ms {
+            return Err(Error::InvalidScript(cue.line,
+                format!("Cue starts after the sequence ends at {}ms", sequence_duration_ms)));
+        }
+        let fixids = try!(find_fixtures(&fixtures, &cue.fixtures)
+            .map_err(|name| Error::InvalidScript(cue.line, format!("Layout has no fixture named '{}'", name))));
+        cues.push((cue, start_ms, end_ms, try!(cue.build_effect()), fixids));
+    }
+
+    let mut buffers = scene.new_buffers();
+    for &(cue, start_ms, end_ms, ref effect, ref fixids) in &cues {
+        try!(scene.render(&**effect, cue.blend, fixids, start_ms, end_ms, &mut buffers));
+    }
+
+    // Write every channel, so channels no cue touches are turned off
+    let chan_ids = try!(seq_dao.get_channel_ids(seqid));
+    let empty = vec![0; sequence.num_frames as usize];
+    for chanid in &chan_ids {
+        let data = buffers.get(chanid).unwrap_or(&empty);
+        try!(data_dao.update_data(seqid, *chanid, data));
+    }
+
+    Ok(cues.len() as u32)
+}
+
+/// Generates a sequence's data from its music. Each mapping's fixtures light up
+/// in time with the energy of a band of the music. Only the mapped fixtures'
+/// channels are changed. Returns the number of channels written.
+pub fn generate_reactive<AB: AudioBackend, CD: ChannelDao, DD: DataDao, FD: FixtureDao, LD: LayoutDao, SD: SequenceDao>(
+    audio_backend: &AB,
+    music_store: &MusicStore,
+    chan_dao: &CD,
+    data_dao: &DD,
+    fix_dao: &FD,
+    layout_dao: &LD,
+    seq_dao: &SD,
+    seqid: u32,
+    mappings: &Vec<ReactiveMapping>
+) -> Result<u32, Error> {
+
+    let sequence = try!(seq_dao.get_sequence(seqid));
+    let (scene, fixtures) = try!(load_scene(chan_dao, fix_dao, layout_dao, &sequence));
+
+    // Check every mapping's fixtures before decoding the music
+    let mut targets = Vec::with_capacity(mappings.len());
+    for mapping in mappings {
+        let fixids = try!(find_fixtures(&fixtures, &mapping.fixtures)
+            .map_err(|name| Error::InvalidReactiveMapping(format!("Layout has no fixture named '{}'", name))));
+        targets.push((mapping, fixids));
+    }
+
+    if !music_store.contains(&sequence.music_file_name) {
+        return Err(Error::MusicNotVerified(
+            format!("Music file '{}' is missing", sequence.music_file_name)));
+    }
+    let decoded = try!(audio_backend.decode(&music_store.path(&sequence.music_file_name)));
+    let analysis = audio::analyze(
+        &decoded,
+        sequence.frame_duration_ms,
+        sequence.num_frames,
+        sequence.music_offset_ms);
+
+    // Lights jump up with the music, then fade out over REACTIVE_DECAY_MS
+    let decay = sequence.frame_duration_ms as f32 / REACTIVE_DECAY_MS as f32;
+    let mut buffers = BTreeMap::new();
+    for (mapping, fixids) in targets {
+        let envelope = match analysis.envelopes.iter().find(|&&(band, _)| band == mapping.band) {
+            Some(&(_, ref envelope)) => envelope,
+            None => continue,
+        };
+        let mut levels = Vec::with_capacity(envelope.len());
+        let mut level: f32 = 0.0;
+        for value in envelope {
+            level = (*value as f32 / MAX_VALUE as f32).max(level - decay);
+            levels.push(level);
+        }
+        let effect = Envelope {
+            color: mapping.color,
+            levels: levels,
+            frame_duration_ms: sequence.frame_duration_ms
+        };
+        let end_ms = sequence.num_frames * sequence.frame_duration_ms;
+        try!(scene.render(&effect, BlendMode::Lighten, &fixids, 0, end_ms, &mut buffers));
+    }
+
+    for (chanid, data) in &buffers {
+        try!(data_dao.update_data(seqid, *chanid, data));
+    }
+    Ok(buffers.len() as u32)
+}
+
+/// Loads the layout of a sequence into a scene for rendering effects.
+/// Also returns the layout's fixtures.
+fn load_scene<CD: ChannelDao, FD: FixtureDao, LD: LayoutDao>(
+    chan_dao: &CD,
+    fix_dao: &FD,
+    layout_dao: &LD,
+    sequence: &Sequence
+) -> Result<(Scene, Vec<Fixture>), Error> {
+
+    let layout = try!(layout_dao.get_layout(sequence.layout_id));
+    let mut fixtures = Vec::new();
+    let mut channels = Vec::new();
+    for fixid in &layout.fixtures {
+        let fixture = try!(fix_dao.get_fixture(*fixid));
+        for chanid in &fixture.channels {
+            channels.push(try!(chan_dao.get_channel(*chanid)));
+        }
+        fixtures.push(fixture);
+    }
+    let scene = try!(Scene::new(
+        &layout, &fixtures, &channels, sequence.frame_duration_ms, sequence.num_frames));
+    Ok((scene, fixtures))
+}
+
+/// Finds the ids of the fixtures with the given names (empty for all fixtures).
+/// Returns the first name no fixture has if there is one.
+fn find_fixtures(fixtures: &Vec<Fixture>, names: &Vec<String>) -> Result<Vec<u32>, String> {
+    let mut fixids = Vec::new();
+    for name in names {
+        let matching = fixtures.iter()
+            .filter(|fixture| fixture.name == *name)
+            .map(|fixture| fixture.fixid)
+            .collect::<Vec<u32>>();
+        if matching.is_empty() {
+            return Err(name.to_owned());
+        }
+        for fixid in matching {
+            if !fixids.contains(&fixid) {
+                fixids.push(fixid);
+            }
+        }
+    }
+    Ok(fixids)
+}
+
+/// Pairs up the channels of two sequences, as (from chanid, to chanid).
+/// Sequences with different layouts are compatible if their layouts have the same
+/// internal channels, with the same names and colors.
+fn get_channel_pairs<CD: ChannelDao, SD: SequenceDao>(
+    chan_dao: &CD,
+    seq_dao: &SD,
+    from: &Sequence,
+    to: &Sequence
+) -> Result<Vec<(u32, u32)>, Error> {
+
+    let from_chan_ids = try!(seq_dao.get_channel_ids(from.seqid));
+    if from.layout_id == to.layout_id {
+        return Ok(from_chan_ids.iter().map(|chanid| (*chanid, *chanid)).collect());
+    }
+
+    let to_chan_ids = try!(seq_dao.get_channel_ids(to.seqid));
+    let incompatible = || Error::IncompatibleLayouts(from.layout_id, to.layout_id);
+    if from_chan_ids.len() != to_chan_ids.len() {
+        return Err(incompatible());
+    }
+    let mut from_by_internal = HashMap::new();
+    for chanid in &from_chan_ids {
+        let channel = try!(chan_dao.get_channel(*chanid));
+        from_by_internal.insert(channel.channel_internal, channel);
+    }
+    let mut chan_pairs = Vec::new();
+    for chanid in &to_chan_ids {
+        let channel = try!(chan_dao.get_channel(*chanid));
+        match from_by_internal.get(&channel.channel_internal) {
+            Some(from_channel) if from_channel.name == channel.name
+                && from_channel.color == channel.color => {
+                chan_pairs.push((from_channel.chanid, channel.chanid));
+            },
+            _ => return Err(incompatible()),
+        }
+    }
+    Ok(chan_pairs)
+}
+
+/// Copies duration_ms of data starting at src_start_ms into dest starting at
+/// dest_start_ms. Each dest frame holds the value of the src frame playing at its time.
+fn resample_into(
+    src: &Vec<u16>,
+    src_frame_ms: u32,
+    src_start_ms: u32,
+    dest: &mut Vec<u16>,
+    dest_frame_ms: u32,
+    dest_start_ms: u32,
+    duration_ms: u32
+) {
+    let start_frame = (dest_start_ms / dest_frame_ms) as usize;
+    let end_frame = ((dest_start_ms + duration_ms) as f32 / dest_frame_ms as f32).ceil() as usize;
+    let end_frame = end_frame.min(dest.len());
+    for frame in start_frame..end_frame {
+        let frame_ms = (frame as u32 * dest_frame_ms).max(dest_start_ms);
+        let src_ms = src_start_ms + frame_ms - dest_start_ms;
+        if let Some(value) = ResampleMode::Hold.sample(src, src_ms as f64 / src_frame_ms as f64) {
+            dest[frame] = value;
+        }
+    }
+}
+
+/// Checks a new sequence's duration against its music, following the policy.
+/// Returns the mismatch if it's only to be warned about.
+fn check_new_duration(sequence: &Sequence, duration_policy: DurationPolicy) -> Result<Option<SequenceIssue>, Error> {
+    match (sequence.check_duration(), duration_policy) {
+        (Some(issue), DurationPolicy::Error) => Err(Error::DurationMismatch(issue.message)),
+        (issue, _) => Ok(issue),
+    }
 }
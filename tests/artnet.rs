@@ -0,0 +1,103 @@
+extern crate proton_cli;
+
+use std::net::UdpSocket;
+use std::time::Duration;
+
+use proton_cli::error::Error;
+use proton_cli::output::{self, ArtNetDestinations, ArtNetSender, DmxFrame, OutputDriver};
+
+
+#[test]
+fn maps_universes_to_port_addresses() {
+    assert_eq!(output::artnet_port_address(1), 0);
+    assert_eq!(output::artnet_port_address(16), 0x000f);
+
+    // Universe 17 is sub-net 1, universe 0; universe 257 is net 1
+    assert_eq!(output::artnet_port_address(17), 0x0010);
+    assert_eq!(output::artnet_port_address(257), 0x0100);
+}
+
+#[test]
+fn builds_artnet_packets() {
+    let data = (0..512).map(|slot| (slot % 256) as u8).collect::<Vec<u8>>();
+    let packet = output::artdmx_packet(9, 0x0123, &data);
+    assert_eq!(packet.len(), 530);
+    assert_eq!(&packet[0..8], b"Art-Net\0");
+    assert_eq!(&packet[8..10], &[0x00, 0x50]); // OpDmx, little endian
+    assert_eq!(&packet[10..12], &[0, 14]); // Protocol version
+    assert_eq!(packet[12], 9); // Sequence
+    assert_eq!(&packet[14..16], &[0x23, 0x01]); // SubUni, Net
+    assert_eq!(&packet[16..18], &[0x02, 0x00]); // Length, big endian
+    assert_eq!(&packet[18..], &data[..]);
+
+    let packet = output::artsync_packet();
+    assert_eq!(packet, vec![b'A', b'r', b't', b'-', b'N', b'e', b't', 0, 0x00, 0x52, 0, 14, 0, 0]);
+}
+
+#[test]
+fn parses_destinations() {
+    let destinations = "1=10.0.0.5, 2=10.0.0.6:7000,10.255.255.255".parse::<ArtNetDestinations>()
+        .expect("Error parsing destinations");
+    let address = |universe| destinations.address(universe).map(|address| address.to_string());
+    assert_eq!(address(1), Some("10.0.0.5:6454".to_owned()));
+    assert_eq!(address(2), Some("10.0.0.6:7000".to_owned()));
+    assert_eq!(address(3), Some("10.255.255.255:6454".to_owned()));
+
+    let destinations = "4=10.0.0.5".parse::<ArtNetDestinations>().expect("Error parsing destinations");
+    assert_eq!(destinations.address(1), None);
+
+    for destinations in &["0=10.0.0.5", "a=10.0.0.5", "1=node", "10.0.0.5,10.0.0.6"] {
+        match destinations.parse::<ArtNetDestinations>() {
+            Err(Error::InvalidOutput(_)) => (),
+            other => panic!("Expected invalid output settings for {}, got {:?}", destinations, other),
+        }
+    }
+}
+
+#[test]
+fn sends_artnet_to_nodes() {
+    let receiver = |_| {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("Error binding receiver");
+        socket.set_read_timeout(Some(Duration::from_secs(5))).expect("Error setting timeout");
+        socket
+    };
+    let (first_node, second_node) = (receiver(1), receiver(2));
+    let destinations = format!("2={},{}",
+        second_node.local_addr().unwrap(),
+        first_node.local_addr().unwrap());
+    let destinations = destinations.parse::<ArtNetDestinations>().expect("Error parsing destinations");
+    let mut sender = ArtNetSender::new(destinations, true).expect("Error creating sender");
+
+    let mut frame = DmxFrame::new();
+    frame.set(1, 10);
+    frame.set(513, 20);
+    frame.set(1025, 30);
+    sender.send(&frame).expect("Error sending frame");
+    sender.send(&frame).expect("Error sending frame");
+
+    // Universes 1 and 3 go to the first node, 2 to the second, each followed by a sync.
+    // (op code, sequence, port-address, first channel)
+    let mut buf = [0; 1024];
+    let mut receive = |node: &UdpSocket| {
+        let (len, _) = node.recv_from(&mut buf).expect("Error receiving packet");
+        if buf[9] == 0x52 {
+            assert_eq!(len, 14);
+            (0x52, 0, 0, 0)
+        } else {
+            assert_eq!(len, 530);
+            (buf[9], buf[12], buf[14], buf[18])
+        }
+    };
+    let first = (0..6).map(|_| receive(&first_node)).collect::<Vec<(u8, u8, u8, u8)>>();
+    assert_eq!(first, vec![
+        (0x50, 1, 0, 10), (0x50, 1, 2, 30), (0x52, 0, 0, 0),
+        (0x50, 2, 0, 10), (0x50, 2, 2, 30), (0x52, 0, 0, 0),
+    ]);
+    let second = (0..4).map(|_| receive(&second_node)).collect::<Vec<(u8, u8, u8, u8)>>();
+    assert_eq!(second, vec![(0x50, 1, 1, 20), (0x52, 0, 0, 0), (0x50, 2, 1, 20), (0x52, 0, 0, 0)]);
+
+    // Every universe needs somewhere to go
+    let destinations = "1=127.0.0.1".parse::<ArtNetDestinations>().expect("Error parsing destinations");
+    let mut sender = ArtNetSender::new(destinations, false).expect("Error creating sender");
+    assert!(sender.send(&frame).is_err());
+}
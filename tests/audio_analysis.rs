@@ -0,0 +1,127 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::f32::consts::PI;
+use std::rc::Rc;
+
+use proton_cli::MusicStore;
+use proton_cli::audio::{self, Band, DecodedAudio, SymphoniaBackend};
+use proton_cli::project_types::{Sequence, TimingTrack};
+use tempdir::TempDir;
+
+
+const SAMPLE_RATE: u32 = 8000;
+
+/// Clicks (short bursts of a 1kHz tone) every period_ms over a quiet 60Hz hum
+fn click_track(period_ms: u32, duration_ms: u32, first_click_ms: u32) -> DecodedAudio {
+    let num_samples = (duration_ms * SAMPLE_RATE / 1000) as usize;
+    let period = (period_ms * SAMPLE_RATE / 1000) as usize;
+    let first_click = (first_click_ms * SAMPLE_RATE / 1000) as usize;
+    let click_length = SAMPLE_RATE as usize / 50;
+    let samples = (0..num_samples)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let hum = 0.05 * (2.0 * PI * 60.0 * t).sin();
+            let click = if i >= first_click && (i - first_click) % period < click_length {
+                let decay = 1.0 - ((i - first_click) % period) as f32 / click_length as f32;
+                0.8 * decay * (2.0 * PI * 1000.0 * t).sin()
+            } else {
+                0.0
+            };
+            hum + click
+        })
+        .collect();
+    DecodedAudio {
+        sample_rate: SAMPLE_RATE,
+        samples: samples
+    }
+}
+
+#[test]
+fn finds_tempo_beats_and_onsets() {
+    let music = click_track(500, 10000, 200);
+    let analysis = audio::analyze(&music, 50, 200, 0);
+
+    let bpm = analysis.tempo_bpm.expect("No tempo found");
+    assert!((bpm - 120.0).abs() < 2.0, "Tempo was {}", bpm);
+    assert_eq!(analysis.onsets, (0..20).map(|beat| 200 + beat * 500).collect::<Vec<u32>>());
+    assert!(analysis.beats.len() >= 19 && analysis.beats.len() <= 20, "Beats: {:?}", analysis.beats);
+    for beat in &analysis.beats {
+        assert_eq!((beat + 300) % 500, 0, "Beat off the clicks: {:?}", analysis.beats);
+    }
+
+    // Clicks are in the mid band, the hum in the bass
+    let (_, ref bass) = analysis.envelopes[0];
+    let (_, ref mid) = analysis.envelopes[1];
+    assert_eq!(analysis.envelopes.iter().map(|&(band, _)| band).collect::<Vec<Band>>(), Band::all());
+    assert_eq!((mid.len(), mid[4]), (200, 255));
+    assert!(mid[3] < 40 && mid[5] < 40);
+    assert!(bass[3] > 100);
+}
+
+#[test]
+fn shifts_times_by_music_offset() {
+    let music = click_track(500, 3000, 200);
+
+    // Music starts 1s into the sequence, so the first click is at 1.2s
+    let analysis = audio::analyze(&music, 50, 60, 1000);
+    assert_eq!(analysis.onsets[0], 1200);
+    assert_eq!(*analysis.onsets.last().unwrap(), 2700);
+    assert_eq!(analysis.envelopes[1].1[5], 0);
+
+    // Skipping the first 400ms of music moves the first click before the sequence
+    let analysis = audio::analyze(&music, 50, 60, -400);
+    assert_eq!(analysis.onsets[0], 300);
+
+    // Silence has no tempo
+    let silence = DecodedAudio { sample_rate: SAMPLE_RATE, samples: vec![0.0; 8000] };
+    let analysis = audio::analyze(&silence, 50, 20, 0);
+    assert_eq!((analysis.tempo_bpm, analysis.beats.len(), analysis.onsets.len()), (None, 0, 0));
+    assert_eq!(analysis.envelopes[0].1, vec![0; 20]);
+}
+
+#[test]
+fn stores_analysis_of_sequence_music() {
+    let root = TempDir::new("proton_analysis").expect("Error creating temp dir");
+    let music_store = MusicStore::new(root.path());
+    let file_name = music_store.add("tests/music/Doctor Who Intro.ogg").expect("Error adding music");
+
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(move |seqid| Ok(Sequence {
+        music_duration_ms: 30000,
+        ..common::test_sequence(seqid, 50, 600, 1, &file_name)
+    }));
+
+    let tracks = Rc::new(RefCell::new(Vec::new()));
+    let envelopes = Rc::new(RefCell::new(Vec::new()));
+    let tracks_ref = tracks.clone();
+    let envelopes_ref = envelopes.clone();
+    let mut timing_dao = dao::TimingDaoTesting::new();
+    timing_dao.set_timing_track_fn = Box::new(move |seqid, name, marks| {
+        tracks_ref.borrow_mut().push(name.clone());
+        Ok(TimingTrack { trackid: 1, seqid: seqid, name: name, marks: marks })
+    });
+    timing_dao.set_envelope_fn = Box::new(move |_, band, data| {
+        envelopes_ref.borrow_mut().push((band, data.len()));
+        Ok(())
+    });
+
+    let analysis = proton_cli::analyze_music(&SymphoniaBackend, &music_store, &sequence_dao, &timing_dao, 1)
+        .expect("Error analyzing music");
+    assert!(analysis.beats.len() > 10);
+    assert!(analysis.beats.iter().all(|beat| beat % 50 == 0 && *beat < 30000));
+    assert_eq!(*tracks.borrow(), vec!["beats".to_owned(), "onsets".to_owned()]);
+    assert_eq!(*envelopes.borrow(), vec![
+        ("bass".to_owned(), 600),
+        ("mid".to_owned(), 600),
+        ("high".to_owned(), 600),
+    ]);
+
+    // Music has to be in the store
+    let empty_store = MusicStore::new(root.path().join("Empty"));
+    assert!(proton_cli::analyze_music(&SymphoniaBackend, &empty_store, &sequence_dao, &timing_dao, 1).is_err());
+}
@@ -0,0 +1,52 @@
+extern crate proton_cli;
+
+mod common;
+mod dao;
+
+use proton_cli::error::Error;
+use proton_cli::project_types::{DurationPolicy, IssueLevel, Project, Sequence};
+
+
+#[test]
+fn checks_duration_against_music() {
+    assert_eq!(common::test_sequence(1, 50, 20, 1, "test.ogg").check_duration(), None);
+    assert_eq!(common::test_sequence(1, 50, 120, 1, "test.ogg").check_duration(), None);
+
+    // The music ends at 1.5 seconds with the offset, after the sequence does
+    let late = Sequence { music_offset_ms: 500, ..common::test_sequence(1, 50, 20, 1, "test.ogg") };
+    let issue = late.check_duration().expect("Expected an issue");
+    assert_eq!(issue.level, IssueLevel::Error);
+    let early = Sequence { music_offset_ms: -500, ..common::test_sequence(1, 50, 20, 1, "test.ogg") };
+    assert!(early.check_duration().is_none());
+
+    let issue = common::test_sequence(1, 50, 121, 1, "test.ogg").check_duration().expect("Expected an issue");
+    assert_eq!(issue.level, IssueLevel::Warning);
+    assert_eq!(issue.to_string(), "sequence 1 (Sequence 1): warning: Sequence runs 5050ms past the end of its music");
+
+    assert_eq!("error".parse::<DurationPolicy>().unwrap(), DurationPolicy::Error);
+    assert!("ignore".parse::<DurationPolicy>().is_err());
+}
+
+#[test]
+fn checks_every_sequence_in_playlist() {
+    let mut project_dao = dao::ProjectDaoTesting::new();
+    project_dao.get_project_fn = Box::new(|name| Ok(Project {
+        name: name,
+        playlist: vec![3, 1, 2],
+        layout_id: 1
+    }));
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(|seqid| match seqid {
+        1 => Ok(common::test_sequence(1, 50, 20, 1, "test.ogg")),
+        2 => Ok(common::test_sequence(2, 50, 10, 1, "test.ogg")),
+        3 => Ok(common::test_sequence(3, 50, 200, 1, "test.ogg")),
+        _ => Err(Error::SequenceNotFound(seqid)),
+    });
+
+    let issues = proton_cli::check_sequences(&project_dao, &sequence_dao, "Test")
+        .expect("Error checking sequences");
+    let found = issues.iter()
+        .map(|issue| (issue.seqid, issue.level))
+        .collect::<Vec<(u32, IssueLevel)>>();
+    assert_eq!(found, vec![(3, IssueLevel::Warning), (2, IssueLevel::Error)]);
+}
@@ -0,0 +1,81 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use proton_cli::error::Error;
+use proton_cli::output::{DmxFrame, OutputDriver};
+use proton_cli::project_types::{Channel, Layout, Sequence};
+
+
+/// Keeps every frame it's sent, and whether it was finished. Clones share
+/// what they keep, so a clone can be given away and still be checked.
+#[derive(Clone)]
+#[allow(dead_code)]
+pub struct RecordingDriver {
+    pub frames: Rc<RefCell<Vec<DmxFrame>>>,
+    pub finished: Rc<RefCell<bool>>,
+}
+
+impl RecordingDriver {
+    #[allow(dead_code)]
+    pub fn new() -> RecordingDriver {
+        RecordingDriver { frames: Rc::new(RefCell::new(Vec::new())), finished: Rc::new(RefCell::new(false)) }
+    }
+}
+
+impl OutputDriver for RecordingDriver {
+    fn send(&mut self, frame: &DmxFrame) -> Result<(), Error> {
+        self.frames.borrow_mut().push(frame.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        *self.finished.borrow_mut() = true;
+        Ok(())
+    }
+}
+
+/// White channel named after its id, with no numbers, location or rotation
+#[allow(dead_code)]
+pub fn test_channel(chanid: u32, channel_internal: u32, channel_dmx: u32) -> Channel {
+    test_named_channel(chanid, &format!("Channel {}", chanid), "W", channel_internal, channel_dmx)
+}
+
+/// Channel with the given name and color, with no numbers, location or rotation
+#[allow(dead_code)]
+pub fn test_named_channel(chanid: u32, name: &str, color: &str, channel_internal: u32, channel_dmx: u32) -> Channel {
+    Channel {
+        chanid: chanid,
+        name: name.to_owned(),
+        numbers: (None, None),
+        color: color.to_owned(),
+        channel_internal: channel_internal,
+        channel_dmx: channel_dmx,
+        location: (None, None, None),
+        rotation: (None, None, None)
+    }
+}
+
+/// Layout named Yard with the given fixtures
+#[allow(dead_code)]
+pub fn test_layout(layout_id: u32, fixtures: Vec<u32>) -> Layout {
+    Layout {
+        layout_id: layout_id,
+        name: "Yard".to_owned(),
+        fixtures: fixtures
+    }
+}
+
+/// Sequence set to a second of the given music, starting with the sequence
+#[allow(dead_code)]
+pub fn test_sequence(seqid: u32, frame_duration_ms: u32, num_frames: u32, layout_id: u32, music_file_name: &str) -> Sequence {
+    Sequence {
+        seqid: seqid,
+        name: format!("Sequence {}", seqid),
+        music_file_name: music_file_name.to_owned(),
+        music_duration_ms: 1000,
+        frame_duration_ms: frame_duration_ms,
+        num_frames: num_frames,
+        layout_id: layout_id,
+        music_offset_ms: 0
+    }
+}
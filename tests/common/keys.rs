@@ -4,6 +4,7 @@ use std::env;
 use std::path::PathBuf;
 
 
+#[allow(dead_code)]
 pub enum Key {
 	GoodKeyPub,
 	GoodKeyPem
@@ -16,6 +17,7 @@ fn get_tests_dir_path() -> PathBuf {
 	curr_dir
 }
 
+#[allow(dead_code)]
 pub fn get_key_file_path(key: Key) -> PathBuf {
 	let key_path = match key {
 		Key::GoodKeyPub => GOOD_KEY_PUB,
@@ -1,5 +1,9 @@
-
+mod factories;
 mod keys;
 
+#[allow(unused_imports)]
+pub use self::factories::{RecordingDriver, test_channel, test_layout, test_named_channel, test_sequence};
+#[allow(unused_imports)]
 pub use self::keys::Key as TestKey;
+#[allow(unused_imports)]
 pub use self::keys::get_key_file_path;
@@ -0,0 +1,172 @@
+extern crate proton_cli;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use proton_cli::error::Error;
+use proton_cli::output::{DmxFrame, OutputDriver, RoutedDriver};
+use proton_cli::project_types::{Controller, ControllerProtocol, EditTargets, IssueLevel, PortAssignment, PortRange};
+
+
+/// The garage controller (id 1) takes universes 1 and 2 on 2 ports of 1 channel.
+/// The shed controller (id 2) takes universe 3 on 1 port of 170 channels.
+fn test_controllers(layout_id: u32) -> Result<Vec<Controller>, Error> {
+    let mut garage = try!(Controller::new(layout_id, "Garage", "10.0.0.5", ControllerProtocol::E131, vec![1, 2], 2, 1));
+    garage.controllerid = 1;
+    let mut shed = try!(Controller::new(layout_id, "Shed", "10.0.0.6:6455", ControllerProtocol::ArtNet, vec![3], 1, 170));
+    shed.controllerid = 2;
+    Ok(vec![garage, shed])
+}
+
+/// Channels 1-6 have internal numbers 11-16. Channel 4 isn't patched, and the
+/// others are on universes 1, 1, 2, 3 and 4.
+fn test_daos() -> (dao::ChannelDaoTesting, dao::LayoutDaoTesting) {
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(|chanid| Ok(common::test_channel(
+        chanid, chanid + 10, match chanid { 1 => 1, 2 => 2, 3 => 513, 5 => 1025, 6 => 1537, _ => 0 })));
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1, 2, 3, 4, 5, 6]));
+    layout_dao.get_layout_fn = Box::new(|layout_id| Ok(common::test_layout(layout_id, vec![7])));
+    layout_dao.layout_exists_fn = Box::new(|layout_id| Ok(layout_id == 1));
+    (channel_dao, layout_dao)
+}
+
+#[test]
+fn adds_controllers_and_wires_ports() {
+    assert_eq!(Controller::parse_universes("1-3,7").unwrap(), vec![1, 2, 3, 7]);
+    assert!(Controller::parse_universes("3-1").is_err());
+    assert_eq!("artnet".parse::<ControllerProtocol>().unwrap(), ControllerProtocol::ArtNet);
+    assert!(Controller::new(1, "Porch", "10.0.0", ControllerProtocol::E131, vec![1], 1, 512).is_err());
+    assert!(Controller::new(1, "Porch", "10.0.0.7", ControllerProtocol::E131, vec![1, 1], 1, 512).is_err());
+    assert!(Controller::new(1, "Porch", "10.0.0.7", ControllerProtocol::ArtNet, vec![40000], 1, 512).is_err());
+    match Controller::new(1, "Porch", "10.0.0.7", ControllerProtocol::E131, vec![1], 0, 512) {
+        Err(Error::InvalidController(_)) => (),
+        other => panic!("Expected invalid controller, got {:?}", other),
+    }
+
+    // Names are unique within a layout
+    let (channel_dao, layout_dao) = test_daos();
+    let mut controller_dao = dao::ControllerDaoTesting::new();
+    controller_dao.get_controllers_fn = Box::new(test_controllers);
+    controller_dao.new_controller_fn = Box::new(|mut controller| {
+        controller.controllerid = 3;
+        Ok(controller)
+    });
+    let porch = Controller::new(1, "Porch", "10.0.0.7", ControllerProtocol::E131, vec![4], 4, 510).unwrap();
+    let added = proton_cli::add_controller(&controller_dao, &layout_dao, &porch).expect("Error adding controller");
+    assert_eq!(added.controllerid, 3);
+    let garage = Controller::new(1, "Garage", "10.0.0.7", ControllerProtocol::E131, vec![4], 4, 510).unwrap();
+    assert!(proton_cli::add_controller(&controller_dao, &layout_dao, &garage).is_err());
+    let elsewhere = Controller::new(2, "Porch", "10.0.0.7", ControllerProtocol::E131, vec![4], 4, 510).unwrap();
+    match proton_cli::add_controller(&controller_dao, &layout_dao, &elsewhere) {
+        Err(Error::LayoutNotFound(layout_id)) => assert_eq!(layout_id, 2),
+        other => panic!("Expected layout not found, got {:?}", other),
+    }
+
+    let wired = Rc::new(RefCell::new(Vec::new()));
+    let wired_channels = wired.clone();
+    let wired_fixtures = wired.clone();
+    controller_dao.set_channel_port_fn = Box::new(move |chanid, port| {
+        wired_channels.borrow_mut().push(("channel", chanid, port));
+        Ok(())
+    });
+    controller_dao.set_fixture_port_fn = Box::new(move |fixid, port| {
+        wired_fixtures.borrow_mut().push(("fixture", fixid, port));
+        Ok(())
+    });
+
+    let port = PortAssignment { controllerid: 2, port: 1 };
+    let num_changed = proton_cli::set_port(&channel_dao, &controller_dao, &layout_dao, 1,
+        &EditTargets::Channels(vec![15, 16]), Some(&port))
+        .expect("Error wiring channels");
+    assert_eq!(num_changed, 2);
+    proton_cli::set_port(&channel_dao, &controller_dao, &layout_dao, 1, &EditTargets::Fixtures(vec![7]), None)
+        .expect("Error unwiring fixture");
+    assert_eq!(*wired.borrow(), vec![("channel", 5, Some(port)), ("channel", 6, Some(port)), ("fixture", 7, None)]);
+
+    // Ports have to exist on a controller in the layout
+    match proton_cli::set_port(&channel_dao, &controller_dao, &layout_dao, 1,
+        &EditTargets::Channels(vec![11]), Some(&PortAssignment { controllerid: 9, port: 1 })) {
+        Err(Error::ControllerNotFound(controllerid)) => assert_eq!(controllerid, 9),
+        other => panic!("Expected controller not found, got {:?}", other),
+    }
+    assert!(proton_cli::set_port(&channel_dao, &controller_dao, &layout_dao, 1,
+        &EditTargets::Channels(vec![11]), Some(&PortAssignment { controllerid: 1, port: 3 })).is_err());
+    assert_eq!(wired.borrow().len(), 3);
+}
+
+#[test]
+fn validates_port_capacity_and_universes() {
+    let (channel_dao, layout_dao) = test_daos();
+    let mut controller_dao = dao::ControllerDaoTesting::new();
+    controller_dao.get_controllers_fn = Box::new(test_controllers);
+    controller_dao.get_ports_fn = Box::new(|_| {
+        let mut ports = BTreeMap::new();
+        ports.insert(1, PortAssignment { controllerid: 1, port: 1 });
+        ports.insert(2, PortAssignment { controllerid: 1, port: 1 });
+        ports.insert(3, PortAssignment { controllerid: 2, port: 1 });
+        ports.insert(4, PortAssignment { controllerid: 1, port: 2 });
+        Ok(ports)
+    });
+
+    let issues = proton_cli::validate_controllers(&channel_dao, &controller_dao, &layout_dao, 1)
+        .expect("Error validating controllers");
+    let messages = issues.iter()
+        .map(|issue| (issue.level.clone(), issue.message.as_ref()))
+        .collect::<Vec<(IssueLevel, &str)>>();
+    assert_eq!(messages, vec![
+        (IssueLevel::Error, "Channel 13 (Channel 3) is on universe 2, which controller 'Shed' isn't sent"),
+        (IssueLevel::Warning, "Channel 14 (Channel 4) is wired to controller 'Garage' but isn't patched"),
+        (IssueLevel::Error, "Port 1 of controller 'Garage' is wired to 2 channels, but can only drive 1"),
+        (IssueLevel::Error, "Universe 4 has patched channels, but no controller is sent it"),
+        (IssueLevel::Warning, "2 patched channel(s) aren't wired to a controller port"),
+    ]);
+
+    // Shows aren't sent to controllers with errors
+    match proton_cli::controller_driver(&channel_dao, &controller_dao, &layout_dao, 1, 100, false) {
+        Err(Error::InvalidController(_)) => (),
+        Err(e) => panic!("Expected invalid controller, got {:?}", e),
+        Ok(_) => panic!("Expected invalid controller, got a driver"),
+    }
+
+    // Unpatched channels and channels on other universes aren't in a port's range
+    let ranges = proton_cli::get_port_ranges(&channel_dao, &controller_dao, &layout_dao, 1)
+        .expect("Error getting port ranges");
+    assert_eq!(ranges, vec![
+        PortRange { controller: "Garage".to_owned(), port: 1, universe: 1, start_slot: 1, end_slot: 2, channels: 2 },
+        PortRange { controller: "Shed".to_owned(), port: 1, universe: 2, start_slot: 1, end_slot: 1, channels: 1 },
+    ]);
+}
+
+#[test]
+fn routes_universes_to_their_drivers() {
+    let garage = common::RecordingDriver::new();
+    let shed = common::RecordingDriver::new();
+    let mut driver = RoutedDriver::new();
+    driver.add(Box::new(garage.clone()), vec![1, 2]);
+    driver.add(Box::new(shed.clone()), vec![3]);
+
+    let mut frame = DmxFrame::new();
+    frame.set(1, 255);
+    frame.set(1025, 128);
+    driver.send(&frame).expect("Error sending frame");
+    let mut universe_2 = DmxFrame::new();
+    universe_2.set(513, 10);
+    driver.send(&universe_2).expect("Error sending frame");
+    driver.finish().expect("Error finishing");
+
+    // Drivers are only sent the universes they take, and nothing if there are none
+    let garage_frames = garage.frames.borrow();
+    assert_eq!(garage_frames.len(), 2);
+    assert_eq!(garage_frames[0].universes.keys().cloned().collect::<Vec<u16>>(), vec![1]);
+    assert_eq!(garage_frames[0].universes[&1][0], 255);
+    assert_eq!(garage_frames[1].universes.keys().cloned().collect::<Vec<u16>>(), vec![2]);
+    let shed_frames = shed.frames.borrow();
+    assert_eq!(shed_frames.len(), 1);
+    assert_eq!(shed_frames[0].universes[&3][0], 128);
+    assert!(*garage.finished.borrow() && *shed.finished.borrow());
+}
@@ -0,0 +1,157 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use proton_cli::MusicStore;
+use proton_cli::audio::SymphoniaBackend;
+use proton_cli::error::Error;
+use proton_cli::project_types::{Channel, DurationPolicy, IssueLevel, Sequence};
+use tempdir::TempDir;
+
+
+/// Sequence 1 has 4 frames of 50ms on layout 1 (channels 1 and 2), and sequence 2
+/// has 8 frames of 25ms on layout 2 (channels 11 and 12, matching layout 1's).
+/// Sequence 4 is on layout 3, which only has channel 21. New sequences get id 3
+/// and use layout 1.
+fn test_daos(
+    created: Rc<RefCell<Vec<Sequence>>>,
+    written: Rc<RefCell<Vec<(u32, u32, Vec<u16>)>>>
+) -> (dao::ChannelDaoTesting, dao::DataDaoTesting, dao::SequenceDaoTesting) {
+
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(|chanid| Ok(Channel {
+        name: "Test".to_owned(),
+        ..common::test_channel(chanid, chanid % 10, chanid % 10)
+    }));
+
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(|seqid| match seqid {
+        1 => Ok(common::test_sequence(1, 50, 4, 1, "test.ogg")),
+        2 => Ok(common::test_sequence(2, 25, 8, 2, "test.ogg")),
+        4 => Ok(common::test_sequence(4, 50, 4, 3, "test.ogg")),
+        _ => Err(Error::SequenceNotFound(seqid)),
+    });
+    sequence_dao.get_channel_ids_fn = Box::new(|seqid| match seqid {
+        1 | 3 => Ok(vec![1, 2]),
+        2 => Ok(vec![11, 12]),
+        _ => Ok(vec![21]),
+    });
+    sequence_dao.new_sequence_fn = Box::new(move |sequence| {
+        let mut sequence = sequence.clone();
+        sequence.seqid = 3;
+        created.borrow_mut().push(sequence.clone());
+        Ok(sequence)
+    });
+
+    let written_new = written.clone();
+    let mut data_dao = dao::DataDaoTesting::new();
+    data_dao.get_data_fn = Box::new(|seqid, chanid| match seqid {
+        1 => Ok((1..5).map(|frame| frame * chanid as u16 * 10).collect()),
+        2 => Ok((1..9).collect()),
+        _ => Ok(vec![0; 4]),
+    });
+    data_dao.new_data_fn = Box::new(move |seqid, chanid, data| {
+        written_new.borrow_mut().push((seqid, chanid, data));
+        Ok(())
+    });
+    data_dao.update_data_fn = Box::new(move |seqid, chanid, data| {
+        written.borrow_mut().push((seqid, chanid, data));
+        Ok(())
+    });
+
+    (channel_dao, data_dao, sequence_dao)
+}
+
+#[test]
+fn clones_sequence_with_data() {
+    let created = Rc::new(RefCell::new(Vec::new()));
+    let written = Rc::new(RefCell::new(Vec::new()));
+    let (_, data_dao, sequence_dao) = test_daos(created.clone(), written.clone());
+
+    let seqid = proton_cli::clone_sequence(&data_dao, &sequence_dao, 1, "Experiment")
+        .expect("Error cloning sequence");
+    assert_eq!(seqid, 3);
+    assert_eq!(created.borrow()[0].name, "Experiment");
+    assert_eq!(created.borrow()[0].num_frames, 4);
+    assert_eq!(*written.borrow(), vec![
+        (3, 1, vec![10, 20, 30, 40]),
+        (3, 2, vec![20, 40, 60, 80]),
+    ]);
+}
+
+#[test]
+fn copies_time_range_between_frame_durations() {
+    let created = Rc::new(RefCell::new(Vec::new()));
+    let written = Rc::new(RefCell::new(Vec::new()));
+    let (channel_dao, data_dao, sequence_dao) = test_daos(created, written.clone());
+
+    // 50ms to 150ms of sequence 1 lands on frames 1 through 4 of sequence 2
+    let num_copied = proton_cli::copy_sequence_data(
+        &channel_dao, &data_dao, &sequence_dao, 1, 50, 150, 2, 25)
+        .expect("Error copying data");
+    assert_eq!(num_copied, 2);
+    assert_eq!(*written.borrow(), vec![
+        (2, 11, vec![1, 20, 20, 30, 30, 6, 7, 8]),
+        (2, 12, vec![1, 40, 40, 60, 60, 6, 7, 8]),
+    ]);
+
+    // Layout 3's channels don't match layout 1's
+    match proton_cli::copy_sequence_data(&channel_dao, &data_dao, &sequence_dao, 4, 0, 50, 1, 0) {
+        Err(Error::IncompatibleLayouts(3, 1)) => {},
+        other => panic!("Expected incompatible layouts, got {:?}", other),
+    }
+    assert!(proton_cli::copy_sequence_data(
+        &channel_dao, &data_dao, &sequence_dao, 1, 0, 50, 2, 200).is_err());
+}
+
+#[test]
+fn concatenates_sequences() {
+    let created = Rc::new(RefCell::new(Vec::new()));
+    let written = Rc::new(RefCell::new(Vec::new()));
+    let (channel_dao, data_dao, sequence_dao) = test_daos(created.clone(), written.clone());
+
+    let root = TempDir::new("proton_concat").expect("Error creating temp dir");
+    let music_store = MusicStore::new(root.path());
+
+    let (seqid, issue) = proton_cli::concat_sequences(
+        &SymphoniaBackend, &music_store, &channel_dao, &data_dao, &sequence_dao, "Both",
+        "tests/music/test_1sec.ogg", &vec![1, 2], DurationPolicy::Warn)
+        .expect("Error concatenating sequences");
+    assert_eq!(seqid, 3);
+
+    // The music is cut off, which is only a warning with this policy
+    let issue = issue.expect("Expected a duration mismatch");
+    assert_eq!((issue.seqid, issue.level), (3, IssueLevel::Error));
+    assert_eq!(issue.message, "Sequence is 400ms long, but its music ends at 1020ms and will be cut off");
+    let concat = created.borrow()[0].clone();
+    assert_eq!((concat.frame_duration_ms, concat.num_frames), (50, 8));
+    assert_eq!((concat.music_duration_ms, concat.music_offset_ms), (1020, 0));
+    assert!(music_store.contains(&concat.music_file_name));
+    assert_eq!(*written.borrow(), vec![
+        (3, 1, vec![10, 20, 30, 40, 1, 3, 5, 7]),
+        (3, 2, vec![20, 40, 60, 80, 1, 3, 5, 7]),
+    ]);
+}
+
+#[test]
+fn concat_rejects_music_that_does_not_fit() {
+    let created = Rc::new(RefCell::new(Vec::new()));
+    let written = Rc::new(RefCell::new(Vec::new()));
+    let (channel_dao, data_dao, sequence_dao) = test_daos(created.clone(), written.clone());
+    let root = TempDir::new("proton_concat").expect("Error creating temp dir");
+    let music_store = MusicStore::new(root.path());
+
+    // The sequences last 400ms, which would cut the second of music off
+    match proton_cli::concat_sequences(
+        &SymphoniaBackend, &music_store, &channel_dao, &data_dao, &sequence_dao, "Both",
+        "tests/music/test_1sec.ogg", &vec![1, 2], DurationPolicy::Error) {
+        Err(Error::DurationMismatch(_)) => (),
+        other => panic!("Expected a duration mismatch, got {:?}", other),
+    }
+    assert!(created.borrow().is_empty());
+}
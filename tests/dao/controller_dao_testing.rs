@@ -0,0 +1,65 @@
+extern crate proton_cli;
+
+use std::collections::BTreeMap;
+
+use proton_cli::dao::ControllerDao;
+use proton_cli::error::Error;
+use proton_cli::project_types::{Controller, PortAssignment};
+
+
+/// Implementation of ControllerDao for testing purposes. Uses given functions to return values.
+/// Functions are boxed so their sizes are known (pointers).
+/// The general naming convention used is trait_function_name_fn, for all trait functions.
+/// &str references are converted to Strings so we don't have to deal with lifetime headaches (bookdude13 tried on 12/25/16)
+#[allow(dead_code)]
+pub struct ControllerDaoTesting {
+	pub new_controller_fn: Box<Fn(Controller) -> Result<Controller, Error>>,
+	pub get_controllers_fn: Box<Fn(u32) -> Result<Vec<Controller>, Error>>,
+	pub delete_controller_fn: Box<Fn(u32) -> Result<(), Error>>,
+	pub set_channel_port_fn: Box<Fn(u32, Option<PortAssignment>) -> Result<(), Error>>,
+	pub set_fixture_port_fn: Box<Fn(u32, Option<PortAssignment>) -> Result<(), Error>>,
+	pub get_ports_fn: Box<Fn(Vec<u32>) -> Result<BTreeMap<u32, PortAssignment>, Error>>,
+}
+
+
+impl ControllerDaoTesting {
+	/// Creates a new ControllerDaoTesting struct with all functions set to return Error::TodoErr
+	#[allow(dead_code)]
+	pub fn new() -> ControllerDaoTesting {
+		ControllerDaoTesting {
+			new_controller_fn: Box::new(|_| -> Result<Controller, Error> { Err(Error::TodoErr) }),
+			get_controllers_fn: Box::new(|_| -> Result<Vec<Controller>, Error> { Err(Error::TodoErr) }),
+			delete_controller_fn: Box::new(|_| -> Result<(), Error> { Err(Error::TodoErr) }),
+			set_channel_port_fn: Box::new(|_, _| -> Result<(), Error> { Err(Error::TodoErr) }),
+			set_fixture_port_fn: Box::new(|_, _| -> Result<(), Error> { Err(Error::TodoErr) }),
+			get_ports_fn: Box::new(|_| -> Result<BTreeMap<u32, PortAssignment>, Error> { Err(Error::TodoErr) })
+		}
+	}
+}
+
+/// The Dao implementation simply calls the corresponding stored function
+impl ControllerDao for ControllerDaoTesting {
+	fn new_controller(&self, controller: &Controller) -> Result<Controller, Error> {
+		(self.new_controller_fn)(controller.to_owned())
+	}
+
+	fn get_controllers(&self, layoutid: u32) -> Result<Vec<Controller>, Error> {
+		(self.get_controllers_fn)(layoutid)
+	}
+
+	fn delete_controller(&self, controllerid: u32) -> Result<(), Error> {
+		(self.delete_controller_fn)(controllerid)
+	}
+
+	fn set_channel_port(&self, chanid: u32, port: Option<&PortAssignment>) -> Result<(), Error> {
+		(self.set_channel_port_fn)(chanid, port.cloned())
+	}
+
+	fn set_fixture_port(&self, fixid: u32, port: Option<&PortAssignment>) -> Result<(), Error> {
+		(self.set_fixture_port_fn)(fixid, port.cloned())
+	}
+
+	fn get_ports(&self, chan_ids: &Vec<u32>) -> Result<BTreeMap<u32, PortAssignment>, Error> {
+		(self.get_ports_fn)(chan_ids.to_owned())
+	}
+}
@@ -0,0 +1,46 @@
+extern crate proton_cli;
+
+use std::collections::BTreeMap;
+
+use proton_cli::dao::DimmingDao;
+use proton_cli::error::Error;
+use proton_cli::project_types::DimmingProfile;
+
+
+/// Implementation of DimmingDao for testing purposes. Uses given functions to return values.
+/// Functions are boxed so their sizes are known (pointers).
+/// The general naming convention used is trait_function_name_fn, for all trait functions.
+#[allow(dead_code)]
+pub struct DimmingDaoTesting {
+	pub set_channel_dimming_fn: Box<Fn(u32, Option<DimmingProfile>) -> Result<(), Error>>,
+	pub set_fixture_dimming_fn: Box<Fn(u32, Option<DimmingProfile>) -> Result<(), Error>>,
+	pub get_dimming_fn: Box<Fn(Vec<u32>) -> Result<BTreeMap<u32, DimmingProfile>, Error>>,
+}
+
+
+impl DimmingDaoTesting {
+	/// Creates a new DimmingDaoTesting struct with all functions set to return Error::TodoErr
+	#[allow(dead_code)]
+	pub fn new() -> DimmingDaoTesting {
+		DimmingDaoTesting {
+			set_channel_dimming_fn: Box::new(|_, _| -> Result<(), Error> { Err(Error::TodoErr) }),
+			set_fixture_dimming_fn: Box::new(|_, _| -> Result<(), Error> { Err(Error::TodoErr) }),
+			get_dimming_fn: Box::new(|_| -> Result<BTreeMap<u32, DimmingProfile>, Error> { Err(Error::TodoErr) })
+		}
+	}
+}
+
+/// The Dao implementation simply calls the corresponding stored function
+impl DimmingDao for DimmingDaoTesting {
+	fn set_channel_dimming(&self, chanid: u32, profile: Option<&DimmingProfile>) -> Result<(), Error> {
+		(self.set_channel_dimming_fn)(chanid, profile.cloned())
+	}
+
+	fn set_fixture_dimming(&self, fixid: u32, profile: Option<&DimmingProfile>) -> Result<(), Error> {
+		(self.set_fixture_dimming_fn)(fixid, profile.cloned())
+	}
+
+	fn get_dimming(&self, chan_ids: &Vec<u32>) -> Result<BTreeMap<u32, DimmingProfile>, Error> {
+		(self.get_dimming_fn)(chan_ids.to_owned())
+	}
+}
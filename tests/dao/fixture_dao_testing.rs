@@ -11,7 +11,7 @@ use proton_cli::project_types::Fixture;
 /// &str references are converted to Strings so we don't have to deal with lifetime headaches (bookdude13 tried on 12/25/16)
 #[allow(dead_code)]
 pub struct FixtureDaoTesting {
-	pub new_fixture_fn: Box<Fn(String, (i32, i32, i32), (i32, i32, i32), Vec<u32>) -> Result<Fixture, Error>>,
+	pub new_fixture_fn: Box<Fn(String, Option<String>, (i32, i32, i32), (i32, i32, i32), (i32, i32, i32), Vec<u32>) -> Result<Fixture, Error>>,
 	pub get_fixture_fn: Box<Fn(u32) -> Result<Fixture, Error>>,
 	pub get_last_fixture_fn: Box<Fn(String) -> Result<Fixture, Error>>,
 	pub get_num_channels_fn: Box<Fn(u32) -> Result<u32, Error>>,
@@ -24,7 +24,7 @@ impl FixtureDaoTesting {
     #[allow(dead_code)]
 	pub fn new() -> FixtureDaoTesting {
 		FixtureDaoTesting {
-			new_fixture_fn: Box::new(|_, _, _, _| -> Result<Fixture, Error> { Err(Error::TodoErr) }),
+			new_fixture_fn: Box::new(|_, _, _, _, _, _| -> Result<Fixture, Error> { Err(Error::TodoErr) }),
 			get_fixture_fn: Box::new(|_| -> Result<Fixture, Error>  { Err(Error::TodoErr) }),
 			get_last_fixture_fn: Box::new(|_| -> Result<Fixture, Error>  { Err(Error::TodoErr) }),
 			get_num_channels_fn: Box::new(|_| -> Result<u32, Error>  { Err(Error::TodoErr) }),
@@ -38,11 +38,13 @@ impl FixtureDao for FixtureDaoTesting {
     fn new_fixture(
         &self, 
         name: &str,
+        fixture_type: Option<&str>,
         location: (i32, i32, i32),
         rotation: (i32, i32, i32),
+        size: (i32, i32, i32),
         channels: Vec<u32>
     ) -> Result<Fixture, Error> {
-    	(self.new_fixture_fn)(name.to_owned(), location, rotation, channels)
+    	(self.new_fixture_fn)(name.to_owned(), fixture_type.map(|fixture_type| fixture_type.to_owned()), location, rotation, size, channels)
     }
     
     fn get_fixture(&self, fixid: u32) -> Result<Fixture, Error> {
@@ -17,6 +17,7 @@ pub struct LayoutDaoTesting {
 	pub get_last_layout_fn: Box<Fn(String) -> Result<Layout, Error>>,
 	pub layout_exists_fn: Box<Fn(u32) -> Result<bool, Error>>,
 	pub patch_channel_fn: Box<Fn(u32, u32, u32) -> Result<u64, Error>>,
+	pub get_channel_ids_fn: Box<Fn(u32) -> Result<Vec<u32>, Error>>,
 }
 
 
@@ -30,7 +31,8 @@ impl LayoutDaoTesting {
 			get_layout_fn: Box::new(|_| -> Result<Layout, Error> { Err(Error::TodoErr) }),
 			get_last_layout_fn: Box::new(|_| -> Result<Layout, Error> { Err(Error::TodoErr) }),
 			layout_exists_fn: Box::new(|_| -> Result<bool, Error> { Err(Error::TodoErr) }),
-			patch_channel_fn: Box::new(|_, _, _| -> Result<u64, Error> { Err(Error::TodoErr) })
+			patch_channel_fn: Box::new(|_, _, _| -> Result<u64, Error> { Err(Error::TodoErr) }),
+			get_channel_ids_fn: Box::new(|_| -> Result<Vec<u32>, Error> { Err(Error::TodoErr) })
 		}
 	}
 }
@@ -66,4 +68,8 @@ impl LayoutDao for LayoutDaoTesting {
     	(self.patch_channel_fn)(layoutid, channel_internal, channel_dmx)
     }
 
+    fn get_channel_ids(&self, layoutid: u32) -> Result<Vec<u32>, Error> {
+    	(self.get_channel_ids_fn)(layoutid)
+    }
+
 }
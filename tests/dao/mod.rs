@@ -1,20 +1,30 @@
 
 mod channel_dao_testing;
+mod controller_dao_testing;
 mod data_dao_testing;
+mod dimming_dao_testing;
 mod fixture_dao_testing;
 mod layout_dao_testing;
+mod patch_dao_testing;
 mod permission_dao_testing;
+mod power_dao_testing;
 mod project_dao_testing;
 mod section_dao_testing;
 mod sequence_dao_testing;
+mod timing_dao_testing;
 mod user_dao_testing;
 
 pub use self::channel_dao_testing::ChannelDaoTesting;
+pub use self::controller_dao_testing::ControllerDaoTesting;
 pub use self::data_dao_testing::DataDaoTesting;
+pub use self::dimming_dao_testing::DimmingDaoTesting;
 pub use self::fixture_dao_testing::FixtureDaoTesting;
 pub use self::layout_dao_testing::LayoutDaoTesting;
+pub use self::patch_dao_testing::PatchDaoTesting;
 pub use self::permission_dao_testing::PermissionDaoTesting;
+pub use self::power_dao_testing::PowerDaoTesting;
 pub use self::project_dao_testing::ProjectDaoTesting;
 pub use self::section_dao_testing::SectionDaoTesting;
 pub use self::sequence_dao_testing::SequenceDaoTesting;
+pub use self::timing_dao_testing::TimingDaoTesting;
 pub use self::user_dao_testing::UserDaoTesting;
@@ -0,0 +1,50 @@
+extern crate proton_cli;
+
+use proton_cli::dao::PatchDao;
+use proton_cli::error::Error;
+use proton_cli::project_types::{LayoutPatch, PatchChange};
+
+
+/// Implementation of PatchDao for testing purposes. Uses given functions to return values.
+/// Functions are boxed so their sizes are known (pointers).
+/// The general naming convention used is trait_function_name_fn, for all trait functions.
+/// &str references are converted to Strings so we don't have to deal with lifetime headaches (bookdude13 tried on 12/25/16)
+#[allow(dead_code)]
+pub struct PatchDaoTesting {
+	pub new_patch_fn: Box<Fn(u32, String, Vec<PatchChange>) -> Result<LayoutPatch, Error>>,
+	pub get_patch_fn: Box<Fn(u32, u32) -> Result<LayoutPatch, Error>>,
+	pub get_patches_fn: Box<Fn(u32) -> Result<Vec<LayoutPatch>, Error>>,
+}
+
+
+impl PatchDaoTesting {
+	/// Creates a new PatchDaoTesting struct with all functions set to return Error::TodoErr
+	#[allow(dead_code)]
+	pub fn new() -> PatchDaoTesting {
+		PatchDaoTesting {
+			new_patch_fn: Box::new(|_, _, _| -> Result<LayoutPatch, Error> { Err(Error::TodoErr) }),
+			get_patch_fn: Box::new(|_, _| -> Result<LayoutPatch, Error> { Err(Error::TodoErr) }),
+			get_patches_fn: Box::new(|_| -> Result<Vec<LayoutPatch>, Error> { Err(Error::TodoErr) })
+		}
+	}
+}
+
+/// The Dao implementation simply calls the corresponding stored function
+impl PatchDao for PatchDaoTesting {
+	fn new_patch(
+		&self,
+		layoutid: u32,
+		source: &str,
+		changes: &Vec<PatchChange>
+	) -> Result<LayoutPatch, Error> {
+		(self.new_patch_fn)(layoutid, source.to_owned(), changes.to_owned())
+	}
+
+	fn get_patch(&self, layoutid: u32, version: u32) -> Result<LayoutPatch, Error> {
+		(self.get_patch_fn)(layoutid, version)
+	}
+
+	fn get_patches(&self, layoutid: u32) -> Result<Vec<LayoutPatch>, Error> {
+		(self.get_patches_fn)(layoutid)
+	}
+}
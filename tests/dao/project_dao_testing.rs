@@ -2,7 +2,7 @@ extern crate proton_cli;
 
 use proton_cli::dao::ProjectDao;
 use proton_cli::error::Error;
-use proton_cli::project_types::Project;
+use proton_cli::project_types::{Project, Schedule};
 
 
 /// Implementation of ProjectDao for testing purposes. Uses given functions to return values.
@@ -14,6 +14,8 @@ pub struct ProjectDaoTesting {
 	pub new_project_fn: Box<Fn(String, u32) -> Result<Project, Error>>,
 	pub get_project_fn: Box<Fn(String) -> Result<Project, Error>>,
 	pub update_project_fn: Box<Fn(Project) -> Result<(), Error>>,
+	pub set_schedule_fn: Box<Fn(String, Schedule) -> Result<(), Error>>,
+	pub get_schedule_fn: Box<Fn(String) -> Result<Schedule, Error>>,
 }
 
 
@@ -24,7 +26,9 @@ impl ProjectDaoTesting {
 		ProjectDaoTesting {
 			new_project_fn: Box::new(|_, _| -> Result<Project, Error> { Err(Error::TodoErr) }),
 			get_project_fn: Box::new(|_| -> Result<Project, Error> { Err(Error::TodoErr) }),
-			update_project_fn: Box::new(|_| -> Result<(), Error> { Err(Error::TodoErr) })
+			update_project_fn: Box::new(|_| -> Result<(), Error> { Err(Error::TodoErr) }),
+			set_schedule_fn: Box::new(|_, _| -> Result<(), Error> { Err(Error::TodoErr) }),
+			get_schedule_fn: Box::new(|_| -> Result<Schedule, Error> { Err(Error::TodoErr) })
 		}
 	}
 }
@@ -42,4 +46,12 @@ impl ProjectDao for ProjectDaoTesting {
     fn update_project(&self, new_project: Project) -> Result<(), Error> {
     	(self.update_project_fn)(new_project)
     }
+
+	fn set_schedule(&self, name: &str, schedule: &Schedule) -> Result<(), Error> {
+		(self.set_schedule_fn)(name.to_owned(), schedule.clone())
+	}
+
+	fn get_schedule(&self, name: &str) -> Result<Schedule, Error> {
+		(self.get_schedule_fn)(name.to_owned())
+	}
 }
@@ -12,9 +12,12 @@ use proton_cli::project_types::Sequence;
 #[allow(dead_code)]
 pub struct SequenceDaoTesting {
 	pub get_sequence_fn: Box<Fn(u32) -> Result<Sequence, Error>>,
+	pub get_all_sequences_fn: Box<Fn() -> Result<Vec<Sequence>, Error>>,
 	pub get_last_sequence_fn: Box<Fn(String) -> Result<Sequence, Error>>,
 	pub new_sequence_fn: Box<Fn(Sequence) -> Result<Sequence, Error>>,
 	pub set_layout_fn: Box<Fn(u32, u32) -> Result<(), Error>>,
+	pub update_sequence_fn: Box<Fn(Sequence) -> Result<(), Error>>,
+	pub retime_sequence_fn: Box<Fn(Sequence, u32, Vec<(u32, Vec<u16>)>) -> Result<(), Error>>,
 	pub sequence_exists_fn: Box<Fn(u32) -> Result<bool, Error>>,
 	pub get_channel_ids_fn: Box<Fn(u32) -> Result<Vec<u32>, Error>>,
 }
@@ -26,9 +29,12 @@ impl SequenceDaoTesting {
 	pub fn new() -> SequenceDaoTesting {
 		SequenceDaoTesting {
 			get_sequence_fn: Box::new(|_| -> Result<Sequence, Error> { Err(Error::TodoErr) }),
+			get_all_sequences_fn: Box::new(|| -> Result<Vec<Sequence>, Error> { Err(Error::TodoErr) }),
 			get_last_sequence_fn: Box::new(|_| -> Result<Sequence, Error>  { Err(Error::TodoErr) }),
 			new_sequence_fn: Box::new(|_| -> Result<Sequence, Error> { Err(Error::TodoErr) }),
 			set_layout_fn: Box::new(|_, _| -> Result<(), Error> { Err(Error::TodoErr) }),
+			update_sequence_fn: Box::new(|_| -> Result<(), Error> { Err(Error::TodoErr) }),
+			retime_sequence_fn: Box::new(|_, _, _| -> Result<(), Error> { Err(Error::TodoErr) }),
 			sequence_exists_fn: Box::new(|_| -> Result<bool, Error> { Err(Error::TodoErr) }),
 			get_channel_ids_fn: Box::new(|_| -> Result<Vec<u32>, Error> { Err(Error::TodoErr) })
 		}
@@ -41,6 +47,10 @@ impl SequenceDao for SequenceDaoTesting {
 		(self.get_sequence_fn)(seqid)
 	}
 
+    fn get_all_sequences(&self) -> Result<Vec<Sequence>, Error> {
+    	(self.get_all_sequences_fn)()
+    }
+
     fn get_last_sequence(&self, name: &str) -> Result<Sequence, Error> {
     	(self.get_last_sequence_fn)(name.to_owned())
     }
@@ -53,6 +63,19 @@ impl SequenceDao for SequenceDaoTesting {
     	(self.set_layout_fn)(seqid, layout_id)
     }
 
+    fn update_sequence(&self, sequence: &Sequence) -> Result<(), Error> {
+    	(self.update_sequence_fn)(sequence.to_owned())
+    }
+
+    fn retime_sequence(
+        &self,
+        sequence: &Sequence,
+        old_frame_duration_ms: u32,
+        data: &Vec<(u32, Vec<u16>)>
+    ) -> Result<(), Error> {
+    	(self.retime_sequence_fn)(sequence.to_owned(), old_frame_duration_ms, data.to_owned())
+    }
+
     fn sequence_exists(&self, seqid: u32) -> Result<bool, Error> {
     	(self.sequence_exists_fn)(seqid)
     }
@@ -0,0 +1,153 @@
+extern crate proton_cli;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use proton_cli::error::Error;
+use proton_cli::project_types::{DimmingCurve, DimmingProfile, EditTargets, TestStep};
+
+
+/// Channels 1-3 have internal numbers 11-13 and are patched to dmx 1-3
+fn test_daos() -> (dao::ChannelDaoTesting, dao::LayoutDaoTesting) {
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(|chanid| Ok(common::test_channel(chanid, chanid + 10, chanid)));
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1, 2, 3]));
+    layout_dao.get_layout_fn = Box::new(|layout_id| Ok(common::test_layout(layout_id, vec![4, 5])));
+    (channel_dao, layout_dao)
+}
+
+#[test]
+fn parses_and_applies_curves() {
+    assert_eq!("square".parse::<DimmingCurve>().unwrap(), DimmingCurve::Square);
+    let table = "table:0,50,255".parse::<DimmingCurve>().unwrap();
+    assert_eq!(table, DimmingCurve::Table(vec![0, 50, 255]));
+    assert_eq!(table.to_string(), "table:0,50,255");
+    assert!("table:0,256".parse::<DimmingCurve>().is_err());
+    assert!("table:7".parse::<DimmingCurve>().is_err());
+    match "log".parse::<DimmingCurve>() {
+        Err(Error::InvalidDimming(_)) => (),
+        other => panic!("Expected invalid dimming, got {:?}", other),
+    }
+
+    assert_eq!(DimmingCurve::Linear.apply(300), 255);
+    assert_eq!(DimmingCurve::Square.apply(128), 64);
+    assert_eq!(DimmingCurve::Square.apply(255), 255);
+    assert_eq!((table.apply(51), table.apply(102), table.apply(204)), (20, 40, 173));
+
+    // Output is kept between the limits, even when the data is off
+    let profile = DimmingProfile::new(DimmingCurve::Square, 10, 200).unwrap();
+    assert_eq!(profile.apply_all(&[0, 128, 255]), vec![10, 64, 200]);
+    assert_eq!(profile.to_string(), "square, 10-200");
+    assert!(DimmingProfile::new(DimmingCurve::Linear, 100, 50).is_err());
+    assert!(DimmingProfile::new(DimmingCurve::Linear, 0, 256).is_err());
+}
+
+#[test]
+fn sets_dimming_on_layout_channels_and_fixtures() {
+    let (channel_dao, layout_dao) = test_daos();
+    let profile = DimmingProfile::new(DimmingCurve::Linear, 0, 128).unwrap();
+
+    let set = Rc::new(RefCell::new(Vec::new()));
+    let set_channel = set.clone();
+    let set_fixture = set.clone();
+    let mut dimming_dao = dao::DimmingDaoTesting::new();
+    dimming_dao.set_channel_dimming_fn = Box::new(move |chanid, profile| {
+        set_channel.borrow_mut().push(("channel", chanid, profile));
+        Ok(())
+    });
+    dimming_dao.set_fixture_dimming_fn = Box::new(move |fixid, profile| {
+        set_fixture.borrow_mut().push(("fixture", fixid, profile));
+        Ok(())
+    });
+
+    // Channels are given by their internal numbers
+    let num_changed = proton_cli::set_dimming(&channel_dao, &dimming_dao, &layout_dao, 1,
+        &EditTargets::Channels(vec![13, 11]), Some(&profile))
+        .expect("Error setting dimming");
+    assert_eq!(num_changed, 2);
+    let num_changed = proton_cli::set_dimming(&channel_dao, &dimming_dao, &layout_dao, 1,
+        &EditTargets::Fixtures(vec![5]), None)
+        .expect("Error clearing dimming");
+    assert_eq!(num_changed, 1);
+    assert_eq!(*set.borrow(), vec![
+        ("channel", 3, Some(profile.clone())),
+        ("channel", 1, Some(profile.clone())),
+        ("fixture", 5, None),
+    ]);
+
+    // Nothing is changed if any target isn't in the layout
+    match proton_cli::set_dimming(&channel_dao, &dimming_dao, &layout_dao, 1,
+        &EditTargets::Channels(vec![12, 14]), Some(&profile)) {
+        Err(Error::ChannelNotFound(channel)) => assert_eq!(channel, 14),
+        other => panic!("Expected channel not found, got {:?}", other),
+    }
+    match proton_cli::set_dimming(&channel_dao, &dimming_dao, &layout_dao, 1,
+        &EditTargets::Fixtures(vec![4, 6]), Some(&profile)) {
+        Err(Error::FixtureNotFound(fixid)) => assert_eq!(fixid, 6),
+        other => panic!("Expected fixture not found, got {:?}", other),
+    }
+    assert_eq!(set.borrow().len(), 3);
+}
+
+#[test]
+fn resolves_channel_dimming_over_conflicting_fixtures() {
+    let bright = DimmingProfile::new(DimmingCurve::Linear, 0, 255).unwrap();
+    let dim = DimmingProfile::new(DimmingCurve::Linear, 0, 100).unwrap();
+    let own = DimmingProfile::new(DimmingCurve::Square, 10, 200).unwrap();
+
+    // The channel's own profile wins, wherever the database sorts it
+    let rows = vec![
+        (1, Some(4), bright.clone()),
+        (1, Some(5), dim.clone()),
+        (1, None, own.clone()),
+        (2, Some(4), bright.clone())];
+    let resolved = proton_cli::dao::resolve_channel_settings(rows, "dimming profiles", Error::InvalidDimming)
+        .expect("Error resolving dimming");
+    assert_eq!(resolved[&1], own);
+    assert_eq!(resolved[&2], bright);
+
+    let rows = vec![(1, Some(4), bright), (1, Some(5), dim)];
+    match proton_cli::dao::resolve_channel_settings(rows, "dimming profiles", Error::InvalidDimming) {
+        Err(Error::InvalidDimming(msg)) => assert_eq!(msg,
+            "Channel 1 is in fixtures 4 and 5, which have different dimming profiles"),
+        other => panic!("Expected invalid dimming, got {:?}", other),
+    }
+}
+
+#[test]
+fn dims_channels_as_they_are_sent() {
+    let (channel_dao, layout_dao) = test_daos();
+    let mut dimming_dao = dao::DimmingDaoTesting::new();
+    dimming_dao.get_dimming_fn = Box::new(|chan_ids| {
+        let mut dimming = BTreeMap::new();
+        if chan_ids.contains(&2) {
+            dimming.insert(2, DimmingProfile::new(DimmingCurve::Square, 0, 255).unwrap());
+        }
+        if chan_ids.contains(&3) {
+            dimming.insert(3, DimmingProfile::new(DimmingCurve::Linear, 20, 100).unwrap());
+        }
+        Ok(dimming)
+    });
+
+    let dimmed = proton_cli::get_dimming(&channel_dao, &dimming_dao, &layout_dao, 1)
+        .expect("Error getting dimming");
+    assert_eq!(dimmed.iter().map(|&(ref channel, _)| channel.chanid).collect::<Vec<u32>>(), vec![2, 3]);
+
+    // The stored data is full on, but the limited channel is sent at its max
+    let steps = vec![TestStep {
+        label: "All on".to_owned(),
+        duration_ms: 50,
+        channels: vec![(1, 1), (2, 2), (3, 3)]
+    }];
+    let mut driver = common::RecordingDriver::new();
+    proton_cli::play_test_steps(&mut driver, &dimming_dao, &steps, &|_| ()).expect("Error sending steps");
+    assert_eq!(&driver.frames.borrow()[0].universes[&1][0..3], &[255, 255, 100]);
+
+    // Blackout at the end of a show is really off, whatever the channel's min
+    assert_eq!(driver.frames.borrow().last().unwrap().universes[&1][2], 0);
+}
@@ -0,0 +1,112 @@
+extern crate proton_cli;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use proton_cli::project_types::{DataEdit, EditTargets, FadeCurve, Fixture};
+
+
+fn params(params: &[&str]) -> Vec<String> {
+    params.iter().map(|param| param.to_string()).collect()
+}
+
+#[test]
+fn parses_effect_params_with_defaults() {
+    assert_eq!(DataEdit::from_params("fade", &params(&["to=100", "curve=in"])).unwrap(),
+        DataEdit::Fade { from: 0, to: 100, curve: FadeCurve::EaseIn });
+    assert_eq!(DataEdit::from_params("chase", &params(&[])).unwrap(),
+        DataEdit::Chase { value: 255, step_ms: 250 });
+    assert!(DataEdit::from_params("strobe", &params(&[])).is_err());
+    assert!(DataEdit::from_params("set", &params(&["level=3"])).is_err());
+    assert!(DataEdit::from_params("twinkle", &params(&["density=101"])).is_err());
+
+    assert_eq!(EditTargets::parse("1-3, 7", false, 10).unwrap(), EditTargets::Channels(vec![1, 2, 3, 7]));
+    assert!(EditTargets::parse("3-1", true, 10).is_err());
+    assert!(EditTargets::parse("1-11", false, 10).is_err());
+    assert!(EditTargets::parse("0-4294967295", false, 10).is_err());
+    assert_eq!(EditTargets::parse("4294967295", true, 10).unwrap(), EditTargets::Fixtures(vec![4294967295]));
+}
+
+#[test]
+fn applies_effects_within_frame_range() {
+    let fade = DataEdit::Fade { from: 0, to: 100, curve: FadeCurve::Linear };
+    let mut data = vec![7; 8];
+    fade.apply(&mut data, 1, 6, 0, 1, 50);
+    assert_eq!(data, vec![7, 0, 25, 50, 75, 100, 7, 7]);
+
+    let ease_in = DataEdit::Fade { from: 0, to: 100, curve: FadeCurve::EaseIn };
+    let mut data = vec![0; 5];
+    ease_in.apply(&mut data, 0, 5, 0, 1, 50);
+    assert_eq!(data, vec![0, 6, 25, 56, 100]);
+
+    // Steps of 100ms are 2 frames, and the two channels take turns
+    let chase = DataEdit::Chase { value: 9, step_ms: 100 };
+    let mut first = vec![1; 8];
+    let mut second = vec![1; 8];
+    chase.apply(&mut first, 0, 8, 0, 2, 50);
+    chase.apply(&mut second, 0, 8, 1, 2, 50);
+    assert_eq!(first, vec![9, 9, 0, 0, 9, 9, 0, 0]);
+    assert_eq!(second, vec![0, 0, 9, 9, 0, 0, 9, 9]);
+
+    // Twinkles hold for a step and repeat with the same seed
+    let twinkle = DataEdit::Twinkle { value: 5, step_ms: 100, density: 50, seed: 3 };
+    let mut data = vec![0; 40];
+    let mut again = vec![0; 40];
+    twinkle.apply(&mut data, 0, 40, 0, 1, 50);
+    twinkle.apply(&mut again, 0, 40, 0, 1, 50);
+    assert_eq!(data, again);
+    assert!(data.chunks(2).all(|step| step[0] == step[1]));
+    assert!(data.contains(&5) && data.contains(&0));
+}
+
+#[test]
+fn edits_fixture_channels_over_time_range() {
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(|chanid| Ok(common::test_channel(chanid, chanid, chanid)));
+    let mut fixture_dao = dao::FixtureDaoTesting::new();
+    fixture_dao.get_fixture_fn = Box::new(|fixid| {
+        let channels = if fixid == 2 { vec![1] } else { vec![2, 3] };
+        let mut fixture = Fixture::new("Arch", None, (0, 0, 0), (0, 0, 0), (0, 0, 0), channels);
+        fixture.fixid = fixid;
+        Ok(fixture)
+    });
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(|seqid| Ok(common::test_sequence(seqid, 50, 10, 1, "test.ogg")));
+    sequence_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1, 2, 3]));
+
+    let updated = Rc::new(RefCell::new(Vec::new()));
+    let updated_ref = updated.clone();
+    let mut data_dao = dao::DataDaoTesting::new();
+    data_dao.get_data_fn = Box::new(|_, _| Ok(vec![0; 10]));
+    data_dao.update_data_fn = Box::new(move |_, chanid, data| {
+        updated_ref.borrow_mut().push((chanid, data));
+        Ok(())
+    });
+
+    // 100ms to 260ms is frames 2 through 5
+    let edit = DataEdit::Set { value: 200 };
+    let num_edited = proton_cli::edit_data(&channel_dao, &data_dao, &fixture_dao, &sequence_dao,
+        1, 100, 260, &edit, &EditTargets::Fixtures(vec![1]))
+        .expect("Error editing data");
+    assert_eq!(num_edited, 2);
+    let expected = vec![0, 0, 200, 200, 200, 200, 0, 0, 0, 0];
+    assert_eq!(*updated.borrow(), vec![(2, expected.clone()), (3, expected)]);
+
+    // A fixture's channels chase together, taking turns with the other fixtures
+    updated.borrow_mut().clear();
+    let chase = DataEdit::Chase { value: 255, step_ms: 50 };
+    let num_edited = proton_cli::edit_data(&channel_dao, &data_dao, &fixture_dao, &sequence_dao,
+        1, 0, 200, &chase, &EditTargets::Fixtures(vec![1, 2]))
+        .expect("Error editing data");
+    assert_eq!(num_edited, 3);
+    let first = vec![255, 0, 255, 0, 0, 0, 0, 0, 0, 0];
+    let second = vec![0, 255, 0, 255, 0, 0, 0, 0, 0, 0];
+    assert_eq!(*updated.borrow(), vec![(2, first.clone()), (3, first), (1, second)]);
+
+    let err = proton_cli::edit_data(&channel_dao, &data_dao, &fixture_dao, &sequence_dao,
+        1, 100, 260, &edit, &EditTargets::Channels(vec![4]));
+    assert!(err.is_err());
+}
@@ -0,0 +1,95 @@
+extern crate proton_cli;
+
+mod common;
+
+use proton_cli::effects::{BlendMode, Color, ColorWash, Rainbow, Scene, Sparkle, Wipe, WipeDirection};
+use proton_cli::project_types::{Channel, Fixture};
+
+
+/// Bush (fixture 1) has two RGB pixels at x=0 and x=2, and Arch (fixture 2)
+/// is a single white channel at x=10. 5 frames of 50ms.
+fn test_scene() -> Scene {
+    let channels = vec![
+        (1, "Bush 1", "R", 0),
+        (2, "Bush 1", "G", 0),
+        (3, "Bush 1", "B", 0),
+        (4, "Bush 2", "R", 2),
+        (5, "Bush 2", "G", 2),
+        (6, "Bush 2", "B", 2),
+        (7, "Arch", "W", 10),
+    ].into_iter()
+        .map(|(chanid, name, color, x)| Channel {
+            location: (Some(x), Some(0), Some(0)),
+            ..common::test_named_channel(chanid, name, color, chanid, chanid)
+        })
+        .collect::<Vec<Channel>>();
+    let mut bush = Fixture::new("Bush", None, (1, 0, 0), (0, 0, 0), (2, 0, 0), vec![1, 2, 3, 4, 5, 6]);
+    bush.fixid = 1;
+    let mut arch = Fixture::new("Arch", None, (10, 0, 0), (0, 0, 0), (0, 0, 0), vec![7]);
+    arch.fixid = 2;
+    let layout = common::test_layout(1, vec![2, 1]);
+    Scene::new(&layout, &vec![bush, arch], &channels, 50, 5).expect("Error creating scene")
+}
+
+#[test]
+fn renders_colors_onto_channel_roles() {
+    let scene = test_scene();
+    let elements = scene.elements(&[]).expect("Error getting elements");
+    assert_eq!(elements.len(), 3);
+    assert_eq!((elements[1].location, elements[2].location), ((0.0, 0.5), (0.2, 0.5)));
+
+    let mut buffers = scene.new_buffers();
+    let wash = ColorWash { start: Color::new(1.0, 0.0, 0.0), end: Color::new(0.0, 0.0, 1.0) };
+    scene.render(&wash, BlendMode::Normal, &[], 0, 250, &mut buffers).expect("Error rendering");
+    assert_eq!(buffers[&1], vec![255, 191, 128, 64, 0]);
+    assert_eq!(buffers[&3], vec![0, 64, 128, 191, 255]);
+    assert_eq!(buffers[&7], vec![255, 191, 128, 191, 255]);
+
+    // Rainbow spreads hues across the bush's pixels
+    let mut buffers = scene.new_buffers();
+    let rainbow = Rainbow { cycles: 1.0, speed: 0.0 };
+    scene.render(&rainbow, BlendMode::Normal, &[1], 0, 50, &mut buffers).expect("Error rendering");
+    assert_eq!((buffers[&1][0], buffers[&2][0], buffers[&3][0]), (255, 0, 0));
+    assert_eq!((buffers[&4][0], buffers[&5][0], buffers[&6][0]), (0, 255, 255));
+    assert_eq!(buffers[&7][0], 0);
+}
+
+#[test]
+fn wipes_across_fixtures_in_location_order() {
+    let scene = test_scene();
+    let mut buffers = scene.new_buffers();
+    let wipe = Wipe { color: Color::white(), direction: WipeDirection::LeftToRight, softness: 0.0 };
+    scene.render(&wipe, BlendMode::Normal, &[], 0, 250, &mut buffers).expect("Error rendering");
+    assert_eq!(buffers[&1], vec![255, 255, 255, 255, 255]);
+    assert_eq!(buffers[&7], vec![0, 0, 0, 0, 255]);
+
+    let mut buffers = scene.new_buffers();
+    let wipe = Wipe { color: Color::white(), direction: WipeDirection::RightToLeft, softness: 0.0 };
+    scene.render(&wipe, BlendMode::Normal, &[], 0, 250, &mut buffers).expect("Error rendering");
+    assert_eq!(buffers[&1], vec![0, 0, 0, 0, 255]);
+    assert_eq!(buffers[&7], vec![255, 255, 255, 255, 255]);
+}
+
+#[test]
+fn layers_blend_into_existing_data() {
+    let scene = test_scene();
+    let mut buffers = scene.new_buffers();
+    let gray = ColorWash { start: Color::new(0.4, 0.4, 0.4), end: Color::new(0.4, 0.4, 0.4) };
+    scene.render(&gray, BlendMode::Normal, &[2], 0, 250, &mut buffers).expect("Error rendering");
+    scene.render(&gray, BlendMode::Add, &[2], 0, 100, &mut buffers).expect("Error rendering");
+    scene.render(&gray, BlendMode::Multiply, &[2], 100, 150, &mut buffers).expect("Error rendering");
+    assert_eq!(buffers[&7], vec![204, 204, 40, 102, 102]);
+
+    assert_eq!(BlendMode::Add.apply(200, 100), 255);
+    assert_eq!(BlendMode::Subtract.apply(50, 100), 0);
+    assert_eq!("lighten".parse::<BlendMode>().unwrap(), BlendMode::Lighten);
+
+    // Sparkles repeat with the same seed and leave everything else alone
+    let sparkle = Sparkle { color: Color::white(), density: 0.5, hold_ms: 50, seed: 7 };
+    let mut first = scene.new_buffers();
+    let mut second = scene.new_buffers();
+    scene.render(&sparkle, BlendMode::Normal, &[], 0, 250, &mut first).expect("Error rendering");
+    scene.render(&sparkle, BlendMode::Normal, &[], 0, 250, &mut second).expect("Error rendering");
+    assert_eq!(first, second);
+    assert!(first.values().all(|data| data.iter().all(|value| *value == 0 || *value == 255)));
+}
@@ -0,0 +1,144 @@
+extern crate libc;
+extern crate proton_cli;
+extern crate tempdir;
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+use proton_cli::error::Error;
+use proton_cli::output::{self, DmxFrame, EnttecDriver, EnttecWidget, OutputDriver, SerialDevice, SerialPort};
+use tempdir::TempDir;
+
+
+/// Keeps everything written to it, marking where breaks were sent
+struct FakeDevice {
+    written: Vec<u8>,
+    flushed: usize, // Number of bytes written before the last flush
+    breaks: Vec<usize>, // Number of bytes written before each break
+}
+
+impl Write for FakeDevice {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushed = self.written.len();
+        Ok(())
+    }
+}
+
+impl SerialDevice for FakeDevice {
+    fn send_break(&mut self, duration_us: u32) -> io::Result<()> {
+        assert!(duration_us >= 88);
+        assert_eq!(self.flushed, self.written.len(), "Break sent before the last frame was flushed");
+        self.breaks.push(self.written.len());
+        Ok(())
+    }
+}
+
+fn test_frame() -> DmxFrame {
+    let mut frame = DmxFrame::new();
+    frame.set(1, 10);
+    frame.set(512, 13);
+    frame.set(513, 20);
+    frame
+}
+
+#[test]
+fn builds_pro_packets() {
+    let packet = output::enttec_pro_packet(&[1, 2, 3]);
+    assert_eq!(packet, vec![0x7e, 6, 4, 0, 0, 1, 2, 3, 0xe7]);
+
+    let packet = output::enttec_pro_packet(&[0; 512]);
+    assert_eq!(packet.len(), 518);
+    assert_eq!(&packet[2..4], &[0x01, 0x02]); // 513, least significant byte first
+
+    assert_eq!("open".parse::<EnttecWidget>().ok(), Some(EnttecWidget::Open));
+    match "ultra".parse::<EnttecWidget>() {
+        Err(Error::InvalidOutput(_)) => (),
+        other => panic!("Expected invalid output settings, got {:?}", other),
+    }
+}
+
+#[test]
+fn sends_pro_packets_for_one_universe() {
+    let device = FakeDevice { written: Vec::new(), flushed: 0, breaks: Vec::new() };
+    let mut driver = EnttecDriver::new(device, EnttecWidget::Pro, 2);
+    driver.send(&test_frame()).expect("Error sending frame");
+    driver.send(&DmxFrame::new()).expect("Error sending frame");
+
+    // The Pro times DMX itself, so needs no breaks
+    let device = driver.into_device();
+    assert!(device.breaks.is_empty());
+    assert_eq!(device.written.len(), 2 * 518);
+    assert_eq!(&device.written[4..6], &[0, 20]);
+
+    // Universes missing from a frame are sent off
+    assert!(device.written[518 + 5..2 * 518 - 1].iter().all(|value| *value == 0));
+}
+
+#[test]
+fn sends_open_dmx_with_breaks() {
+    let device = FakeDevice { written: Vec::new(), flushed: 0, breaks: Vec::new() };
+    let mut driver = EnttecDriver::new(device, EnttecWidget::Open, 1);
+    driver.send(&test_frame()).expect("Error sending frame");
+    driver.send(&test_frame()).expect("Error sending frame");
+
+    let device = driver.into_device();
+    assert_eq!(device.breaks, vec![0, 513]);
+    assert_eq!(device.written.len(), 2 * 513);
+    assert_eq!(&device.written[0..2], &[0, 10]); // Start code, then channel 1
+    assert_eq!(device.written[512], 13);
+}
+
+#[test]
+fn writes_to_file_backed_port() {
+    let root = TempDir::new("proton_enttec").expect("Error creating temp dir");
+    let path = root.path().join("ttyFAKE");
+    File::create(&path).expect("Error creating fake device");
+
+    let port = SerialPort::open(&path).expect("Error opening fake device");
+    let mut driver = EnttecDriver::new(port, EnttecWidget::Pro, 1);
+    driver.send(&test_frame()).expect("Error sending frame");
+    driver.finish().expect("Error finishing");
+
+    let mut written = Vec::new();
+    File::open(&path).and_then(|mut file| file.read_to_end(&mut written)).expect("Error reading fake device");
+    assert_eq!(written.len(), 518);
+    assert_eq!(&written[0..6], &[0x7e, 6, 0x01, 0x02, 0, 10]);
+    assert_eq!(written[517], 0xe7);
+
+    assert!(SerialPort::open(root.path().join("missing")).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn writes_raw_bytes_to_terminal() {
+    use std::os::unix::io::FromRawFd;
+    use std::ptr;
+
+    let (mut master, mut slave) = (0, 0);
+    let result = unsafe {
+        libc::openpty(&mut master, &mut slave, ptr::null_mut(), ptr::null_mut(), ptr::null_mut())
+    };
+    assert_eq!(result, 0, "Error opening pseudo-terminal");
+    let slave_path = format!("/proc/self/fd/{}", slave);
+
+    // Terminals translate newlines unless they're set to raw, which would corrupt the data
+    let port = SerialPort::open(&slave_path).expect("Error opening pseudo-terminal");
+    let mut driver = EnttecDriver::new(port, EnttecWidget::Pro, 1);
+    let mut frame = DmxFrame::new();
+    frame.set(1, 0x0a);
+    frame.set(2, 0x0d);
+    frame.set(3, 0x03);
+    driver.send(&frame).expect("Error sending frame");
+
+    let mut master = unsafe { File::from_raw_fd(master) };
+    let mut received = vec![0; 518];
+    master.read_exact(&mut received).expect("Error reading pseudo-terminal");
+    assert_eq!(&received[0..8], &[0x7e, 6, 0x01, 0x02, 0, 0x0a, 0x0d, 0x03]);
+    assert_eq!(received[517], 0xe7);
+    unsafe { libc::close(slave); }
+}
@@ -0,0 +1,39 @@
+extern crate proton_cli;
+
+use proton_cli::project_types::{ChannelPlacement, Fixture};
+
+
+#[test]
+fn fixture_surrounds_its_channels() {
+    let placements = vec![
+        ChannelPlacement {
+            location: (Some(0), Some(0), Some(0)),
+            rotation: (Some(0), Some(90), None),
+            width: Some(4),
+            height: Some(2)
+        },
+        ChannelPlacement {
+            location: (Some(10), Some(0), Some(0)),
+            rotation: (Some(0), Some(80), None),
+            width: Some(4),
+            height: Some(2)
+        },
+    ];
+    let (location, rotation, size) = Fixture::geometry(&placements);
+    assert_eq!(location, (5, 0, 0));
+    assert_eq!(rotation, (0, 85, 0));
+    assert_eq!(size, (14, 2, 0));
+}
+
+#[test]
+fn unknown_placements_default_to_origin() {
+    let placements = vec![
+        ChannelPlacement {
+            location: (None, None, None),
+            rotation: (None, None, None),
+            width: None,
+            height: None
+        },
+    ];
+    assert_eq!(Fixture::geometry(&placements), ((0, 0, 0), (0, 0, 0), (0, 0, 0)));
+}
@@ -0,0 +1,230 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use proton_cli::{MusicStore, PlaybackEvent};
+use proton_cli::audio::SymphoniaBackend;
+use proton_cli::error::Error;
+use proton_cli::project_types::{Channel, Fixture, Sequence, TestStep, TimingMark, TimingTrack};
+use tempdir::TempDir;
+
+
+/// Channels 1-4 are an RGB fixture plus a spare red, patched out of order.
+/// Channel 5 isn't patched.
+fn arch_channel(chanid: u32) -> Result<Channel, Error> {
+    let (color, channel_dmx) = match chanid {
+        1 => ("R", 10),
+        2 => ("G", 11),
+        3 => ("B", 2),
+        4 => ("R", 600),
+        5 => ("W", 0),
+        _ => return Err(Error::ChannelNotFound(chanid)),
+    };
+    Ok(common::test_named_channel(chanid, &format!("Arch {}", color), color, chanid + 100, channel_dmx))
+}
+
+fn test_daos() -> (dao::ChannelDaoTesting, dao::FixtureDaoTesting, dao::LayoutDaoTesting) {
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(arch_channel);
+    let mut fixture_dao = dao::FixtureDaoTesting::new();
+    fixture_dao.get_fixture_fn = Box::new(|fixid| Ok(Fixture {
+        fixid: fixid,
+        name: if fixid == 1 { "Arch".to_owned() } else { "Spare".to_owned() },
+        fixture_type: None,
+        location: (0, 0, 0),
+        rotation: (0, 0, 0),
+        size: (0, 0, 0),
+        channels: if fixid == 1 { vec![1, 2, 3, 4] } else { vec![5] }
+    }));
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.get_layout_fn = Box::new(|layout_id| Ok(common::test_layout(layout_id, vec![1, 2])));
+    layout_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![5, 4, 3, 2, 1]));
+    (channel_dao, fixture_dao, layout_dao)
+}
+
+fn labels(steps: &[TestStep]) -> Vec<&str> {
+    steps.iter().map(|step| step.label.as_str()).collect()
+}
+
+#[test]
+fn chases_layout_in_dmx_order() {
+    let (channel_dao, _, layout_dao) = test_daos();
+    let steps = proton_cli::chase_layout_steps(&channel_dao, &layout_dao, 1, 500)
+        .expect("Error building chase");
+
+    assert_eq!(labels(&steps), vec![
+        "Arch B (B, internal 103, dmx 2)",
+        "Arch R (R, internal 101, dmx 10)",
+        "Arch G (G, internal 102, dmx 11)",
+        "Arch R (R, internal 104, dmx 600)",
+    ]);
+    assert_eq!(steps[3].channels, vec![(4, 600)]);
+    assert!(steps.iter().all(|step| step.duration_ms == 500));
+
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![5]));
+    match proton_cli::chase_layout_steps(&channel_dao, &layout_dao, 1, 500) {
+        Err(Error::NothingPatched(what)) => assert_eq!(what, "layout 1"),
+        other => panic!("Expected nothing patched, got {:?}", other),
+    }
+}
+
+#[test]
+fn tests_fixture_by_channel_and_color() {
+    let (channel_dao, fixture_dao, layout_dao) = test_daos();
+    let steps = proton_cli::test_fixture_steps(&channel_dao, &fixture_dao, &layout_dao, 1, 1, 1000)
+        .expect("Error building fixture test");
+
+    // Each channel, then each color in the order it's first seen, then everything
+    assert_eq!(&labels(&steps)[4..], &[
+        "All B (internal 103)",
+        "All R (internal 101, 104)",
+        "All G (internal 102)",
+        "All on (internal 103, 101, 102, 104)",
+    ]);
+    assert_eq!(steps[5].channels, vec![(1, 10), (4, 600)]);
+    assert_eq!(steps.len(), 8);
+
+    match proton_cli::test_fixture_steps(&channel_dao, &fixture_dao, &layout_dao, 1, 3, 1000) {
+        Err(Error::FixtureNotFound(fixid)) => assert_eq!(fixid, 3),
+        other => panic!("Expected fixture not found, got {:?}", other),
+    }
+    match proton_cli::test_fixture_steps(&channel_dao, &fixture_dao, &layout_dao, 1, 2, 1000) {
+        Err(Error::NothingPatched(what)) => assert_eq!(what, "fixture 'Spare'"),
+        other => panic!("Expected nothing patched, got {:?}", other),
+    }
+}
+
+#[test]
+fn tests_one_channel() {
+    let (channel_dao, _, layout_dao) = test_daos();
+    let steps = proton_cli::test_channel_steps(&channel_dao, &layout_dao, 1, 102, 10000)
+        .expect("Error building channel test");
+    assert_eq!(steps, vec![TestStep {
+        label: "Arch G (G, internal 102, dmx 11)".to_owned(),
+        duration_ms: 10000,
+        channels: vec![(2, 11)]
+    }]);
+
+    match proton_cli::test_channel_steps(&channel_dao, &layout_dao, 1, 105, 10000) {
+        Err(Error::NothingPatched(what)) => assert_eq!(what, "channel 105"),
+        other => panic!("Expected nothing patched, got {:?}", other),
+    }
+    match proton_cli::test_channel_steps(&channel_dao, &layout_dao, 1, 7, 10000) {
+        Err(Error::ChannelNotFound(channel)) => assert_eq!(channel, 7),
+        other => panic!("Expected channel not found, got {:?}", other),
+    }
+}
+
+#[test]
+fn sends_steps_in_turn() {
+    let (channel_dao, _, layout_dao) = test_daos();
+    let steps = proton_cli::chase_layout_steps(&channel_dao, &layout_dao, 1, 50)
+        .expect("Error building chase");
+    let mut dimming_dao = dao::DimmingDaoTesting::new();
+    dimming_dao.get_dimming_fn = Box::new(|_| Ok(BTreeMap::new()));
+    let mut driver = common::RecordingDriver::new();
+    let events = RefCell::new(Vec::new());
+    let report = proton_cli::play_test_steps(&mut driver, &dimming_dao, &steps, &|event| events.borrow_mut().push(event))
+        .expect("Error sending steps");
+    assert_eq!(report.sequences, 4);
+    assert_eq!(events.borrow()[1], PlaybackEvent::TestStep(2, 4, steps[1].label.clone()));
+    assert_eq!(report.frames_sent + report.frames_dropped, 8);
+
+    // Every frame has both universes, with only the step's channel on
+    let frames = driver.frames.borrow();
+    let lit = frames[..frames.len() - 1].iter()
+        .map(|frame| {
+            assert_eq!(frame.universes.len(), 2);
+            frame.universes.iter()
+                .flat_map(|(universe, data)| data.iter()
+                    .enumerate()
+                    .filter(|&(_, value)| *value > 0)
+                    .map(move |(slot, _)| (*universe as u32 - 1) * 512 + slot as u32 + 1))
+                .collect::<Vec<u32>>()
+        })
+        .collect::<Vec<Vec<u32>>>();
+    let mut order = lit.iter()
+        .map(|channels| {
+            assert_eq!(channels.len(), 1);
+            channels[0]
+        })
+        .collect::<Vec<u32>>();
+    order.dedup();
+    assert_eq!(order, vec![2, 10, 11, 600]);
+    let blackout = frames.last().unwrap();
+    assert!(blackout.universes.values().all(|data| data.iter().all(|value| *value == 0)));
+}
+
+#[test]
+fn saves_steps_as_sequence() {
+    let root = TempDir::new("proton_identify").expect("Error creating temp dir");
+    let music_store = MusicStore::new(root.path());
+    let (channel_dao, fixture_dao, layout_dao) = test_daos();
+    let steps = proton_cli::test_fixture_steps(&channel_dao, &fixture_dao, &layout_dao, 1, 1, 125)
+        .expect("Error building fixture test");
+
+    let created: Rc<RefCell<Option<Sequence>>> = Rc::new(RefCell::new(None));
+    let created_new = created.clone();
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.new_sequence_fn = Box::new(move |sequence| {
+        let mut sequence = sequence.clone();
+        sequence.seqid = 8;
+        *created_new.borrow_mut() = Some(sequence.clone());
+        Ok(sequence)
+    });
+    let created_get = created.clone();
+    sequence_dao.get_sequence_fn = Box::new(move |seqid| created_get.borrow().clone().ok_or(Error::SequenceNotFound(seqid)));
+    sequence_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1, 2, 3, 4, 5]));
+
+    let written = Rc::new(RefCell::new(Vec::new()));
+    let written_update = written.clone();
+    let mut data_dao = dao::DataDaoTesting::new();
+    data_dao.new_data_default_fn = Box::new(|_, _, _| Ok(()));
+    data_dao.update_data_fn = Box::new(move |_, chanid, data| {
+        written_update.borrow_mut().push((chanid, data));
+        Ok(())
+    });
+
+    let tracks = Rc::new(RefCell::new(Vec::new()));
+    let tracks_set = tracks.clone();
+    let mut timing_dao = dao::TimingDaoTesting::new();
+    timing_dao.set_timing_track_fn = Box::new(move |seqid, name, marks| {
+        tracks_set.borrow_mut().push((name.clone(), marks.clone()));
+        Ok(TimingTrack { trackid: 1, seqid: seqid, name: name, marks: marks })
+    });
+
+    let (seqid, _) = proton_cli::save_test_steps(&SymphoniaBackend, &music_store, &data_dao, &layout_dao,
+        &sequence_dao, &timing_dao, "Arch test", "tests/music/test_1sec.ogg", 1, &steps)
+        .expect("Error saving steps");
+    assert_eq!(seqid, 8);
+    let sequence = created.borrow().clone().unwrap();
+    assert_eq!((sequence.num_frames, sequence.frame_duration_ms), (20, 50));
+
+    // Steps are 125ms and frames 50ms, so the blue channel is on for the first
+    // two frames, then for its color step and the last (all on) step
+    let written = written.borrow();
+    assert_eq!(written.iter().map(|&(chanid, _)| chanid).collect::<Vec<u32>>(), vec![1, 2, 3, 4]);
+    let blue = &written[2].1;
+    assert_eq!(blue.iter().map(|value| *value > 0).collect::<Vec<bool>>(), vec![
+        true, true, false, false, false, false, false, false, false, false,
+        true, true, false, false, false, false, false, true, true, true,
+    ]);
+
+    // The steps are labeled in a timing track
+    let tracks = tracks.borrow();
+    assert_eq!(tracks.len(), 1);
+    assert_eq!(tracks[0].0, proton_cli::TEST_STEPS_TRACK);
+    assert_eq!(tracks[0].1[1], TimingMark {
+        start_ms: 125,
+        end_ms: Some(250),
+        label: Some("Arch R (R, internal 101, dmx 10)".to_owned())
+    });
+    assert_eq!(tracks[0].1.len(), 8);
+}
@@ -0,0 +1,110 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::Write;
+use std::rc::Rc;
+
+use tempdir::TempDir;
+
+use proton_cli::error::Error;
+use proton_cli::project_types::{LayoutPatch, PatchChange};
+
+
+/// Two patches: v1 moved channel 1 from dmx 1 to 10, v2 moved it from 10 to 20
+/// and channel 2 from dmx 2 to 21. Current mapping is 1->20, 2->21.
+fn test_daos() -> (dao::ChannelDaoTesting, dao::LayoutDaoTesting, dao::PatchDaoTesting) {
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(|chanid| Ok(common::test_channel(chanid, chanid, chanid + 19)));
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.layout_exists_fn = Box::new(|_| Ok(true));
+    layout_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1, 2]));
+    let mut patch_dao = dao::PatchDaoTesting::new();
+    patch_dao.get_patches_fn = Box::new(|layout_id| Ok(vec![
+        LayoutPatch {
+            patchid: 1,
+            layout_id: layout_id,
+            version: 1,
+            source: "patch_1.json".to_owned(),
+            changes: vec![PatchChange { channel_internal: 1, old_dmx: 1, new_dmx: 10 }]
+        },
+        LayoutPatch {
+            patchid: 2,
+            layout_id: layout_id,
+            version: 2,
+            source: "patch_2.json".to_owned(),
+            changes: vec![
+                PatchChange { channel_internal: 1, old_dmx: 10, new_dmx: 20 },
+                PatchChange { channel_internal: 2, old_dmx: 2, new_dmx: 21 }
+            ]
+        }
+    ]));
+    (channel_dao, layout_dao, patch_dao)
+}
+
+#[test]
+fn mapping_at_past_versions_undoes_newer_patches() {
+    let (channel_dao, layout_dao, patch_dao) = test_daos();
+
+    let v1 = proton_cli::get_layout_mapping(&channel_dao, &layout_dao, &patch_dao, 1, Some(1))
+        .expect("Error getting layout mapping");
+    assert_eq!(v1.into_iter().collect::<Vec<(u32, u32)>>(), vec![(1, 10), (2, 2)]);
+
+    let v0 = proton_cli::get_layout_mapping(&channel_dao, &layout_dao, &patch_dao, 1, Some(0))
+        .expect("Error getting layout mapping");
+    assert_eq!(v0.into_iter().collect::<Vec<(u32, u32)>>(), vec![(1, 1), (2, 2)]);
+}
+
+#[test]
+#[should_panic(expected = "PatchNotFound")]
+fn mapping_fails_if_version_not_in_history() {
+    let (channel_dao, layout_dao, patch_dao) = test_daos();
+    let _ = proton_cli::get_layout_mapping(&channel_dao, &layout_dao, &patch_dao, 1, Some(3))
+        .expect("Error getting layout mapping");
+}
+
+#[test]
+fn rollback_records_changed_channels_as_new_version() {
+    let (channel_dao, layout_dao, mut patch_dao) = test_daos();
+    let recorded = Rc::new(RefCell::new(Vec::new()));
+    let recorded_ref = recorded.clone();
+    patch_dao.new_patch_fn = Box::new(move |layout_id, source, changes| {
+        recorded_ref.borrow_mut().push((source.clone(), changes.clone()));
+        Ok(LayoutPatch { patchid: 3, layout_id: layout_id, version: 3, source: source, changes: changes })
+    });
+
+    let version = proton_cli::rollback_layout(&channel_dao, &layout_dao, &patch_dao, 1, 1)
+        .expect("Error rolling back layout");
+    assert_eq!(version, 3);
+    assert_eq!(*recorded.borrow(), vec![("rollback to version 1".to_owned(), vec![
+        PatchChange { channel_internal: 1, old_dmx: 20, new_dmx: 10 },
+        PatchChange { channel_internal: 2, old_dmx: 21, new_dmx: 2 }
+    ])]);
+}
+
+#[test]
+fn patches_nothing_if_a_channel_is_not_in_the_layout() {
+    let (channel_dao, layout_dao, mut patch_dao) = test_daos();
+    let recorded = Rc::new(RefCell::new(Vec::new()));
+    let recorded_ref = recorded.clone();
+    patch_dao.new_patch_fn = Box::new(move |layout_id, source, changes| {
+        recorded_ref.borrow_mut().extend(changes.clone());
+        Ok(LayoutPatch { patchid: 3, layout_id: layout_id, version: 3, source: source, changes: changes })
+    });
+
+    let dir = TempDir::new("proton_patch").expect("Error creating temp dir");
+    let patch_path = dir.path().join("patch.json");
+    let mut file = File::create(&patch_path).expect("Error creating patch file");
+    file.write_all(br#"{"patches":[{"internalChannel":1,"dmxChannel":30},{"internalChannel":3,"dmxChannel":31}]}"#)
+        .expect("Error writing patch file");
+
+    match proton_cli::patch_layout(&channel_dao, &layout_dao, &patch_dao, 1, &patch_path) {
+        Err(Error::InvalidPatch(msg)) => assert_eq!(msg, "Internal channel 3 is not in layout 1"),
+        other => panic!("Expected invalid patch, got {:?}", other),
+    }
+    assert!(recorded.borrow().is_empty());
+}
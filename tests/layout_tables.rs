@@ -0,0 +1,104 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+mod common;
+mod dao;
+
+use std::fs::File;
+use std::io::{Read, Write};
+
+use tempdir::TempDir;
+
+use proton_cli::project_types::{Channel, FilePatch, FileTable, Fixture, IssueLevel};
+
+
+#[test]
+fn parses_quoted_cells_and_skips_blank_lines() {
+    let table = FileTable::parse("a,b,c\n\n1,\"x, \"\"y\"\"\",\"multi\nline\"\n 2 ,3\n", ',')
+        .expect("Error parsing table");
+    assert_eq!(table.header, vec!["a", "b", "c"]);
+    assert_eq!(table.rows.len(), 2);
+    assert_eq!(table.rows[0].line, 3);
+    assert_eq!(table.rows[0].cells, vec!["1", "x, \"y\"", "multi\nline"]);
+    assert_eq!(table.rows[1].line, 5);
+    assert_eq!(table.get(&table.rows[1], "a"), Some("2"));
+    assert_eq!(table.get(&table.rows[1], "c"), None);
+
+    let tsv = FileTable::parse("a\tb\n1\t2,3", '\t').expect("Error parsing table");
+    assert_eq!(tsv.rows[0].cells, vec!["1", "2,3"]);
+}
+
+#[test]
+fn reports_bad_layout_rows_by_line() {
+    let dir = TempDir::new("proton_layout").expect("Error creating temp dir");
+    let layout_path = dir.path().join("Test.tsv");
+    let mut file = File::create(&layout_path).expect("Error creating layout file");
+    file.write_all(b"internalChannel\tdmxChannel\tfixtureName\tchannelName\tcolor\tlocation\trotation\n\
+        1\t1\tArch\tArch 1\tW\t0,0,0\t0,0,0\n\
+        2\tabc\tArch\tArch 2\tW\t0,0,0\t0,0\n")
+        .expect("Error writing layout file");
+
+    let issues = proton_cli::validate_layout(&layout_path, Some("Test")).expect("Error validating layout");
+    let errors = issues.iter()
+        .filter(|issue| issue.level == IssueLevel::Error)
+        .map(|issue| issue.to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(errors, vec![
+        "row 3: error: Field 'dmxChannel' must be a whole number",
+        "row 3: error: Can't parse rotation '0,0': The layout being read or decoded is invalid: \
+            Locations must be of the form x,y,z",
+    ]);
+
+    // Tables without a layoutName column have to be given a name
+    let issues = proton_cli::validate_layout(&layout_path, None).expect("Error validating layout");
+    assert_eq!(issues[0].to_string(), "layout: error: No layout name given; use --name or a layoutName column");
+}
+
+#[test]
+fn reports_every_bad_patch_row() {
+    let table = FileTable::parse("dmxChannel,internalChannel,notes\n5,1,moved\nx,2,\n7,,\n", ',')
+        .expect("Error parsing table");
+    let err = FilePatch::from_table(&table).err().expect("Bad patch rows were accepted");
+    assert_eq!(err.to_string(), "Invalid patch file: \
+        line 3: dmxChannel 'x' is not a whole number\n\
+        line 4: internalChannel '' is not a whole number");
+
+    let table = FileTable::parse("dmxChannel,internalChannel\n5,1\n", ',').expect("Error parsing table");
+    let patch = FilePatch::from_table(&table).expect("Error reading patch");
+    assert_eq!((patch.patches[0].internalChannel, patch.patches[0].dmxChannel), (1, 5));
+}
+
+#[test]
+fn exports_layout_channels_in_order() {
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(|chanid| Ok(Channel {
+        numbers: (Some(chanid), None),
+        location: (Some(1), None, Some(3)),
+        ..common::test_named_channel(chanid, &format!("Arch {}", chanid), "W", chanid, chanid + 10)
+    }));
+    let mut fixture_dao = dao::FixtureDaoTesting::new();
+    fixture_dao.get_fixture_fn = Box::new(|fixid| {
+        let mut fixture = Fixture::new("Arch", Some("RGB pixel"), (0, 0, 0), (0, 0, 0), (6, 2, 0), vec![2, 1]);
+        fixture.fixid = fixid;
+        Ok(fixture)
+    });
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.get_layout_fn = Box::new(|layout_id| Ok(common::test_layout(layout_id, vec![1])));
+
+    let dir = TempDir::new("proton_layout").expect("Error creating temp dir");
+    let output_path = dir.path().join("Test.csv");
+    proton_cli::export_layout(&channel_dao, &fixture_dao, &layout_dao, 1, &output_path)
+        .expect("Error exporting layout");
+
+    let mut text = String::new();
+    let _ = File::open(&output_path).and_then(|mut file| file.read_to_string(&mut text))
+        .expect("Error reading exported layout");
+    assert_eq!(text, "layoutName,internalChannel,dmxChannel,fixtureName,channelName,color,\
+        num_primary,num_secondary,location,rotation,fixtureType,fixtureSize\n\
+        Yard,1,11,Arch,Arch 1,W,1,,\"1,,3\",,RGB pixel,\"6,2,0\"\n\
+        Yard,2,12,Arch,Arch 2,W,2,,\"1,,3\",,RGB pixel,\"6,2,0\"\n");
+
+    // Exported layouts pass validation
+    let issues = proton_cli::validate_layout(&output_path, None).expect("Error validating layout");
+    assert!(issues.iter().all(|issue| issue.level == IssueLevel::Warning), "{:?}", issues);
+}
@@ -0,0 +1,31 @@
+extern crate proton_cli;
+
+use std::path::Path;
+
+use proton_cli::audio::{AudioBackend, SymphoniaBackend};
+use proton_cli::error::Error;
+
+
+#[test]
+fn reads_ogg_and_mp3_info() {
+    let info = proton_cli::get_music_info(&SymphoniaBackend, "tests/music/test_1sec.ogg")
+        .expect("Error reading ogg info");
+    assert_eq!((info.duration_ms, info.sample_rate, info.channels), (1020, 44100, 2));
+
+    // Both encodings of the same song are about the same length
+    let ogg = SymphoniaBackend.get_info(Path::new("tests/music/Doctor Who Intro.ogg"))
+        .expect("Error reading ogg info");
+    let mp3 = SymphoniaBackend.get_info(Path::new("tests/music/Doctor Who Intro.mp3"))
+        .expect("Error reading mp3 info");
+    assert_eq!(mp3.sample_rate, 44100);
+    assert!((ogg.duration_ms as i32 - mp3.duration_ms as i32).abs() < 100);
+}
+
+#[test]
+fn rejects_unsupported_files() {
+    match proton_cli::get_music_info(&SymphoniaBackend, "tests/music/song.mid") {
+        Err(Error::UnsupportedFileType(ext)) => assert_eq!(ext, "mid"),
+        other => panic!("Expected an unsupported file type, got {:?}", other),
+    }
+    assert!(proton_cli::get_music_info(&SymphoniaBackend, "tests/music/missing.ogg").is_err());
+}
@@ -0,0 +1,124 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::rc::Rc;
+
+use proton_cli::MusicStore;
+use proton_cli::audio::SymphoniaBackend;
+use proton_cli::error::Error;
+use proton_cli::project_types::{DurationPolicy, Project};
+use tempdir::TempDir;
+
+
+#[test]
+fn stores_music_by_content() {
+    let root = TempDir::new("proton_music").expect("Error creating temp dir");
+    let music_store = MusicStore::new(root.path().join("Music"));
+    assert_eq!(music_store.list().expect("Error listing empty store"), Vec::<String>::new());
+
+    let file_name = proton_cli::add_music(&SymphoniaBackend, &music_store, "tests/music/test_1sec.ogg")
+        .expect("Error adding music");
+    assert_eq!(file_name.len(), 64 + ".ogg".len());
+    assert!(file_name.ends_with(".ogg"));
+    assert!(music_store.contains(&file_name));
+    assert!(music_store.verify(&file_name).expect("Error verifying music"));
+
+    // Adding the same file again stores it once
+    let copy_path = root.path().join("copy.OGG");
+    fs::copy("tests/music/test_1sec.ogg", &copy_path).expect("Error copying music");
+    assert_eq!(music_store.add(&copy_path).expect("Error adding copy"), file_name);
+    assert_eq!(music_store.list().expect("Error listing store"), vec![file_name.clone()]);
+
+    // Changed files fail verification
+    let mut file = OpenOptions::new().append(true).open(music_store.path(&file_name))
+        .expect("Error opening stored music");
+    file.write_all(b"extra").expect("Error changing stored music");
+    assert!(!music_store.verify(&file_name).expect("Error verifying changed music"));
+
+    assert!(proton_cli::add_music(&SymphoniaBackend, &music_store, "tests/music/song.mid").is_err());
+}
+
+#[test]
+fn keeps_rejected_sequences_music_out_of_the_store() {
+    let root = TempDir::new("proton_music").expect("Error creating temp dir");
+    let music_store = MusicStore::new(root.path().join("Music"));
+    let data_dao = dao::DataDaoTesting::new();
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.get_layout_fn = Box::new(|layout_id| Ok(common::test_layout(layout_id, vec![])));
+    let sequence_dao = dao::SequenceDaoTesting::new();
+
+    // The sequence lasts 500ms, which would cut the second of music off
+    match proton_cli::new_sequence(
+        &SymphoniaBackend, &music_store, &data_dao, &layout_dao, &sequence_dao, "Short",
+        "tests/music/test_1sec.ogg", 500, None, Some(1), DurationPolicy::Error) {
+        Err(Error::DurationMismatch(_)) => (),
+        other => panic!("Expected a duration mismatch, got {:?}", other),
+    }
+    assert_eq!(music_store.list().expect("Error listing store"), Vec::<String>::new());
+}
+
+#[test]
+fn collects_unused_music_and_verifies_playlists() {
+    let root = TempDir::new("proton_music").expect("Error creating temp dir");
+    let music_store = MusicStore::new(root.path());
+    let used = music_store.add("tests/music/test_1sec.ogg").expect("Error adding music");
+    let unused = music_store.add("tests/music/Doctor Who Intro.mp3").expect("Error adding music");
+
+    let used_seq = used.clone();
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_all_sequences_fn = Box::new(move || Ok(vec![
+        common::test_sequence(1, 50, 21, 1, &used_seq),
+        common::test_sequence(2, 50, 21, 1, &used_seq),
+    ]));
+    let used_seq = used.clone();
+    sequence_dao.get_sequence_fn = Box::new(move |seqid| match seqid {
+        3 => Ok(common::test_sequence(3, 50, 21, 1, "missing.ogg")),
+        _ => Ok(common::test_sequence(seqid, 50, 21, 1, &used_seq)),
+    });
+
+    let files = proton_cli::list_music(&sequence_dao, &music_store).expect("Error listing music");
+    assert_eq!(files.len(), 2);
+    assert!(files.contains(&(used.clone(), vec![1, 2])));
+    assert!(files.contains(&(unused.clone(), vec![])));
+
+    // A dry run doesn't remove anything
+    assert_eq!(proton_cli::gc_music(&sequence_dao, &music_store, true).expect("Error in dry run"),
+        vec![unused.clone()]);
+    assert!(music_store.contains(&unused));
+    assert_eq!(proton_cli::gc_music(&sequence_dao, &music_store, false).expect("Error collecting music"),
+        vec![unused.clone()]);
+    assert_eq!(music_store.list().expect("Error listing store"), vec![used.clone()]);
+
+    let mut project_dao = dao::ProjectDaoTesting::new();
+    project_dao.get_project_fn = Box::new(|name| Ok(Project {
+        name: name,
+        playlist: vec![1, 3],
+        layout_id: 1
+    }));
+    let issues = proton_cli::verify_music(&project_dao, &sequence_dao, &music_store, "Test")
+        .expect("Error verifying music");
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].seqid, 3);
+    assert!(issues[0].message.contains("add-music --for-sequence=3"));
+
+    // Sequences with music named by its original file name can be moved into the store
+    let updated = Rc::new(RefCell::new(None));
+    let updated_ref = updated.clone();
+    sequence_dao.update_sequence_fn = Box::new(move |sequence| {
+        *updated_ref.borrow_mut() = Some(sequence);
+        Ok(())
+    });
+    let file_name = proton_cli::add_music_for_sequence(&SymphoniaBackend, &music_store, &sequence_dao, 3,
+        "tests/music/test_1sec.ogg")
+        .expect("Error adding music for sequence");
+    assert_eq!(file_name, used);
+    let updated = updated.borrow().clone().expect("Sequence wasn't updated");
+    assert_eq!(updated.seqid, 3);
+    assert_eq!(updated.music_file_name, used);
+}
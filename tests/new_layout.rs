@@ -0,0 +1,135 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+mod dao;
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::Write;
+use std::rc::Rc;
+
+use tempdir::TempDir;
+
+use proton_cli::project_types::{Channel, Fixture, Layout, SparePolicy};
+
+
+/// Two RGB elements in one fixture (rows interleaved with another fixture),
+/// plus two spare channels with different fixture names
+const TEST_LAYOUT: &'static str = r#"{"layoutName":"Test",
+    "fixtures":[{"fixtureName":"Bush","fixtureType":"RGB pixel","roles":["R","G","B"]}],
+    "channels":[
+        {"internalChannel":1,"dmxChannel":1,"fixtureName":"Bush","channelName":"Bush 1","color":"R",
+            "location":"0,0,0","rotation":"0,0,0"},
+        {"internalChannel":2,"dmxChannel":2,"fixtureName":"Bush","channelName":"Bush 1","color":"G",
+            "location":"0,0,0","rotation":"0,0,0"},
+        {"internalChannel":3,"dmxChannel":3,"fixtureName":"Bush","channelName":"Bush 1","color":"B",
+            "location":"0,0,0","rotation":"0,0,0"},
+        {"internalChannel":4,"dmxChannel":4,"fixtureName":"Arch","channelName":"Arch 1","color":"W",
+            "location":"0,0,0","rotation":"0,0,0"},
+        {"internalChannel":5,"dmxChannel":5,"fixtureName":"Bush","channelName":"Bush 2","color":"R",
+            "location":"2,0,0","rotation":"0,0,0"},
+        {"internalChannel":6,"dmxChannel":6,"fixtureName":"Bush","channelName":"Bush 2","color":"G",
+            "location":"2,0,0","rotation":"0,0,0"},
+        {"internalChannel":7,"dmxChannel":7,"fixtureName":"Bush","channelName":"Bush 2","color":"B",
+            "location":"2,0,0","rotation":"0,0,0"},
+        {"internalChannel":8,"dmxChannel":8,"fixtureName":"Spare","channelName":"Spare","color":"",
+            "location":"0,0,0","rotation":"0,0,0"},
+        {"internalChannel":9,"dmxChannel":9,"fixtureName":"X","channelName":"X","color":"",
+            "location":"0,0,0","rotation":"0,0,0"}
+    ]}"#;
+
+/// Creates a layout from the given file contents and returns the (name, type, channels)
+/// of each fixture created, in order
+fn new_layout_fixtures(
+    file_name: &str,
+    contents: &str,
+    spare_policy: SparePolicy
+) -> Vec<(String, Option<String>, Vec<u32>)> {
+    let dir = TempDir::new("proton_layout").expect("Error creating temp dir");
+    let layout_path = dir.path().join(file_name);
+    let mut file = File::create(&layout_path).expect("Error creating layout file");
+    file.write_all(contents.as_bytes()).expect("Error writing layout file");
+
+    // Channel ids match internal channel numbers
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.new_channel_fn = Box::new(|name, primary, secondary, color, internal, dmx, location, rotation| {
+        Ok(Channel {
+            chanid: internal,
+            name: name,
+            numbers: (primary, secondary),
+            color: color,
+            channel_internal: internal,
+            channel_dmx: dmx,
+            location: location,
+            rotation: rotation
+        })
+    });
+
+    let created = Rc::new(RefCell::new(Vec::new()));
+    let created_ref = created.clone();
+    let mut fixture_dao = dao::FixtureDaoTesting::new();
+    fixture_dao.new_fixture_fn = Box::new(move |name, fixture_type, location, rotation, size, channels| {
+        created_ref.borrow_mut().push((name.clone(), fixture_type.clone(), channels.clone()));
+        let mut fixture = Fixture::new(&name, None, location, rotation, size, channels);
+        fixture.fixture_type = fixture_type;
+        fixture.fixid = created_ref.borrow().len() as u32;
+        Ok(fixture)
+    });
+
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.new_layout_fn = Box::new(|name, fixtures| {
+        assert_eq!(name, "Test");
+        Ok(Layout { layout_id: 1, name: name, fixtures: fixtures })
+    });
+
+    let _ = proton_cli::new_layout(&channel_dao, &fixture_dao, &layout_dao, &layout_path, None, spare_policy)
+        .expect("Error creating layout");
+    let fixtures = created.borrow().clone();
+    fixtures
+}
+
+#[test]
+fn groups_channels_by_fixture_name() {
+    let fixtures = new_layout_fixtures("layout.json", TEST_LAYOUT, SparePolicy::Reserve);
+    assert_eq!(fixtures, vec![
+        ("Bush".to_owned(), Some("RGB pixel".to_owned()), vec![1, 2, 3, 5, 6, 7]),
+        ("Arch".to_owned(), None, vec![4]),
+        ("Spare".to_owned(), None, vec![8, 9]),
+    ]);
+}
+
+#[test]
+fn spare_channels_can_keep_their_fixtures() {
+    let fixtures = new_layout_fixtures("layout.json", TEST_LAYOUT, SparePolicy::Fixture);
+    let names = fixtures.iter()
+        .map(|&(ref name, _, ref channels)| (name.to_owned(), channels.to_owned()))
+        .collect::<Vec<(String, Vec<u32>)>>();
+    assert_eq!(names, vec![
+        ("Bush".to_owned(), vec![1, 2, 3, 5, 6, 7]),
+        ("Arch".to_owned(), vec![4]),
+        ("Spare".to_owned(), vec![8]),
+        ("X".to_owned(), vec![9]),
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "InvalidLayout")]
+fn fails_if_element_does_not_match_fixture_roles() {
+    let layout_json = TEST_LAYOUT.replace(
+        r#""channelName":"Bush 2","color":"B""#,
+        r#""channelName":"Bush 2","color":"W""#);
+    let _ = new_layout_fixtures("layout.json", &layout_json, SparePolicy::Reserve);
+}
+
+#[test]
+fn creates_layout_from_csv() {
+    let fixtures = new_layout_fixtures("Test.csv", "\
+        layoutName,internalChannel,dmxChannel,fixtureName,channelName,color,location,rotation,fixtureType,fixtureSize\n\
+        Test,1,1,Arch,Arch 1,W,\"0,0,0\",\"0,0,0\",Arch string,\"4,1,0\"\n\
+        ,2,5,Arch,Arch 2,W,\"2,0,0\",\"0,0,0\",,\n\
+        Test,3,6,Spare,Spare,,,,,\n", SparePolicy::Reserve);
+    assert_eq!(fixtures, vec![
+        ("Arch".to_owned(), Some("Arch string".to_owned()), vec![1, 2]),
+        ("Spare".to_owned(), None, vec![3]),
+    ]);
+}
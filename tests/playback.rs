@@ -0,0 +1,239 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::net::UdpSocket;
+use std::time::Duration;
+
+use proton_cli::{MusicStore, PlaybackEvent};
+use proton_cli::audio::SilentPlayer;
+use proton_cli::error::Error;
+use proton_cli::output::{self, DmxFrame, E131Sender, OutputDriver};
+use proton_cli::project_types::{DimmingCurve, DimmingProfile, Project, Sequence, TestPattern};
+use tempdir::TempDir;
+
+
+#[test]
+fn maps_dmx_channels_to_universes() {
+    assert_eq!(output::universe_slot(1), (1, 0));
+    assert_eq!(output::universe_slot(512), (1, 511));
+    assert_eq!(output::universe_slot(513), (2, 0));
+    assert_eq!(output::universe_slot(1100), (3, 75));
+
+    let mut frame = DmxFrame::new();
+    frame.set(3, 128);
+    frame.set(514, 300);
+    frame.set(0, 255);
+    assert_eq!(frame.universes.keys().cloned().collect::<Vec<u16>>(), vec![1, 2]);
+    assert_eq!(frame.universes[&1][2], 128);
+    assert_eq!(frame.universes[&2][1], 255);
+    assert_eq!(frame.universes[&2].len(), 512);
+}
+
+#[test]
+fn builds_e131_packets() {
+    let cid = [7; 16];
+    let data = (0..512).map(|slot| slot as u8).collect::<Vec<u8>>();
+    let packet = output::e131_packet(&cid, "Proton", 100, 42, 0, 259, &data);
+
+    assert_eq!(packet.len(), 638);
+    assert_eq!(&packet[0..4], &[0x00, 0x10, 0x00, 0x00]);
+    assert_eq!(&packet[4..16], b"ASC-E1.17\0\0\0");
+    assert_eq!(&packet[16..18], &[0x72, 0x6e]); // Root layer length, 622
+    assert_eq!(&packet[18..22], &[0, 0, 0, 4]);
+    assert_eq!(&packet[22..38], &cid);
+    assert_eq!(&packet[38..40], &[0x72, 0x58]); // Framing layer length, 600
+    assert_eq!(&packet[40..44], &[0, 0, 0, 2]);
+    assert_eq!(&packet[44..50], b"Proton");
+    assert!(packet[50..108].iter().all(|byte| *byte == 0));
+    assert_eq!(packet[108], 100); // Priority
+    assert_eq!(packet[111], 42); // Sequence number
+    assert_eq!(&packet[113..115], &[1, 3]); // Universe
+    assert_eq!(&packet[115..117], &[0x72, 0x0b]); // DMP layer length, 523
+    assert_eq!(&packet[117..125], &[0x02, 0xa1, 0, 0, 0, 1, 0x02, 0x01]);
+    assert_eq!(packet[125], 0); // Start code
+    assert_eq!(&packet[126..], &data[..]);
+}
+
+#[test]
+fn sends_e131_to_receiver() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").expect("Error binding receiver");
+    receiver.set_read_timeout(Some(Duration::from_secs(5))).expect("Error setting timeout");
+    let address = receiver.local_addr().expect("Error getting receiver address");
+
+    let mut sender = E131Sender::new(Some(address), "Proton test", 150).expect("Error creating sender");
+    assert_eq!(sender.address(9), address);
+    let mut frame = DmxFrame::new();
+    frame.set(1, 255);
+    frame.set(513, 17);
+    sender.send(&frame).expect("Error sending frame");
+    sender.send(&frame).expect("Error sending frame");
+
+    let mut buf = [0; 1024];
+    let mut packets = Vec::new();
+    for _ in 0..4 {
+        let (len, _) = receiver.recv_from(&mut buf).expect("Error receiving packet");
+        packets.push(buf[..len].to_vec());
+    }
+    // (universe, sequence number, first channel)
+    let summary = packets.iter()
+        .map(|packet| (packet[114], packet[111], packet[126]))
+        .collect::<Vec<(u8, u8, u8)>>();
+    assert_eq!(summary, vec![(1, 1, 255), (2, 1, 17), (1, 2, 255), (2, 2, 17)]);
+    assert!(packets.iter().all(|packet| packet[108] == 150 && packet.len() == 638));
+
+    // Receivers are told when the show is over
+    sender.finish().expect("Error finishing");
+    let (len, _) = receiver.recv_from(&mut buf).expect("Error receiving packet");
+    assert_eq!(buf[112] & 0x40, 0x40);
+    assert_eq!(len, 126);
+
+    // Multicast goes to each universe's group
+    let multicast = E131Sender::new(None, "Proton test", 100).expect("Error creating sender");
+    assert_eq!(multicast.address(1).to_string(), "239.255.0.1:5568");
+    assert_eq!(multicast.address(258).to_string(), "239.255.1.2:5568");
+    assert!(E131Sender::new(None, "Proton test", 201).is_err());
+}
+
+#[test]
+fn parses_addresses() {
+    assert_eq!(output::parse_address("10.0.0.5", 5568).ok().map(|address| address.to_string()),
+        Some("10.0.0.5:5568".to_owned()));
+    assert_eq!(output::parse_address("10.0.0.5:6000", 5568).ok().map(|address| address.to_string()),
+        Some("10.0.0.5:6000".to_owned()));
+    match output::parse_address("controller", 5568) {
+        Err(Error::InvalidOutput(_)) => (),
+        other => panic!("Expected invalid output settings, got {:?}", other),
+    }
+}
+
+#[test]
+fn plays_playlist_in_time() {
+    let root = TempDir::new("proton_playback").expect("Error creating temp dir");
+    let music_store = MusicStore::new(root.path());
+    let file_name = music_store.add("tests/music/test_1sec.ogg").expect("Error adding music");
+
+    let mut project_dao = dao::ProjectDaoTesting::new();
+    project_dao.get_project_fn = Box::new(|name| Ok(Project {
+        name: name,
+        playlist: vec![1, 2],
+        layout_id: 1
+    }));
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(move |seqid| Ok(Sequence {
+        music_offset_ms: if seqid == 1 { 0 } else { 40 },
+        ..common::test_sequence(seqid, 25, if seqid == 1 { 8 } else { 4 }, 1, &file_name)
+    }));
+    sequence_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1, 2, 3]));
+
+    // Channel 3 isn't patched, so isn't sent
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(|chanid| Ok(common::test_channel(chanid, chanid, match chanid { 1 => 1, 2 => 600, _ => 0 })));
+
+    // Channel 1 counts up each frame, so frames can be told apart
+    let mut data_dao = dao::DataDaoTesting::new();
+    data_dao.get_data_fn = Box::new(|seqid, chanid| {
+        let num_frames = if seqid == 1 { 8 } else { 4 };
+        Ok((0..num_frames).map(|frame| if chanid == 1 { frame * 10 + seqid as u16 } else { 200 }).collect())
+    });
+
+    // Channel 2 is limited, so is sent lower than its data
+    let mut dimming_dao = dao::DimmingDaoTesting::new();
+    dimming_dao.get_dimming_fn = Box::new(|_| {
+        let mut dimming = BTreeMap::new();
+        dimming.insert(2, DimmingProfile::new(DimmingCurve::Linear, 0, 150).unwrap());
+        Ok(dimming)
+    });
+
+    let mut driver = common::RecordingDriver::new();
+    let events = RefCell::new(Vec::new());
+    let report = proton_cli::play_project(
+        &SilentPlayer,
+        &mut driver,
+        &channel_dao,
+        &data_dao,
+        &dimming_dao,
+        &project_dao,
+        &sequence_dao,
+        &music_store,
+        "Test",
+        &|event| events.borrow_mut().push(event))
+        .expect("Error playing project");
+
+    assert_eq!(report.sequences, 2);
+    assert_eq!(*events.borrow(), vec![
+        PlaybackEvent::Playing("Sequence 1".to_owned()),
+        PlaybackEvent::Playing("Sequence 2".to_owned())]);
+    assert_eq!(report.frames_sent + report.frames_dropped, 12);
+    let frames = driver.frames.borrow();
+    assert_eq!(frames.len() as u32, report.frames_sent + 1);
+    assert!(*driver.finished.borrow());
+
+    // Frames are sent in order, each with both universes
+    let firsts = frames.iter()
+        .map(|frame| frame.universes[&1][0])
+        .collect::<Vec<u8>>();
+    let (first, second) = firsts[..firsts.len() - 1].split_at(firsts.iter().position(|value| value % 10 == 2).unwrap());
+    assert!(first.windows(2).all(|pair| pair[0] < pair[1]) && first.iter().all(|value| value % 10 == 1));
+    assert!(second.windows(2).all(|pair| pair[0] < pair[1]) && second.iter().all(|value| value % 10 == 2));
+    assert!(frames.iter().all(|frame| frame.universes.len() == 2));
+    assert!(frames[..frames.len() - 1].iter().all(|frame| frame.universes[&2][87] == 150));
+
+    // Then a blackout
+    let blackout = frames.last().unwrap();
+    assert!(blackout.universes.values().all(|data| data.iter().all(|value| *value == 0)));
+
+    // Music has to be in the store
+    let empty_store = MusicStore::new(root.path().join("Empty"));
+    let mut driver = common::RecordingDriver::new();
+    match proton_cli::play_project(&SilentPlayer, &mut driver, &channel_dao, &data_dao, &dimming_dao,
+        &project_dao, &sequence_dao, &empty_store, "Test", &|_| ()) {
+        Err(Error::MusicNotVerified(_)) => assert!(driver.frames.borrow().is_empty()),
+        other => panic!("Expected music not verified, got {:?}", other),
+    }
+}
+
+#[test]
+fn makes_test_patterns() {
+    assert_eq!("chase".parse::<TestPattern>().ok(), Some(TestPattern::Chase));
+    match "strobe".parse::<TestPattern>() {
+        Err(Error::InvalidTestPattern(pattern)) => assert_eq!(pattern, "strobe"),
+        other => panic!("Expected an invalid test pattern, got {:?}", other),
+    }
+
+    assert_eq!(TestPattern::Full.value(3, 4, 1234), 255);
+    assert_eq!(TestPattern::Ramp.value(0, 4, 0), 0);
+    assert_eq!(TestPattern::Ramp.value(0, 4, 1999), 255);
+    assert_eq!(TestPattern::Ramp.value(2, 4, 2000), 0);
+    let chase = (0..6).map(|step| TestPattern::Chase.value(1, 3, step * 250)).collect::<Vec<u16>>();
+    assert_eq!(chase, vec![0, 255, 0, 0, 255, 0]);
+}
+
+#[test]
+fn sends_test_patterns_to_layout() {
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1, 2, 3]));
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(|chanid| Ok(common::test_channel(chanid, chanid, match chanid { 1 => 5, 2 => 0, _ => 520 })));
+
+    let mut dimming_dao = dao::DimmingDaoTesting::new();
+    dimming_dao.get_dimming_fn = Box::new(|_| Ok(BTreeMap::new()));
+
+    let mut driver = common::RecordingDriver::new();
+    let report = proton_cli::play_test_pattern(&mut driver, &channel_dao, &dimming_dao, &layout_dao, 1, TestPattern::Full, 100)
+        .expect("Error sending test pattern");
+    assert_eq!(report.frames_sent + report.frames_dropped, 4);
+    assert!(*driver.finished.borrow());
+
+    // Only patched channels are lit
+    let frames = driver.frames.borrow();
+    let frame = &frames[0];
+    assert_eq!(frame.universes.keys().cloned().collect::<Vec<u16>>(), vec![1, 2]);
+    assert_eq!(frame.universes[&1].iter().filter(|value| **value > 0).count(), 1);
+    assert_eq!((frame.universes[&1][4], frame.universes[&2][7]), (255, 255));
+    assert!(frames.last().unwrap().universes[&2].iter().all(|value| *value == 0));
+}
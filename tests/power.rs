@@ -0,0 +1,172 @@
+extern crate proton_cli;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use proton_cli::error::Error;
+use proton_cli::project_types::{Channel, ChannelLoad, Controller, ControllerProtocol, DimmingCurve, DimmingProfile, EditTargets, LoadGroup, Overload, PortAssignment, Sequence};
+
+
+fn assert_near(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 0.01, "Expected {}, got {}", expected, actual);
+}
+
+fn porch(watts: f64) -> ChannelLoad {
+    ChannelLoad::new(watts, Some("Porch".to_owned())).unwrap()
+}
+
+/// Channel 1 is on universe 1 and channel 2 on universe 2, both on the porch circuit.
+/// Channel 3 has no load, and channel 4 isn't patched.
+fn porch_channel(chanid: u32) -> Result<Channel, Error> {
+    Ok(common::test_channel(chanid, chanid + 10, match chanid { 1 => 1, 2 => 600, 3 => 2, _ => 0 }))
+}
+
+#[test]
+fn finds_overloads_in_runs() {
+    let group = LoadGroup::Circuit("Porch".to_owned());
+    let overloads = Overload::find(&group, &[0.0, 300.0, 250.0, 0.0, 300.0], 50, 100.0, 2.0);
+    assert_eq!(overloads, vec![
+        Overload { group: group.clone(), start_ms: 50, end_ms: 150, peak_amps: 3.0 },
+        Overload { group: group.clone(), start_ms: 200, end_ms: 250, peak_amps: 3.0 },
+    ]);
+    assert!(Overload::find(&group, &[200.0, 0.0], 50, 100.0, 2.0).is_empty());
+
+    assert!(ChannelLoad::new(-1.0, None).is_err());
+    match ChannelLoad::new(10.0, Some(" ".to_owned())) {
+        Err(Error::InvalidPower(_)) => (),
+        other => panic!("Expected invalid power settings, got {:?}", other),
+    }
+}
+
+#[test]
+fn reports_load_by_circuit_controller_universe_and_total() {
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(|seqid| Ok(Sequence {
+        music_duration_ms: 200,
+        ..common::test_sequence(seqid, 50, 4, 3, "test.ogg")
+    }));
+    sequence_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1, 2, 3, 4]));
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(porch_channel);
+    let mut data_dao = dao::DataDaoTesting::new();
+    data_dao.get_data_fn = Box::new(|_, chanid| Ok(match chanid {
+        1 => vec![0, 255, 255, 0],
+        _ => vec![255, 255, 255, 0],
+    }));
+
+    // Channel 2 is limited to about half, so draws about half its load
+    let mut dimming_dao = dao::DimmingDaoTesting::new();
+    dimming_dao.get_dimming_fn = Box::new(|_| {
+        let mut dimming = BTreeMap::new();
+        dimming.insert(2, DimmingProfile::new(DimmingCurve::Linear, 0, 128).unwrap());
+        Ok(dimming)
+    });
+    let mut power_dao = dao::PowerDaoTesting::new();
+    power_dao.get_loads_fn = Box::new(|_| {
+        let mut loads = BTreeMap::new();
+        loads.insert(1, porch(100.0));
+        loads.insert(2, porch(200.0));
+        loads.insert(4, porch(1000.0));
+        Ok(loads)
+    });
+    power_dao.get_circuit_limits_fn = Box::new(|layout_id| {
+        assert_eq!(layout_id, 3);
+        let mut limits = BTreeMap::new();
+        limits.insert("Porch".to_owned(), 2.0);
+        Ok(limits)
+    });
+
+    // Channels 1 and 2 are wired to the porch controller
+    let mut controller_dao = dao::ControllerDaoTesting::new();
+    controller_dao.get_controllers_fn = Box::new(|layout_id| {
+        let mut controller = try!(Controller::new(layout_id, "Porch box", "10.0.0.5", ControllerProtocol::E131, vec![1, 2], 4, 510));
+        controller.controllerid = 5;
+        Ok(vec![controller])
+    });
+    controller_dao.get_ports_fn = Box::new(|_| {
+        let mut ports = BTreeMap::new();
+        ports.insert(1, PortAssignment { controllerid: 5, port: 1 });
+        ports.insert(2, PortAssignment { controllerid: 5, port: 2 });
+        Ok(ports)
+    });
+
+    let report = proton_cli::check_power(&channel_dao, &controller_dao, &data_dao, &dimming_dao, &power_dao, &sequence_dao, 7, 100.0)
+        .expect("Error checking power");
+    assert_eq!(report.unrated_channels, 1);
+
+    let groups = report.summaries.iter()
+        .map(|summary| summary.group.clone())
+        .collect::<Vec<LoadGroup>>();
+    assert_eq!(groups, vec![
+        LoadGroup::Circuit("Porch".to_owned()),
+        LoadGroup::Controller("Porch box".to_owned()),
+        LoadGroup::Universe(1),
+        LoadGroup::Universe(2),
+        LoadGroup::Total,
+    ]);
+    let circuit = &report.summaries[0];
+    assert_near(circuit.peak_watts, 200.39);
+    assert_eq!((circuit.peak_ms, circuit.max_amps), (50, Some(2.0)));
+    assert_near(circuit.average_watts, 125.29);
+    assert_near(report.summaries[1].peak_watts, circuit.peak_watts);
+    assert_near(report.summaries[2].average_watts, 50.0);
+    assert_eq!(report.summaries[3].peak_ms, 0);
+    let total = &report.summaries[4];
+    assert_near(total.peak_watts, circuit.peak_watts);
+    assert_eq!(total.max_amps, None);
+
+    // Both channels together are just over the porch's limit
+    assert_eq!(report.overloads.len(), 1);
+    assert_eq!((report.overloads[0].start_ms, report.overloads[0].end_ms), (50, 150));
+    assert_near(report.overloads[0].peak_amps, 2.0);
+
+    match proton_cli::check_power(&channel_dao, &controller_dao, &data_dao, &dimming_dao, &power_dao, &sequence_dao, 7, 0.0) {
+        Err(Error::InvalidPower(_)) => (),
+        other => panic!("Expected invalid power settings, got {:?}", other),
+    }
+}
+
+#[test]
+fn sets_loads_and_circuit_limits() {
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(porch_channel);
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1, 2, 3, 4]));
+    layout_dao.layout_exists_fn = Box::new(|layout_id| Ok(layout_id == 1));
+
+    let set = Rc::new(RefCell::new(Vec::new()));
+    let set_load = set.clone();
+    let mut power_dao = dao::PowerDaoTesting::new();
+    power_dao.set_channel_load_fn = Box::new(move |chanid, load| {
+        set_load.borrow_mut().push((chanid, load));
+        Ok(())
+    });
+    let limits = Rc::new(RefCell::new(Vec::new()));
+    let limits_set = limits.clone();
+    power_dao.set_circuit_limit_fn = Box::new(move |layout_id, circuit, max_amps| {
+        limits_set.borrow_mut().push((layout_id, circuit, max_amps));
+        Ok(())
+    });
+
+    let num_changed = proton_cli::set_load(&channel_dao, &layout_dao, &power_dao, 1,
+        &EditTargets::Channels(vec![12, 13]), Some(&porch(60.0)))
+        .expect("Error setting loads");
+    assert_eq!(num_changed, 2);
+    assert_eq!(*set.borrow(), vec![(2, Some(porch(60.0))), (3, Some(porch(60.0)))]);
+
+    proton_cli::set_circuit_limit(&layout_dao, &power_dao, 1, "Porch", Some(12.0))
+        .expect("Error setting limit");
+    proton_cli::set_circuit_limit(&layout_dao, &power_dao, 1, "Porch", None)
+        .expect("Error clearing limit");
+    assert_eq!(*limits.borrow(), vec![(1, "Porch".to_owned(), Some(12.0)), (1, "Porch".to_owned(), None)]);
+    assert!(proton_cli::set_circuit_limit(&layout_dao, &power_dao, 1, "Porch", Some(0.0)).is_err());
+    match proton_cli::set_circuit_limit(&layout_dao, &power_dao, 2, "Porch", Some(12.0)) {
+        Err(Error::LayoutNotFound(layout_id)) => assert_eq!(layout_id, 2),
+        other => panic!("Expected layout not found, got {:?}", other),
+    }
+    assert_eq!(limits.borrow().len(), 2);
+}
@@ -0,0 +1,109 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use proton_cli::MusicStore;
+use proton_cli::audio::{Band, SymphoniaBackend};
+use proton_cli::effects::Color;
+use proton_cli::error::Error;
+use proton_cli::project_types::{Fixture, ReactiveMapping, Sequence};
+use tempdir::TempDir;
+
+
+#[test]
+fn parses_mappings() {
+    let mapping = "bass:Megatree:red".parse::<ReactiveMapping>().expect("Error parsing mapping");
+    assert_eq!(mapping, ReactiveMapping {
+        band: Band::Bass,
+        fixtures: vec!["Megatree".to_owned()],
+        color: Color::new(1.0, 0.0, 0.0)
+    });
+    let mapping = "high:Net 1, Net 2".parse::<ReactiveMapping>().expect("Error parsing mapping");
+    assert_eq!((mapping.fixtures.len(), mapping.color), (2, Color::white()));
+    assert!("mid:*".parse::<ReactiveMapping>().expect("Error parsing mapping").fixtures.is_empty());
+
+    match "treble:*".parse::<ReactiveMapping>() {
+        Err(Error::InvalidBand(band)) => assert_eq!(band, "treble"),
+        other => panic!("Expected an invalid band, got {:?}", other),
+    }
+    assert!("bass".parse::<ReactiveMapping>().is_err());
+    assert!("bass:Tree,:red".parse::<ReactiveMapping>().is_err());
+    assert!("bass:Tree:purple".parse::<ReactiveMapping>().is_err());
+}
+
+#[test]
+fn generates_data_for_mapped_fixtures() {
+    let root = TempDir::new("proton_reactive").expect("Error creating temp dir");
+    let music_store = MusicStore::new(root.path());
+    let file_name = music_store.add("tests/music/test_1sec.ogg").expect("Error adding music");
+
+    // Megatree is one RGB pixel (channels 1-3), Net is a single channel (4),
+    // and Arch (5) isn't mapped
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(|chanid| Ok(match chanid {
+        1 => common::test_named_channel(chanid, "Pixel", "R", chanid, chanid),
+        2 => common::test_named_channel(chanid, "Pixel", "G", chanid, chanid),
+        3 => common::test_named_channel(chanid, "Pixel", "B", chanid, chanid),
+        _ => common::test_channel(chanid, chanid, chanid),
+    }));
+    let mut fixture_dao = dao::FixtureDaoTesting::new();
+    fixture_dao.get_fixture_fn = Box::new(|fixid| {
+        let (name, channels) = match fixid {
+            1 => ("Megatree", vec![1, 2, 3]),
+            2 => ("Net", vec![4]),
+            _ => ("Arch", vec![5]),
+        };
+        let mut fixture = Fixture::new(name, None, (0, 0, 0), (0, 0, 0), (0, 0, 0), channels);
+        fixture.fixid = fixid;
+        Ok(fixture)
+    });
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.get_layout_fn = Box::new(|layout_id| Ok(common::test_layout(layout_id, vec![1, 2, 3])));
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(move |seqid| Ok(Sequence {
+        music_duration_ms: 1020,
+        ..common::test_sequence(seqid, 50, 21, 1, &file_name)
+    }));
+
+    let updated = Rc::new(RefCell::new(Vec::new()));
+    let updated_ref = updated.clone();
+    let mut data_dao = dao::DataDaoTesting::new();
+    data_dao.update_data_fn = Box::new(move |_, chanid, data| {
+        updated_ref.borrow_mut().push((chanid, data));
+        Ok(())
+    });
+
+    let generate = |mappings: &[&str]| {
+        let mappings = mappings.iter()
+            .map(|mapping| mapping.parse::<ReactiveMapping>().expect("Error parsing mapping"))
+            .collect::<Vec<ReactiveMapping>>();
+        proton_cli::generate_reactive(&SymphoniaBackend, &music_store, &channel_dao, &data_dao,
+            &fixture_dao, &layout_dao, &sequence_dao, 1, &mappings)
+    };
+
+    let num_channels = generate(&["bass:Megatree:red", "high:Net"]).expect("Error generating data");
+    assert_eq!(num_channels, 4);
+    let updated = updated.borrow();
+    assert_eq!(updated.iter().map(|&(chanid, _)| chanid).collect::<Vec<u32>>(), vec![1, 2, 3, 4]);
+    assert!(updated.iter().all(|&(_, ref data)| data.len() == 21));
+    assert_eq!(updated[0].1.iter().max(), Some(&255));
+    assert_eq!(updated[1].1, vec![0; 21]);
+    assert_eq!(updated[3].1.iter().max(), Some(&255));
+
+    // Levels fade out rather than dropping straight to zero
+    for &(_, ref data) in updated.iter() {
+        for frames in data.windows(2) {
+            assert!(frames[1] as i32 >= frames[0] as i32 - 64, "Dropped too fast: {:?}", data);
+        }
+    }
+
+    match generate(&["bass:Tree"]) {
+        Err(Error::InvalidReactiveMapping(_)) => {},
+        other => panic!("Expected an invalid mapping, got {:?}", other),
+    }
+}
@@ -0,0 +1,87 @@
+extern crate proton_cli;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use proton_cli::error::Error;
+use proton_cli::project_types::Sequence;
+
+
+/// 4 frames of 50ms with a 100ms music offset, 2 seconds of music,
+/// and one channel with data [1, 2, 3, 4]
+fn test_daos(
+    updated_sequence: Rc<RefCell<Option<Sequence>>>,
+    updated_data: Rc<RefCell<Vec<Vec<u16>>>>
+) -> (dao::DataDaoTesting, dao::SequenceDaoTesting) {
+
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(|seqid| Ok(Sequence {
+        music_duration_ms: 2000,
+        music_offset_ms: 100,
+        ..common::test_sequence(seqid, 50, 4, 1, "test.ogg")
+    }));
+    sequence_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1]));
+    sequence_dao.update_sequence_fn = Box::new(move |sequence| {
+        *updated_sequence.borrow_mut() = Some(sequence);
+        Ok(())
+    });
+
+    let mut data_dao = dao::DataDaoTesting::new();
+    data_dao.get_data_fn = Box::new(|_, _| Ok(vec![1, 2, 3, 4]));
+    data_dao.update_data_fn = Box::new(move |_, _, data| {
+        updated_data.borrow_mut().push(data);
+        Ok(())
+    });
+
+    (data_dao, sequence_dao)
+}
+
+#[test]
+fn resizes_at_end_or_start() {
+    let updated_sequence = Rc::new(RefCell::new(None));
+    let updated_data = Rc::new(RefCell::new(Vec::new()));
+    let (mut data_dao, sequence_dao) = test_daos(updated_sequence.clone(), updated_data.clone());
+
+    let resize = |duration_ms: u32, at_start: bool| {
+        let num_frames = proton_cli::resize_sequence(&data_dao, &sequence_dao, 1, duration_ms, at_start)
+            .expect("Error resizing sequence");
+        let sequence = updated_sequence.borrow().clone().expect("Sequence not updated");
+        let data = updated_data.borrow_mut().pop().expect("Data not updated");
+        (num_frames, sequence.num_frames, sequence.music_offset_ms, data)
+    };
+
+    assert_eq!(resize(300, false), (6, 6, 100, vec![1, 2, 3, 4, 0, 0]));
+    assert_eq!(resize(120, false), (3, 3, 100, vec![1, 2, 3]));
+    assert_eq!(resize(300, true), (6, 6, 200, vec![0, 0, 1, 2, 3, 4]));
+    assert_eq!(resize(100, true), (2, 2, 0, vec![3, 4]));
+
+    // Data shorter than the sequence is padded out before frames are taken off the start
+    data_dao.get_data_fn = Box::new(|_, _| Ok(vec![1]));
+    let num_frames = proton_cli::resize_sequence(&data_dao, &sequence_dao, 1, 100, true)
+        .expect("Error resizing sequence");
+    assert_eq!(num_frames, 2);
+    assert_eq!(updated_data.borrow_mut().pop(), Some(vec![0, 0]));
+
+    match proton_cli::resize_sequence(&data_dao, &sequence_dao, 1, 0, false) {
+        Err(Error::InvalidSequenceDuration(0)) => {},
+        other => panic!("Expected an invalid duration, got {:?}", other),
+    }
+}
+
+#[test]
+fn sets_music_offset_within_sequence_and_music() {
+    let updated_sequence = Rc::new(RefCell::new(None));
+    let updated_data = Rc::new(RefCell::new(Vec::new()));
+    let (_, sequence_dao) = test_daos(updated_sequence.clone(), updated_data);
+
+    proton_cli::set_music_offset(&sequence_dao, 1, -1500).expect("Error setting offset");
+    assert_eq!(updated_sequence.borrow().clone().unwrap().music_offset_ms, -1500);
+
+    // The sequence is 200ms long and the music 2 seconds
+    assert!(proton_cli::set_music_offset(&sequence_dao, 1, 200).is_err());
+    assert!(proton_cli::set_music_offset(&sequence_dao, 1, -2000).is_err());
+    assert!(proton_cli::set_music_offset(&sequence_dao, 1, i32::min_value()).is_err());
+}
@@ -0,0 +1,63 @@
+extern crate proton_cli;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use proton_cli::error::Error;
+use proton_cli::project_types::ResampleMode;
+
+
+#[test]
+fn resamples_data_with_each_mode() {
+    let data = vec![0, 100, 200];
+    assert_eq!(ResampleMode::Linear.resample(&data, 50, 25), vec![0, 50, 100, 150, 200, 200]);
+    assert_eq!(ResampleMode::Nearest.resample(&data, 50, 25), vec![0, 100, 100, 200, 200, 200]);
+    assert_eq!(ResampleMode::Hold.resample(&data, 50, 25), vec![0, 0, 100, 100, 200, 200]);
+    assert_eq!(ResampleMode::Hold.resample(&data, 50, 30), vec![0, 0, 100, 100, 200]);
+    assert_eq!(ResampleMode::Linear.resample(&vec![0, 10, 20, 30], 25, 50), vec![0, 20]);
+
+    assert_eq!("hold".parse::<ResampleMode>().unwrap(), ResampleMode::Hold);
+    assert!("cubic".parse::<ResampleMode>().is_err());
+}
+
+#[test]
+fn retimes_sequence_data_and_frames() {
+    let retimed = Rc::new(RefCell::new(None));
+    let retimed_ref = retimed.clone();
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(|seqid| Ok(common::test_sequence(seqid, 50, 3, 1, "test.ogg")));
+    sequence_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1, 2]));
+    sequence_dao.retime_sequence_fn = Box::new(move |sequence, old_frame_duration_ms, data| {
+        *retimed_ref.borrow_mut() = Some((sequence, old_frame_duration_ms, data));
+        Ok(())
+    });
+
+    let mut data_dao = dao::DataDaoTesting::new();
+    data_dao.get_data_fn = Box::new(|_, chanid| Ok(vec![0, 100 * chanid as u16, 0]));
+
+    let num_frames = proton_cli::retime_sequence(&data_dao, &sequence_dao, 1, 25, ResampleMode::Linear)
+        .expect("Error retiming sequence");
+    assert_eq!(num_frames, 6);
+    let (sequence, old_frame_duration_ms, data) = retimed.borrow_mut().take().expect("Sequence not retimed");
+    assert_eq!((sequence.frame_duration_ms, sequence.num_frames, old_frame_duration_ms), (25, 6, 50));
+    assert_eq!(data, vec![
+        (1, vec![0, 50, 100, 50, 0, 0]),
+        (2, vec![0, 100, 200, 100, 0, 0]),
+    ]);
+
+    // Nothing is stored if any channel's data can't be read
+    data_dao.get_data_fn = Box::new(|seqid, chanid| match chanid {
+        1 => Ok(vec![0, 0, 0]),
+        _ => Err(Error::ChannelDataNotFound(seqid, chanid)),
+    });
+    assert!(proton_cli::retime_sequence(&data_dao, &sequence_dao, 1, 25, ResampleMode::Linear).is_err());
+    assert!(retimed.borrow().is_none());
+
+    match proton_cli::retime_sequence(&data_dao, &sequence_dao, 1, 20, ResampleMode::Hold) {
+        Err(Error::InvalidFrameDuration(20)) => {},
+        other => panic!("Expected an invalid frame duration, got {:?}", other),
+    }
+}
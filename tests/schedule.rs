@@ -0,0 +1,270 @@
+extern crate chrono;
+extern crate proton_cli;
+extern crate tempdir;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, NaiveDateTime, Weekday};
+use proton_cli::{Clock, MusicStore, PlaybackEvent};
+use proton_cli::audio::SilentPlayer;
+use proton_cli::error::Error;
+use proton_cli::output::{DmxFrame, OutputDriver};
+use proton_cli::project_types::{Project, Schedule, ScheduleDecision, ScheduledSequence, Sequence};
+use tempdir::TempDir;
+
+
+const SHOW_NIGHTS: &'static str = r#"{
+    "windows": [{"start": "17:30", "end": "17:39"}],
+    "repeat": true,
+    "intermission": {"seqid": 9, "every_minutes": 5}
+}"#;
+
+/// A clock that only moves when it's slept on
+struct FakeClock {
+    now: RefCell<NaiveDateTime>,
+    sleeps: RefCell<Vec<NaiveDateTime>>,
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> NaiveDateTime {
+        *self.now.borrow()
+    }
+
+    fn sleep_until(&self, time: NaiveDateTime) {
+        self.sleeps.borrow_mut().push(time);
+        if time > *self.now.borrow() {
+            *self.now.borrow_mut() = time;
+        }
+    }
+}
+
+/// Counts the frames it's sent
+struct CountingDriver {
+    frames: u32,
+}
+
+impl OutputDriver for CountingDriver {
+    fn send(&mut self, _frame: &DmxFrame) -> Result<(), Error> {
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+/// A time on Friday 16 October 2026
+fn friday(hour: u32, min: u32, sec: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2026, 10, 16).unwrap().and_hms_opt(hour, min, sec).unwrap()
+}
+
+fn scheduled(seqid: u32, start: NaiveDateTime, duration_ms: u32, intermission: bool) -> ScheduledSequence {
+    ScheduledSequence {
+        seqid: seqid,
+        start: start,
+        duration_ms: duration_ms,
+        intermission: intermission
+    }
+}
+
+fn show_durations() -> BTreeMap<u32, u32> {
+    let mut durations = BTreeMap::new();
+    durations.insert(1, 90000);
+    durations.insert(2, 150000);
+    durations.insert(9, 30000);
+    durations
+}
+
+#[test]
+fn parses_schedules() {
+    let text = r#"{"windows": [{"days": ["fri", "Saturday"], "start": "17:30", "end": "22:00"}], "shuffle": true}"#;
+    let schedule = Schedule::from_json(text).expect("Error parsing schedule");
+    assert_eq!(schedule.windows[0].days, vec![Weekday::Fri, Weekday::Sat]);
+    assert_eq!(schedule.windows[0].start.to_string(), "17:30:00");
+    assert!(!schedule.repeat && schedule.shuffle && schedule.intermission.is_none());
+
+    // Windows run every day by default, and schedules are stored as they're read
+    let schedule = Schedule::from_json(SHOW_NIGHTS).expect("Error parsing schedule");
+    assert_eq!(schedule.windows[0].days.len(), 7);
+    let stored = schedule.to_json().expect("Error encoding schedule");
+    assert_eq!(Schedule::from_json(&stored).ok(), Some(schedule));
+
+    for text in &[
+        r#"{"windows": []}"#,
+        r#"{"windows": [{"days": [], "start": "17:30", "end": "22:00"}]}"#,
+        r#"{"windows": [{"days": ["caturday"], "start": "17:30", "end": "22:00"}]}"#,
+        r#"{"windows": [{"start": "5:30pm", "end": "22:00"}]}"#,
+        r#"{"windows": [{"start": "17:30", "end": "17:30"}]}"#,
+        r#"{"windows": [{"start": "17:30", "end": "22:00"}], "intermission": {"seqid": 9, "every_minutes": 0}}"#,
+    ] {
+        match Schedule::from_json(text) {
+            Err(Error::InvalidSchedule(_)) => (),
+            other => panic!("Expected an invalid schedule for {}, got {:?}", text, other),
+        }
+    }
+    assert!(Schedule::from_json(r#"{"start": "17:30"}"#).is_err());
+}
+
+#[test]
+fn finds_shows() {
+    let text = r#"{"windows": [{"days": ["fri"], "start": "23:00", "end": "01:00"}]}"#;
+    let schedule = Schedule::from_json(text).expect("Error parsing schedule");
+    let late_show = (friday(23, 0, 0), friday(23, 0, 0) + chrono::Duration::hours(2));
+
+    // Windows that end before they start run past midnight
+    assert_eq!(schedule.show_at(friday(23, 30, 0)), Some(late_show));
+    assert_eq!(schedule.show_at(friday(23, 30, 0) + chrono::Duration::hours(1)), Some(late_show));
+    assert_eq!(schedule.show_at(friday(22, 59, 59)), None);
+    assert_eq!(schedule.next_show(friday(12, 0, 0)).map(|show| show.0), Some(friday(23, 0, 0)));
+
+    // The next one is a week later
+    assert_eq!(schedule.next_show(friday(23, 30, 0)).map(|show| show.0),
+        Some(friday(23, 0, 0) + chrono::Duration::days(7)));
+}
+
+#[test]
+fn builds_running_order() {
+    let schedule = Schedule::from_json(SHOW_NIGHTS).expect("Error parsing schedule");
+    let order = schedule.running_order(&[1, 2], &show_durations(), friday(17, 30, 0), friday(17, 39, 0))
+        .expect("Error building running order");
+
+    // The playlist loops, the intermission plays once 5 minutes have passed,
+    // and the last sequence starts before the end and runs over it
+    assert_eq!(order, vec![
+        scheduled(1, friday(17, 30, 0), 90000, false),
+        scheduled(2, friday(17, 31, 30), 150000, false),
+        scheduled(1, friday(17, 34, 0), 90000, false),
+        scheduled(9, friday(17, 35, 30), 30000, true),
+        scheduled(2, friday(17, 36, 0), 150000, false),
+        scheduled(1, friday(17, 38, 30), 90000, false),
+    ]);
+    assert_eq!(order.last().unwrap().end(), friday(17, 40, 0));
+
+    // Playlists that don't repeat are played once
+    let once = Schedule { repeat: false, intermission: None, ..schedule.clone() };
+    let order = once.running_order(&[1, 2], &show_durations(), friday(17, 30, 0), friday(17, 39, 0))
+        .expect("Error building running order");
+    assert_eq!(order.len(), 2);
+
+    match schedule.running_order(&[1, 3], &show_durations(), friday(17, 30, 0), friday(17, 39, 0)) {
+        Err(Error::SequenceNotFound(seqid)) => assert_eq!(seqid, 3),
+        other => panic!("Expected sequence not found, got {:?}", other),
+    }
+}
+
+#[test]
+fn shuffles_the_same_way_each_time() {
+    let text = r#"{"windows": [{"start": "17:00", "end": "21:00"}], "repeat": true, "shuffle": true}"#;
+    let schedule = Schedule::from_json(text).expect("Error parsing schedule");
+    let playlist = (1..9).collect::<Vec<u32>>();
+    let durations = playlist.iter()
+        .map(|seqid| (*seqid, 60000))
+        .collect::<BTreeMap<u32, u32>>();
+
+    let order = schedule.running_order(&playlist, &durations, friday(17, 0, 0), friday(21, 0, 0))
+        .expect("Error building running order")
+        .iter()
+        .map(|scheduled| scheduled.seqid)
+        .collect::<Vec<u32>>();
+    assert_eq!(order.len(), 240);
+    let again = schedule.running_order(&playlist, &durations, friday(17, 0, 0), friday(21, 0, 0))
+        .expect("Error building running order")
+        .iter()
+        .map(|scheduled| scheduled.seqid)
+        .collect::<Vec<u32>>();
+    assert_eq!(order, again);
+
+    // Each time through plays every sequence once, without playing the same one twice in a row
+    let passes = order.chunks(8).collect::<Vec<&[u32]>>();
+    for pass in &passes {
+        let mut sorted = pass.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, playlist);
+    }
+    assert!(order.windows(2).all(|pair| pair[0] != pair[1]));
+    assert!(passes.iter().any(|pass| *pass != &playlist[..]));
+    assert!(passes.windows(2).any(|pair| pair[0] != pair[1]));
+}
+
+#[test]
+fn decides_what_plays() {
+    let schedule = Schedule::from_json(SHOW_NIGHTS).expect("Error parsing schedule");
+    let decide = |now| schedule.decide(&[1, 2], &show_durations(), now).expect("Error deciding");
+
+    assert_eq!(decide(friday(17, 30, 0)), ScheduleDecision::Play { seqid: 1, position_ms: 0, intermission: false });
+    assert_eq!(decide(friday(17, 35, 45)), ScheduleDecision::Play { seqid: 9, position_ms: 15000, intermission: true });
+
+    // The last sequence finishes after the show ends, then lights wait for tomorrow
+    assert_eq!(decide(friday(17, 39, 30)), ScheduleDecision::Play { seqid: 1, position_ms: 60000, intermission: false });
+    let tomorrow = friday(17, 30, 0) + chrono::Duration::days(1);
+    assert_eq!(decide(friday(17, 40, 0)), ScheduleDecision::Wait(tomorrow));
+    assert_eq!(decide(friday(9, 0, 0)), ScheduleDecision::Wait(friday(17, 30, 0)));
+
+    // Once a playlist that doesn't repeat is over, nothing plays for the rest of the show
+    let once = Schedule { repeat: false, ..schedule.clone() };
+    assert_eq!(once.decide(&[1, 2], &show_durations(), friday(17, 33, 0)).ok(),
+        Some(ScheduleDecision::Play { seqid: 2, position_ms: 90000, intermission: false }));
+    assert_eq!(once.decide(&[1, 2], &show_durations(), friday(17, 35, 0)).ok(), Some(ScheduleDecision::Wait(tomorrow)));
+
+    // Decisions for a stored project use the durations of its sequences
+    let mut project_dao = dao::ProjectDaoTesting::new();
+    project_dao.get_project_fn = Box::new(|name| Ok(Project { name: name, playlist: vec![1, 2], layout_id: 1 }));
+    project_dao.get_schedule_fn = Box::new(|_| Schedule::from_json(SHOW_NIGHTS));
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(|seqid| {
+        let duration_ms = show_durations()[&seqid];
+        Ok(Sequence {
+            music_duration_ms: duration_ms,
+            ..common::test_sequence(seqid, 50, duration_ms / 50, 1, "music.ogg")
+        })
+    });
+    let clock = FakeClock { now: RefCell::new(friday(17, 36, 10)), sleeps: RefCell::new(Vec::new()) };
+    assert_eq!(proton_cli::get_schedule_decision(&clock, &project_dao, &sequence_dao, "Test").ok(),
+        Some(ScheduleDecision::Play { seqid: 2, position_ms: 10000, intermission: false }));
+}
+
+#[test]
+fn plays_scheduled_show() {
+    let root = TempDir::new("proton_schedule").expect("Error creating temp dir");
+    let music_store = MusicStore::new(root.path());
+    let file_name = music_store.add("tests/music/test_1sec.ogg").expect("Error adding music");
+
+    let mut project_dao = dao::ProjectDaoTesting::new();
+    project_dao.get_project_fn = Box::new(|name| Ok(Project { name: name, playlist: vec![1, 2], layout_id: 1 }));
+    project_dao.get_schedule_fn = Box::new(|_| Schedule::from_json(r#"{"windows": [{"start": "20:00", "end": "20:01"}]}"#));
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(move |seqid| Ok(common::test_sequence(seqid, 25, 4, 1, &file_name)));
+    sequence_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1]));
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(|chanid| Ok(common::test_channel(chanid, 1, 1)));
+    let mut data_dao = dao::DataDaoTesting::new();
+    data_dao.get_data_fn = Box::new(|_, _| Ok(vec![255; 4]));
+    let mut dimming_dao = dao::DimmingDaoTesting::new();
+    dimming_dao.get_dimming_fn = Box::new(|_| Ok(BTreeMap::new()));
+
+    // Waits for the show, plays each sequence at its time, then waits for the show to end
+    let clock = FakeClock { now: RefCell::new(friday(19, 59, 0)), sleeps: RefCell::new(Vec::new()) };
+    let mut driver = CountingDriver { frames: 0 };
+    let events = RefCell::new(Vec::new());
+    let report = proton_cli::play_schedule(&clock, &SilentPlayer, &mut driver, &channel_dao, &data_dao, &dimming_dao,
+        &project_dao, &sequence_dao, &music_store, "Test", &|event| events.borrow_mut().push(event))
+        .expect("Error playing schedule");
+    assert_eq!(report.sequences, 2);
+    assert_eq!(events.borrow()[0], PlaybackEvent::ShowTimes(friday(20, 0, 0), friday(20, 1, 0)));
+    assert_eq!(events.borrow().len(), 3);
+    assert_eq!(driver.frames, report.frames_sent + 1);
+    assert_eq!(*clock.sleeps.borrow(), vec![
+        friday(20, 0, 0),
+        friday(20, 0, 0) + chrono::Duration::milliseconds(100),
+        friday(20, 1, 0),
+    ]);
+
+    // Joining a show late skips what should have already played
+    let clock = FakeClock { now: RefCell::new(friday(20, 0, 30)), sleeps: RefCell::new(Vec::new()) };
+    let report = proton_cli::play_schedule(&clock, &SilentPlayer, &mut driver, &channel_dao, &data_dao, &dimming_dao,
+        &project_dao, &sequence_dao, &music_store, "Test", &|_| ())
+        .expect("Error playing schedule");
+    assert_eq!(report.sequences, 0);
+    assert_eq!(clock.now(), friday(20, 1, 0));
+}
@@ -0,0 +1,133 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::Write;
+use std::rc::Rc;
+
+use proton_cli::effects::BlendMode;
+use proton_cli::error::Error;
+use proton_cli::project_types::{Channel, Fixture, SequenceScript, TimeAnchor, TimingMark, TimingTrack};
+use tempdir::TempDir;
+
+
+#[test]
+fn parses_cues_and_times() {
+    assert_eq!(SequenceScript::parse_time("00:12.500", 1).unwrap(), 12500);
+    assert_eq!(SequenceScript::parse_time("1:02:03", 1).unwrap(), 3723000);
+    assert_eq!(SequenceScript::parse_time("3.25", 1).unwrap(), 3250);
+    for time in vec!["1:75", "-1", "2000:00:00", "5000000", "71583:00"] {
+        match SequenceScript::parse_time(time, 4) {
+            Err(Error::InvalidScript(4, _)) => (),
+            other => panic!("Expected an error for '{}', got {:?}", time, other),
+        }
+    }
+
+    let script = SequenceScript::parse("\
+        # Comment\n\
+        \n\
+        00:12.500-00:15.000 Megatree rainbow speed=2\n\
+        0-1 \"Bush 1\",Arch wash color=#ff0000 blend=add\n\
+        beats#2-beats#3.end * strobe\n")
+        .expect("Error parsing script");
+    assert_eq!(script.cues.len(), 3);
+    assert_eq!((script.cues[0].line, script.cues[0].start.clone(), script.cues[0].end.clone()),
+        (3, TimeAnchor::Ms(12500), TimeAnchor::Ms(15000)));
+    assert_eq!(script.cues[2].start, TimeAnchor::Mark { track: "beats".to_owned(), index: 2, end: false });
+    assert_eq!(script.cues[2].end, TimeAnchor::Mark { track: "beats".to_owned(), index: 3, end: true });
+    assert_eq!(script.cues[0].params.get("speed"), Some(&"2".to_owned()));
+    assert_eq!(script.cues[1].fixtures, vec!["Bush 1".to_owned(), "Arch".to_owned()]);
+    assert_eq!(script.cues[1].blend, BlendMode::Add);
+    assert!(script.cues[1].params.get("blend").is_none());
+
+    // Errors point at the line of the bad cue
+    let errors = vec![
+        "0-1 * fireworks",
+        "0-1 * wash colour=red",
+        "0-1 * wash color=purple",
+        "2-1 * wash",
+        "0-1 \"Bush 1 wash",
+        "0-1 * wash blend=screen",
+        "0-1 wash",
+        "beats#0-1 * wash",
+        "0-2000:00:00 * wash",
+    ];
+    for text in errors {
+        match SequenceScript::parse(&format!("# Comment\n{}", text)) {
+            Err(Error::InvalidScript(line, _)) => assert_eq!(line, 2),
+            other => panic!("Expected an error for '{}', got {:?}", text, other),
+        }
+    }
+}
+
+#[test]
+fn compiles_script_into_channel_data() {
+    let mut channel_dao = dao::ChannelDaoTesting::new();
+    channel_dao.get_channel_fn = Box::new(|chanid| Ok(Channel {
+        location: (Some(chanid as i32), Some(0), Some(0)),
+        ..common::test_channel(chanid, chanid, chanid)
+    }));
+    let mut fixture_dao = dao::FixtureDaoTesting::new();
+    fixture_dao.get_fixture_fn = Box::new(|fixid| {
+        let (name, channels) = if fixid == 1 { ("Megatree", vec![1]) } else { ("Arch", vec![2]) };
+        let mut fixture = Fixture::new(name, None, (fixid as i32, 0, 0), (0, 0, 0), (0, 0, 0), channels);
+        fixture.fixid = fixid;
+        Ok(fixture)
+    });
+    let mut layout_dao = dao::LayoutDaoTesting::new();
+    layout_dao.get_layout_fn = Box::new(|layout_id| Ok(common::test_layout(layout_id, vec![1, 2])));
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(|seqid| Ok(common::test_sequence(seqid, 50, 6, 1, "test.ogg")));
+    sequence_dao.get_channel_ids_fn = Box::new(|_| Ok(vec![1, 2]));
+
+    let updated = Rc::new(RefCell::new(Vec::new()));
+    let updated_ref = updated.clone();
+    let mut data_dao = dao::DataDaoTesting::new();
+    data_dao.update_data_fn = Box::new(move |_, chanid, data| {
+        updated_ref.borrow_mut().push((chanid, data));
+        Ok(())
+    });
+
+    let mut timing_dao = dao::TimingDaoTesting::new();
+    timing_dao.get_timing_tracks_fn = Box::new(|seqid| Ok(vec![TimingTrack {
+        trackid: 1,
+        seqid: seqid,
+        name: "beats".to_owned(),
+        marks: vec![
+            TimingMark { start_ms: 0, end_ms: None, label: None },
+            TimingMark { start_ms: 200, end_ms: Some(300), label: None },
+        ]
+    }]));
+
+    let root = TempDir::new("proton_script").expect("Error creating temp dir");
+    let compile = |script: &str| {
+        let script_path = root.path().join("show.txt");
+        let mut file = File::create(&script_path).expect("Error creating script file");
+        file.write_all(script.as_bytes()).expect("Error writing script file");
+        proton_cli::compile_script(&channel_dao, &data_dao, &fixture_dao, &layout_dao, &sequence_dao,
+            &timing_dao, 1, &script_path)
+    };
+
+    let num_cues = compile("\
+        0:00-0:00.150 Megatree wash color=#404040\n\
+        0:00.100-0:00.200 Megatree wash color=#404040 blend=add\n\
+        beats#2-beats#2.end * wash color=white\n")
+        .expect("Error compiling script");
+    assert_eq!(num_cues, 3);
+    assert_eq!(*updated.borrow(), vec![
+        (1, vec![64, 64, 128, 64, 255, 255]),
+        (2, vec![0, 0, 0, 0, 255, 255]),
+    ]);
+
+    // Nothing is written unless the whole script compiles
+    updated.borrow_mut().clear();
+    assert!(compile("0-0.1 * wash\n0-0.1 Tree wash\n").is_err());
+    assert!(compile("0-0.1 * wash\n1-2 * wash\n").is_err());
+    assert!(compile("0-0.1 * wash\nbeats#3-beats#4 * wash\n").is_err());
+    assert!(compile("0-0.1 * wash\nbeats#2-beats#1 * wash\n").is_err());
+    assert!(updated.borrow().is_empty());
+}
@@ -0,0 +1,245 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+mod common;
+mod dao;
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use proton_cli::error::Error;
+use proton_cli::project_types::{Sequence, TimeAnchor, TimingFile, TimingMark, TimingTrack};
+use tempdir::TempDir;
+
+
+const XLIGHTS_TIMING: &'static str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<timings>
+  <timing name="Lyrics" SourceVersion="2019.22">
+    <EffectLayer>
+      <Effect label="Run run Rudolph" starttime="1000" endtime="3500" />
+      <Effect label="" starttime="3500" endtime="4000" />
+    </EffectLayer>
+    <EffectLayer>
+      <Effect label="Run" starttime="1000" endtime="1400" />
+      <Effect label="run" starttime="1400" endtime="1800" />
+    </EffectLayer>
+  </timing>
+  <timing name="Drums &amp; Bass">
+    <EffectLayer>
+      <Effect starttime="500" endtime="500" />
+    </EffectLayer>
+  </timing>
+</timings>
+"#;
+
+const VIXEN_MARKS: &'static str = r#"<?xml version="1.0" encoding="utf-8"?>
+<ArrayOfMarkCollection xmlns:i="http://www.w3.org/2001/XMLSchema-instance">
+  <MarkCollection>
+    <Name>Phrases</Name>
+    <Marks>
+      <Mark>
+        <Duration>PT2.5S</Duration>
+        <StartTime>PT1S</StartTime>
+        <Text>Verse</Text>
+      </Mark>
+      <Mark>
+        <Duration>PT0S</Duration>
+        <StartTime>PT1M2.05S</StartTime>
+        <Text />
+      </Mark>
+    </Marks>
+  </MarkCollection>
+  <MarkCollection>
+    <Name>Old</Name>
+    <Marks xmlns:d2p1="http://schemas.microsoft.com/2003/10/Serialization/">
+      <d2p1:duration>PT0.25S</d2p1:duration>
+      <d2p1:duration>PT0.75S</d2p1:duration>
+    </Marks>
+  </MarkCollection>
+</ArrayOfMarkCollection>
+"#;
+
+fn mark(start_ms: u32, end_ms: Option<u32>, label: Option<&str>) -> TimingMark {
+    TimingMark {
+        start_ms: start_ms,
+        end_ms: end_ms,
+        label: label.map(|label| label.to_owned())
+    }
+}
+
+fn write_file(root: &TempDir, name: &str, text: &str) -> PathBuf {
+    let path = root.path().join(name);
+    let mut file = File::create(&path).expect("Error creating timing file");
+    file.write_all(text.as_bytes()).expect("Error writing timing file");
+    path
+}
+
+fn track(name: &str, marks: Vec<TimingMark>) -> TimingTrack {
+    TimingTrack {
+        trackid: 1,
+        seqid: 1,
+        name: name.to_owned(),
+        marks: marks
+    }
+}
+
+#[test]
+fn parses_audacity_labels() {
+    let text = "0.500000\t0.500000\tKick\n1.25\t2.000000\tVerse one\n\\\t200.0\t4000.0\n3\t3\t\n";
+    let timing_file = TimingFile::parse_audacity(text, "cues").expect("Error parsing labels");
+    assert_eq!(timing_file.tracks, vec![("cues".to_owned(), vec![
+        mark(500, None, Some("Kick")),
+        mark(1250, Some(2000), Some("Verse one")),
+        mark(3000, None, None),
+    ])]);
+
+    match TimingFile::parse_audacity("1.0\t0.5\tBackwards\n", "cues") {
+        Err(Error::InvalidTimingFile(description)) => assert!(description.contains("line 1")),
+        other => panic!("Expected an invalid timing file, got {:?}", other),
+    }
+    assert!(TimingFile::parse_audacity("0.5\n", "cues").is_err());
+    assert!(TimingFile::parse_audacity("half\t1.0\n", "cues").is_err());
+}
+
+#[test]
+fn parses_xlights_timings() {
+    let timing_file = TimingFile::parse_xlights(XLIGHTS_TIMING).expect("Error parsing timings");
+    assert_eq!(timing_file.tracks, vec![
+        ("Lyrics".to_owned(), vec![
+            mark(1000, Some(3500), Some("Run run Rudolph")),
+            mark(3500, Some(4000), None),
+        ]),
+        ("Lyrics 2".to_owned(), vec![
+            mark(1000, Some(1400), Some("Run")),
+            mark(1400, Some(1800), Some("run")),
+        ]),
+        ("Drums & Bass".to_owned(), vec![mark(500, None, None)]),
+    ]);
+
+    assert!(TimingFile::parse_xlights("<timings></timings>").is_err());
+    assert!(TimingFile::parse_xlights(
+        r#"<timing name="Beats"><EffectLayer><Effect starttime="soon" endtime="1"/></EffectLayer></timing>"#)
+        .is_err());
+}
+
+#[test]
+fn parses_vixen_mark_collections() {
+    let timing_file = TimingFile::parse_vixen(VIXEN_MARKS).expect("Error parsing marks");
+    assert_eq!(timing_file.tracks, vec![
+        ("Phrases".to_owned(), vec![
+            mark(1000, Some(3500), Some("Verse")),
+            mark(62050, None, None),
+        ]),
+        ("Old".to_owned(), vec![TimingMark::at(250), TimingMark::at(750)]),
+    ]);
+
+    assert!(TimingFile::parse_vixen("<ArrayOfMarkCollection />").is_err());
+}
+
+#[test]
+fn reads_timing_files_by_extension() {
+    let root = TempDir::new("proton_timing").expect("Error creating temp dir");
+    let labels_path = write_file(&root, "Chorus cues.txt", "1.5\t1.5\tGo\n");
+    let timing_file = TimingFile::read(&labels_path).expect("Error reading labels");
+    assert_eq!(timing_file.tracks, vec![("Chorus cues".to_owned(), vec![mark(1500, None, Some("Go"))])]);
+
+    let xlights_path = write_file(&root, "song.xtiming", XLIGHTS_TIMING);
+    assert_eq!(TimingFile::read(&xlights_path).expect("Error reading timings").tracks.len(), 3);
+
+    let vixen_path = write_file(&root, "song.tim", VIXEN_MARKS);
+    assert_eq!(TimingFile::read(&vixen_path).expect("Error reading marks").tracks.len(), 2);
+
+    let other_path = write_file(&root, "song.lms", "");
+    match TimingFile::read(&other_path) {
+        Err(Error::UnsupportedFileType(extension)) => assert_eq!(extension, "lms"),
+        other => panic!("Expected an unsupported file type, got {:?}", other),
+    }
+}
+
+#[test]
+fn resolves_time_anchors() {
+    let tracks = vec![
+        track("beats", vec![TimingMark::at(0), TimingMark::at(500), TimingMark::at(1000)]),
+        track("phrases", vec![mark(1000, Some(3500), Some("Verse")), mark(4000, None, None)]),
+    ];
+    let resolve = |anchor: &str| anchor.parse::<TimeAnchor>().and_then(|anchor| anchor.resolve(&tracks));
+
+    assert_eq!(resolve("1500").ok(), Some(1500));
+    assert_eq!(resolve("beats#2").ok(), Some(500));
+    assert_eq!(resolve("phrases#1.end").ok(), Some(3500));
+    assert_eq!(resolve("phrases#2.end").ok(), Some(4000));
+
+    // Track names can have # in them, the index is after the last one
+    assert_eq!("Song #2#3".parse::<TimeAnchor>().ok(), Some(TimeAnchor::Mark {
+        track: "Song #2".to_owned(),
+        index: 3,
+        end: false
+    }));
+
+    assert!(resolve("beats#4").is_err());
+    assert!(resolve("lyrics#1").is_err());
+    for anchor in &["beats#0", "beats", "#1", "beats#1.start", "-20"] {
+        match anchor.parse::<TimeAnchor>() {
+            Err(Error::InvalidTimeAnchor(_)) => (),
+            other => panic!("Expected an invalid time anchor for {}, got {:?}", anchor, other),
+        }
+    }
+}
+
+#[test]
+fn imports_timing_into_sequence() {
+    let root = TempDir::new("proton_timing").expect("Error creating temp dir");
+    let labels_path = write_file(&root, "cues.txt", "2.0\t2.0\tB\n1.0\t1.5\tA\n");
+    let xlights_path = write_file(&root, "song.xtiming", XLIGHTS_TIMING);
+    let late_path = write_file(&root, "late.txt", "1.0\t1.0\n30.0\t30.0\n");
+
+    let mut sequence_dao = dao::SequenceDaoTesting::new();
+    sequence_dao.get_sequence_fn = Box::new(|seqid| Ok(Sequence {
+        music_duration_ms: 10000,
+        ..common::test_sequence(seqid, 50, 200, 1, "test.ogg")
+    }));
+    let stored = Rc::new(RefCell::new(Vec::new()));
+    let stored_ref = stored.clone();
+    let mut timing_dao = dao::TimingDaoTesting::new();
+    timing_dao.set_timing_track_fn = Box::new(move |seqid, name, marks| {
+        stored_ref.borrow_mut().push(name.clone());
+        Ok(TimingTrack { trackid: 1, seqid: seqid, name: name, marks: marks })
+    });
+
+    // Marks are sorted, and a single track can be renamed
+    let tracks = proton_cli::import_timing(&sequence_dao, &timing_dao, 1, &labels_path, Some("hits"))
+        .expect("Error importing labels");
+    assert_eq!(tracks, vec![track("hits", vec![mark(1000, Some(1500), Some("A")), mark(2000, None, Some("B"))])]);
+
+    let tracks = proton_cli::import_timing(&sequence_dao, &timing_dao, 1, &xlights_path, None)
+        .expect("Error importing timings");
+    assert_eq!(tracks.len(), 3);
+    assert_eq!(*stored.borrow(), vec!["hits", "Lyrics", "Lyrics 2", "Drums & Bass"]);
+
+    // Nothing is stored if any track is invalid
+    stored.borrow_mut().clear();
+    assert!(proton_cli::import_timing(&sequence_dao, &timing_dao, 1, &xlights_path, Some("lyrics")).is_err());
+    match proton_cli::import_timing(&sequence_dao, &timing_dao, 1, &late_path, None) {
+        Err(Error::InvalidTimingFile(description)) => assert!(description.contains("30000ms")),
+        other => panic!("Expected an invalid timing file, got {:?}", other),
+    }
+    assert!(stored.borrow().is_empty());
+}
+
+#[test]
+fn resolves_times_against_stored_tracks() {
+    let mut timing_dao = dao::TimingDaoTesting::new();
+    timing_dao.get_timing_tracks_fn = Box::new(|seqid| {
+        assert_eq!(seqid, 2);
+        Ok(vec![track("beats", vec![TimingMark::at(250), TimingMark::at(750)])])
+    });
+    assert_eq!(proton_cli::resolve_time(&timing_dao, 2, "beats#2").ok(), Some(750));
+
+    // Plain times don't need the sequence's tracks
+    let timing_dao = dao::TimingDaoTesting::new();
+    assert_eq!(proton_cli::resolve_time(&timing_dao, 2, "1200").ok(), Some(1200));
+    assert!(proton_cli::resolve_time(&timing_dao, 2, "beats#1").is_err());
+}
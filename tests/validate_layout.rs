@@ -0,0 +1,63 @@
+extern crate proton_cli;
+extern crate tempdir;
+
+use std::fs::File;
+use std::io::Write;
+
+use tempdir::TempDir;
+
+use proton_cli::project_types::{IssueLevel, LayoutIssue};
+
+
+/// Writes the given layout JSON to a temporary file and validates it
+fn validate_json(layout_json: &str) -> Vec<LayoutIssue> {
+    let dir = TempDir::new("proton_layout").expect("Error creating temp dir");
+    let layout_path = dir.path().join("layout.json");
+    let mut file = File::create(&layout_path).expect("Error creating layout file");
+    file.write_all(layout_json.as_bytes()).expect("Error writing layout file");
+    proton_cli::validate_layout(&layout_path, None).expect("Error validating layout")
+}
+
+#[test]
+fn reports_all_problems_with_row_numbers() {
+    let issues = validate_json(r#"{"layoutName":"Test","channels":[
+        {"internalChannel":1,"dmxChannel":1,"fixtureName":"Sun","channelName":"Sun 1","color":"R",
+            "num_primary":0,"num_secondary":0,"location":"0,0,0","rotation":"0,0,0"},
+        {"internalChannel":1,"dmxChannel":3,"fixtureName":"Sun","channelName":"Sun 1","color":"G",
+            "num_primary":0,"num_secondary":0,"location":"0,a,0","rotation":"0,0,0"},
+        {"internalChannel":2,"dmxChannel":3,"fixtureName":"Sun","channelName":"Sun 1","color":"Y",
+            "num_primary":0,"num_secondary":0,"location":"0,0,0","rotation":"0,0"}
+    ]}"#);
+
+    let errors = issues.iter()
+        .filter(|issue| issue.level == IssueLevel::Error)
+        .map(|issue| issue.row)
+        .collect::<Vec<Option<usize>>>();
+    // Duplicate internal channel and bad location on row 2, duplicate dmx and bad rotation on row 3
+    assert_eq!(errors, vec![Some(2), Some(2), Some(3), Some(3)]);
+
+    assert!(issues.iter().any(|issue| issue.row == Some(3) && issue.message.contains("'Y'")));
+    assert!(issues.iter().any(|issue| issue.row.is_none() && issue.message == "DMX channel 2 is unused"));
+}
+
+#[test]
+fn reports_ignored_fields_and_inconsistent_fixtures() {
+    let issues = validate_json(r#"{"layoutName":"Test","channels":[
+        {"internalChannel":1,"dmxChannel":1,"fixtureName":"Sun","channelName":"Sun 1","color":"R",
+            "num_primary":0,"num_secondary":0,"location":"0,0,0","rotation":"0,0,0","notes":""},
+        {"internalChannel":2,"dmxChannel":2,"fixtureName":"Sun","channelName":"Sun 1","color":"G",
+            "num_primary":0,"num_secondary":0,"location":"0,0,0","rotation":"0,0,0"},
+        {"internalChannel":3,"dmxChannel":3,"fixtureName":"Sun","channelName":"Sun 2","color":"R",
+            "num_primary":0,"num_secondary":0,"location":"0,0,0","rotation":"0,0,0"},
+        {"internalChannel":4,"dmxChannel":4,"fixtureName":"Sun","channelName":"Sun 2","color":"G",
+            "num_primary":0,"num_secondary":0,"location":"0,0,0","rotation":"0,0,0"},
+        {"internalChannel":5,"dmxChannel":5,"fixtureName":"Sun","channelName":"Sun 3","color":"R",
+            "num_primary":0,"num_secondary":0,"location":"0,0,0","rotation":"0,0,0"}
+    ]}"#);
+
+    assert_eq!(issues, vec![
+        LayoutIssue::warning(Some(1), "Unknown field 'notes' is ignored".to_owned()),
+        LayoutIssue::warning(Some(5), "Fixture 'Sun' element 'Sun 3' has 1 channel(s) (R), \
+            but most of its elements have (G,R)".to_owned()),
+    ]);
+}